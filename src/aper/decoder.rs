@@ -1,15 +1,31 @@
 use byteorder::{ByteOrder, BigEndian};
 use super::*;
+use per;
 
 #[derive(Debug, PartialEq)]
 pub enum DecodeError {
+    DefaultValueEncoded,
+    InvalidCharacter,
     InvalidChoice,
+    InvalidOid,
+    InvalidReal,
+    InvalidSize,
+    InvalidTime,
+    InvalidUtf8,
     MalformedLength,
     MalformedInt,
     MissingSizeConstraint,
     MissingValueConstraint,
+    NonCanonicalFragmentation,
+    NonCanonicalReal,
+    NonCanonicalTime,
+    NonMinimalInt,
+    NonMinimalLength,
     NotEnoughBits,
     NotImplemented,
+    TrailingZeroBits,
+    UnknownExtension,
+    UnsortedSetOf,
 }
 
 /// A bit-wise cursor used to decode aligned PER messagses.
@@ -25,22 +41,66 @@ pub enum DecodeError {
 /// let x = i16::from_aper(&mut d, UNCONSTRAINED).unwrap();
 /// println!("x = {}", x); // Prints x = 43
 /// ```
+///
+/// By default, a `Decoder` accepts any valid encoding. With [set_canonical()](#method.set_canonical), it also checks
+/// that the input follows the CANONICAL-PER restrictions of X.691, as needed when an encoding is signed, and returns
+/// an `Err` naming the problem if it doesn't.
+#[derive(Clone)]
 pub struct Decoder<'a> {
     data: &'a [u8],
     len: usize,
     pos: usize,
+    lossless: bool,
+    canonical: bool,
 }
 
 impl<'a> Decoder<'a> {
     /// Construct a new `Decoder` with an array of bytes.
+    #[allow(mismatched_lifetime_syntaxes, clippy::redundant_field_names)]
     pub fn new(data: &'a [u8]) -> Decoder {
         Decoder {
             data: data,
             len: 8 * data.len(),
             pos: 0,
+            lossless: false,
+            canonical: false,
         }
     }
 
+    /// Construct a `Decoder` for the contents of a nested encoding (e.g. an open type) found by this one, with the
+    /// same settings.
+    pub fn sub_decoder<'b>(&self, data: &'b [u8]) -> Decoder<'b> {
+        let mut decoder = Decoder::new(data);
+        decoder.lossless = self.lossless;
+        decoder.canonical = self.canonical;
+        decoder
+    }
+
+    /// Enable or disable lossless mode. In lossless mode, the parts of a message the decoder doesn't know about
+    /// (e.g. extension additions added in later versions of a protocol) are kept as raw octets so the message can be
+    /// re-encoded exactly; otherwise they are dropped.
+    pub fn set_lossless(&mut self, lossless: bool) {
+        self.lossless = lossless;
+    }
+
+    /// Check if lossless mode is enabled.
+    pub fn is_lossless(&self) -> bool {
+        self.lossless
+    }
+
+    /// Enable or disable the checks of the CANONICAL-PER restrictions: lengths and unconstrained integers in as few
+    /// octets as possible, fragments of the largest possible size, no trailing 0 bits in BIT STRINGs with named bits,
+    /// no DEFAULT components encoded with their default value, SET OF elements sorted by their encodings and REAL and
+    /// time values in their canonical forms.
+    pub fn set_canonical(&mut self, canonical: bool) {
+        self.canonical = canonical;
+    }
+
+    /// Check if the input is checked against the CANONICAL-PER restrictions.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
     /// Read `n` bits. Where `0 <= n <= 8`. See [read_to_vec()](#method.read_to_vec) for larger `n`.
     /// Returns an `Err` if the read would consume more bits than are available. Else, returns the bits as a u8 with
     /// left-padding.
@@ -87,6 +147,11 @@ impl<'a> Decoder<'a> {
         Ok(ret)
     }
 
+    /// Skip any bits up to the next octet boundary, as required before octet-aligned fields in aligned PER.
+    pub fn align(&mut self) {
+        self.pos = self.pos.div_ceil(8) * 8;
+    }
+
     /// Read a byte.
     pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
         let ret = self.read(8);
@@ -96,6 +161,25 @@ impl<'a> Decoder<'a> {
         Ok(ret.unwrap())
     }
 
+    /// Read `n` bits, where `0 <= n <= 64`, as an unsigned integer.
+    pub fn read_uint(&mut self, n: usize) -> Result<u64, DecodeError> {
+        if n > 64 {
+            return Err(DecodeError::NotImplemented);
+        }
+        if self.pos + n > self.len {
+            return Err(DecodeError::NotEnoughBits);
+        }
+
+        let mut ret: u64 = 0;
+        let mut left = n;
+        while left > 0 {
+            let m = if left > 8 { 8 } else { left };
+            ret = (ret << m) | self.read(m).unwrap() as u64;
+            left -= m;
+        }
+        Ok(ret)
+    }
+
     /// Read `len` bits into `content`.
     /// Returns an `Err` if the read would consume more bits than are available. Else, the bits as a `u8`s with
     /// left-padding are pushed onto `content`.
@@ -123,8 +207,7 @@ impl<'a> Decoder<'a> {
             content.push(self.read(len).unwrap());
         } else {
             let num_bytes = (len as f64 / 8.).ceil() as usize;
-            for i in 0..num_bytes {
-                println!("read byte {}", i);
+            for _ in 0..num_bytes {
                 content.push(self.read_u8().unwrap());
             }
             self.pos -= len % 8;
@@ -134,24 +217,126 @@ impl<'a> Decoder<'a> {
 
     /// Decode an aligned PER length determinant
     pub fn decode_length(&mut self) -> Result<usize, DecodeError> {
-        let mut ret = self.read_u8();
+        let ret = per::read_length(self, true);
         if ret.is_err() {
-            return Err(DecodeError::MalformedLength);
+            return Err(ret.err().unwrap());
         }
+        match ret.unwrap() {
+            (_, true) => Err(DecodeError::NotImplemented),
+            (len, false) => Ok(len),
+        }
+    }
 
-        let mut b = ret.unwrap();
-        if b & LENGTH_DET_FRAG > 0 {
-            return Err(DecodeError::NotImplemented);
-        } else if b & LENGTH_DET_LONG > 0 {
-            let len: usize = (b & LENGTH_MASK_LONG) as usize;
-            ret = self.read_u8();
+    /// Decode an unconstrained (octet-aligned) length determinant and the items it counts, reassembling fragments if
+    /// the content was fragmented. `items(decoder, n)` is called to decode each run of `n` items. Returns the total
+    /// number of items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\x02\x46\x4f";
+    /// let mut d = aper::Decoder::new(data);
+    /// let mut content: Vec<u8> = Vec::new();
+    /// d.decode_fragmented(|d, n| d.read_to_vec(&mut content, n * 8)).unwrap();
+    /// println!("{:?}", content); // Prints [70, 79]
+    /// ```
+    pub fn decode_fragmented<F>(&mut self, mut items: F) -> Result<usize, DecodeError>
+        where F: FnMut(&mut Decoder, usize) -> Result<(), DecodeError>
+    {
+        let mut total = 0;
+        // CANONICAL-PER only allows a fragment of fewer than 64K items just before the last length determinant
+        let mut last_fragment = false;
+        loop {
+            let ret = self.decode_fragment_length();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let (n, more) = ret.unwrap();
+            if more {
+                if self.canonical && last_fragment {
+                    return Err(DecodeError::NonCanonicalFragmentation);
+                }
+                last_fragment = n < 65536;
+            }
+
+            let ret = items(self, n);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            total += n;
+            if !more {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Decode an unconstrained (octet-aligned) length determinant that may be the length of a fragment. Returns the
+    /// number of items and whether more fragments follow them.
+    ///
+    /// See [decode_fragmented()](#method.decode_fragmented) to decode all the fragments.
+    pub fn decode_fragment_length(&mut self) -> Result<(usize, bool), DecodeError> {
+        per::read_length(self, true)
+    }
+
+    /// Read the octets of an open type value (e.g. the `value` of an S1AP/NGAP `ProtocolIE-Field`), i.e. a complete
+    /// encoding preceded by an unconstrained length determinant, without decoding them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\x02\x80\x2b";
+    /// let mut d = aper::Decoder::new(data);
+    /// println!("{:?}", d.decode_open_type_bytes().unwrap()); // Prints [128, 43]
+    /// ```
+    pub fn decode_open_type_bytes(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let mut content: Vec<u8> = Vec::new();
+        let ret = self.decode_fragmented(|d, n| d.read_to_vec(&mut content, n * 8));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(content)
+    }
+
+    /// Decode an open type value as a `T`, using a decoder bounded to the octets of the value. Any bits of the value
+    /// that `T` doesn't use are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\x02\x80\x2b";
+    /// let mut d = aper::Decoder::new(data);
+    /// println!("{}", d.decode_open_type::<i16>(UNCONSTRAINED).unwrap()); // Prints 43
+    /// ```
+    pub fn decode_open_type<T: APerElement>(&mut self, constraints: Constraints) -> Result<T, DecodeError> {
+        let ret = self.decode_open_type_bytes();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let content = ret.unwrap();
+        let mut decoder = self.sub_decoder(&content);
+        T::from_aper(&mut decoder, constraints)
+    }
+
+    /// Decode a normally small non-negative whole number, as used for the indexes of extension alternatives of CHOICE
+    /// types and extension values of ENUMERATED types.
+    pub fn decode_normally_small(&mut self) -> Result<u64, DecodeError> {
+        let ret = self.read(1);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if ret.unwrap() == 0 {
+            let ret = self.read(6);
             if ret.is_err() {
-                return Err(DecodeError::MalformedLength);
+                return Err(ret.err().unwrap());
             }
-            b = ret.unwrap();
-            return Ok((len << 8) + b as usize);
+            return Ok(ret.unwrap() as u64);
         }
-        Ok((b & LENGTH_MASK_SHORT) as usize)
+        self.align();
+        let ret = self.decode_int(Some(0), None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(ret.unwrap() as u64)
     }
 
     /// Decode an Aligned PER integer between `min` and `max`
@@ -173,62 +358,11 @@ impl<'a> Decoder<'a> {
     /// println!("x = {}", x); // Prints x = 501
     /// println!("y = {}", y); // Prints y = 503
     /// ```
+    #[allow(clippy::unnecessary_unwrap)]
     pub fn decode_int(&mut self, min: Option<i64>, max: Option<i64>) -> Result<i64, DecodeError> {
         if min.is_some() && max.is_some() {
             // constrained
-            let l = min.unwrap();
-            let h = max.unwrap();
-            let range = h - l + 1;
-            let n_bits = (range as f64).log2().ceil() as usize;
-
-            if n_bits < 8 {
-                let ret = self.read(n_bits);
-                if ret.is_err() {
-                    return Err(ret.err().unwrap());
-                }
-                return Ok(ret.unwrap() as i64 + l);
-            }
-
-            // Simple case, no length determinant
-            if n_bits <= 16 {
-                let mut ret = self.read_u8();
-                if ret.is_err() {
-                    return Err(ret.err().unwrap());
-                }
-
-                let mut b: u16 = ret.unwrap() as u16;
-                if n_bits > 8 {
-                    ret = self.read_u8();
-                    if ret.is_err() {
-                        return Err(ret.err().unwrap());
-                    }
-                    b = (ret.unwrap() as u16) + (b << 8);
-                }
-                return Ok(b as i64 + l);
-            }
-
-            // Need to decode length determinant
-            let ret = self.decode_length();
-            if ret.is_err() {
-                return Err(ret.err().unwrap());
-            }
-
-            let len: usize = ret.unwrap();
-            if len > 8 {
-                unimplemented!();
-            }
-
-            let mut content: Vec<u8> = Vec::with_capacity(len);
-            let res = self.read_to_vec(&mut content, len * 8);
-            if res.is_err() {
-                return Err(res.err().unwrap());
-            }
-
-            let val = BigEndian::read_uint(&content.as_slice(), len) as i64 + l;
-            if val < l || val > h {
-                return Err(DecodeError::MalformedInt);
-            }
-            return Ok(val);
+            return per::read_constrained(self, min.unwrap(), max.unwrap(), true);
         }
 
         let ret = self.decode_length();
@@ -237,18 +371,98 @@ impl<'a> Decoder<'a> {
         }
 
         let len = ret.unwrap();
+        if len == 0 || len > 8 {
+            return Err(DecodeError::MalformedInt);
+        }
         let mut content: Vec<u8> = Vec::with_capacity(len);
         let res = self.read_to_vec(&mut content, len * 8);
         if res.is_err() {
             return Err(res.err().unwrap());
         }
+        if self.canonical && len > 1 && match min {
+            None => content[0] == 0x00 && content[1] & 0x80 == 0 || content[0] == 0xFF && content[1] & 0x80 > 0,
+            Some(_) => content[0] == 0x00,
+        } {
+            return Err(DecodeError::NonMinimalInt);
+        }
 
-        if min.is_none() {
+        match min {
             // unconstrained
-            Ok(BigEndian::read_int(&content, len))
-        } else {
+            None => Ok(BigEndian::read_int(&content, len)),
             // semiconstrained
-            Ok(BigEndian::read_int(&content, len) + min.unwrap())
+            Some(l) => {
+                let val = BigEndian::read_uint(&content, len) as i128 + l as i128;
+                if val > i64::MAX as i128 {
+                    return Err(DecodeError::MalformedInt);
+                }
+                Ok(val as i64)
+            }
+        }
+    }
+
+    /// Decode a component with a DEFAULT value, given its bit in the preamble. Returns `default` if it's absent. With
+    /// CANONICAL-PER, an encoded value equal to the default is an `Err`.
+    pub fn decode_default<T: APerElement + PartialEq>(&mut self,
+                                                       present: bool,
+                                                       default: T,
+                                                       constraints: Constraints)
+                                                       -> Result<T, DecodeError> {
+        if !present {
+            return Ok(default);
+        }
+        let ret = T::from_aper(self, constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        if self.canonical && value == default {
+            return Err(DecodeError::DefaultValueEncoded);
+        }
+        Ok(value)
+    }
+
+    /// Decode a SET OF `T` (see [encode_set_of](fn.encode_set_of.html)). `constraints` limits the size of the SET OF,
+    /// and `element_constraints` are the constraints of each element. With CANONICAL-PER, the elements must be sorted
+    /// by their encodings.
+    pub fn decode_set_of<T: APerElement>(&mut self,
+                                         constraints: Constraints,
+                                         element_constraints: Constraints)
+                                         -> Result<Vec<T>, DecodeError> {
+        let (lb, ub) = constraints.size.map_or((0, None), |sz_constr| sz_constr.size_bounds());
+        let ret = match ub {
+            Some(ub) if ub == lb && ub < 65536 => Ok(ub as usize),
+            Some(ub) if ub < 65536 => self.decode_int(Some(lb), Some(ub)).map(|n| n as usize),
+            _ => self.decode_length(),
+        };
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let len = ret.unwrap();
+        if !constraints.check_size(len) {
+            return Err(DecodeError::InvalidSize);
+        }
+
+        let mut elements = Vec::with_capacity(len);
+        for _ in 0..len {
+            let ret = T::from_aper(self, element_constraints);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            elements.push(ret.unwrap());
+        }
+        if self.canonical {
+            let mut prev: Option<Vec<u8>> = None;
+            for x in &elements {
+                let bytes = match x.to_aper(element_constraints) {
+                    Ok(enc) => enc.bytes().clone(),
+                    Err(_) => return Err(DecodeError::UnsortedSetOf),
+                };
+                if prev.as_ref().is_some_and(|p| *p > bytes) {
+                    return Err(DecodeError::UnsortedSetOf);
+                }
+                prev = Some(bytes);
+            }
         }
+        Ok(elements)
     }
 }
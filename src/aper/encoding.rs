@@ -1,9 +1,11 @@
-use byteorder::{BigEndian, WriteBytesExt};
 use super::*;
-use utils::shift_bytes_left;
+use per;
 
 #[derive(Debug, PartialEq)]
 pub enum EncodeError {
+    InvalidCharacter,
+    InvalidSize,
+    InvalidValue,
     MissingSizeConstraint,
     MissingValueConstraint,
     NotImplemented,
@@ -12,7 +14,9 @@ pub enum EncodeError {
 
 /// A wrapper for an aligned PER encoding.
 ///
-/// An `Encoding` is just a vector of bytes with right-padding at the end if necessary.
+/// An `Encoding` is just a vector of bytes with right-padding at the end if necessary. It also remembers where it was
+/// padded to an octet boundary (see [align()](#method.align)), so that the padding can be recomputed when it is
+/// appended to another `Encoding`.
 ///
 /// # Examples
 ///
@@ -28,22 +32,27 @@ pub enum EncodeError {
 pub struct Encoding {
     bytes: Vec<u8>,
     r_padding: usize,
+    alignments: Vec<(usize, usize)>,
 }
 
 impl Encoding {
     /// Construct a new, empty `Encoding`.
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Encoding {
         Encoding {
             bytes: Vec::new(),
             r_padding: 0,
+            alignments: Vec::new(),
         }
     }
 
     /// Construct a new `Encoding` with `bytes` and `r_pad` bits of right-padding.
+    #[allow(clippy::redundant_field_names)]
     pub fn with_bytes_and_padding(bytes: Vec<u8>, r_pad: usize) -> Encoding {
         Encoding {
             bytes: bytes,
             r_padding: r_pad,
+            alignments: Vec::new(),
         }
     }
 
@@ -52,43 +61,105 @@ impl Encoding {
         Self::with_bytes_and_padding(bytes, 0)
     }
 
+    /// Construct a new `Encoding` with the `n` least significant bits of `value`, where `0 <= n <= 64`.
+    pub fn with_uint(value: u64, n: usize) -> Encoding {
+        let mut bytes: Vec<u8> = Vec::with_capacity(n.div_ceil(8));
+        let mut left = n;
+        while left > 0 {
+            let m = if left > 8 { 8 } else { left };
+            bytes.push(((value >> (left - m)) as u8 & (0xFF >> (8 - m))) << (8 - m));
+            left -= m;
+        }
+        Self::with_bytes_and_padding(bytes, n.div_ceil(8) * 8 - n)
+    }
+
     /// Append `other` to the end of `self`, starting with the `r_padding`th LSB of `self`.
+    ///
+    /// Any octet-alignment padding in `other` is recomputed for the position it ends up at in `self`.
     pub fn append(&mut self, other: &Encoding) -> Result<(), EncodeError> {
-        let mut bytes = other.bytes().clone();
-        let r_padding = other.r_padding();
-
-        let n = self.bytes.len();
-
-        if n == 0 {
-            self.bytes.append(&mut bytes);
-            self.r_padding = r_padding;
+        if other.alignments.is_empty() {
+            self.append_bits(&other.bytes, other.r_padding);
             return Ok(());
         }
 
-        if bytes.len() == 0 {
-            return Ok(());
+        let mut start = 0;
+        for &(offset, pad) in &other.alignments {
+            let (bytes, r_padding) = other.bit_range(start, offset);
+            self.append_bits(&bytes, r_padding);
+            self.align();
+            start = offset + pad;
         }
+        let (bytes, r_padding) = other.bit_range(start, other.num_bits());
+        self.append_bits(&bytes, r_padding);
+
+        Ok(())
+    }
+
+    /// Append the `n` least significant bits of `value`, where `0 <= n <= 64`.
+    pub fn append_uint(&mut self, value: u64, n: usize) {
+        let other = Encoding::with_uint(value, n);
+        self.append_bits(&other.bytes, other.r_padding);
+    }
 
-        // Fill LSBs of self.bytes first
-        if self.r_padding > 0 {
-            let mask = 0xFF << r_padding;
-            self.bytes[n - 1] |= (bytes[0] & mask) >> (8 - self.r_padding);
+    /// Pad `self` with 0s up to the next octet boundary, as required before octet-aligned fields in aligned PER.
+    ///
+    /// This assumes `self` starts on an octet boundary of the complete message. If it doesn't, appending it to the
+    /// encoding that precedes it will move the padding to the right place.
+    pub fn align(&mut self) {
+        self.alignments.push((self.num_bits(), self.r_padding));
+        self.r_padding = 0;
+    }
 
-            shift_bytes_left(&mut bytes, self.r_padding);
+    fn append_bits(&mut self, bytes: &[u8], r_padding: usize) {
+        if bytes.is_empty() {
+            return;
+        }
 
-            let len = 8 - r_padding;
-            if len <= self.r_padding {
-                self.r_padding -= len;
-                bytes.remove(0);
-            }
+        if self.r_padding == 0 {
+            self.bytes.extend_from_slice(bytes);
+            self.r_padding = r_padding;
+            return;
+        }
+
+        // Each byte of `bytes` straddles the last byte of `self` and a new byte
+        let shift = self.r_padding;
+        for b in bytes {
+            let n = self.bytes.len();
+            self.bytes[n - 1] |= b >> (8 - shift);
+            self.bytes.push(b << shift);
+        }
+
+        let r_padding = shift + r_padding;
+        if r_padding >= 8 {
+            self.bytes.pop();
+            self.r_padding = r_padding - 8;
         } else {
             self.r_padding = r_padding;
         }
+    }
 
-        // Just append everything else
-        self.bytes.append(&mut bytes);
+    /// Copy bits `start..end` of `self` into left-aligned bytes, returning them with their right-padding.
+    fn bit_range(&self, start: usize, end: usize) -> (Vec<u8>, usize) {
+        let n = end - start;
+        let mut bytes: Vec<u8>;
+        if start.is_multiple_of(8) {
+            bytes = self.bytes[start / 8..(end.div_ceil(8))].to_vec();
+        } else {
+            bytes = vec![0; n.div_ceil(8)];
+            for i in 0..n {
+                let j = start + i;
+                if self.bytes[j / 8] & (0x80 >> (j % 8)) > 0 {
+                    bytes[i / 8] |= 0x80 >> (i % 8);
+                }
+            }
+        }
 
-        Ok(())
+        let r_padding = bytes.len() * 8 - n;
+        if r_padding > 0 {
+            let last = bytes.len() - 1;
+            bytes[last] &= 0xFF << r_padding;
+        }
+        (bytes, r_padding)
     }
 
     /// Get a reference to the bytes of an encoding.
@@ -110,19 +181,111 @@ impl Encoding {
     pub fn set_r_padding(&mut self, n: usize) {
         self.r_padding = n;
     }
+
+    /// Get the number of bits in the encoding, not counting right-padding.
+    pub fn num_bits(&self) -> usize {
+        self.bytes.len() * 8 - self.r_padding
+    }
 }
 
 /// Encode an aligned PER length determinant.
 pub fn encode_length(len: usize) -> Result<Encoding, EncodeError> {
-    if len < 128 {
-        return Ok(Encoding::with_bytes(vec![(len as u8 & LENGTH_MASK_SHORT) | LENGTH_DET_SHORT]));
-    } else if len < 65535 {
-        let upper = (len >> 8) as u8;
-        let lower = len as u8;
-        return Ok(Encoding::with_bytes(vec![(upper & LENGTH_MASK_LONG) | LENGTH_DET_LONG, lower]));
-    } else {
+    if len >= 16384 {
         return Err(EncodeError::NotImplemented);
     }
+    let mut enc = Encoding::new();
+    per::write_length(&mut enc, len, false, true);
+    Ok(enc)
+}
+
+/// Encode `count` items preceded by an unconstrained (octet-aligned) length determinant, splitting them into fragments
+/// of up to 64K items if there are 16K or more. `items(start, end)` should encode items `start..end`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{Encoding, encode_fragmented};
+///
+/// let data = vec![0u8; 20000];
+/// let enc = encode_fragmented(data.len(), |start, end| Ok(Encoding::with_bytes(data[start..end].to_vec()))).unwrap();
+/// println!("{:?}", &enc.bytes()[..1]); // Prints [193], a fragment of 16K items
+/// ```
+pub fn encode_fragmented<F>(count: usize, items: F) -> Result<Encoding, EncodeError>
+    where F: Fn(usize, usize) -> Result<Encoding, EncodeError>
+{
+    let mut enc = Encoding::new();
+    let mut start = 0;
+    loop {
+        let (n, fragment) = per::fragment_length(count - start);
+        per::write_length(&mut enc, n, fragment, true);
+
+        let ret = items(start, start + n);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = enc.append(&ret.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        start += n;
+        if n < 16384 {
+            return Ok(enc);
+        }
+    }
+}
+
+/// Encode an open type value from its complete encoding `inner`: the encoding is padded to whole octets (or replaced
+/// by a single 0 octet if it's empty) and preceded by an unconstrained length determinant.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{APerElement, UNCONSTRAINED, encode_open_type};
+///
+/// let enc = encode_open_type(&true.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [1, 128]
+/// ```
+pub fn encode_open_type(inner: &Encoding) -> Result<Encoding, EncodeError> {
+    let bytes = if inner.num_bits() == 0 {
+        vec![0x00]
+    } else {
+        inner.bytes().clone()
+    };
+    encode_fragmented(bytes.len(), |start, end| Ok(Encoding::with_bytes(bytes[start..end].to_vec())))
+}
+
+/// Encode a normally small non-negative whole number, as used for the indexes of extension alternatives of CHOICE
+/// types and extension values of ENUMERATED types.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::encode_normally_small;
+///
+/// println!("{:?}", encode_normally_small(5).unwrap().bytes()); // Prints [10]
+/// ```
+pub fn encode_normally_small(n: u64) -> Result<Encoding, EncodeError> {
+    if n < 64 {
+        return Ok(Encoding::with_uint(n, 7));
+    }
+    if n > i64::MAX as u64 {
+        return Err(EncodeError::InvalidValue);
+    }
+    let mut enc = Encoding::with_uint(1, 1);
+    enc.align();
+    let ret = encode_int(n as i64, Some(0), None);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let ret = enc.append(&ret.unwrap());
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(enc)
 }
 
 /// Encode an aligned PER integer between `min` and `max`.
@@ -143,74 +306,115 @@ pub fn encode_length(len: usize) -> Result<Encoding, EncodeError> {
 /// let x = 501;
 /// println!("{:?}", encode_int(x, Some(500), Some(503).unwrap().bytes()); // Prints [64]
 /// ```
+#[allow(clippy::unnecessary_unwrap)]
 pub fn encode_int(value: i64, min: Option<i64>, max: Option<i64>) -> Result<Encoding, EncodeError> {
     if min.is_some() && max.is_some() {
         // constrained
         let l = min.unwrap();
         let h = max.unwrap();
-        let v = value - l;
-        let range = h - l + 1;
-        let n_bits = (range as f64).log2().ceil() as usize;
-
-        // No alignment
-        if n_bits < 8 {
-            return Ok(Encoding::with_bytes_and_padding(vec![(v as u8) << (8 - n_bits)], 8 - n_bits));
-        }
-
-        // Simple case, no length determinant
-        if n_bits <= 16 {
-            let mut bytes = vec![v as u8];
-
-            if n_bits > 8 {
-                bytes.insert(0, (v >> 8) as u8);
-            }
-            return Ok(Encoding::with_bytes(bytes));
-        }
-
-        // Need to encode with length determinant
-        let len = (n_bits as f64 / 8.).ceil() as usize;
-        let ret = encode_length(len);
-        if ret.is_err() {
-            return Err(ret.err().unwrap());
-        }
-        let mut enc = ret.unwrap();
-        let mut bytes: Vec<u8> = Vec::new();
-        let res = bytes.write_uint::<BigEndian>(v as u64, len);
-        if res.is_err() {
-            return Err(EncodeError::WriteError);
-        }
-        let ret = enc.append(&Encoding::with_bytes(bytes));
-        if ret.is_err() {
-            return Err(ret.err().unwrap());
+        if value < l || value > h {
+            return Err(EncodeError::InvalidValue);
         }
+        let mut enc = Encoding::new();
+        per::write_constrained(&mut enc, value, l, h, true);
         return Ok(enc);
     }
 
-    let n_bits = (value as f64).log2().ceil() as usize;
-    let len = (n_bits as f64 / 8.).ceil() as usize;
-    let res = encode_length(len);
+    // Semi-constrained values are offsets from the lower bound in unsigned form, unconstrained values are in two's
+    // complement form, both in as few octets as possible
+    let mut bytes: Vec<u8> = match min {
+        None => value.to_be_bytes().to_vec(),
+        Some(l) if value >= l => ((value as i128 - l as i128) as u64).to_be_bytes().to_vec(),
+        Some(_) => return Err(EncodeError::InvalidValue),
+    };
+    while bytes.len() > 1 && match min {
+        None => bytes[0] == 0x00 && bytes[1] & 0x80 == 0 || bytes[0] == 0xFF && bytes[1] & 0x80 > 0,
+        Some(_) => bytes[0] == 0x00,
+    } {
+        bytes.remove(0);
+    }
+
+    let res = encode_length(bytes.len());
     if res.is_err() {
         return Err(res.err().unwrap());
     }
     let mut enc = res.unwrap();
-    let mut bytes: Vec<u8> = Vec::new();
+    let ret = enc.append(&Encoding::with_bytes(bytes));
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(enc)
+}
 
-    if min.is_none() {
-        // unconstrained
-        let ret = bytes.write_uint::<BigEndian>(value as u64, len);
-        if ret.is_err() {
-            return Err(EncodeError::WriteError);
-        }
-    } else {
-        // semiconstrained
-        let ret = bytes.write_uint::<BigEndian>((value - min.unwrap()) as u64, len);
+/// Encode a component with a DEFAULT value. Returns `None` if `value` is the default, in which case it is omitted
+/// and its bit in the preamble is clear, as CANONICAL-PER requires.
+pub fn encode_default<T: APerElement + PartialEq>(value: &T,
+                                                  default: &T,
+                                                  constraints: Constraints)
+                                                  -> Result<Option<Encoding>, EncodeError> {
+    if value == default {
+        return Ok(None);
+    }
+    let ret = value.to_aper(constraints);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(Some(ret.unwrap()))
+}
+
+/// Encode a SET OF `T`, with the elements sorted by their encodings (each padded to whole octets), as CANONICAL-PER
+/// requires. `constraints` limits the size of the SET OF, and `element_constraints` are the constraints of each
+/// element. The number of elements is left out if the size is fixed, is a constrained whole number if the upper bound
+/// is below 64K, and is a length determinant otherwise.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{Constraint, Constraints, UNCONSTRAINED, encode_set_of};
+///
+/// let constraints = Constraints {
+///     value: None,
+///     size: Some(Constraint::new(None, Some(8))),
+/// };
+/// let enc = encode_set_of(&[300u16, 2, 1], constraints, UNCONSTRAINED).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [48, 0, 1, 0, 2, 1, 44]
+/// ```
+pub fn encode_set_of<T: APerElement>(elements: &[T],
+                                     constraints: Constraints,
+                                     element_constraints: Constraints)
+                                     -> Result<Encoding, EncodeError> {
+    if !constraints.check_size(elements.len()) {
+        return Err(EncodeError::InvalidSize);
+    }
+    let (lb, ub) = constraints.size.map_or((0, None), |sz_constr| sz_constr.size_bounds());
+    let n = elements.len() as i64;
+
+    let mut encodings = Vec::with_capacity(elements.len());
+    for x in elements {
+        let ret = x.to_aper(element_constraints);
         if ret.is_err() {
-            return Err(EncodeError::WriteError);
+            return Err(ret.err().unwrap());
         }
+        encodings.push(ret.unwrap());
     }
-    let ret = enc.append(&Encoding::with_bytes(bytes));
+    encodings.sort_by(|a, b| a.bytes().cmp(b.bytes()));
+
+    // The count is a constrained whole number if the upper bound is below 64K, and is left out if the size is fixed
+    let ret = match ub {
+        Some(ub) if ub == lb && ub < 65536 => Ok(Encoding::new()),
+        Some(ub) if ub < 65536 => encode_int(n, Some(lb), Some(ub)),
+        _ => encode_length(encodings.len()),
+    };
     if ret.is_err() {
         return Err(ret.err().unwrap());
     }
+    let mut enc = ret.unwrap();
+    for x in &encodings {
+        let ret = enc.append(x);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+    }
     Ok(enc)
 }
@@ -1,7 +1,11 @@
 mod decoder;
 mod encoding;
+/// A serde `Serializer` and `Deserializer` for aligned PER.
+#[cfg(feature = "serde")]
+pub mod serde;
 pub use self::decoder::{Decoder, DecodeError};
-pub use self::encoding::{Encoding, EncodeError, encode_int, encode_length};
+pub use self::encoding::{Encoding, EncodeError, encode_default, encode_fragmented, encode_int, encode_length,
+                         encode_normally_small, encode_open_type, encode_set_of};
 
 pub const LENGTH_DET_SHORT: u8 = 0b0000_0000;
 pub const LENGTH_DET_LONG: u8 = 0b1000_0000;
@@ -12,7 +16,12 @@ pub const LENGTH_MASK_LONG: u8 = 0b0011_1111;
 
 /// An interval that desribes the limits on some value.
 /// To indicate something is unbounded, set `min` and `max` to `None`.
-#[derive(Debug, Copy, Clone)]
+///
+/// As a size constraint, a missing lower bound is 0, as in `SIZE(MIN..max)`, so a fixed size `n` is written
+/// `Constraint::new(Some(n), Some(n))`. Every type reads its size constraint this way (see
+/// [size_bounds()](#method.size_bounds)). `BitString` used to take `Constraint::new(None, Some(n))` to be a fixed
+/// size of `n`, which now allows any size up to `n`.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Constraint {
     min: Option<i64>,
     max: Option<i64>,
@@ -20,7 +29,8 @@ pub struct Constraint {
 
 impl Constraint {
     /// Construct a new `Constraint`.
-    pub fn new(min: Option<i64>, max: Option<i64>) -> Constraint {
+    #[allow(clippy::redundant_field_names)]
+    pub const fn new(min: Option<i64>, max: Option<i64>) -> Constraint {
         Constraint {
             min: min,
             max: max,
@@ -36,16 +46,48 @@ impl Constraint {
     pub fn max(&self) -> Option<i64> {
         self.max
     }
+
+    /// Get the bounds of a size constraint: the lower bound, which is 0 if there is none, and the upper bound.
+    pub fn size_bounds(&self) -> (i64, Option<i64>) {
+        (self.min.unwrap_or(0), self.max)
+    }
+
+    /// Get the fixed size described by a size constraint, if its bounds are equal.
+    pub fn fixed_size(&self) -> Option<i64> {
+        match self.size_bounds() {
+            (lb, Some(ub)) if lb == ub => Some(ub),
+            _ => None,
+        }
+    }
+
+    /// Check that a size of `n` is within the bounds of a size constraint.
+    pub fn allows_size(&self, n: usize) -> bool {
+        let (lb, ub) = self.size_bounds();
+        lb <= n as i64 && ub.is_none_or(|ub| n as i64 <= ub)
+    }
 }
 
 /// A pair of `Constraint`s that describes the constraints on the value (if applicable) and encoded size of a type.
 /// A value is considered unconstrained if `value` and `size` are both set to `None`.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Constraints {
     pub value: Option<Constraint>,
     pub size: Option<Constraint>,
 }
 
+impl Constraints {
+    /// Check that a size of `n` satisfies the size constraint, if there is one. Every encoding checks sizes this way.
+    pub fn check_size(&self, n: usize) -> bool {
+        self.size.is_none_or(|sz_constr| sz_constr.allows_size(n))
+    }
+
+    /// Get the fixed size described by the size constraint, if there is one and its bounds are equal. Types with a
+    /// fixed size are encoded without a length determinant.
+    pub fn fixed_size(&self) -> Option<usize> {
+        self.size.and_then(|sz_constr| sz_constr.fixed_size()).map(|n| n as usize)
+    }
+}
+
 pub const UNCONSTRAINED: Constraints = Constraints {
     value: None,
     size: None,
@@ -78,10 +120,8 @@ pub const UNCONSTRAINED: Constraints = Constraints {
 /// }
 ///
 /// impl APerElement for Foo {
-///    type Result = Self;
-///    const TAG: u32 = 0xBEEF;
 ///    const CONSTRAINTS: Constraints = UNCONSTRAINED;
-///    fn from_aper(decoder: &mut aper::Decoder, constraints: Constraints) -> Result<Self::Result, aper::DecodeError> {
+///    fn from_aper(decoder: &mut aper::Decoder, constraints: Constraints) -> Result<Self, aper::DecodeError> {
 ///        let bar = BitString::from_aper(decoder , Constraints {
 ///            value: None,
 ///            size: Some(Constraint::new(Some(4), Some(4))),
@@ -159,7 +199,7 @@ pub const UNCONSTRAINED: Constraints = Constraints {
 ///             0 => {
 ///                 let bs = BitString::from_aper(decoder , Constraints {
 ///                     value: None,
-///                     size: Some(Constraint::new(None, Some(4))),
+///                     size: Some(Constraint::new(Some(4), Some(4))),
 ///                 });
 ///                 if bs.is_err() {
 ///                     Err(bs.err().unwrap())
@@ -200,7 +240,7 @@ pub const UNCONSTRAINED: Constraints = Constraints {
 ///                 enc.append(&encode_int(0, Some(0), Some(2)).unwrap());
 ///                 enc.append(&a.to_aper(Constraints {
 ///                     value: None,
-///                     size: Some(Constraint::new(None, Some(4))),
+///                     size: Some(Constraint::new(Some(4), Some(4))),
 ///                 }).unwrap());
 ///             },
 ///             Foo::bar{a: ref a} => {
@@ -0,0 +1,1595 @@
+use serde::{de, ser};
+use std::collections::{HashMap, VecDeque};
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::str;
+use super::*;
+
+// The name of the newtype struct a `Constrained` value is serialized as starts with this, followed by its value and
+// size constraints, e.g. "$asn1::aper::Constrained 0..7 -" for INTEGER (0..7)
+const CONSTRAINED: &str = "$asn1::aper::Constrained";
+
+// Long enough for the prefix and two constraints with bounds of 20 characters
+const NAME_LEN: usize = 128;
+
+const fn push_name(mut name: [u8; NAME_LEN], mut len: usize, s: &[u8]) -> ([u8; NAME_LEN], usize) {
+    let mut i = 0;
+    while i < s.len() {
+        name[len] = s[i];
+        len += 1;
+        i += 1;
+    }
+    (name, len)
+}
+
+const fn push_bound(name: [u8; NAME_LEN], len: usize, bound: Option<i64>, none: &[u8]) -> ([u8; NAME_LEN], usize) {
+    let n = match bound {
+        Some(n) => n,
+        None => return push_name(name, len, none),
+    };
+    let (mut name, mut len) = if n < 0 { push_name(name, len, b"-") } else { (name, len) };
+    let mut digits = [0u8; 20];
+    let mut k = 0;
+    let mut v = n.unsigned_abs();
+    loop {
+        digits[k] = b'0' + (v % 10) as u8;
+        k += 1;
+        v /= 10;
+        if v == 0 {
+            break;
+        }
+    }
+    while k > 0 {
+        k -= 1;
+        name[len] = digits[k];
+        len += 1;
+    }
+    (name, len)
+}
+
+const fn push_constraint(name: [u8; NAME_LEN], len: usize, constraint: Option<Constraint>) -> ([u8; NAME_LEN], usize) {
+    match constraint {
+        Some(constraint) => {
+            let (name, len) = push_name(name, len, b" ");
+            let (name, len) = push_bound(name, len, constraint.min, b"MIN");
+            let (name, len) = push_name(name, len, b"..");
+            push_bound(name, len, constraint.max, b"MAX")
+        }
+        None => push_name(name, len, b" -"),
+    }
+}
+
+/// The name of the newtype struct a `Constrained<T, C>` is serialized as, which carries `C::CONSTRAINTS`.
+struct Name<C>(PhantomData<C>);
+
+impl<C: Constrain> Name<C> {
+    const ENCODED: ([u8; NAME_LEN], usize) = {
+        let (name, len) = push_name([0; NAME_LEN], 0, CONSTRAINED.as_bytes());
+        let (name, len) = push_constraint(name, len, C::CONSTRAINTS.value);
+        push_constraint(name, len, C::CONSTRAINTS.size)
+    };
+    const NAME: &'static str = match str::from_utf8(Self::ENCODED.0.split_at(Self::ENCODED.1).0) {
+        Ok(name) => name,
+        Err(_) => panic!("the name of a constrained value isn't UTF-8"),
+    };
+}
+
+/// Get the constraints carried by the name of a newtype struct, if it is that of a `Constrained` value.
+fn constraints_of(name: &str) -> Option<Constraints> {
+    let mut parts = name.strip_prefix(CONSTRAINED)?.split(' ').skip(1).map(|part| {
+        if part == "-" {
+            return Some(None);
+        }
+        let (min, max) = part.split_once("..")?;
+        let min = if min == "MIN" { None } else { Some(min.parse().ok()?) };
+        let max = if max == "MAX" { None } else { Some(max.parse().ok()?) };
+        Some(Some(Constraint::new(min, max)))
+    });
+    Some(Constraints {
+        value: parts.next()??,
+        size: parts.next()??,
+    })
+}
+
+/// An error returned by the aligned PER [Serializer](struct.Serializer.html) or
+/// [Deserializer](struct.Deserializer.html).
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    Decode(DecodeError),
+    Encode(EncodeError),
+    /// An error reported by a `Serialize` or `Deserialize` implementation.
+    Message(String),
+    // Decoding starts over now that the OPTIONAL fields of a struct are known
+    #[doc(hidden)]
+    Restart,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Decode(ref e) => write!(f, "aligned PER decode error: {:?}", e),
+            Error::Encode(ref e) => write!(f, "aligned PER encode error: {:?}", e),
+            Error::Message(ref msg) => f.write_str(msg),
+            Error::Restart => f.write_str("aligned PER decoding restarted"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+/// The PER-visible constraints of a [Constrained](struct.Constrained.html) value.
+pub trait Constrain {
+    const CONSTRAINTS: Constraints;
+}
+
+/// A value of type `T` with the PER-visible constraints `C::CONSTRAINTS`, for use as the type of a field.
+///
+/// The value constraint limits an integer, or gives the range of the indexes of the variants of an enum, and the size
+/// constraint limits the length of a string, a byte array or a sequence. With any other serializer than the aligned
+/// PER one, a `Constrained<T, C>` is serialized and deserialized as a `T`.
+///
+/// # Examples
+///
+/// ```
+/// // Foo ::= SEQUENCE {
+/// //     a INTEGER (0..7),
+/// //     b SEQUENCE (SIZE (1..4)) OF BOOLEAN
+/// // }
+/// extern crate asn1;
+/// #[macro_use]
+/// extern crate serde_derive;
+/// use asn1::aper::{Constraint, Constraints};
+/// use asn1::aper::serde::{self, Constrain, Constrained};
+///
+/// struct A;
+/// impl Constrain for A {
+///     const CONSTRAINTS: Constraints = Constraints {
+///         value: Some(Constraint::new(Some(0), Some(7))),
+///         size: None,
+///     };
+/// }
+///
+/// struct B;
+/// impl Constrain for B {
+///     const CONSTRAINTS: Constraints = Constraints {
+///         value: None,
+///         size: Some(Constraint::new(Some(1), Some(4))),
+///     };
+/// }
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Foo {
+///     a: Constrained<u8, A>,
+///     b: Constrained<Vec<bool>, B>,
+/// }
+///
+/// let foo = Foo {
+///     a: Constrained::new(5),
+///     b: Constrained::new(vec![true, false]),
+/// };
+/// println!("{:?}", serde::encode(&foo).unwrap().bytes()); // Prints [168]
+/// ```
+pub struct Constrained<T, C: Constrain> {
+    pub value: T,
+    constraints: PhantomData<C>,
+}
+
+impl<T, C: Constrain> Constrained<T, C> {
+    /// Construct a new `Constrained` value.
+    pub fn new(value: T) -> Constrained<T, C> {
+        Constrained {
+            value,
+            constraints: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, C: Constrain> fmt::Debug for Constrained<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: Clone, C: Constrain> Clone for Constrained<T, C> {
+    fn clone(&self) -> Self {
+        Constrained::new(self.value.clone())
+    }
+}
+
+impl<T: PartialEq, C: Constrain> PartialEq for Constrained<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T, C: Constrain> From<T> for Constrained<T, C> {
+    fn from(value: T) -> Self {
+        Constrained::new(value)
+    }
+}
+
+impl<T: ser::Serialize, C: Constrain> ser::Serialize for Constrained<T, C> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(Name::<C>::NAME, &self.value)
+    }
+}
+
+impl<'de, T: de::Deserialize<'de>, C: Constrain> de::Deserialize<'de> for Constrained<T, C> {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(Name::<C>::NAME, ConstrainedVisitor(PhantomData))
+    }
+}
+
+struct ConstrainedVisitor<T, C>(PhantomData<(T, C)>);
+
+impl<'de, T: de::Deserialize<'de>, C: Constrain> de::Visitor<'de> for ConstrainedVisitor<T, C> {
+    type Value = Constrained<T, C>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a constrained value")
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let ret = T::deserialize(deserializer);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(Constrained::new(ret.unwrap()))
+    }
+}
+
+/// Encode the number `n` of items limited by `size` and the items, like the length of an OCTET STRING if `octets` is
+/// set and like that of a SEQUENCE OF otherwise. `items(start, end)` should encode items `start..end`.
+fn encode_counted<F>(n: usize, size: Option<Constraint>, octets: bool, items: F) -> Result<Encoding, EncodeError>
+    where F: Fn(usize, usize) -> Result<Encoding, EncodeError>
+{
+    let (lb, ub) = match size {
+        Some(sz_constr) => sz_constr.size_bounds(),
+        None => (0, None),
+    };
+    if (n as i64) < lb || ub.is_some_and(|ub| n as i64 > ub) {
+        return Err(EncodeError::InvalidSize);
+    }
+
+    let mut enc = match ub {
+        Some(ub) if ub < 65536 && ub == lb => Encoding::new(),
+        Some(ub) if ub < 65536 => {
+            let ret = encode_int(n as i64, Some(lb), Some(ub));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap()
+        }
+        _ => return encode_fragmented(n, items),
+    };
+    // The octets are octet-aligned unless there are at most two of them
+    if octets && (Some(lb) != ub || lb > 2) {
+        enc.align();
+    }
+
+    let ret = items(0, n);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let ret = enc.append(&ret.unwrap());
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(enc)
+}
+
+/// Decode the number of items limited by `size`. See `encode_counted`. Returns the number of items that follow and
+/// whether more fragments follow them.
+fn decode_count(decoder: &mut Decoder, size: Option<Constraint>, octets: bool) -> Result<(usize, bool), DecodeError> {
+    let (lb, ub) = match size {
+        Some(sz_constr) => sz_constr.size_bounds(),
+        None => (0, None),
+    };
+    let n = match ub {
+        Some(ub) if ub < 65536 && ub == lb => ub,
+        Some(ub) if ub < 65536 => {
+            let ret = decoder.decode_int(Some(lb), Some(ub));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap()
+        }
+        _ => return decoder.decode_fragment_length(),
+    };
+    if octets && (Some(lb) != ub || lb > 2) {
+        decoder.align();
+    }
+    Ok((n as usize, false))
+}
+
+fn check_count(n: usize, size: Option<Constraint>) -> Result<(), Error> {
+    match size.map(|sz_constr| sz_constr.size_bounds()) {
+        Some((lb, ub)) if (n as i64) < lb || ub.is_some_and(|ub| n as i64 > ub) => {
+            Err(Error::Decode(DecodeError::InvalidSize))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A serde `Serializer` that encodes values with aligned PER.
+///
+/// The serde data model maps to ASN.1 types as follows:
+///
+/// | Rust                                  | ASN.1                                                            |
+/// |---------------------------------------|------------------------------------------------------------------|
+/// | `bool`                                | BOOLEAN                                                          |
+/// | `i8`, `i16`, `i32`, `u8`, `u16`, `u32` | INTEGER constrained to the range of the type                    |
+/// | `i64`, `u64`                          | INTEGER, INTEGER (0..MAX)                                        |
+/// | `f32`, `f64`                          | REAL                                                             |
+/// | `char`, `String`                      | UTF8String                                                       |
+/// | byte arrays                           | OCTET STRING                                                     |
+/// | `()`, unit structs                    | NULL                                                             |
+/// | `Option<T>` fields of structs         | OPTIONAL components, with their presence bits in the preamble   |
+/// | other `Option<T>`                     | SEQUENCE { value T OPTIONAL }, i.e. a presence bit and the value |
+/// | structs and tuples                    | SEQUENCE                                                         |
+/// | `Vec<T>` and other sequences          | SEQUENCE OF                                                      |
+/// | maps                                  | SEQUENCE OF SEQUENCE { key K, value V }                          |
+/// | enums                                 | CHOICE or ENUMERATED                                             |
+///
+/// Constraints are given with [Constrained](struct.Constrained.html). The index of the variant of an enum is a
+/// constrained whole number, so an enum must be `Constrained` to the range of the indexes of its variants for the
+/// `Serializer`, which can't tell how many there are.
+pub struct Serializer {
+    output: Encoding,
+    // The constraints of the next value
+    constraints: Constraints,
+    // Whether the next value is a field of a struct, and if so, its presence bit when it is an `Option`
+    component: bool,
+    presence: Option<bool>,
+}
+
+impl Default for Serializer {
+    fn default() -> Serializer {
+        Serializer::new()
+    }
+}
+
+impl Serializer {
+    /// Construct a new `Serializer` with an empty output.
+    pub fn new() -> Serializer {
+        Serializer {
+            output: Encoding::new(),
+            constraints: UNCONSTRAINED,
+            component: false,
+            presence: None,
+        }
+    }
+
+    /// Get the encoding of what was serialized.
+    pub fn into_encoding(self) -> Encoding {
+        self.output
+    }
+
+    fn take_constraints(&mut self) -> Constraints {
+        // Only an `Option` can be an OPTIONAL component, so what it contains isn't one
+        self.component = false;
+        let constraints = self.constraints;
+        self.constraints = UNCONSTRAINED;
+        constraints
+    }
+
+    fn append(&mut self, enc: Result<Encoding, EncodeError>) -> Result<(), Error> {
+        if enc.is_err() {
+            return Err(Error::Encode(enc.err().unwrap()));
+        }
+        let ret = self.output.append(&enc.unwrap());
+        if ret.is_err() {
+            return Err(Error::Encode(ret.err().unwrap()));
+        }
+        Ok(())
+    }
+
+    fn int(&mut self, value: i64, min: Option<i64>, max: Option<i64>) -> Result<(), Error> {
+        let (min, max) = match self.take_constraints().value {
+            Some(constraint) => (constraint.min(), constraint.max()),
+            None => (min, max),
+        };
+        if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+            return Err(Error::Encode(EncodeError::InvalidValue));
+        }
+        self.append(encode_int(value, min, max))
+    }
+
+    fn variant(&mut self, index: u32) -> Result<(), Error> {
+        match self.take_constraints().value {
+            Some(constraint) => {
+                let index = index as i64;
+                if constraint.min().is_some_and(|min| index < min) || constraint.max().is_some_and(|max| index > max) {
+                    return Err(Error::Encode(EncodeError::InvalidValue));
+                }
+                self.append(encode_int(index, constraint.min(), constraint.max()))
+            }
+            None => Err(Error::Encode(EncodeError::MissingValueConstraint)),
+        }
+    }
+}
+
+/// Encode `value` with aligned PER. See [Serializer](struct.Serializer.html).
+pub fn encode<T: ser::Serialize + ?Sized>(value: &T) -> Result<Encoding, Error> {
+    let mut serializer = Serializer::new();
+    let ret = value.serialize(&mut serializer);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(serializer.into_encoding())
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = SeqSerializer<'a>;
+    type SerializeStruct = StructSerializer<'a>;
+    type SerializeStructVariant = StructSerializer<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.take_constraints();
+        self.append(v.to_aper(UNCONSTRAINED))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.int(v as i64, Some(i8::MIN as i64), Some(i8::MAX as i64))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.int(v as i64, Some(i16::MIN as i64), Some(i16::MAX as i64))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.int(v as i64, Some(i32::MIN as i64), Some(i32::MAX as i64))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.int(v, None, None)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.int(v as i64, Some(0), Some(u8::MAX as i64))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.int(v as i64, Some(0), Some(u16::MAX as i64))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.int(v as i64, Some(0), Some(u32::MAX as i64))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        if v > i64::MAX as u64 {
+            return Err(Error::Encode(EncodeError::InvalidValue));
+        }
+        self.int(v as i64, Some(0), None)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.take_constraints();
+        self.append(v.to_aper(UNCONSTRAINED))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        let constraints = self.take_constraints();
+        self.append(v.to_string().to_aper(constraints))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        let size = self.take_constraints().size;
+        self.append(encode_counted(v.len(), size, true, |start, end| {
+            Ok(Encoding::with_bytes(v[start..end].to_vec()))
+        }))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        if self.component {
+            self.presence = Some(false);
+        } else {
+            self.output.append_uint(0, 1);
+        }
+        self.take_constraints();
+        Ok(())
+    }
+
+    fn serialize_some<T: ser::Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        // The presence bit of a field goes in the preamble of the struct, and the constraints are those of the value
+        if self.component {
+            self.component = false;
+            self.presence = Some(true);
+        } else {
+            self.output.append_uint(1, 1);
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.take_constraints();
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, index: u32, _: &'static str) -> Result<(), Error> {
+        self.variant(index)
+    }
+
+    fn serialize_newtype_struct<T: ser::Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<(), Error> {
+        if let Some(constraints) = constraints_of(name) {
+            self.constraints = constraints;
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ser::Serialize + ?Sized>(self,
+                                                             _: &'static str,
+                                                             index: u32,
+                                                             _: &'static str,
+                                                             value: &T)
+                                                             -> Result<(), Error> {
+        let ret = self.variant(index);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer<'a>, Error> {
+        let size = self.take_constraints().size;
+        Ok(SeqSerializer {
+            parent: self,
+            size,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, Error> {
+        self.take_constraints();
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, Error> {
+        self.take_constraints();
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, index: u32, _: &'static str, _: usize) -> Result<Self, Error> {
+        let ret = self.variant(index);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SeqSerializer<'a>, Error> {
+        self.serialize_seq(len)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<StructSerializer<'a>, Error> {
+        self.take_constraints();
+        Ok(StructSerializer::new(self))
+    }
+
+    fn serialize_struct_variant(self,
+                                _: &'static str,
+                                index: u32,
+                                _: &'static str,
+                                _: usize)
+                                -> Result<StructSerializer<'a>, Error> {
+        let ret = self.variant(index);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(StructSerializer::new(self))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeTuple for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializes the fields of a struct, which are only written once the presence bits of the OPTIONAL ones are known.
+#[doc(hidden)]
+pub struct StructSerializer<'a> {
+    parent: &'a mut Serializer,
+    preamble: Vec<bool>,
+    fields: Encoding,
+}
+
+impl<'a> StructSerializer<'a> {
+    fn new(parent: &'a mut Serializer) -> StructSerializer<'a> {
+        StructSerializer {
+            parent,
+            preamble: Vec::new(),
+            fields: Encoding::new(),
+        }
+    }
+
+    fn field<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let mut serializer = Serializer::new();
+        serializer.component = true;
+        let ret = value.serialize(&mut serializer);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if let Some(present) = serializer.presence {
+            self.preamble.push(present);
+        }
+        let ret = self.fields.append(&serializer.output);
+        if ret.is_err() {
+            return Err(Error::Encode(ret.err().unwrap()));
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        for present in self.preamble {
+            self.parent.output.append_uint(present as u64, 1);
+        }
+        let ret = self.parent.output.append(&self.fields);
+        if ret.is_err() {
+            return Err(Error::Encode(ret.err().unwrap()));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for StructSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, _: &'static str, value: &T) -> Result<(), Error> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for StructSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, _: &'static str, value: &T) -> Result<(), Error> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+/// Serializes the items of a sequence or map, which are only written when the number of items is known.
+#[doc(hidden)]
+pub struct SeqSerializer<'a> {
+    parent: &'a mut Serializer,
+    size: Option<Constraint>,
+    items: Vec<Encoding>,
+}
+
+impl<'a> SeqSerializer<'a> {
+    fn item<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<Encoding, Error> {
+        let mut serializer = Serializer::new();
+        let ret = value.serialize(&mut serializer);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(serializer.into_encoding())
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        let items = &self.items;
+        let enc = encode_counted(items.len(), self.size, false, |start, end| {
+            let mut enc = Encoding::new();
+            for item in &items[start..end] {
+                let ret = enc.append(item);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+            }
+            Ok(enc)
+        });
+        self.parent.append(enc)
+    }
+}
+
+impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let ret = self.item(value);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        self.items.push(ret.unwrap());
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeMap for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ser::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let ret = self.item(key);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        self.items.push(ret.unwrap());
+        Ok(())
+    }
+
+    fn serialize_value<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let ret = self.item(value);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match self.items.last_mut() {
+            Some(entry) => {
+                let ret = entry.append(&ret.unwrap());
+                if ret.is_err() {
+                    return Err(Error::Encode(ret.err().unwrap()));
+                }
+                Ok(())
+            }
+            None => Err(Error::Message("map value without a key".to_string())),
+        }
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+/// A serde `Deserializer` that decodes values from aligned PER. See [Serializer](struct.Serializer.html) for the
+/// ASN.1 types the Rust types are read as.
+///
+/// The encoding isn't self-describing, so `deserialize_any` isn't supported. When an enum isn't `Constrained`, the
+/// index of its variant is read as a number in `0..n` for `n` variants.
+///
+/// The presence bits of the OPTIONAL fields of a struct come before its fields, but serde only tells which fields are
+/// `Option`s as they are read. So the first time a struct is met, its fields are read from made up values (zeros,
+/// empty strings and sequences, the first variant of an enum) to count its `Option` fields, and then decoding starts
+/// over. Values are read with [decode()](#method.decode), which does this, and a field type must accept the made up
+/// values. Structs are told apart by their names and the names of their fields.
+pub struct Deserializer<'a> {
+    decoder: Decoder<'a>,
+    // The constraints of the next value
+    constraints: Constraints,
+    // The number of `Option` fields of the structs met so far
+    layouts: HashMap<Layout, usize>,
+    // The presence bits of the fields of the current struct, and whether the next value is one of them
+    preamble: VecDeque<bool>,
+    component: bool,
+}
+
+// The name of a struct, or of an enum and the index of the variant, and its fields
+type Layout = (&'static str, Option<u32>, &'static [&'static str]);
+
+impl<'a> Deserializer<'a> {
+    /// Construct a new `Deserializer` of the encoding in `data`.
+    pub fn new(data: &'a [u8]) -> Deserializer<'a> {
+        Deserializer::with_decoder(Decoder::new(data))
+    }
+
+    /// Construct a new `Deserializer` reading from `decoder`, e.g. one set up for CANONICAL-PER.
+    pub fn with_decoder(decoder: Decoder<'a>) -> Deserializer<'a> {
+        Deserializer {
+            decoder,
+            constraints: UNCONSTRAINED,
+            layouts: HashMap::new(),
+            preamble: VecDeque::new(),
+            component: false,
+        }
+    }
+
+    /// Decode the next `T`.
+    pub fn decode<T: de::Deserialize<'a>>(&mut self) -> Result<T, Error> {
+        let start = self.decoder.clone();
+        loop {
+            match T::deserialize(&mut *self) {
+                Err(Error::Restart) => {
+                    self.decoder = start.clone();
+                    self.constraints = UNCONSTRAINED;
+                    self.preamble.clear();
+                    self.component = false;
+                }
+                ret => return ret,
+            }
+        }
+    }
+
+    fn take_constraints(&mut self) -> Constraints {
+        self.component = false;
+        let constraints = self.constraints;
+        self.constraints = UNCONSTRAINED;
+        constraints
+    }
+
+    fn int(&mut self, min: Option<i64>, max: Option<i64>) -> Result<i64, Error> {
+        let (min, max) = match self.take_constraints().value {
+            Some(constraint) => (constraint.min(), constraint.max()),
+            None => (min, max),
+        };
+        let ret = self.decoder.decode_int(min, max);
+        if ret.is_err() {
+            return Err(Error::Decode(ret.err().unwrap()));
+        }
+        let value = ret.unwrap();
+        if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+            return Err(Error::Decode(DecodeError::MalformedInt));
+        }
+        Ok(value)
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let constraints = self.take_constraints();
+        let ret = String::from_aper(&mut self.decoder, constraints);
+        if ret.is_err() {
+            return Err(Error::Decode(ret.err().unwrap()));
+        }
+        Ok(ret.unwrap())
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, Error> {
+        let size = self.take_constraints().size;
+        let mut content: Vec<u8> = Vec::new();
+        loop {
+            let ret = decode_count(&mut self.decoder, size, true);
+            if ret.is_err() {
+                return Err(Error::Decode(ret.err().unwrap()));
+            }
+            let (n, more) = ret.unwrap();
+            let ret = self.decoder.read_to_vec(&mut content, 8 * n);
+            if ret.is_err() {
+                return Err(Error::Decode(ret.err().unwrap()));
+            }
+            if !more {
+                break;
+            }
+        }
+        let ret = check_count(content.len(), size);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(content)
+    }
+
+    // Read the fields of a struct, after the presence bits of its `Option` fields
+    fn fields<V: de::Visitor<'a>>(&mut self, layout: Layout, len: usize, visitor: V) -> Result<V::Value, Error> {
+        let optional = match self.layouts.get(&layout) {
+            Some(&optional) => optional,
+            None => {
+                let mut optional = 0;
+                let ret = visitor.visit_seq(ProbeFields {
+                    left: len,
+                    optional: Some(&mut optional),
+                });
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                self.layouts.insert(layout, optional);
+                return Err(Error::Restart);
+            }
+        };
+        let mut preamble = VecDeque::with_capacity(optional);
+        for _ in 0..optional {
+            let ret = self.decoder.read(1);
+            if ret.is_err() {
+                return Err(Error::Decode(ret.err().unwrap()));
+            }
+            preamble.push_back(ret.unwrap() == 1);
+        }
+        let outer = mem::replace(&mut self.preamble, preamble);
+        let ret = visitor.visit_seq(Fields {
+            de: self,
+            left: len,
+            components: true,
+        });
+        let preamble = mem::replace(&mut self.preamble, outer);
+        if ret.is_ok() && !preamble.is_empty() {
+            return Err(Error::Message("fewer Option fields than presence bits".to_string()));
+        }
+        ret
+    }
+}
+
+/// Decode a `T` from its aligned PER encoding `data`. See [Deserializer](struct.Deserializer.html).
+pub fn decode<'a, T: de::Deserialize<'a>>(data: &'a [u8]) -> Result<T, Error> {
+    Deserializer::new(data).decode()
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::Decode(DecodeError::NotImplemented))
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.take_constraints();
+        let ret = bool::from_aper(&mut self.decoder, UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(Error::Decode(ret.err().unwrap()));
+        }
+        visitor.visit_bool(ret.unwrap())
+    }
+
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.int(Some(i8::MIN as i64), Some(i8::MAX as i64));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_i64(ret.unwrap())
+    }
+
+    fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.int(Some(i16::MIN as i64), Some(i16::MAX as i64));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_i64(ret.unwrap())
+    }
+
+    fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.int(Some(i32::MIN as i64), Some(i32::MAX as i64));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_i64(ret.unwrap())
+    }
+
+    fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.int(None, None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_i64(ret.unwrap())
+    }
+
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.int(Some(0), Some(u8::MAX as i64));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_i64(ret.unwrap())
+    }
+
+    fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.int(Some(0), Some(u16::MAX as i64));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_i64(ret.unwrap())
+    }
+
+    fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.int(Some(0), Some(u32::MAX as i64));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_i64(ret.unwrap())
+    }
+
+    fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.int(Some(0), None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_i64(ret.unwrap())
+    }
+
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.take_constraints();
+        let ret = f64::from_aper(&mut self.decoder, UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(Error::Decode(ret.err().unwrap()));
+        }
+        visitor.visit_f64(ret.unwrap())
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.string();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let s = ret.unwrap();
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Error::Decode(DecodeError::InvalidSize)),
+        }
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.string();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_string(ret.unwrap())
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let ret = self.bytes();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        visitor.visit_byte_buf(ret.unwrap())
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // The presence bit of a field is in the preamble of the struct
+        let present = if self.component {
+            self.component = false;
+            match self.preamble.pop_front() {
+                Some(present) => present,
+                None => return Err(Error::Message("more Option fields than presence bits".to_string())),
+            }
+        } else {
+            let ret = self.decoder.read(1);
+            if ret.is_err() {
+                return Err(Error::Decode(ret.err().unwrap()));
+            }
+            ret.unwrap() == 1
+        };
+        if !present {
+            self.take_constraints();
+            return visitor.visit_none();
+        }
+        // The constraints are those of the value
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.take_constraints();
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(self,
+                                                       name: &'static str,
+                                                       visitor: V)
+                                                       -> Result<V::Value, Error> {
+        if let Some(constraints) = constraints_of(name) {
+            self.constraints = constraints;
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let size = self.take_constraints().size;
+        let ret = decode_count(&mut self.decoder, size, false);
+        if ret.is_err() {
+            return Err(Error::Decode(ret.err().unwrap()));
+        }
+        let (left, more) = ret.unwrap();
+        visitor.visit_seq(Items {
+            de: self,
+            size,
+            left,
+            more,
+            count: left,
+        })
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.take_constraints();
+        visitor.visit_seq(Fields {
+            de: self,
+            left: len,
+            components: false,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(self,
+                                                     _: &'static str,
+                                                     len: usize,
+                                                     visitor: V)
+                                                     -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let size = self.take_constraints().size;
+        let ret = decode_count(&mut self.decoder, size, false);
+        if ret.is_err() {
+            return Err(Error::Decode(ret.err().unwrap()));
+        }
+        let (left, more) = ret.unwrap();
+        visitor.visit_map(Items {
+            de: self,
+            size,
+            left,
+            more,
+            count: left,
+        })
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(self,
+                                               name: &'static str,
+                                               fields: &'static [&'static str],
+                                               visitor: V)
+                                               -> Result<V::Value, Error> {
+        self.take_constraints();
+        self.fields((name, None, fields), fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(self,
+                                             name: &'static str,
+                                             variants: &'static [&'static str],
+                                             visitor: V)
+                                             -> Result<V::Value, Error> {
+        if variants.is_empty() {
+            return Err(Error::Decode(DecodeError::InvalidChoice));
+        }
+        let ret = self.int(Some(0), Some(variants.len() as i64 - 1));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let index = ret.unwrap();
+        if index < 0 || index as usize >= variants.len() {
+            return Err(Error::Decode(DecodeError::InvalidChoice));
+        }
+        visitor.visit_enum(Variant {
+            de: self,
+            name,
+            index: index as u32,
+        })
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::Decode(DecodeError::NotImplemented))
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::Decode(DecodeError::NotImplemented))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// A fixed number of values, e.g. the fields of a struct.
+struct Fields<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    left: usize,
+    // Whether the values are the fields of a struct, whose presence bits are in its preamble
+    components: bool,
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for Fields<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        self.de.component = self.components;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.left)
+    }
+}
+
+/// The items of a sequence or map, which may be in several fragments.
+struct Items<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    size: Option<Constraint>,
+    // The number of items left in the current fragment, and whether more fragments follow it
+    left: usize,
+    more: bool,
+    count: usize,
+}
+
+impl<'a, 'de> Items<'a, 'de> {
+    fn next(&mut self) -> Result<bool, Error> {
+        while self.left == 0 {
+            if !self.more {
+                let ret = check_count(self.count, self.size);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                return Ok(false);
+            }
+            let ret = self.de.decoder.decode_fragment_length();
+            if ret.is_err() {
+                return Err(Error::Decode(ret.err().unwrap()));
+            }
+            let (left, more) = ret.unwrap();
+            self.left = left;
+            self.more = more;
+            self.count += left;
+        }
+        self.left -= 1;
+        Ok(true)
+    }
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for Items<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        let ret = self.next();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if !ret.unwrap() {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+impl<'a, 'de> de::MapAccess<'de> for Items<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        let ret = self.next();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if !ret.unwrap() {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+/// The chosen variant of an enum.
+struct Variant<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    name: &'static str,
+    index: u32,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for Variant<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let ret = seed.deserialize(de::value::U32Deserializer::<Error>::new(self.index));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok((ret.unwrap(), self))
+    }
+}
+
+impl<'a, 'de> de::VariantAccess<'de> for Variant<'a, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Fields {
+            de: self.de,
+            left: len,
+            components: false,
+        })
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(self,
+                                           fields: &'static [&'static str],
+                                           visitor: V)
+                                           -> Result<V::Value, Error> {
+        self.de.fields((self.name, Some(self.index), fields), fields.len(), visitor)
+    }
+}
+
+/// Made up values, read to find the `Option` fields of a struct.
+struct Probe<'a> {
+    // The count of `Option` fields, if the value is a field of the struct being probed
+    optional: Option<&'a mut usize>,
+}
+
+impl<'a, 'de> de::Deserializer<'de> for Probe<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(0)
+    }
+
+    fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(0)
+    }
+
+    fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(0)
+    }
+
+    fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(0)
+    }
+
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(0)
+    }
+
+    fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(0)
+    }
+
+    fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(0)
+    }
+
+    fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(0)
+    }
+
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(0.0)
+    }
+
+    fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_char('0')
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(String::new())
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bytes(&[])
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_byte_buf(Vec::new())
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if let Some(optional) = self.optional {
+            *optional += 1;
+        }
+        visitor.visit_none()
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(self,
+                                                       _: &'static str,
+                                                       visitor: V)
+                                                       -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(ProbeFields {
+            left: 0,
+            optional: None,
+        })
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(ProbeFields {
+            left: len,
+            optional: None,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(self,
+                                                     _: &'static str,
+                                                     len: usize,
+                                                     visitor: V)
+                                                     -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(de::value::MapDeserializer::new(std::iter::empty::<((), ())>()))
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(self,
+                                               _: &'static str,
+                                               fields: &'static [&'static str],
+                                               visitor: V)
+                                               -> Result<V::Value, Error> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(self,
+                                             _: &'static str,
+                                             _: &'static [&'static str],
+                                             visitor: V)
+                                             -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(0)
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for Probe<'a> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let ret = seed.deserialize(de::value::U32Deserializer::<Error>::new(0));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok((ret.unwrap(), Probe { optional: None }))
+    }
+}
+
+impl<'a, 'de> de::VariantAccess<'de> for Probe<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(self,
+                                           fields: &'static [&'static str],
+                                           visitor: V)
+                                           -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+/// The made up fields of a struct, or elements of a tuple.
+struct ProbeFields<'a> {
+    left: usize,
+    optional: Option<&'a mut usize>,
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for ProbeFields<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        seed.deserialize(Probe {
+            optional: self.optional.as_deref_mut(),
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.left)
+    }
+}
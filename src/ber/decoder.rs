@@ -0,0 +1,564 @@
+use super::*;
+
+// Limits the recursion needed to find the end of nested indefinite-length elements
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    ConstructedDefiniteLength,
+    ConstructedString,
+    DefaultValueEncoded,
+    IndefiniteLength,
+    InvalidBool,
+    InvalidCharacter,
+    InvalidLength,
+    InvalidOid,
+    InvalidReal,
+    InvalidSegment,
+    InvalidTime,
+    InvalidUnusedBits,
+    InvalidUtf8,
+    MalformedIdentifier,
+    MalformedInt,
+    MalformedLength,
+    NestingTooDeep,
+    NonCanonicalReal,
+    NonCanonicalTime,
+    NonMinimalInt,
+    NonMinimalLength,
+    NonZeroUnusedBits,
+    NotEnoughBytes,
+    TrailingData,
+    TrailingZeroBits,
+    UnexpectedForm,
+    UnexpectedTag,
+    UnsegmentedString,
+    UnsortedSet,
+    UnsortedSetOf,
+}
+
+/// An element read by a [Decoder](struct.Decoder.html): its identifier and contents octets. The contents of an
+/// indefinite-length element don't include the end-of-contents element. Use
+/// [Decoder::sub_decoder](struct.Decoder.html#method.sub_decoder) to read the elements in the contents of a
+/// constructed element.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Element<'a> {
+    pub tag: Tag,
+    pub constructed: bool,
+    pub indefinite: bool,
+    pub contents: &'a [u8],
+}
+
+/// A cursor used to decode BER messages.
+///
+/// A `Decoder` accepts anything BER allows unless it is set to check the input against a canonical subset of BER
+/// (see [set_rules()](#method.set_rules)), in which case an encoding that isn't canonical is an `Err` naming the
+/// problem.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::ber::{self, BerElement};
+///
+/// let data = b"\x02\x01\x2b"; // 43
+/// let mut d = ber::Decoder::new(data);
+/// let x = i16::from_ber(&mut d, i16::TAG).unwrap();
+/// println!("x = {}", x); // Prints x = 43
+/// ```
+pub struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    rules: Rules,
+}
+
+impl<'a> Decoder<'a> {
+    /// Construct a new `Decoder` with an array of bytes.
+    pub fn new(data: &'a [u8]) -> Decoder<'a> {
+        Decoder {
+            data,
+            pos: 0,
+            rules: Rules::Ber,
+        }
+    }
+
+    /// Construct a `Decoder` for the contents of a constructed element found by this one, with the same rules.
+    pub fn sub_decoder<'b>(&self, data: &'b [u8]) -> Decoder<'b> {
+        let mut decoder = Decoder::new(data);
+        decoder.rules = self.rules;
+        decoder
+    }
+
+    /// Set the rules the input is checked against.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    /// Get the rules the input is checked against.
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// Get the number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Check if all of the bytes have been read.
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    /// Returns an `Err` if there are bytes left to read, e.g. after the last component of a SEQUENCE.
+    pub fn finish(&self) -> Result<(), DecodeError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::TrailingData)
+        }
+    }
+
+    fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        if self.pos >= self.data.len() {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        self.pos += 1;
+        Ok(self.data[self.pos - 1])
+    }
+
+    /// Read the identifier octets of an element. Returns the tag and whether the element is constructed.
+    pub fn read_identifier(&mut self) -> Result<(Tag, bool), DecodeError> {
+        let ret = self.read_u8();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let b = ret.unwrap();
+        let class = match b >> 6 {
+            0 => Class::Universal,
+            1 => Class::Application,
+            2 => Class::ContextSpecific,
+            _ => Class::Private,
+        };
+        let constructed = b & 0x20 != 0;
+        if b & 0x1f != 0x1f {
+            return Ok((Tag::new(class, (b & 0x1f) as u32), constructed));
+        }
+
+        // High tag number form: base 128, most significant group first, without leading zero groups
+        let mut number: u32 = 0;
+        loop {
+            let ret = self.read_u8();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let b = ret.unwrap();
+            if number == 0 && b == 0x80 || number > u32::MAX >> 7 {
+                return Err(DecodeError::MalformedIdentifier);
+            }
+            number = (number << 7) | (b & 0x7f) as u32;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        if number < 0x1f {
+            return Err(DecodeError::MalformedIdentifier);
+        }
+        Ok((Tag::new(class, number), constructed))
+    }
+
+    /// Read the length octets of an element. A definite length is checked against the number of bytes left.
+    pub fn read_length(&mut self) -> Result<Length, DecodeError> {
+        let ret = self.read_u8();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let b = ret.unwrap();
+        let len = match b {
+            0x80 if self.rules == Rules::Der => return Err(DecodeError::IndefiniteLength),
+            0x80 => return Ok(Length::Indefinite),
+            0xff => return Err(DecodeError::MalformedLength),
+            b if b < 0x80 => b as usize,
+            b => {
+                let mut len: usize = 0;
+                for _ in 0..b & 0x7f {
+                    let ret = self.read_u8();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    if len > usize::MAX >> 8 {
+                        return Err(DecodeError::MalformedLength);
+                    }
+                    len = (len << 8) | ret.unwrap() as usize;
+                }
+                // The short form must be used if possible, and the long form can't have a leading zero octet
+                let n = (usize::BITS - len.leading_zeros()).div_ceil(8);
+                if self.rules != Rules::Ber && (len < 0x80 || n != (b & 0x7f) as u32) {
+                    return Err(DecodeError::NonMinimalLength);
+                }
+                len
+            }
+        };
+        if len > self.data.len() - self.pos {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        Ok(Length::Definite(len))
+    }
+
+    /// Get the tag of the next element without reading it, e.g. to check for an optional component or pick the
+    /// alternative of a CHOICE.
+    pub fn peek_tag(&self) -> Result<Tag, DecodeError> {
+        let mut d = Decoder {
+            data: self.data,
+            pos: self.pos,
+            rules: self.rules,
+        };
+        let ret = d.read_identifier();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(ret.unwrap().0)
+    }
+
+    /// Read the next element, whatever its tag.
+    pub fn read_element(&mut self) -> Result<Element<'a>, DecodeError> {
+        self.read_element_at(0)
+    }
+
+    fn read_element_at(&mut self, depth: usize) -> Result<Element<'a>, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::NestingTooDeep);
+        }
+        let ret = self.read_identifier();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let (tag, constructed) = ret.unwrap();
+        let ret = self.read_length();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let start = self.pos;
+        match ret.unwrap() {
+            Length::Definite(_) if constructed && self.rules == Rules::Cer => {
+                Err(DecodeError::ConstructedDefiniteLength)
+            }
+            Length::Definite(len) => {
+                self.pos += len;
+                Ok(Element {
+                    tag,
+                    constructed,
+                    indefinite: false,
+                    contents: &self.data[start..self.pos],
+                })
+            }
+            Length::Indefinite => {
+                if !constructed {
+                    return Err(DecodeError::MalformedLength);
+                }
+                // The contents are the elements up to the end-of-contents element, which is two zero octets
+                loop {
+                    if self.data[self.pos..].starts_with(&[0x00, 0x00]) {
+                        break;
+                    }
+                    let ret = self.read_element_at(depth + 1);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+                self.pos += 2;
+                Ok(Element {
+                    tag,
+                    constructed,
+                    indefinite: true,
+                    contents: &self.data[start..self.pos - 2],
+                })
+            }
+        }
+    }
+
+    /// Read the next element, which must have tag `tag`.
+    pub fn decode_element(&mut self, tag: Tag) -> Result<Element<'a>, DecodeError> {
+        let ret = self.read_element();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let element = ret.unwrap();
+        if element.tag != tag {
+            return Err(DecodeError::UnexpectedTag);
+        }
+        Ok(element)
+    }
+
+    /// Read the contents octets of a primitive element with tag `tag`.
+    pub fn decode_primitive(&mut self, tag: Tag) -> Result<&'a [u8], DecodeError> {
+        let ret = self.decode_element(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let element = ret.unwrap();
+        if element.constructed {
+            return Err(DecodeError::UnexpectedForm);
+        }
+        Ok(element.contents)
+    }
+
+    /// Read a constructed element with tag `tag` and return a `Decoder` for its contents.
+    pub fn decode_constructed(&mut self, tag: Tag) -> Result<Decoder<'a>, DecodeError> {
+        let ret = self.decode_element(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let element = ret.unwrap();
+        if !element.constructed {
+            return Err(DecodeError::UnexpectedForm);
+        }
+        Ok(self.sub_decoder(element.contents))
+    }
+
+    /// Read a constructed element with tag `tag` holding the components of a SET and return a `Decoder` for them.
+    /// With CER and DER, the components must be sorted by their tags.
+    pub fn decode_set(&mut self, tag: Tag) -> Result<Decoder<'a>, DecodeError> {
+        let ret = self.decode_constructed(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let d = ret.unwrap();
+        if self.rules == Rules::Ber {
+            return Ok(d);
+        }
+
+        let mut check = self.sub_decoder(d.data);
+        let mut prev = None;
+        while !check.is_empty() {
+            let ret = check.read_element();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let tag = Some(ret.unwrap().tag);
+            if tag < prev {
+                return Err(DecodeError::UnsortedSet);
+            }
+            prev = tag;
+        }
+        Ok(d)
+    }
+
+    /// Read a constructed element with tag `tag` holding the elements of a SET OF and return a `Decoder` for them.
+    /// With CER and DER, the elements must be sorted by their encodings.
+    pub fn decode_set_of(&mut self, tag: Tag) -> Result<Decoder<'a>, DecodeError> {
+        let ret = self.decode_constructed(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let d = ret.unwrap();
+        if self.rules == Rules::Ber {
+            return Ok(d);
+        }
+
+        let mut check = self.sub_decoder(d.data);
+        let mut prev: &[u8] = &[];
+        while !check.is_empty() {
+            let start = check.pos;
+            let ret = check.read_element();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let element = &check.data[start..check.pos];
+            if element < prev {
+                return Err(DecodeError::UnsortedSetOf);
+            }
+            prev = element;
+        }
+        Ok(d)
+    }
+
+    /// Read the octets of a string type with tag `tag`, which may be in the constructed form, i.e. split into
+    /// segments that are each encoded as an OCTET STRING (X.690 8.7.3). DER only allows the primitive form, and CER
+    /// requires the constructed form with 1000-octet segments for strings longer than 1000 octets.
+    pub fn decode_string(&mut self, tag: Tag) -> Result<Vec<u8>, DecodeError> {
+        let ret = self.decode_element(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut content = Vec::new();
+        let ret = self.read_segments(ret.unwrap(),
+                                Tag::OCTET_STRING,
+                                &mut |bytes| {
+                                    content.extend_from_slice(bytes);
+                                    Ok(())
+                                },
+                                0);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(content)
+    }
+
+    /// Read a BIT STRING with tag `tag`, which may be in the constructed form (X.690 8.6.4). Returns the bytes and
+    /// the number of bits. The unused bits must be 0 with CER and DER, which restrict the form as for
+    /// [decode_string()](#method.decode_string).
+    pub fn decode_bit_string(&mut self, tag: Tag) -> Result<(Vec<u8>, usize), DecodeError> {
+        let ret = self.decode_element(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut content = Vec::new();
+        let mut unused = 0;
+        let ret = self.read_segments(ret.unwrap(),
+                                     Tag::BIT_STRING,
+                                     &mut |bytes| {
+                                         // Only the last segment can have unused bits
+                                         if bytes.is_empty() || unused > 0 || bytes[0] > 7 ||
+                                            bytes.len() == 1 && bytes[0] > 0 {
+                                             return Err(DecodeError::InvalidUnusedBits);
+                                         }
+                                         unused = bytes[0] as usize;
+                                         content.extend_from_slice(&bytes[1..]);
+                                         Ok(())
+                                     },
+                                     0);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if self.rules != Rules::Ber && unused > 0 && content[content.len() - 1] & !(0xff << unused) != 0 {
+            return Err(DecodeError::NonZeroUnusedBits);
+        }
+        let n = content.len() * 8 - unused;
+        Ok((content, n))
+    }
+
+    /// Read an INTEGER with tag `tag` that fits in an `i64`. Redundant leading octets are only allowed with BER.
+    pub fn decode_int(&mut self, tag: Tag) -> Result<i64, DecodeError> {
+        let ret = self.decode_primitive(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut bytes = ret.unwrap();
+        if bytes.is_empty() {
+            return Err(DecodeError::MalformedInt);
+        }
+        while bytes.len() > 1 && (bytes[0] == 0x00 && bytes[1] < 0x80 || bytes[0] == 0xff && bytes[1] >= 0x80) {
+            if self.rules != Rules::Ber {
+                return Err(DecodeError::NonMinimalInt);
+            }
+            bytes = &bytes[1..];
+        }
+        if bytes.len() > 8 {
+            return Err(DecodeError::MalformedInt);
+        }
+        let mut value: i64 = if bytes[0] >= 0x80 { -1 } else { 0 };
+        for &b in bytes {
+            value = (value << 8) | b as i64;
+        }
+        Ok(value)
+    }
+
+    /// Read an element explicitly tagged with `tag`, i.e. a constructed element whose only content is the encoding
+    /// of a `T`.
+    pub fn decode_explicit<T: BerElement>(&mut self, tag: Tag) -> Result<T, DecodeError> {
+        let ret = self.decode_constructed(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut d = ret.unwrap();
+        let ret = T::from_ber(&mut d, T::TAG);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        let ret = d.finish();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(value)
+    }
+
+    /// Read an optional component with tag `tag` and a DEFAULT value. Returns `default` if the component is absent.
+    /// With DER, the default value must not be encoded.
+    pub fn decode_default<T: BerElement + PartialEq>(&mut self, tag: Tag, default: T) -> Result<T, DecodeError> {
+        if self.is_empty() || self.peek_tag() != Ok(tag) {
+            return Ok(default);
+        }
+        let ret = T::from_ber(self, tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        if self.rules != Rules::Ber && value == default {
+            return Err(DecodeError::DefaultValueEncoded);
+        }
+        Ok(value)
+    }
+
+    // Pass the contents of each primitive segment of a string type to `f`, in order
+    fn read_segments<F>(&self, element: Element, segment_tag: Tag, f: &mut F, depth: usize) -> Result<(), DecodeError>
+        where F: FnMut(&[u8]) -> Result<(), DecodeError>
+    {
+        if self.rules == Rules::Cer {
+            return self.read_cer_segments(element, segment_tag, f);
+        }
+        if !element.constructed {
+            return f(element.contents);
+        }
+        if self.rules == Rules::Der {
+            return Err(DecodeError::ConstructedString);
+        }
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::NestingTooDeep);
+        }
+        let mut d = self.sub_decoder(element.contents);
+        while !d.is_empty() {
+            let ret = d.decode_element(segment_tag);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = self.read_segments(ret.unwrap(), segment_tag, f, depth + 1);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(())
+    }
+
+    // With CER, a string is segmented if and only if it's longer than 1000 octets, and all of the segments except
+    // the last are exactly 1000 octets
+    fn read_cer_segments<F>(&self, element: Element, segment_tag: Tag, f: &mut F) -> Result<(), DecodeError>
+        where F: FnMut(&[u8]) -> Result<(), DecodeError>
+    {
+        if !element.constructed {
+            if element.contents.len() > CER_SEGMENT_LEN {
+                return Err(DecodeError::UnsegmentedString);
+            }
+            return f(element.contents);
+        }
+
+        let mut d = self.sub_decoder(element.contents);
+        let mut segments = Vec::new();
+        while !d.is_empty() {
+            let ret = d.decode_primitive(segment_tag);
+            if ret.is_err() {
+                return Err(match ret.err().unwrap() {
+                    DecodeError::UnexpectedForm => DecodeError::InvalidSegment,
+                    e => e,
+                });
+            }
+            segments.push(ret.unwrap());
+        }
+        if segments.len() < 2 {
+            return Err(DecodeError::ConstructedString);
+        }
+        let last = segments.len() - 1;
+        if segments[..last].iter().any(|s| s.len() != CER_SEGMENT_LEN) || segments[last].is_empty() ||
+           segments[last].len() > CER_SEGMENT_LEN {
+            return Err(DecodeError::InvalidSegment);
+        }
+        for s in segments {
+            let ret = f(s);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use super::*;
+use bit_string::BitString;
+
+#[derive(Debug, PartialEq)]
+pub enum EncodeError {
+    InvalidValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Contents {
+    Primitive(Vec<u8>),
+    // The contents octets of a string type, which CER splits into segments (X.690 9.2)
+    String(Vec<u8>),
+    // Likewise, including the initial octet with the number of unused bits
+    BitString(Vec<u8>),
+    Constructed(Vec<Encoding>),
+    // The components of a SET, which CER and DER sort by their tags
+    Set(Vec<Encoding>),
+    // The elements of a SET OF, which CER and DER sort by their encodings
+    SetOf(Vec<Encoding>),
+}
+
+/// A BER encoding of an element, kept as a tree so the lengths of constructed elements can be computed when it is
+/// written out with a particular set of [Rules](enum.Rules.html).
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::ber::{BerElement, Encoding, Tag};
+///
+/// // SEQUENCE { [0] EXPLICIT BOOLEAN }
+/// let field = Encoding::explicit(Tag::context(0), true.to_ber(Tag::BOOLEAN).unwrap());
+/// let enc = Encoding::constructed(Tag::SEQUENCE, vec![field]);
+/// println!("enc = {:x?}", enc.bytes()); // Prints enc = [30, 5, a0, 3, 1, 1, ff]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encoding {
+    tag: Tag,
+    contents: Contents,
+    indefinite: bool,
+}
+
+impl Encoding {
+    /// Construct a primitive element with tag `tag`.
+    pub fn primitive(tag: Tag, contents: Vec<u8>) -> Encoding {
+        Encoding {
+            tag,
+            contents: Contents::Primitive(contents),
+            indefinite: false,
+        }
+    }
+
+    /// Construct an element with tag `tag` holding the octets of a string type (e.g. an OCTET STRING). It is
+    /// primitive, except with CER, which splits strings longer than 1000 octets into segments.
+    pub fn string(tag: Tag, bytes: Vec<u8>) -> Encoding {
+        Encoding {
+            tag,
+            contents: Contents::String(bytes),
+            indefinite: false,
+        }
+    }
+
+    /// Construct an element with tag `tag` holding a BIT STRING. It is primitive, except with CER, which splits long
+    /// bit strings into segments.
+    pub fn bit_string(tag: Tag, bits: &BitString) -> Encoding {
+        let bytes = bits.to_bytes();
+        let mut contents = Vec::with_capacity(bytes.len() + 1);
+        contents.push((bytes.len() * 8 - bits.get_num_bits()) as u8);
+        contents.extend_from_slice(&bytes);
+        Encoding {
+            tag,
+            contents: Contents::BitString(contents),
+            indefinite: false,
+        }
+    }
+
+    /// Construct a constructed element with tag `tag` whose contents are `elements`.
+    pub fn constructed(tag: Tag, elements: Vec<Encoding>) -> Encoding {
+        Encoding {
+            tag,
+            contents: Contents::Constructed(elements),
+            indefinite: false,
+        }
+    }
+
+    /// Construct a constructed element with tag `tag` whose contents are the components of a SET. When written with
+    /// CER or DER, the components are sorted by their tags. (This is the canonical order of X.680 8.6 unless there
+    /// is an untagged CHOICE component, which is ordered by the smallest tag of its alternatives.)
+    pub fn set(tag: Tag, elements: Vec<Encoding>) -> Encoding {
+        Encoding {
+            tag,
+            contents: Contents::Set(elements),
+            indefinite: false,
+        }
+    }
+
+    /// Construct a constructed element with tag `tag` whose contents are the `elements` of a SET OF. When written
+    /// with CER or DER, the elements are sorted by their encodings.
+    pub fn set_of(tag: Tag, elements: Vec<Encoding>) -> Encoding {
+        Encoding {
+            tag,
+            contents: Contents::SetOf(elements),
+            indefinite: false,
+        }
+    }
+
+    /// Explicitly tag `inner` with `tag`.
+    pub fn explicit(tag: Tag, inner: Encoding) -> Encoding {
+        Encoding::constructed(tag, vec![inner])
+    }
+
+    /// Use the indefinite form of the length octets when writing a constructed element with BER. This has no effect
+    /// on primitive elements, which always have a definite length, or when writing with CER or DER, which always use
+    /// the indefinite and definite forms respectively.
+    pub fn set_indefinite(&mut self, indefinite: bool) {
+        self.indefinite = indefinite;
+    }
+
+    /// Get the tag.
+    pub fn tag(&self) -> Tag {
+        self.tag
+    }
+
+    /// Check if the element is constructed. (Strings are primitive here even if CER would segment them.)
+    pub fn is_constructed(&self) -> bool {
+        self.elements().is_some()
+    }
+
+    /// Get the contents octets of a primitive element.
+    pub fn contents(&self) -> Option<&[u8]> {
+        match self.contents {
+            Contents::Primitive(ref bytes) |
+            Contents::String(ref bytes) |
+            Contents::BitString(ref bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Get the elements in the contents of a constructed element.
+    pub fn elements(&self) -> Option<&[Encoding]> {
+        match self.contents {
+            Contents::Constructed(ref elements) | Contents::Set(ref elements) | Contents::SetOf(ref elements) => {
+                Some(elements)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the bytes of the encoding, written with BER.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.to_bytes(Rules::Ber)
+    }
+
+    /// Get the bytes of the encoding, written with `rules`.
+    pub fn to_bytes(&self, rules: Rules) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes, rules);
+        bytes
+    }
+
+    /// Append the bytes of the encoding, written with `rules`, to `bytes`.
+    pub fn write_to(&self, bytes: &mut Vec<u8>, rules: Rules) {
+        let elements = match self.contents {
+            Contents::String(ref contents) if rules == Rules::Cer && contents.len() > CER_SEGMENT_LEN => {
+                let segments = contents.chunks(CER_SEGMENT_LEN)
+                    .map(|s| Encoding::primitive(Tag::OCTET_STRING, s.to_vec()))
+                    .collect();
+                return Encoding::constructed(self.tag, segments).write_to(bytes, rules);
+            }
+            Contents::BitString(ref contents) if rules == Rules::Cer && contents.len() > CER_SEGMENT_LEN => {
+                // Each segment has its own initial octet, which is 0 except in the last one
+                let data = &contents[1..];
+                let n = data.len().div_ceil(CER_SEGMENT_LEN - 1);
+                let segments = data.chunks(CER_SEGMENT_LEN - 1)
+                    .enumerate()
+                    .map(|(i, s)| {
+                        let mut segment = vec![if i == n - 1 { contents[0] } else { 0 }];
+                        segment.extend_from_slice(s);
+                        Encoding::primitive(Tag::BIT_STRING, segment)
+                    })
+                    .collect();
+                return Encoding::constructed(self.tag, segments).write_to(bytes, rules);
+            }
+            Contents::Primitive(ref contents) | Contents::String(ref contents) | Contents::BitString(ref contents) => {
+                write_identifier(bytes, self.tag, false);
+                write_length(bytes, Length::Definite(contents.len()));
+                bytes.extend_from_slice(contents);
+                return;
+            }
+            Contents::Constructed(ref elements) | Contents::Set(ref elements) | Contents::SetOf(ref elements) => {
+                elements
+            }
+        };
+        write_identifier(bytes, self.tag, true);
+
+        let mut encodings: Vec<Vec<u8>> = match self.contents {
+            Contents::Set(_) if rules != Rules::Ber => {
+                let mut elements: Vec<&Encoding> = elements.iter().collect();
+                elements.sort_by_key(|e| e.tag);
+                elements.iter().map(|e| e.to_bytes(rules)).collect()
+            }
+            _ => elements.iter().map(|e| e.to_bytes(rules)).collect(),
+        };
+        if let Contents::SetOf(_) = self.contents {
+            if rules != Rules::Ber {
+                encodings.sort();
+            }
+        }
+        if self.indefinite && rules == Rules::Ber || rules == Rules::Cer {
+            write_length(bytes, Length::Indefinite);
+            for e in &encodings {
+                bytes.extend_from_slice(e);
+            }
+            bytes.extend_from_slice(&[0x00, 0x00]);
+        } else {
+            write_length(bytes, Length::Definite(encodings.iter().map(|e| e.len()).sum()));
+            for e in &encodings {
+                bytes.extend_from_slice(e);
+            }
+        }
+    }
+}
+
+/// Append the identifier octets of an element to `bytes`. Tag numbers of 31 and above use the high tag number form.
+pub fn write_identifier(bytes: &mut Vec<u8>, tag: Tag, constructed: bool) {
+    let class = match tag.class {
+        Class::Universal => 0x00,
+        Class::Application => 0x40,
+        Class::ContextSpecific => 0x80,
+        Class::Private => 0xc0,
+    };
+    let form = if constructed { 0x20 } else { 0x00 };
+    if tag.number < 0x1f {
+        bytes.push(class | form | tag.number as u8);
+        return;
+    }
+
+    bytes.push(class | form | 0x1f);
+    let n = (32 - tag.number.leading_zeros() as usize).div_ceil(7);
+    for i in (0..n).rev() {
+        let more = if i > 0 { 0x80 } else { 0x00 };
+        bytes.push(more | ((tag.number >> (7 * i)) & 0x7f) as u8);
+    }
+}
+
+/// Append the length octets of an element to `bytes`, in the short form if possible and otherwise in the long form
+/// with as few octets as possible.
+pub fn write_length(bytes: &mut Vec<u8>, len: Length) {
+    let len = match len {
+        Length::Indefinite => {
+            bytes.push(0x80);
+            return;
+        }
+        Length::Definite(len) => len,
+    };
+    if len < 0x80 {
+        bytes.push(len as u8);
+        return;
+    }
+
+    let n = (usize::BITS as usize - len.leading_zeros() as usize).div_ceil(8);
+    bytes.push(0x80 | n as u8);
+    for i in (0..n).rev() {
+        bytes.push((len >> (8 * i)) as u8);
+    }
+}
+
+/// Encode an INTEGER with tag `tag` in as few octets as possible.
+pub fn encode_int(value: i64, tag: Tag) -> Encoding {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < 7 && (bytes[start] == 0x00 && bytes[start + 1] < 0x80 ||
+                        bytes[start] == 0xff && bytes[start + 1] >= 0x80) {
+        start += 1;
+    }
+    Encoding::primitive(tag, bytes[start..].to_vec())
+}
+
+/// Encode a component with a DEFAULT value and tag `tag`. Returns `None` if `value` is the default, in which case it
+/// is omitted, as DER requires.
+pub fn encode_default<T: BerElement + PartialEq>(value: &T,
+                                                 default: &T,
+                                                 tag: Tag)
+                                                 -> Result<Option<Encoding>, EncodeError> {
+    if value == default {
+        return Ok(None);
+    }
+    let ret = value.to_ber(tag);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(Some(ret.unwrap()))
+}
@@ -0,0 +1,167 @@
+mod decoder;
+mod encoding;
+pub use self::decoder::{Decoder, DecodeError, Element};
+pub use self::encoding::{Encoding, EncodeError, encode_default, encode_int, write_identifier, write_length};
+
+/// The class of a tag.
+///
+/// The variants are declared in the canonical order of X.680 8.6, so sorting tags sorts them the way DER orders the
+/// components of a SET.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Class {
+    Universal,
+    Application,
+    ContextSpecific,
+    Private,
+}
+
+/// A tag, i.e. a class and a number.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag {
+    pub class: Class,
+    pub number: u32,
+}
+
+impl Tag {
+    pub const BOOLEAN: Tag = Tag::universal(1);
+    pub const INTEGER: Tag = Tag::universal(2);
+    pub const BIT_STRING: Tag = Tag::universal(3);
+    pub const OCTET_STRING: Tag = Tag::universal(4);
+    pub const NULL: Tag = Tag::universal(5);
+    pub const OBJECT_IDENTIFIER: Tag = Tag::universal(6);
+    pub const REAL: Tag = Tag::universal(9);
+    pub const ENUMERATED: Tag = Tag::universal(10);
+    pub const UTF8_STRING: Tag = Tag::universal(12);
+    pub const RELATIVE_OID: Tag = Tag::universal(13);
+    pub const SEQUENCE: Tag = Tag::universal(16);
+    pub const SET: Tag = Tag::universal(17);
+    pub const NUMERIC_STRING: Tag = Tag::universal(18);
+    pub const PRINTABLE_STRING: Tag = Tag::universal(19);
+    pub const IA5_STRING: Tag = Tag::universal(22);
+    pub const UTC_TIME: Tag = Tag::universal(23);
+    pub const GENERALIZED_TIME: Tag = Tag::universal(24);
+    pub const VISIBLE_STRING: Tag = Tag::universal(26);
+    pub const UNIVERSAL_STRING: Tag = Tag::universal(28);
+    pub const BMP_STRING: Tag = Tag::universal(30);
+
+    /// Construct a new `Tag`.
+    pub const fn new(class: Class, number: u32) -> Tag {
+        Tag { class, number }
+    }
+
+    /// Construct a tag of the universal class.
+    pub const fn universal(number: u32) -> Tag {
+        Tag::new(Class::Universal, number)
+    }
+
+    /// Construct a tag of the application class, e.g. `[APPLICATION 3]`.
+    pub const fn application(number: u32) -> Tag {
+        Tag::new(Class::Application, number)
+    }
+
+    /// Construct a context-specific tag, e.g. `[0]`.
+    pub const fn context(number: u32) -> Tag {
+        Tag::new(Class::ContextSpecific, number)
+    }
+
+    /// Construct a tag of the private class, e.g. `[PRIVATE 7]`.
+    pub const fn private(number: u32) -> Tag {
+        Tag::new(Class::Private, number)
+    }
+}
+
+/// The length of an element: the number of contents octets, or indefinite if the contents are terminated by an
+/// end-of-contents element.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Length {
+    Definite(usize),
+    Indefinite,
+}
+
+/// The encoding rules a [Decoder](struct.Decoder.html) checks its input against or an
+/// [Encoding](struct.Encoding.html) is written with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rules {
+    /// The Basic Encoding Rules, which allow several encodings of most values.
+    Ber,
+    /// The Canonical Encoding Rules (X.690 clause 9), a subset of BER with one encoding for each value that can be
+    /// written without knowing the lengths of constructed elements in advance.
+    Cer,
+    /// The Distinguished Encoding Rules (X.690 clause 10), a subset of BER with one encoding for each value.
+    Der,
+}
+
+// The maximum number of contents octets of a primitive string with CER, and the size of its segments otherwise
+const CER_SEGMENT_LEN: usize = 1000;
+
+/// Trait for BER encoding/decoding.
+///
+/// Each method takes the tag to use, which is `Self::TAG` unless the type is implicitly tagged. Explicit tagging is
+/// done with [Decoder::decode_explicit](struct.Decoder.html#method.decode_explicit) and
+/// [Encoding::explicit](struct.Encoding.html#method.explicit). An untagged CHOICE has no tag of its own, so its
+/// implementation should peek at the tag of the next element to pick an alternative and ignore `tag`.
+///
+/// # Examples
+///
+/// ```
+/// // Foo ::= SEQUENCE {
+/// //     a INTEGER,
+/// //     b [0] IMPLICIT BOOLEAN OPTIONAL,
+/// // }
+/// extern crate asn1;
+/// use asn1::ber::{self, BerElement, Encoding, Tag};
+///
+/// struct Foo {
+///     a: i32,
+///     b: Option<bool>,
+/// }
+///
+/// impl BerElement for Foo {
+///     const TAG: Tag = Tag::SEQUENCE;
+///
+///     fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+///         let d = decoder.decode_constructed(tag);
+///         if d.is_err() {
+///             return Err(d.err().unwrap());
+///         }
+///         let mut d = d.unwrap();
+///
+///         let a = i32::from_ber(&mut d, i32::TAG);
+///         if a.is_err() {
+///             return Err(a.err().unwrap());
+///         }
+///         let mut b = None;
+///         if d.peek_tag() == Ok(Tag::context(0)) {
+///             let ret = bool::from_ber(&mut d, Tag::context(0));
+///             if ret.is_err() {
+///                 return Err(ret.err().unwrap());
+///             }
+///             b = Some(ret.unwrap());
+///         }
+///
+///         let ret = d.finish();
+///         if ret.is_err() {
+///             return Err(ret.err().unwrap());
+///         }
+///         Ok(Foo { a: a.unwrap(), b })
+///     }
+///
+///     fn to_ber(&self, tag: Tag) -> Result<Encoding, ber::EncodeError> {
+///         let mut elements = vec![self.a.to_ber(i32::TAG).unwrap()];
+///         if let Some(b) = self.b {
+///             elements.push(b.to_ber(Tag::context(0)).unwrap());
+///         }
+///         Ok(Encoding::constructed(tag, elements))
+///     }
+/// }
+/// ```
+pub trait BerElement: Sized {
+    /// The tag of the type when it isn't tagged.
+    const TAG: Tag;
+
+    /// Read an element with tag `tag`.
+    fn from_ber(decoder: &mut Decoder, tag: Tag) -> Result<Self, DecodeError>;
+
+    /// Encode the value as an element with tag `tag`.
+    fn to_ber(&self, tag: Tag) -> Result<Encoding, EncodeError>;
+}
@@ -1,9 +1,17 @@
-use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError};
-use std::cmp;
-use utils::shift_bytes_left;
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_int, encode_length};
+use ber::{self, BerElement, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Range};
 
 /// A bit string.
 ///
+/// Bits are numbered the same way ASN.1 numbers them: bit 0 is the leading bit, which is stored in the most
+/// significant bit of the first byte. Any unused bits at the end of the last byte are always 0.
+///
 /// # Examples
 ///
 /// ```
@@ -14,17 +22,22 @@ use utils::shift_bytes_left;
 /// b.set(0, true);
 /// println!("b[0] = {}", b.is_set(0)); // Prints b[0] = true
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct BitString {
     data: Vec<u8>,
     num_bits: usize,
 }
 
 impl BitString {
+    /// Construct an empty `BitString`.
+    pub fn new() -> BitString {
+        BitString::default()
+    }
+
     /// Consturct a `BitString` of length `n` with all values set to 0.
     pub fn with_len(n: usize) -> BitString {
         let mut ret = BitString {
-            data: Vec::<u8>::with_capacity(n / 8),
+            data: Vec::<u8>::with_capacity(n.div_ceil(8)),
             num_bits: 0,
         };
         ret.set_num_bits(n);
@@ -33,6 +46,9 @@ impl BitString {
 
     /// Consturct a `BitString` of length `n` with initial values contained in `data`.
     ///
+    /// The bits are taken from `data` starting with the most significant bit of the first byte. Missing bytes are
+    /// filled with 0 and any bits of `data` beyond `n` are ignored.
+    ///
     /// # Examples
     ///
     /// ```
@@ -44,11 +60,33 @@ impl BitString {
     /// println!("b[0] = {}", b.is_set(0)); // Prints b[0] = false
     /// println!("b[14] = {}", b.is_set(14)); // Prints b[14] = true
     /// ```
-    pub fn with_bytes_and_len(data: &Vec<u8>, n: usize) -> BitString {
-        BitString {
-            data: data.clone(),
-            num_bits: n,
+    pub fn with_bytes_and_len(data: &[u8], n: usize) -> BitString {
+        let mut ret = BitString {
+            data: data.to_vec(),
+            num_bits: 0,
+        };
+        ret.set_num_bits(n);
+        ret
+    }
+
+    /// Construct a `BitString` from a slice of bits.
+    pub fn from_bits(bits: &[bool]) -> BitString {
+        bits.iter().cloned().collect()
+    }
+
+    /// Construct a `BitString` of length `n` from the `n` least significant bits of `value`, most significant bit
+    /// first. This is handy for fields like `gNB-ID` that are bit strings holding an integer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n > 64`.
+    pub fn from_uint(value: u64, n: usize) -> BitString {
+        assert!(n <= 64, "a u64 holds at most 64 bits");
+        let mut ret = BitString::with_len(n);
+        for i in 0..n {
+            ret.set(i, (value >> (n - i - 1)) & 1 == 1);
         }
+        ret
     }
 
     /// Get the length of a `BitString`
@@ -59,38 +97,259 @@ impl BitString {
     /// Set the length of a `BitString` and initialize any new values to 0
     pub fn set_num_bits(&mut self, n: usize) {
         self.num_bits = n;
-        self.data.resize(n, 0);
+        self.data.resize(n.div_ceil(8), 0);
+        self.clear_unused_bits();
     }
 
-    /// Check if bit `i` is set.
+    /// Check if bit `i` is set. Bits beyond the end of the `BitString` are never set.
     pub fn is_set(&self, i: usize) -> bool {
-        let mut bucket = i / 8;
-        let pos = (i as i64 - bucket as i64 * 8) as usize;
-        if bucket > self.data.len() {
+        if i >= self.num_bits {
             return false;
         }
-
-        bucket = cmp::max(0, self.data.len() as i64 - bucket as i64 - 1) as usize;
-        (self.data[bucket] & (1 << pos)) > 0
+        self.data[i / 8] & (0x80 >> (i % 8)) > 0
     }
 
-    /// Set bit `i` to `val`.
+    /// Set bit `i` to `val`. Does nothing if `i` is beyond the end of the `BitString`.
     pub fn set(&mut self, i: usize, val: bool) {
-        let mut bucket = i / 8;
-        let pos = (i as i64 - bucket as i64 * 8) as usize;
-        if bucket > self.data.len() {
+        if i >= self.num_bits {
             return;
         }
-
-        bucket = cmp::max(0, self.data.len() as i64 - bucket as i64 - 1) as usize;
         if val {
-            self.data[bucket] |= 1 << pos;
+            self.data[i / 8] |= 0x80 >> (i % 8);
         } else {
-            self.data[bucket] &= 0xFF & !(1 << pos);
+            self.data[i / 8] &= !(0x80 >> (i % 8));
+        }
+    }
+
+    /// Append a bit to the end of the `BitString`.
+    pub fn push(&mut self, val: bool) {
+        let n = self.num_bits;
+        self.set_num_bits(n + 1);
+        self.set(n, val);
+    }
+
+    /// Shorten the `BitString` to `n` bits. Does nothing if it is already `n` bits or shorter.
+    pub fn truncate(&mut self, n: usize) {
+        if n < self.num_bits {
+            self.set_num_bits(n);
+        }
+    }
+
+    /// Remove trailing 0 bits, but never shorten the `BitString` below `min` bits.
+    pub fn trim_trailing_zeros(&mut self, min: usize) {
+        let mut n = self.num_bits;
+        while n > min && !self.is_set(n - 1) {
+            n -= 1;
+        }
+        self.truncate(n);
+    }
+
+    /// Copy the bits in `range` into a new `BitString`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` extends beyond the end of the `BitString`.
+    pub fn slice(&self, range: Range<usize>) -> BitString {
+        assert!(range.start <= range.end && range.end <= self.num_bits,
+                "range {:?} out of bounds for BitString of length {}",
+                range,
+                self.num_bits);
+        range.map(|i| self.is_set(i)).collect()
+    }
+
+    /// Iterate over the bits, starting with bit 0.
+    pub fn iter(&self) -> BitStringIter<'_> {
+        BitStringIter {
+            bits: self,
+            pos: 0,
+        }
+    }
+
+    /// Get the bytes of the `BitString`. The last byte is right-padded with 0s.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.data.clone()
+    }
+
+    /// Interpret the `BitString` as an unsigned integer, most significant bit first.
+    /// Returns `None` if it is longer than 64 bits.
+    pub fn to_uint(&self) -> Option<u64> {
+        if self.num_bits > 64 {
+            return None;
+        }
+        Some(self.iter().fold(0, |acc, b| (acc << 1) | b as u64))
+    }
+
+    fn clear_unused_bits(&mut self) {
+        let used = self.num_bits % 8;
+        if used > 0 {
+            let n = self.data.len();
+            self.data[n - 1] &= 0xFF << (8 - used);
+        }
+    }
+
+    fn zip_with<F: Fn(u8, u8) -> u8>(&self, other: &BitString, f: F) -> BitString {
+        let mut ret = BitString::with_len(::std::cmp::max(self.num_bits, other.num_bits));
+        for (i, b) in ret.data.iter_mut().enumerate() {
+            *b = f(*self.data.get(i).unwrap_or(&0), *other.data.get(i).unwrap_or(&0));
+        }
+        ret.clear_unused_bits();
+        ret
+    }
+}
+
+/// An iterator over the bits of a `BitString`. See [BitString::iter](struct.BitString.html#method.iter).
+pub struct BitStringIter<'a> {
+    bits: &'a BitString,
+    pos: usize,
+}
+
+impl<'a> Iterator for BitStringIter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.pos >= self.bits.num_bits {
+            return None;
+        }
+        self.pos += 1;
+        Some(self.bits.is_set(self.pos - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.bits.num_bits - self.pos;
+        (n, Some(n))
+    }
+}
+
+impl<'a> ExactSizeIterator for BitStringIter<'a> {}
+
+impl<'a> IntoIterator for &'a BitString {
+    type Item = bool;
+    type IntoIter = BitStringIter<'a>;
+
+    fn into_iter(self) -> BitStringIter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<bool> for BitString {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> BitString {
+        let mut ret = BitString::new();
+        ret.extend(iter);
+        ret
+    }
+}
+
+impl Extend<bool> for BitString {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for b in iter {
+            self.push(b);
+        }
+    }
+}
+
+macro_rules! bit_op_impl {
+    ($tr:ident, $method:ident, $op:tt) => {
+        impl<'a, 'b> $tr<&'b BitString> for &'a BitString {
+            type Output = BitString;
+
+            /// The shorter operand is extended with 0s to the length of the longer one.
+            fn $method(self, other: &'b BitString) -> BitString {
+                self.zip_with(other, |a, b| a $op b)
+            }
+        }
+
+        impl $tr for BitString {
+            type Output = BitString;
+
+            fn $method(self, other: BitString) -> BitString {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+bit_op_impl!(BitAnd, bitand, &);
+bit_op_impl!(BitOr, bitor, |);
+bit_op_impl!(BitXor, bitxor, ^);
+
+impl Not for &BitString {
+    type Output = BitString;
+
+    fn not(self) -> BitString {
+        let mut ret = BitString {
+            data: self.data.iter().map(|b| !b).collect(),
+            num_bits: self.num_bits,
+        };
+        ret.clear_unused_bits();
+        ret
+    }
+}
+
+impl Not for BitString {
+    type Output = BitString;
+
+    fn not(self) -> BitString {
+        !&self
+    }
+}
+
+/// Formats a `BitString` in ASN.1 bstring notation, e.g. `'0101'B`.
+impl fmt::Display for BitString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'")?;
+        for b in self {
+            write!(f, "{}", b as u8)?;
+        }
+        write!(f, "'B")
+    }
+}
+
+impl BitString {
+    fn fmt_hex(&self, f: &mut fmt::Formatter, upper: bool) -> fmt::Result {
+        write!(f, "'")?;
+        for i in 0..self.num_bits.div_ceil(4) {
+            let nibble = (self.data[i / 2] >> (4 * (1 - i % 2))) & 0x0F;
+            if upper {
+                write!(f, "{:X}", nibble)?;
+            } else {
+                write!(f, "{:x}", nibble)?;
+            }
         }
+        write!(f, "'H")
+    }
+}
+
+/// Formats a `BitString` in ASN.1 hstring notation, e.g. `'5A'H`. A length that is not a multiple of 4 is padded with
+/// trailing 0s.
+impl fmt::UpperHex for BitString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_hex(f, true)
     }
 }
 
+/// Same as the `UpperHex` implementation, but with lowercase digits.
+impl fmt::LowerHex for BitString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_hex(f, false)
+    }
+}
+
+macro_rules! from_uint_impl {
+    ($t:ident) => {
+        impl From<$t> for BitString {
+            /// Construct a `BitString` as wide as `$t`, most significant bit first.
+            fn from(value: $t) -> BitString {
+                BitString::from_uint(value as u64, $t::BITS as usize)
+            }
+        }
+    };
+}
+
+from_uint_impl!(u8);
+from_uint_impl!(u16);
+from_uint_impl!(u32);
+from_uint_impl!(u64);
+
 impl APerElement for BitString {
     const CONSTRAINTS: Constraints = Constraints {
         value: None,
@@ -104,25 +363,50 @@ impl APerElement for BitString {
         }
 
         let sz_constr = constraints.size.unwrap();
-        if sz_constr.max().is_none() || sz_constr.max().unwrap() == 0 {
-            return Ok(BitString::with_len(0));
-        }
+        let len = match sz_constr.size_bounds() {
+            (_, Some(0)) => return Ok(BitString::new()),
+            (min, Some(max)) if min == max => {
+                if max >= 65536 {
+                    return Err(DecodeError::NotImplemented);
+                }
+                if max > 16 {
+                    decoder.align();
+                }
+                max as usize
+            }
+            (min, Some(max)) if max < 65536 => {
+                let ret = decoder.decode_int(Some(min), Some(max));
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                decoder.align();
+                ret.unwrap() as usize
+            }
+            _ => {
+                let ret = decoder.decode_length();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            }
+        };
 
-        let len = sz_constr.max().unwrap() as usize;
-        if len >= 65535 {
-            unimplemented!();
+        let mut content: Vec<u8> = Vec::with_capacity(len.div_ceil(8));
+        for _ in 0..len / 8 {
+            let ret = decoder.read_u8();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            content.push(ret.unwrap());
         }
 
-        let num_bytes = (len as f64 / 8.).ceil() as usize;
-        let mut content: Vec<u8> = Vec::with_capacity(num_bytes);
-        let ret = decoder.read_to_vec(&mut content, len);
-        if ret.is_err() {
-            return Err(ret.err().unwrap());
-        }
-
-        let delta = num_bytes * 8 - len;
-        if delta > 0 && num_bytes > 1 {
-            shift_bytes_left(&mut content, delta);
+        let rem = len % 8;
+        if rem > 0 {
+            let ret = decoder.read(rem);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            content.push(ret.unwrap() << (8 - rem));
         }
 
         Ok(BitString::with_bytes_and_len(&content, len))
@@ -134,25 +418,216 @@ impl APerElement for BitString {
         }
 
         let sz_constr = constraints.size.unwrap();
-        if sz_constr.max().is_none() || sz_constr.max().unwrap() == 0 {
-            return Ok(Encoding::new());
+        let n = self.num_bits as i64;
+        let mut enc = match sz_constr.size_bounds() {
+            (_, Some(0)) => return Ok(Encoding::new()),
+            (min, Some(max)) if min == max => {
+                if max >= 65536 {
+                    return Err(EncodeError::NotImplemented);
+                }
+                if n != max {
+                    return Err(EncodeError::InvalidSize);
+                }
+                let mut enc = Encoding::new();
+                if max > 16 {
+                    enc.align();
+                }
+                enc
+            }
+            (min, Some(max)) if max < 65536 => {
+                if n < min || n > max {
+                    return Err(EncodeError::InvalidSize);
+                }
+                let ret = encode_int(n, Some(min), Some(max));
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let mut enc = ret.unwrap();
+                enc.align();
+                enc
+            }
+            (min, _) => {
+                if n < min {
+                    return Err(EncodeError::InvalidSize);
+                }
+                let ret = encode_length(self.num_bits);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            }
+        };
+
+        let ret = enc.append(&Encoding::with_bytes_and_padding(self.data.clone(), self.data.len() * 8 - self.num_bits));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
         }
+        Ok(enc)
+    }
+}
+
+impl BerElement for BitString {
+    const TAG: Tag = Tag::BIT_STRING;
 
-        let len = sz_constr.max().unwrap() as usize;
-        if len >= 65535 {
-            return Err(EncodeError::NotImplemented);
+    /// Construct a `BitString` from a BER encoding.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_bit_string(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
         }
+        let (content, n) = ret.unwrap();
+        Ok(BitString::with_bytes_and_len(&content, n))
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::bit_string(tag, self))
+    }
+}
 
-        let mut l_padding = 0;
-        let r_padding = 0;
-        if self.num_bits < 8 {
-            l_padding = 8 - self.num_bits;
-        } else if self.num_bits <= 16 {
-            l_padding = 16 - self.num_bits;
+impl OerElement for BitString {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `BitString` from an OER encoding. A `BitString` with a fixed size is just its bits, otherwise they are
+    /// preceded by a length determinant and an octet with the number of unused bits, which must be 0s with COER.
+    fn from_oer(decoder: &mut oer::Decoder, constraints: Constraints) -> Result<Self, oer::DecodeError> {
+        if let Some(n) = constraints.fixed_size() {
+            let ret = decoder.read_bytes(n.div_ceil(8));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            return Ok(BitString::with_bytes_and_len(ret.unwrap(), n));
         }
-        let mut bytes = self.data.clone();
-        shift_bytes_left(&mut bytes, l_padding); // XXX: this is incorrect for n_bits > 8
-        let enc = Encoding::with_bytes_and_padding(bytes, r_padding + l_padding);
+
+        let ret = decoder.decode_length();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = decoder.read_bytes(ret.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let contents = ret.unwrap();
+        if contents.is_empty() || contents[0] > 7 || contents.len() == 1 && contents[0] != 0 {
+            return Err(oer::DecodeError::InvalidUnusedBits);
+        }
+        let unused = contents[0] as usize;
+        if decoder.is_canonical() && contents[contents.len() - 1] & ((1 << unused) - 1) != 0 {
+            return Err(oer::DecodeError::NonZeroUnusedBits);
+        }
+        let n = (contents.len() - 1) * 8 - unused;
+        if !constraints.check_size(n) {
+            return Err(oer::DecodeError::InvalidSize);
+        }
+        Ok(BitString::with_bytes_and_len(&contents[1..], n))
+    }
+
+    fn to_oer(&self, constraints: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        if !constraints.check_size(self.num_bits) {
+            return Err(oer::EncodeError::InvalidSize);
+        }
+        if constraints.fixed_size().is_some() {
+            return Ok(oer::Encoding::with_bytes(self.to_bytes()));
+        }
+
+        let bytes = self.to_bytes();
+        let mut enc = oer::encode_length(bytes.len() + 1);
+        enc.append(&oer::Encoding::with_bytes(vec![(bytes.len() * 8 - self.num_bits) as u8]));
+        enc.append(&oer::Encoding::with_bytes(bytes));
         Ok(enc)
     }
 }
+
+impl XerElement for BitString {
+    const NAME: &'static str = "BIT_STRING";
+
+    /// Read a `BitString` from an XER encoding, which is its bits as `0` and `1` characters. Whitespace between them
+    /// is ignored, except with CXER, where it's an `Err`.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        let ret = decoder.decode_text();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut bits = BitString::new();
+        for c in ret.unwrap().chars() {
+            match c {
+                '0' | '1' => bits.push(c == '1'),
+                ' ' | '\t' | '\n' | '\r' if decoder.is_canonical() => {
+                    return Err(xer::DecodeError::UnexpectedWhitespace)
+                }
+                ' ' | '\t' | '\n' | '\r' => continue,
+                _ => return Err(xer::DecodeError::InvalidBits),
+            }
+        }
+        Ok(bits)
+    }
+
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        let bits: String = self.into_iter().map(|b| if b { '1' } else { '0' }).collect();
+        Ok(xer::Encoding::text(&bits))
+    }
+}
+
+/// A `BitString` with a fixed size is encoded as a string of hexadecimal digits, padded with 0 bits to a whole
+/// number of octets. Otherwise, it's an object with the digits as `value` and the number of bits as `length`.
+impl JerElement for BitString {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `BitString` from a JER encoding.
+    fn from_jer(value: &jer::Value, constraints: Constraints) -> Result<Self, jer::DecodeError> {
+        let (hex, n) = match constraints.fixed_size() {
+            Some(n) => (value, n),
+            None => {
+                let hex = value.member("value");
+                if hex.is_err() {
+                    return Err(hex.err().unwrap());
+                }
+                let ret = value.member("length");
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let ret = ret.unwrap().as_number();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match ret.unwrap().parse() {
+                    Ok(n) => (hex.unwrap(), n),
+                    Err(_) => return Err(jer::DecodeError::MalformedInt),
+                }
+            }
+        };
+        let ret = hex.as_str();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = jer::decode_hex(ret.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let bytes = ret.unwrap();
+        if bytes.len() != n.div_ceil(8) {
+            return Err(jer::DecodeError::InvalidBits);
+        }
+        if !constraints.check_size(n) {
+            return Err(jer::DecodeError::InvalidSize);
+        }
+        Ok(BitString::with_bytes_and_len(&bytes, n))
+    }
+
+    fn to_jer(&self, constraints: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        if !constraints.check_size(self.num_bits) {
+            return Err(jer::EncodeError::InvalidSize);
+        }
+        let hex = jer::encode_octet_string(&self.to_bytes(), None).unwrap();
+        if constraints.fixed_size().is_some() {
+            return Ok(hex);
+        }
+        Ok(jer::Value::Object(vec![("value".to_string(), hex),
+                                   ("length".to_string(), jer::Value::Number(self.num_bits.to_string()))]))
+    }
+}
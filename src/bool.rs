@@ -1,4 +1,8 @@
 use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError};
+use ber::{self, BerElement, Rules, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
 
 impl APerElement for bool {
     const CONSTRAINTS: Constraints = Constraints {
@@ -19,3 +23,90 @@ impl APerElement for bool {
         Ok(Encoding::with_bytes_and_padding(vec![(*self as u8) << 7], 7))
     }
 }
+
+impl BerElement for bool {
+    const TAG: Tag = Tag::BOOLEAN;
+
+    /// Read a `bool` from a BER encoding. Any non-zero contents octet is `true`, except with DER, where `true` is
+    /// always `0xff`.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_primitive(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let contents = ret.unwrap();
+        if contents.len() != 1 {
+            return Err(ber::DecodeError::InvalidLength);
+        }
+        if decoder.rules() != Rules::Ber && contents[0] != 0x00 && contents[0] != 0xff {
+            return Err(ber::DecodeError::InvalidBool);
+        }
+        Ok(contents[0] != 0)
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::primitive(tag, vec![if *self { 0xff } else { 0x00 }]))
+    }
+}
+
+impl OerElement for bool {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `bool` from an OER encoding. Any non-zero octet is `true`, except with COER, where `true` is always
+    /// `0xff`.
+    fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = decoder.read_u8();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let b = ret.unwrap();
+        if decoder.is_canonical() && b != 0x00 && b != 0xff {
+            return Err(oer::DecodeError::InvalidBool);
+        }
+        Ok(b != 0)
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        Ok(oer::Encoding::with_bytes(vec![if *self { 0xff } else { 0x00 }]))
+    }
+}
+
+impl XerElement for bool {
+    const NAME: &'static str = "BOOLEAN";
+
+    /// Read a `bool` from an XER encoding, which is the empty element `<true/>` or `<false/>`.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        let ret = decoder.decode_empty_element();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ret.unwrap() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(xer::DecodeError::InvalidBool),
+        }
+    }
+
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        Ok(xer::Encoding::empty_element(if *self { "true" } else { "false" }))
+    }
+}
+
+impl JerElement for bool {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `bool` from a JER encoding, which is `true` or `false`.
+    fn from_jer(value: &jer::Value, _: Constraints) -> Result<Self, jer::DecodeError> {
+        value.as_bool()
+    }
+
+    fn to_jer(&self, _: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        Ok(jer::Value::Bool(*self))
+    }
+}
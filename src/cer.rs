@@ -0,0 +1,47 @@
+use ber::{BerElement, Decoder, DecodeError, EncodeError, Rules};
+
+/// Encode `value` with CER.
+///
+/// Constructed elements are written with indefinite lengths, strings longer than 1000 octets are split into
+/// 1000-octet segments and the components of SET and SET OF values are sorted. Implementations of `BerElement` for
+/// SEQUENCE and SET types should omit components equal to their DEFAULT value (see
+/// [ber::encode_default](../ber/fn.encode_default.html)).
+pub fn encode<T: BerElement>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    let ret = value.to_ber(T::TAG);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(ret.unwrap().to_bytes(Rules::Cer))
+}
+
+/// Construct a [ber::Decoder](../ber/struct.Decoder.html) that rejects any encoding that isn't valid CER.
+pub fn decoder(data: &[u8]) -> Decoder<'_> {
+    let mut decoder = Decoder::new(data);
+    decoder.set_rules(Rules::Cer);
+    decoder
+}
+
+/// Decode a `T` from `data`, which must be its CER encoding and nothing else.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::{ber, cer};
+///
+/// assert_eq!(Ok(vec![true]), cer::decode::<Vec<bool>>(b"\x30\x80\x01\x01\xff\x00\x00"));
+/// assert_eq!(Err(ber::DecodeError::ConstructedDefiniteLength), cer::decode::<Vec<bool>>(b"\x30\x03\x01\x01\xff"));
+/// ```
+pub fn decode<T: BerElement>(data: &[u8]) -> Result<T, DecodeError> {
+    let mut d = decoder(data);
+    let ret = T::from_ber(&mut d, T::TAG);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let value = ret.unwrap();
+    let ret = d.finish();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(value)
+}
@@ -0,0 +1,592 @@
+use aper::{APerElement, Constraint, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_fragmented,
+           encode_int};
+use ber::{self, BerElement, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
+use std::fmt;
+
+/// The set of characters a known-multiplier character string may contain, i.e. the effective PermittedAlphabet of a
+/// type like `PrintableString (FROM("0".."9"))`.
+///
+/// Characters are kept in canonical order (by code point), which determines the value each character is encoded as
+/// when the alphabet is remapped.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::PermittedAlphabet;
+///
+/// let digits = PermittedAlphabet::new("0123456789");
+/// println!("{}", digits.char_bits()); // Prints 4
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermittedAlphabet {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl PermittedAlphabet {
+    /// Construct a `PermittedAlphabet` made of the characters in `chars`, as in `FROM("0123456789")`.
+    pub fn new(chars: &str) -> PermittedAlphabet {
+        let ranges: Vec<(u32, u32)> = chars.chars().map(|c| (c as u32, c as u32)).collect();
+        Self::with_code_ranges(&ranges)
+    }
+
+    /// Construct a `PermittedAlphabet` made of inclusive character ranges, as in `FROM("a".."z" | "A".."Z")`.
+    pub fn from_ranges(ranges: &[(char, char)]) -> PermittedAlphabet {
+        let ranges: Vec<(u32, u32)> = ranges.iter().map(|&(lo, hi)| (lo as u32, hi as u32)).collect();
+        Self::with_code_ranges(&ranges)
+    }
+
+    pub(crate) fn with_code_ranges(ranges: &[(u32, u32)]) -> PermittedAlphabet {
+        let mut sorted: Vec<(u32, u32)> = ranges.iter().filter(|&&(lo, hi)| lo <= hi).cloned().collect();
+        sorted.sort();
+
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(sorted.len());
+        for (lo, hi) in sorted {
+            if let Some(last) = merged.last_mut() {
+                if lo as u64 <= last.1 as u64 + 1 {
+                    if hi > last.1 {
+                        last.1 = hi;
+                    }
+                    continue;
+                }
+            }
+            merged.push((lo, hi));
+        }
+        PermittedAlphabet { ranges: merged }
+    }
+
+    /// Get the number of characters in the alphabet.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|&(lo, hi)| (hi - lo) as u64 + 1).sum()
+    }
+
+    /// Check if the alphabet has no characters.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Check if `c` is in the alphabet.
+    pub fn contains(&self, c: char) -> bool {
+        self.index_of(c as u32).is_some()
+    }
+
+    /// Get the characters that are in `self` or in `other`, as in `FROM("a".."z" | "0".."9")`.
+    pub fn union(&self, other: &PermittedAlphabet) -> PermittedAlphabet {
+        let ranges: Vec<(u32, u32)> = self.ranges.iter().chain(other.ranges.iter()).cloned().collect();
+        Self::with_code_ranges(&ranges)
+    }
+
+    /// Get the characters that are in both `self` and `other`, e.g. the effective alphabet of `IA5String (FROM(...))`.
+    pub fn intersection(&self, other: &PermittedAlphabet) -> PermittedAlphabet {
+        let mut ranges = Vec::new();
+        for &(lo, hi) in &self.ranges {
+            for &(other_lo, other_hi) in &other.ranges {
+                ranges.push((lo.max(other_lo), hi.min(other_hi)));
+            }
+        }
+        Self::with_code_ranges(&ranges)
+    }
+
+    /// Get the number of bits used to encode each character in aligned PER.
+    ///
+    /// This is the number of bits needed to distinguish every character of the alphabet, rounded up to a power of 2.
+    pub fn char_bits(&self) -> usize {
+        let b = self.unaligned_char_bits();
+        if b == 0 {
+            return 0;
+        }
+        b.next_power_of_two()
+    }
+
+    /// Get the number of bits used to encode each character in unaligned PER, i.e. the number of bits needed to
+    /// distinguish every character of the alphabet.
+    pub fn unaligned_char_bits(&self) -> usize {
+        let n = self.len();
+        let mut b: usize = 0;
+        while (1u64 << b) < n {
+            b += 1;
+        }
+        b
+    }
+
+    /// Characters are encoded as their own code if it fits in `b` bits, otherwise as their index in the alphabet.
+    pub(crate) fn is_remapped(&self, b: usize) -> bool {
+        match self.ranges.last() {
+            Some(&(_, hi)) => b < 32 && hi as u64 > (1u64 << b) - 1,
+            None => false,
+        }
+    }
+
+    pub(crate) fn index_of(&self, code: u32) -> Option<u64> {
+        let mut base: u64 = 0;
+        for &(lo, hi) in &self.ranges {
+            if code < lo {
+                return None;
+            }
+            if code <= hi {
+                return Some(base + (code - lo) as u64);
+            }
+            base += (hi - lo) as u64 + 1;
+        }
+        None
+    }
+
+    pub(crate) fn code_at(&self, index: u64) -> Option<u32> {
+        let mut base: u64 = 0;
+        for &(lo, hi) in &self.ranges {
+            let n = (hi - lo) as u64 + 1;
+            if index < base + n {
+                return Some(lo + (index - base) as u32);
+            }
+            base += n;
+        }
+        None
+    }
+}
+
+/// Encode `s` as a known-multiplier character string made of characters from `alphabet`, with `size` giving the
+/// limits on the number of characters.
+pub(crate) fn encode_chars(s: &str, alphabet: &PermittedAlphabet, size: Option<Constraint>)
+                           -> Result<Encoding, EncodeError> {
+    let b = alphabet.char_bits();
+    let remapped = alphabet.is_remapped(b);
+
+    let mut values: Vec<u64> = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        let code = c as u32;
+        match alphabet.index_of(code) {
+            Some(i) if remapped => values.push(i),
+            Some(_) => values.push(code as u64),
+            None => return Err(EncodeError::InvalidCharacter),
+        }
+    }
+    let encode_range = |start: usize, end: usize| {
+        let mut enc = Encoding::new();
+        for v in &values[start..end] {
+            let ret = enc.append(&Encoding::with_uint(*v, b));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(enc)
+    };
+
+    let n = values.len() as i64;
+    let (lb, ub) = match size {
+        Some(sz_constr) => sz_constr.size_bounds(),
+        None => (0, None),
+    };
+    if n < lb || ub.is_some() && n > ub.unwrap() {
+        return Err(EncodeError::InvalidSize);
+    }
+
+    let mut enc = match ub {
+        Some(ub) if ub == lb && ub < 65536 => Encoding::new(),
+        Some(ub) if ub < 65536 => {
+            let ret = encode_int(n, Some(lb), Some(ub));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap()
+        }
+        _ => return encode_fragmented(values.len(), encode_range),
+    };
+
+    // The characters are octet-aligned unless they can never take more than two octets
+    if ub.unwrap() * b as i64 > 16 {
+        enc.align();
+    }
+
+    let ret = encode_range(0, values.len());
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let ret = enc.append(&ret.unwrap());
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(enc)
+}
+
+/// Decode a known-multiplier character string made of characters from `alphabet`. See `encode_chars`.
+pub(crate) fn decode_chars(decoder: &mut Decoder, alphabet: &PermittedAlphabet, size: Option<Constraint>)
+                           -> Result<String, DecodeError> {
+    let b = alphabet.char_bits();
+    let remapped = alphabet.is_remapped(b);
+    let mut s = String::new();
+    let mut decode_run = |decoder: &mut Decoder, n: usize| {
+        for _ in 0..n {
+            let ret = decoder.read_uint(b);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let value = ret.unwrap();
+
+            let code = if remapped {
+                alphabet.code_at(value)
+            } else if value <= u32::MAX as u64 && alphabet.index_of(value as u32).is_some() {
+                Some(value as u32)
+            } else {
+                None
+            };
+            match code.and_then(::std::char::from_u32) {
+                Some(c) => s.push(c),
+                None => return Err(DecodeError::InvalidCharacter),
+            }
+        }
+        Ok(())
+    };
+
+    let (lb, ub) = match size {
+        Some(sz_constr) => sz_constr.size_bounds(),
+        None => (0, None),
+    };
+
+    let n = match ub {
+        Some(ub) if ub == lb && ub < 65536 => ub,
+        Some(ub) if ub < 65536 => {
+            let ret = decoder.decode_int(Some(lb), Some(ub));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap()
+        }
+        _ => {
+            let ret = decoder.decode_fragmented(decode_run);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let n = ret.unwrap() as i64;
+            if n < lb || ub.is_some() && n > ub.unwrap() {
+                return Err(DecodeError::InvalidSize);
+            }
+            return Ok(s);
+        }
+    };
+    if n < lb || n > ub.unwrap() {
+        return Err(DecodeError::InvalidSize);
+    }
+
+    if ub.unwrap() * b as i64 > 16 {
+        decoder.align();
+    }
+
+    let ret = decode_run(decoder, n as usize);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(s)
+}
+
+macro_rules! known_multiplier_string {
+    ($(#[$attr:meta])* $name:ident, $ranges:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(String);
+
+        impl $name {
+            /// Construct a new value from `s`. Returns `None` if `s` contains characters that aren't allowed in this
+            /// type.
+            pub fn new(s: &str) -> Option<$name> {
+                let alphabet = Self::alphabet();
+                if s.chars().all(|c| alphabet.contains(c)) {
+                    Some($name(s.to_string()))
+                } else {
+                    None
+                }
+            }
+
+            /// Get the full alphabet of this type.
+            pub fn alphabet() -> PermittedAlphabet {
+                PermittedAlphabet::with_code_ranges(&$ranges)
+            }
+
+            /// Get the value as a `&str`.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// Read a value from an aligned PER encoding of a type with a PermittedAlphabet constraint.
+            pub fn from_aper_with_alphabet(decoder: &mut Decoder,
+                                           constraints: Constraints,
+                                           alphabet: &PermittedAlphabet)
+                                           -> Result<$name, DecodeError> {
+                let ret = decode_chars(decoder, alphabet, constraints.size);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok($name(ret.unwrap()))
+            }
+
+            /// Encode a value of a type with a PermittedAlphabet constraint.
+            pub fn to_aper_with_alphabet(&self,
+                                         constraints: Constraints,
+                                         alphabet: &PermittedAlphabet)
+                                         -> Result<Encoding, EncodeError> {
+                encode_chars(&self.0, alphabet, constraints.size)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(s: $name) -> String {
+                s.0
+            }
+        }
+
+        impl APerElement for $name {
+            const CONSTRAINTS: Constraints = Constraints {
+                value: None,
+                size: None,
+            };
+
+            /// Read a value from an aligned PER encoding. Only the size constraint is used.
+            fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+                Self::from_aper_with_alphabet(decoder, constraints, &Self::alphabet())
+            }
+
+            fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+                self.to_aper_with_alphabet(constraints, &Self::alphabet())
+            }
+        }
+    };
+}
+
+known_multiplier_string!(
+    /// An ASN.1 `NumericString`: digits and space.
+    NumericString,
+    [(0x20, 0x20), (0x30, 0x39)]
+);
+
+known_multiplier_string!(
+    /// An ASN.1 `PrintableString`: letters, digits, space and `'()+,-./:=?`.
+    PrintableString,
+    [(0x20, 0x20), (0x27, 0x29), (0x2B, 0x2F), (0x30, 0x3A), (0x3D, 0x3D), (0x3F, 0x3F), (0x41, 0x5A), (0x61, 0x7A)]
+);
+
+known_multiplier_string!(
+    /// An ASN.1 `VisibleString` (a.k.a. `ISO646String`): the printable ASCII characters.
+    VisibleString,
+    [(0x20, 0x7E)]
+);
+
+known_multiplier_string!(
+    /// An ASN.1 `IA5String`: all of ASCII, including control characters.
+    IA5String,
+    [(0x00, 0x7F)]
+);
+
+known_multiplier_string!(
+    /// An ASN.1 `BMPString`: the Basic Multilingual Plane, 16 bits per character.
+    BMPString,
+    [(0x0000, 0xFFFF)]
+);
+
+known_multiplier_string!(
+    /// An ASN.1 `UniversalString`: any character, 32 bits per character.
+    UniversalString,
+    [(0x0000_0000, 0xFFFF_FFFF)]
+);
+
+// The BER encoding of a known-multiplier character string is its characters in `$width` octets each, big-endian
+macro_rules! ber_string_impl {
+    ($name:ident, $tag:ident, $width:expr) => {
+        impl BerElement for $name {
+            const TAG: Tag = Tag::$tag;
+
+            /// Read a value from a BER encoding.
+            fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+                let ret = decoder.decode_string(tag);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let bytes = ret.unwrap();
+                let chars = bytes.chunks_exact($width);
+                if !chars.remainder().is_empty() {
+                    return Err(ber::DecodeError::InvalidLength);
+                }
+
+                let mut s = String::with_capacity(chars.len());
+                for c in chars {
+                    match ::std::char::from_u32(c.iter().fold(0, |acc, &b| (acc << 8) | b as u32)) {
+                        Some(c) => s.push(c),
+                        None => return Err(ber::DecodeError::InvalidCharacter),
+                    }
+                }
+                match $name::new(&s) {
+                    Some(s) => Ok(s),
+                    None => Err(ber::DecodeError::InvalidCharacter),
+                }
+            }
+
+            fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+                let mut bytes = Vec::with_capacity(self.0.len() * $width);
+                for c in self.0.chars() {
+                    bytes.extend_from_slice(&(c as u32).to_be_bytes()[4 - $width..]);
+                }
+                Ok(ber::Encoding::string(tag, bytes))
+            }
+        }
+    };
+}
+
+ber_string_impl!(NumericString, NUMERIC_STRING, 1);
+ber_string_impl!(PrintableString, PRINTABLE_STRING, 1);
+ber_string_impl!(VisibleString, VISIBLE_STRING, 1);
+ber_string_impl!(IA5String, IA5_STRING, 1);
+ber_string_impl!(BMPString, BMP_STRING, 2);
+ber_string_impl!(UniversalString, UNIVERSAL_STRING, 4);
+
+// The OER encoding of a known-multiplier character string is the same as its BER contents octets, preceded by a
+// length determinant unless the size constraint fixes the number of characters
+macro_rules! oer_string_impl {
+    ($name:ident, $width:expr) => {
+        impl OerElement for $name {
+            const CONSTRAINTS: Constraints = Constraints {
+                value: None,
+                size: None,
+            };
+
+            /// Read a value from an OER encoding. Only the size constraint is used.
+            fn from_oer(decoder: &mut oer::Decoder, constraints: Constraints) -> Result<Self, oer::DecodeError> {
+                let len = match constraints.fixed_size() {
+                    Some(n) => n * $width,
+                    None => {
+                        let ret = decoder.decode_length();
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                        ret.unwrap()
+                    }
+                };
+                let ret = decoder.read_bytes(len);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let chars = ret.unwrap().chunks_exact($width);
+                if !chars.remainder().is_empty() {
+                    return Err(oer::DecodeError::InvalidLength);
+                }
+                if !constraints.check_size(chars.len()) {
+                    return Err(oer::DecodeError::InvalidSize);
+                }
+
+                let mut s = String::with_capacity(chars.len());
+                for c in chars {
+                    match ::std::char::from_u32(c.iter().fold(0, |acc, &b| (acc << 8) | b as u32)) {
+                        Some(c) => s.push(c),
+                        None => return Err(oer::DecodeError::InvalidCharacter),
+                    }
+                }
+                match $name::new(&s) {
+                    Some(s) => Ok(s),
+                    None => Err(oer::DecodeError::InvalidCharacter),
+                }
+            }
+
+            fn to_oer(&self, constraints: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+                let n = self.0.chars().count();
+                if !constraints.check_size(n) {
+                    return Err(oer::EncodeError::InvalidSize);
+                }
+                let mut bytes = Vec::with_capacity(n * $width);
+                for c in self.0.chars() {
+                    bytes.extend_from_slice(&(c as u32).to_be_bytes()[4 - $width..]);
+                }
+                if constraints.fixed_size().is_some() {
+                    return Ok(oer::Encoding::with_bytes(bytes));
+                }
+                oer::encode_string(&bytes, None)
+            }
+        }
+    };
+}
+
+oer_string_impl!(NumericString, 1);
+oer_string_impl!(PrintableString, 1);
+oer_string_impl!(VisibleString, 1);
+oer_string_impl!(IA5String, 1);
+oer_string_impl!(BMPString, 2);
+oer_string_impl!(UniversalString, 4);
+
+// The XER encoding of a character string is its characters as text, in an element named after the type
+macro_rules! xer_string_impl {
+    ($name:ident) => {
+        impl XerElement for $name {
+            const NAME: &'static str = stringify!($name);
+
+            /// Read a value from an XER encoding.
+            fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+                let ret = decoder.decode_text();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match $name::new(&ret.unwrap()) {
+                    Some(s) => Ok(s),
+                    None => Err(xer::DecodeError::InvalidCharacter),
+                }
+            }
+
+            fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+                Ok(xer::Encoding::text(&self.0))
+            }
+        }
+    };
+}
+
+xer_string_impl!(NumericString);
+xer_string_impl!(PrintableString);
+xer_string_impl!(VisibleString);
+xer_string_impl!(IA5String);
+xer_string_impl!(BMPString);
+xer_string_impl!(UniversalString);
+
+// The JER encoding of a character string is a string. Only the size constraint is checked
+macro_rules! jer_string_impl {
+    ($name:ident) => {
+        impl JerElement for $name {
+            const CONSTRAINTS: Constraints = Constraints {
+                value: None,
+                size: None,
+            };
+
+            /// Read a value from a JER encoding.
+            fn from_jer(value: &jer::Value, constraints: Constraints) -> Result<Self, jer::DecodeError> {
+                let ret = value.as_str();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let s = ret.unwrap();
+                if !constraints.check_size(s.chars().count()) {
+                    return Err(jer::DecodeError::InvalidSize);
+                }
+                match $name::new(s) {
+                    Some(s) => Ok(s),
+                    None => Err(jer::DecodeError::InvalidCharacter),
+                }
+            }
+
+            fn to_jer(&self, constraints: Constraints) -> Result<jer::Value, jer::EncodeError> {
+                if !constraints.check_size(self.0.chars().count()) {
+                    return Err(jer::EncodeError::InvalidSize);
+                }
+                Ok(jer::Value::String(self.0.clone()))
+            }
+        }
+    };
+}
+
+jer_string_impl!(NumericString);
+jer_string_impl!(PrintableString);
+jer_string_impl!(VisibleString);
+jer_string_impl!(IA5String);
+jer_string_impl!(BMPString);
+jer_string_impl!(UniversalString);
@@ -0,0 +1,48 @@
+use ber::{BerElement, Decoder, DecodeError, EncodeError, Rules};
+
+/// Encode `value` with DER.
+///
+/// The types of the crate encode their values canonically, and [Encoding](../ber/struct.Encoding.html) takes care of
+/// definite lengths and the order of the components of SET and SET OF values. Implementations of `BerElement` for
+/// SEQUENCE and SET types should omit components equal to their DEFAULT value (see
+/// [ber::encode_default](../ber/fn.encode_default.html)).
+pub fn encode<T: BerElement>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    let ret = value.to_ber(T::TAG);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(ret.unwrap().to_bytes(Rules::Der))
+}
+
+/// Construct a [ber::Decoder](../ber/struct.Decoder.html) that rejects any encoding that isn't valid DER.
+pub fn decoder(data: &[u8]) -> Decoder<'_> {
+    let mut decoder = Decoder::new(data);
+    decoder.set_rules(Rules::Der);
+    decoder
+}
+
+/// Decode a `T` from `data`, which must be its DER encoding and nothing else.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::{ber, der};
+///
+/// assert_eq!(Ok(true), der::decode::<bool>(b"\x01\x01\xff"));
+/// assert_eq!(Err(ber::DecodeError::InvalidBool), der::decode::<bool>(b"\x01\x01\x01"));
+/// assert_eq!(Err(ber::DecodeError::NonMinimalLength), der::decode::<bool>(b"\x01\x81\x01\xff"));
+/// ```
+pub fn decode<T: BerElement>(data: &[u8]) -> Result<T, DecodeError> {
+    let mut d = decoder(data);
+    let ret = T::from_ber(&mut d, T::TAG);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let value = ret.unwrap();
+    let ret = d.finish();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(value)
+}
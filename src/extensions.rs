@@ -1,2 +1,326 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, UNCONSTRAINED, encode_length,
+           encode_normally_small, encode_open_type};
+use bit_string::BitString;
+use oer::{self, OerElement};
+
 /// A marker for extendable types.
 pub type ExtensionMarker = bool;
+
+/// The extension additions of a value of an extensible SEQUENCE type, i.e. the presence bitmap and the open type
+/// encoding of each addition that is present. Read it after the root components when the extension bit is set. It
+/// can be read from and encoded with either aligned PER or OER.
+///
+/// # Examples
+///
+/// ```
+/// // Foo ::= SEQUENCE { a INTEGER (0..7), ..., b BOOLEAN }
+/// let data = b"\xb0\x10\x01\x80"; // a = 3, b = TRUE
+/// let mut d = aper::Decoder::new(data);
+/// let is_ext = ExtensionMarker::from_aper(&mut d, UNCONSTRAINED).unwrap();
+/// let a = d.decode_int(Some(0), Some(7)).unwrap();
+/// let ext = ExtensionAdditions::from_aper(&mut d, UNCONSTRAINED).unwrap();
+/// let b = ext.decode::<bool>(0, UNCONSTRAINED); // Some(Ok(true))
+/// let unknown = ext.into_unknown(1); // Anything after b
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtensionAdditions {
+    pub(crate) values: Vec<Option<Vec<u8>>>,
+    pub(crate) lossless: bool,
+    pub(crate) canonical: bool,
+}
+
+impl ExtensionAdditions {
+    /// Construct the `ExtensionAdditions` of a value to encode from the encodings of the additions the type knows
+    /// about (`None` for those that are absent) and the ones it doesn't know about.
+    pub fn new(known: Vec<Option<Encoding>>, unknown: &UnknownExtensions) -> ExtensionAdditions {
+        let values = known.into_iter()
+            .map(|e| e.map(|e| if e.num_bits() == 0 { vec![0x00] } else { e.bytes().clone() }))
+            .collect();
+        ExtensionAdditions::with_values(values, unknown)
+    }
+
+    /// Same as [new()](#method.new), with the OER encodings of the additions the type knows about.
+    pub fn new_oer(known: Vec<Option<oer::Encoding>>, unknown: &UnknownExtensions) -> ExtensionAdditions {
+        let values = known.into_iter().map(|e| e.map(|e| e.into_bytes())).collect();
+        ExtensionAdditions::with_values(values, unknown)
+    }
+
+    fn with_values(mut values: Vec<Option<Vec<u8>>>, unknown: &UnknownExtensions) -> ExtensionAdditions {
+        if values.len() < unknown.count {
+            values.resize(unknown.count, None);
+        }
+        for &(i, ref bytes) in &unknown.values {
+            if i >= values.len() {
+                values.resize(i + 1, None);
+            }
+            values[i] = Some(bytes.clone());
+        }
+        ExtensionAdditions {
+            values,
+            lossless: false,
+            canonical: false,
+        }
+    }
+
+    /// Get the number of additions in the presence bitmap.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if no additions are present, in which case the extension bit should be clear.
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(|v| v.is_none())
+    }
+
+    /// Get the octets of the open type encoding of the `i`th addition, if it's present.
+    pub fn get(&self, i: usize) -> Option<&[u8]> {
+        match self.values.get(i) {
+            Some(Some(bytes)) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Decode the `i`th addition as a `T`, if it's present.
+    pub fn decode<T: APerElement>(&self, i: usize, constraints: Constraints) -> Option<Result<T, DecodeError>> {
+        self.get(i).map(|bytes| {
+            let mut decoder = Decoder::new(bytes);
+            decoder.set_lossless(self.lossless);
+            T::from_aper(&mut decoder, constraints)
+        })
+    }
+
+    /// Decode the `i`th addition as a `T` from an OER encoding, if it's present. The value must use all of the
+    /// octets of the open type.
+    pub fn decode_oer<T: OerElement>(&self, i: usize, constraints: Constraints) -> Option<Result<T, oer::DecodeError>> {
+        self.get(i).map(|bytes| {
+            let mut decoder = oer::Decoder::new(bytes);
+            decoder.set_lossless(self.lossless);
+            decoder.set_canonical(self.canonical);
+            let ret = T::from_oer(&mut decoder, constraints);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let value = ret.unwrap();
+            let ret = decoder.finish();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            Ok(value)
+        })
+    }
+
+    /// Get the additions after the first `known`, which the decoded type doesn't know about. They are only kept if
+    /// they were decoded in lossless mode.
+    pub fn into_unknown(self, known: usize) -> UnknownExtensions {
+        if !self.lossless || self.values.len() <= known {
+            return UnknownExtensions::default();
+        }
+        UnknownExtensions {
+            count: self.values.len(),
+            values: self.values.into_iter().enumerate().skip(known).filter_map(|(i, v)| v.map(|v| (i, v))).collect(),
+        }
+    }
+}
+
+impl APerElement for ExtensionAdditions {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read the presence bitmap and the additions that are present.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        // The size of the bitmap is a normally small length
+        let ret = decoder.read(1);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let n = if ret.unwrap() == 0 {
+            let ret = decoder.read(6);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap() as usize + 1
+        } else {
+            let ret = decoder.decode_length();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap()
+        };
+
+        let mut present = Vec::with_capacity(n);
+        for _ in 0..n {
+            let ret = decoder.read(1);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            present.push(ret.unwrap() > 0);
+        }
+
+        let mut values = Vec::with_capacity(n);
+        for p in present {
+            if !p {
+                values.push(None);
+                continue;
+            }
+            let ret = decoder.decode_open_type_bytes();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            values.push(Some(ret.unwrap()));
+        }
+        Ok(ExtensionAdditions {
+            values,
+            lossless: decoder.is_lossless(),
+            canonical: false,
+        })
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        let n = self.values.len();
+        if n == 0 {
+            return Err(EncodeError::InvalidSize);
+        }
+        let mut enc = if n <= 64 {
+            Encoding::with_uint(n as u64 - 1, 7)
+        } else {
+            let mut enc = Encoding::with_uint(1, 1);
+            let ret = encode_length(n);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = enc.append(&ret.unwrap());
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            enc
+        };
+
+        for v in &self.values {
+            let ret = enc.append(&Encoding::with_uint(v.is_some() as u64, 1));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        for bytes in self.values.iter().flatten() {
+            let ret = encode_open_type(&Encoding::with_bytes(bytes.clone()));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = enc.append(&ret.unwrap());
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(enc)
+    }
+}
+
+/// In OER, the presence bitmap is encoded like a BIT STRING, with a length determinant and an octet with the number of
+/// unused bits.
+impl OerElement for ExtensionAdditions {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read the presence bitmap and the additions that are present.
+    fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = BitString::from_oer(decoder, UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let present = ret.unwrap();
+
+        let mut values = Vec::with_capacity(present.get_num_bits());
+        for p in &present {
+            if !p {
+                values.push(None);
+                continue;
+            }
+            let ret = decoder.decode_open_type_bytes();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            values.push(Some(ret.unwrap()));
+        }
+        Ok(ExtensionAdditions {
+            values,
+            lossless: decoder.is_lossless(),
+            canonical: decoder.is_canonical(),
+        })
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        if self.values.is_empty() {
+            return Err(oer::EncodeError::InvalidSize);
+        }
+        let present: BitString = self.values.iter().map(|v| v.is_some()).collect();
+        let ret = present.to_oer(UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut enc = ret.unwrap();
+        for bytes in self.values.iter().flatten() {
+            enc.append(&oer::encode_open_type(&oer::Encoding::with_bytes(bytes.clone())));
+        }
+        Ok(enc)
+    }
+}
+
+/// The extension additions of a SEQUENCE value that the decoding type didn't know about, kept so the value can be
+/// re-encoded exactly. See [ExtensionAdditions](struct.ExtensionAdditions.html).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct UnknownExtensions {
+    // The size of the received presence bitmap
+    pub(crate) count: usize,
+    // The position and octets of each unknown addition that was present
+    pub(crate) values: Vec<(usize, Vec<u8>)>,
+}
+
+impl UnknownExtensions {
+    /// Check if there are no unknown additions.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// An alternative of an extensible CHOICE type that the decoding type doesn't know about: its index among the
+/// extension alternatives and the octets of its open type encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnknownAlternative {
+    pub index: u64,
+    pub value: Vec<u8>,
+}
+
+impl APerElement for UnknownAlternative {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read the index and value of an extension alternative. Read it after the extension bit.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let index = decoder.decode_normally_small();
+        if index.is_err() {
+            return Err(index.err().unwrap());
+        }
+        let value = decoder.decode_open_type_bytes();
+        if value.is_err() {
+            return Err(value.err().unwrap());
+        }
+        Ok(UnknownAlternative {
+            index: index.unwrap(),
+            value: value.unwrap(),
+        })
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        let ret = encode_normally_small(self.index);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut enc = ret.unwrap();
+        let ret = encode_open_type(&Encoding::with_bytes(self.value.clone()));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = enc.append(&ret.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(enc)
+    }
+}
@@ -1,5 +1,11 @@
+#![allow(clippy::legacy_numeric_constants)]
+
 use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_int};
 use std::{i8, i16, i32, u8, u16, u32};
+use ber::{self, BerElement, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
 
 macro_rules! int_impl {
     ($t:ident) => {
@@ -25,6 +31,91 @@ macro_rules! int_impl {
                 Ok(ret.unwrap())
             }
         }
+
+        impl BerElement for $t {
+            const TAG: Tag = Tag::INTEGER;
+
+            /// Read an `$t` from a BER encoding.
+            fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+                let ret = decoder.decode_int(tag);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let value = ret.unwrap();
+                if value < $t::MIN as i64 || value > $t::MAX as i64 {
+                    return Err(ber::DecodeError::MalformedInt);
+                }
+                Ok(value as $t)
+            }
+
+            fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+                Ok(ber::encode_int(*self as i64, tag))
+            }
+        }
+
+        impl OerElement for $t {
+            const CONSTRAINTS: Constraints = Constraints {
+                value: None,
+                size: None,
+            };
+
+            /// Read an `$t` from an OER encoding, which has the width of the type.
+            fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+                let ret = decoder.decode_int(Some($t::MIN as i64), Some($t::MAX as i64));
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok(ret.unwrap() as $t)
+            }
+
+            fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+                oer::encode_int(*self as i64, Some($t::MIN as i64), Some($t::MAX as i64))
+            }
+        }
+
+        impl JerElement for $t {
+            const CONSTRAINTS: Constraints = Constraints {
+                value: None,
+                size: None,
+            };
+
+            /// Read an `$t` from a JER encoding, which is a number without a fraction or exponent.
+            fn from_jer(value: &jer::Value, _: Constraints) -> Result<Self, jer::DecodeError> {
+                let ret = value.as_number();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match ret.unwrap().parse() {
+                    Ok(value) => Ok(value),
+                    Err(_) => Err(jer::DecodeError::MalformedInt),
+                }
+            }
+
+            fn to_jer(&self, _: Constraints) -> Result<jer::Value, jer::EncodeError> {
+                Ok(jer::Value::Number(self.to_string()))
+            }
+        }
+
+        impl XerElement for $t {
+            const NAME: &'static str = "INTEGER";
+
+            /// Read an `$t` from an XER encoding, which is its decimal digits.
+            fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+                let ret = decoder.decode_int();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let value = ret.unwrap();
+                if value < $t::MIN as i64 || value > $t::MAX as i64 {
+                    return Err(xer::DecodeError::MalformedInt);
+                }
+                Ok(value as $t)
+            }
+
+            fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+                Ok(xer::Encoding::text(&self.to_string()))
+            }
+        }
     };
 }
 
@@ -0,0 +1,385 @@
+use super::*;
+
+// Limits the recursion needed to parse nested arrays and objects
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    InvalidBits,
+    InvalidCharacter,
+    InvalidHex,
+    InvalidOid,
+    InvalidReal,
+    InvalidSize,
+    InvalidTime,
+    MalformedInt,
+    MalformedJson,
+    MissingMember,
+    NestingTooDeep,
+    TrailingData,
+    UnexpectedType,
+    UnknownMember,
+}
+
+/// Parse JSON text into a [Value](enum.Value.html).
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::jer::{self, JerElement, UNCONSTRAINED};
+///
+/// let value = jer::parse("{\"a\": [1, 2]}").unwrap();
+/// let a = Vec::<i32>::from_jer(value.member("a").unwrap(), UNCONSTRAINED).unwrap();
+/// println!("{:?}", a); // Prints [1, 2]
+/// ```
+pub fn parse(json: &str) -> Result<Value, DecodeError> {
+    let mut parser = Parser {
+        json: json.as_bytes(),
+        pos: 0,
+    };
+    let ret = parser.parse_value(0);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    parser.skip_whitespace();
+    if parser.pos < parser.json.len() {
+        return Err(DecodeError::TrailingData);
+    }
+    Ok(ret.unwrap())
+}
+
+struct Parser<'a> {
+    json: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.json.len() && b" \t\n\r".contains(&self.json[self.pos]) {
+            self.pos += 1;
+        }
+    }
+
+    // Skip whitespace, then consume `b` if it comes next
+    fn eat(&mut self, b: u8) -> bool {
+        self.skip_whitespace();
+        if self.json.get(self.pos) == Some(&b) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), DecodeError> {
+        if self.eat(b) {
+            Ok(())
+        } else {
+            Err(DecodeError::MalformedJson)
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Value, DecodeError> {
+        if depth >= MAX_DEPTH {
+            return Err(DecodeError::NestingTooDeep);
+        }
+        self.skip_whitespace();
+        let rest = &self.json[self.pos..];
+        for &(word, ref value) in &[(&b"null"[..], Value::Null),
+                                    (&b"true"[..], Value::Bool(true)),
+                                    (&b"false"[..], Value::Bool(false))] {
+            if rest.starts_with(word) {
+                self.pos += word.len();
+                return Ok(value.clone());
+            }
+        }
+        match rest.first() {
+            Some(b'"') => {
+                let ret = self.parse_string();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok(Value::String(ret.unwrap()))
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if self.eat(b']') {
+                    return Ok(Value::Array(items));
+                }
+                loop {
+                    let ret = self.parse_value(depth + 1);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    items.push(ret.unwrap());
+                    if self.eat(b']') {
+                        return Ok(Value::Array(items));
+                    }
+                    let ret = self.expect(b',');
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = Vec::new();
+                if self.eat(b'}') {
+                    return Ok(Value::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let name = self.parse_string();
+                    if name.is_err() {
+                        return Err(name.err().unwrap());
+                    }
+                    let ret = self.expect(b':');
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    let value = self.parse_value(depth + 1);
+                    if value.is_err() {
+                        return Err(value.err().unwrap());
+                    }
+                    members.push((name.unwrap(), value.unwrap()));
+                    if self.eat(b'}') {
+                        return Ok(Value::Object(members));
+                    }
+                    let ret = self.expect(b',');
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+            }
+            Some(_) => self.parse_number(),
+            None => Err(DecodeError::MalformedJson),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value, DecodeError> {
+        let start = self.pos;
+        let digits = |p: &mut Parser| {
+            let n = p.json[p.pos..].iter().take_while(|b| b.is_ascii_digit()).count();
+            p.pos += n;
+            n
+        };
+        if self.json.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        let n = digits(self);
+        if n == 0 || n > 1 && self.json[self.pos - n] == b'0' {
+            return Err(DecodeError::MalformedJson);
+        }
+        if self.json.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            if digits(self) == 0 {
+                return Err(DecodeError::MalformedJson);
+            }
+        }
+        if let Some(b'e') | Some(b'E') = self.json.get(self.pos) {
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.json.get(self.pos) {
+                self.pos += 1;
+            }
+            if digits(self) == 0 {
+                return Err(DecodeError::MalformedJson);
+            }
+        }
+        // Only ASCII characters were read, so this can't fail
+        Ok(Value::Number(String::from_utf8_lossy(&self.json[start..self.pos]).into_owned()))
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, DecodeError> {
+        let hex = match self.json.get(self.pos..self.pos + 4) {
+            Some(hex) => String::from_utf8_lossy(hex).into_owned(),
+            None => return Err(DecodeError::MalformedJson),
+        };
+        self.pos += 4;
+        match u32::from_str_radix(&hex, 16) {
+            Ok(n) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => Ok(n),
+            _ => Err(DecodeError::MalformedJson),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, DecodeError> {
+        if self.json.get(self.pos) != Some(&b'"') {
+            return Err(DecodeError::MalformedJson);
+        }
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let b = match self.json.get(self.pos) {
+                Some(&b) => b,
+                None => return Err(DecodeError::MalformedJson),
+            };
+            self.pos += 1;
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let e = match self.json.get(self.pos) {
+                        Some(&e) => e,
+                        None => return Err(DecodeError::MalformedJson),
+                    };
+                    self.pos += 1;
+                    let c = match e {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let ret = self.parse_hex4();
+                            if ret.is_err() {
+                                return Err(ret.err().unwrap());
+                            }
+                            let mut n = ret.unwrap();
+                            // A character outside the BMP is escaped as a surrogate pair
+                            if (0xd800..0xdc00).contains(&n) && self.json[self.pos..].starts_with(b"\\u") {
+                                self.pos += 2;
+                                let ret = self.parse_hex4();
+                                if ret.is_err() {
+                                    return Err(ret.err().unwrap());
+                                }
+                                let low = ret.unwrap();
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return Err(DecodeError::MalformedJson);
+                                }
+                                n = 0x10000 + ((n - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            match ::std::char::from_u32(n) {
+                                Some(c) => c,
+                                None => return Err(DecodeError::MalformedJson),
+                            }
+                        }
+                        _ => return Err(DecodeError::MalformedJson),
+                    };
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                b if b < 0x20 => return Err(DecodeError::MalformedJson),
+                b => bytes.push(b),
+            }
+        }
+        // The input is a `str` and escapes only add whole characters
+        Ok(String::from_utf8(bytes).unwrap())
+    }
+}
+
+impl Value {
+    /// Get the value of a BOOLEAN.
+    pub fn as_bool(&self) -> Result<bool, DecodeError> {
+        match *self {
+            Value::Bool(b) => Ok(b),
+            _ => Err(DecodeError::UnexpectedType),
+        }
+    }
+
+    /// Get the JSON text of a number.
+    pub fn as_number(&self) -> Result<&str, DecodeError> {
+        match *self {
+            Value::Number(ref n) => Ok(n),
+            _ => Err(DecodeError::UnexpectedType),
+        }
+    }
+
+    /// Get the contents of a string, e.g. the identifier of an ENUMERATED value.
+    pub fn as_str(&self) -> Result<&str, DecodeError> {
+        match *self {
+            Value::String(ref s) => Ok(s),
+            _ => Err(DecodeError::UnexpectedType),
+        }
+    }
+
+    /// Get the items of an array, e.g. a SEQUENCE OF value.
+    pub fn as_array(&self) -> Result<&[Value], DecodeError> {
+        match *self {
+            Value::Array(ref items) => Ok(items),
+            _ => Err(DecodeError::UnexpectedType),
+        }
+    }
+
+    /// Get the members of an object, e.g. a SEQUENCE value.
+    pub fn as_object(&self) -> Result<&[(String, Value)], DecodeError> {
+        match *self {
+            Value::Object(ref members) => Ok(members),
+            _ => Err(DecodeError::UnexpectedType),
+        }
+    }
+
+    /// Get the member `name` of an object if it's there, e.g. an OPTIONAL component of a SEQUENCE.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match *self {
+            Value::Object(ref members) => members.iter().find(|m| m.0 == name).map(|m| &m.1),
+            _ => None,
+        }
+    }
+
+    /// Get the member `name` of an object, which must be there, e.g. a mandatory component of a SEQUENCE.
+    pub fn member(&self, name: &str) -> Result<&Value, DecodeError> {
+        let ret = self.as_object();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ret.unwrap().iter().find(|m| m.0 == name) {
+            Some(m) => Ok(&m.1),
+            None => Err(DecodeError::MissingMember),
+        }
+    }
+
+    /// Check that every member of an object is named in `names`, e.g. the components of a SEQUENCE type that isn't
+    /// extensible.
+    pub fn check_members(&self, names: &[&str]) -> Result<(), DecodeError> {
+        let ret = self.as_object();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if ret.unwrap().iter().any(|m| !names.contains(&m.0.as_str())) {
+            return Err(DecodeError::UnknownMember);
+        }
+        Ok(())
+    }
+
+    /// Get the name and value of the only member of an object, i.e. the chosen alternative of a CHOICE value.
+    pub fn single_member(&self) -> Result<(&str, &Value), DecodeError> {
+        let ret = self.as_object();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ret.unwrap() {
+            [(name, value)] => Ok((name, value)),
+            [] => Err(DecodeError::MissingMember),
+            _ => Err(DecodeError::UnknownMember),
+        }
+    }
+}
+
+/// Decode an OCTET STRING with a size constraint from a string of hexadecimal digits, in either case.
+pub fn decode_octet_string(value: &Value, size: Option<Constraint>) -> Result<Vec<u8>, DecodeError> {
+    let ret = value.as_str();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let ret = decode_hex(ret.unwrap());
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let bytes = ret.unwrap();
+    if size.is_some_and(|sz_constr| !sz_constr.allows_size(bytes.len())) {
+        return Err(DecodeError::InvalidSize);
+    }
+    Ok(bytes)
+}
+
+pub(crate) fn decode_hex(s: &str) -> Result<Vec<u8>, DecodeError> {
+    if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(DecodeError::InvalidHex);
+    }
+    Ok(s.as_bytes()
+        .chunks(2)
+        .map(|d| u8::from_str_radix(::std::str::from_utf8(d).unwrap(), 16).unwrap())
+        .collect())
+}
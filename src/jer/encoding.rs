@@ -0,0 +1,99 @@
+use super::*;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum EncodeError {
+    InvalidSize,
+    InvalidValue,
+}
+
+/// A JSON value, which is the JER encoding of an ASN.1 value. The members of an object keep their order.
+///
+/// `Display` writes the value as compact JSON text.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::jer::{JerElement, Value, UNCONSTRAINED};
+///
+/// // Foo ::= SEQUENCE { a INTEGER, b BOOLEAN }
+/// let foo = Value::Object(vec![("a".to_string(), 5.to_jer(UNCONSTRAINED).unwrap()),
+///                              ("b".to_string(), true.to_jer(UNCONSTRAINED).unwrap())]);
+/// println!("{}", foo); // Prints {"a":5,"b":true}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    /// A number, as its JSON text.
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(ref n) => f.write_str(n),
+            Value::String(ref s) => write_string(f, s),
+            Value::Array(ref items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Value::Object(ref members) => {
+                f.write_str("{")?;
+                for (i, (name, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Encode an OCTET STRING with a size constraint, as a string of hexadecimal digits.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::jer::encode_octet_string;
+///
+/// println!("{}", encode_octet_string(&[0xde, 0xad], None).unwrap()); // Prints "DEAD"
+/// ```
+pub fn encode_octet_string(bytes: &[u8], size: Option<Constraint>) -> Result<Value, EncodeError> {
+    if size.is_some_and(|sz_constr| !sz_constr.allows_size(bytes.len())) {
+        return Err(EncodeError::InvalidSize);
+    }
+    Ok(Value::String(bytes.iter().map(|b| format!("{:02X}", b)).collect()))
+}
@@ -0,0 +1,104 @@
+mod decoder;
+mod encoding;
+pub use self::decoder::{DecodeError, decode_octet_string, parse};
+pub(crate) use self::decoder::decode_hex;
+pub use self::encoding::{EncodeError, Value, encode_octet_string};
+pub use aper::{Constraint, Constraints, UNCONSTRAINED};
+
+/// Trait for JER encoding/decoding.
+///
+/// The constraints are the same PER-visible [Constraints](struct.Constraints.html) as for aligned PER. JER only uses
+/// the size constraint, to check sizes and to write a BIT STRING with a fixed size as just its hexadecimal digits.
+///
+/// Following X.697, a SEQUENCE is an object with a member for each component that is present, a CHOICE is an object
+/// whose only member is the chosen alternative and an ENUMERATED value is a string holding its identifier.
+///
+/// # Examples
+///
+/// ```
+/// // Foo ::= CHOICE {
+/// //     a INTEGER,
+/// //     b ENUMERATED { red, green }
+/// // }
+/// extern crate asn1;
+/// use asn1::jer::{self, Constraints, JerElement, Value, UNCONSTRAINED};
+///
+/// enum Foo {
+///     A(i32),
+///     BRed,
+///     BGreen,
+/// }
+///
+/// impl JerElement for Foo {
+///     const CONSTRAINTS: Constraints = UNCONSTRAINED;
+///
+///     fn from_jer(value: &Value, _: Constraints) -> Result<Self, jer::DecodeError> {
+///         let ret = value.single_member();
+///         if ret.is_err() {
+///             return Err(ret.err().unwrap());
+///         }
+///         match ret.unwrap() {
+///             ("a", v) => {
+///                 let ret = i32::from_jer(v, UNCONSTRAINED);
+///                 if ret.is_err() {
+///                     return Err(ret.err().unwrap());
+///                 }
+///                 Ok(Foo::A(ret.unwrap()))
+///             }
+///             ("b", v) => match v.as_str() {
+///                 Ok("red") => Ok(Foo::BRed),
+///                 Ok("green") => Ok(Foo::BGreen),
+///                 _ => Err(jer::DecodeError::UnexpectedType),
+///             },
+///             _ => Err(jer::DecodeError::UnknownMember),
+///         }
+///     }
+///
+///     fn to_jer(&self, _: Constraints) -> Result<Value, jer::EncodeError> {
+///         let (name, value) = match *self {
+///             Foo::A(a) => ("a", a.to_jer(UNCONSTRAINED).unwrap()),
+///             Foo::BRed => ("b", Value::String("red".to_string())),
+///             Foo::BGreen => ("b", Value::String("green".to_string())),
+///         };
+///         Ok(Value::Object(vec![(name.to_string(), value)]))
+///     }
+/// }
+/// ```
+pub trait JerElement: Sized {
+    /// PER-visible Constraints
+    const CONSTRAINTS: Constraints;
+
+    /// Read a value with constraints `constraints` from its JSON value.
+    fn from_jer(value: &Value, constraints: Constraints) -> Result<Self, DecodeError>;
+
+    /// Encode the value, which must satisfy `constraints`, as a JSON value.
+    fn to_jer(&self, constraints: Constraints) -> Result<Value, EncodeError>;
+}
+
+/// Encode `value` with JER, as compact JSON text.
+pub fn encode<T: JerElement>(value: &T) -> Result<String, EncodeError> {
+    let ret = value.to_jer(T::CONSTRAINTS);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(ret.unwrap().to_string())
+}
+
+/// Decode a `T` from the JSON text `json`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::jer;
+///
+/// assert_eq!(Ok(vec![true, false]), jer::decode::<Vec<bool>>("[true, false]"));
+/// assert_eq!(Err(jer::DecodeError::UnexpectedType), jer::decode::<Vec<bool>>("[1]"));
+/// ```
+pub fn decode<T: JerElement>(json: &str) -> Result<T, DecodeError> {
+    let ret = parse(json);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    T::from_jer(&ret.unwrap(), T::CONSTRAINTS)
+}
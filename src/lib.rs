@@ -4,37 +4,116 @@
 //!
 //! Below are the currently supported ASN.1 types and their corresponding types/constructs in Rust.
 //!
-//! | ASN.1 Type     | Rust Type             |
-//! |----------------|-----------------------|
-//! | BIT STRING     | BitString             |
-//! | INTEGER*       | i8,i16,i32,u8,u16,u32 |
-//! | NULL           | ()                    |
-//! | OCTET STRING   | Vec\<u8\>             |
-//! | SEQUENCE       | struct                |
-//! | SEQUENCE OF    | Vec\<T\>              |
-//! | CHOICE         | enum                  |
+//! | ASN.1 Type        | Rust Type             |
+//! |-------------------|-----------------------|
+//! | BIT STRING        | BitString             |
+//! | BIT STRING {…}    | NamedBitString\<N\>   |
+//! | BMPString         | BMPString             |
+//! | DATE              | Date                  |
+//! | DATE-TIME         | DateTime              |
+//! | DURATION          | Duration              |
+//! | GeneralizedTime   | GeneralizedTime       |
+//! | IA5String         | IA5String             |
+//! | INTEGER*          | i8,i16,i32,u8,u16,u32 |
+//! | NULL              | ()                    |
+//! | NumericString     | NumericString         |
+//! | OBJECT IDENTIFIER | ObjectIdentifier      |
+//! | OCTET STRING      | Vec\<u8\>             |
+//! | PrintableString   | PrintableString       |
+//! | REAL              | f64                   |
+//! | RELATIVE-OID      | RelativeOid           |
+//! | SEQUENCE          | struct                |
+//! | SEQUENCE OF       | Vec\<T\>              |
+//! | TIME-OF-DAY       | TimeOfDay             |
+//! | UniversalString   | UniversalString       |
+//! | UTCTime           | UTCTime               |
+//! | UTF8String        | String                |
+//! | VisibleString     | VisibleString         |
+//! | CHOICE            | enum                  |
 //!
 //! *`INTEGER` fields of arbitrary widths (in PER encodings) can be decoded/encoded as long as they fit in an `i64`
 //! (see [aper::Decoder::decode_int](aper/struct.Decoder.html#method.decode_int) and
 //! [aper::encode_int](aper/fn.encode_int.html)).
+//!
+//! # Cargo Features
+//!
+//! - `chrono`: conversions between the time types and those of the `chrono` crate.
+//! - `serde`: `Serialize` and `Deserialize` for the value types above, and a serde `Serializer` and `Deserializer` for
+//!   aligned PER (see [aper::serde](aper/serde/index.html)).
+//! - `time`: conversions between the time types and those of the `time` crate.
 extern crate byteorder;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "time")]
+extern crate time as time_crate;
 
 /// Tools for encoding and decoding ASN.1 messages of the Aligned PER flavor.
 pub mod aper;
 
+/// Tools for encoding and decoding ASN.1 messages with the Basic Encoding Rules.
+pub mod ber;
+
+/// Encoding and decoding with the Canonical Encoding Rules, the canonical subset of BER suited to streaming.
+pub mod cer;
+
+/// Encoding and decoding with the Distinguished Encoding Rules, the canonical subset of BER used by X.509.
+pub mod der;
+
+/// Tools for encoding and decoding ASN.1 messages with the Octet Encoding Rules and their canonical form.
+pub mod oer;
+
+/// Tools for encoding and decoding ASN.1 messages with the JSON Encoding Rules.
+pub mod jer;
+
+/// Tools for encoding and decoding ASN.1 messages with the XML Encoding Rules and their canonical form.
+pub mod xer;
+
+/// Schema-driven transcoding between aligned and unaligned PER, for relaying messages without their Rust types.
+pub mod transcode;
+
+/// Runtime schemas and dynamic values, for decoding and encoding messages whose types are only known at runtime.
+pub mod schema;
+
+/// Parsing of ASN.1 modules written in X.680 notation into an abstract syntax tree, and of values written in value
+/// notation.
+pub mod notation;
+
 mod bit_string;
+mod char_string;
 mod integer;
+mod named_bits;
 mod sequence_of;
 mod sequence;
 mod bool;
 mod extensions;
 mod null;
-mod utils;
+mod object_set;
+mod oid;
+mod per;
+mod time;
+mod utf8_string;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+/// The contents octets of REAL values, shared by the encoding rules that use them.
+pub mod real;
 
-pub use bit_string::BitString;
+pub use bit_string::{BitString, BitStringIter};
+#[allow(unused_imports)]
 pub use bool::*;
+pub use char_string::{BMPString, IA5String, NumericString, PermittedAlphabet, PrintableString, UniversalString,
+                      VisibleString};
 pub use extensions::*;
+#[allow(unused_imports)]
 pub use integer::*;
+pub use named_bits::{NamedBit, NamedBitString};
+#[allow(unused_imports)]
 pub use null::*;
+pub use object_set::{Criticality, ObjectSet, ObjectSetEntry, OpenValue, ProtocolContainer, ProtocolField};
+pub use oid::{ObjectIdentifier, OidError, RelativeOid};
 pub use sequence::*;
+#[allow(unused_imports)]
 pub use sequence_of::*;
+pub use time::{Date, DateTime, Duration, GeneralizedTime, TimeError, TimeOfDay, UTCTime};
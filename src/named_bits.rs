@@ -0,0 +1,331 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError};
+use ber::{self, BerElement, Rules, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
+use bit_string::BitString;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Trait for the names in the NamedBitList of a BIT STRING type, usually implemented by an enum declared with
+/// [named_bits!](macro.named_bits.html).
+pub trait NamedBit: Copy {
+    /// The position of the named bit.
+    fn bit(self) -> usize;
+
+    /// The name of the bit at position `bit`, if there is one.
+    fn from_bit(bit: usize) -> Option<Self>;
+}
+
+/// A BIT STRING with a NamedBitList, e.g. `BIT STRING { flagA(0), flagB(1), flagC(2) } (SIZE(1..8))`.
+///
+/// Values that differ only in trailing 0 bits are equal, as X.680 requires for these types. When encoding, trailing 0
+/// bits are removed as X.691 requires (but never below the lower bound of the size constraint).
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate asn1;
+/// use asn1::NamedBitString;
+///
+/// named_bits! {
+///     pub enum Flags {
+///         FlagA = 0,
+///         FlagB = 1,
+///         FlagC = 2,
+///     }
+/// }
+///
+/// let mut flags = NamedBitString::<Flags>::new();
+/// flags.set(Flags::FlagB, true);
+/// println!("{}", flags.is_set(Flags::FlagB)); // Prints true
+/// println!("{}", flags.bits()); // Prints '01'B
+/// ```
+pub struct NamedBitString<N: NamedBit> {
+    bits: BitString,
+    names: PhantomData<N>,
+}
+
+impl<N: NamedBit> NamedBitString<N> {
+    /// Construct a `NamedBitString` with no bits set.
+    pub fn new() -> NamedBitString<N> {
+        Self::from_bits(BitString::new())
+    }
+
+    /// Construct a `NamedBitString` from the underlying `BitString`.
+    pub fn from_bits(bits: BitString) -> NamedBitString<N> {
+        NamedBitString {
+            bits,
+            names: PhantomData,
+        }
+    }
+
+    /// Check if the bit called `name` is set.
+    pub fn is_set(&self, name: N) -> bool {
+        self.bits.is_set(name.bit())
+    }
+
+    /// Set the bit called `name` to `val`, growing the `BitString` if needed.
+    pub fn set(&mut self, name: N, val: bool) {
+        let i = name.bit();
+        if i >= self.bits.get_num_bits() {
+            if !val {
+                return;
+            }
+            self.bits.set_num_bits(i + 1);
+        }
+        self.bits.set(i, val);
+    }
+
+    /// Get the names of all bits that are set. Set bits without a name are skipped.
+    pub fn names(&self) -> Vec<N> {
+        self.bits.iter().enumerate().filter(|&(_, b)| b).filter_map(|(i, _)| N::from_bit(i)).collect()
+    }
+
+    /// Get the underlying `BitString`.
+    pub fn bits(&self) -> &BitString {
+        &self.bits
+    }
+
+    /// Get the underlying `BitString` with trailing 0 bits removed.
+    pub fn trimmed(&self) -> BitString {
+        let mut bits = self.bits.clone();
+        bits.trim_trailing_zeros(0);
+        bits
+    }
+}
+
+impl<N: NamedBit> Default for NamedBitString<N> {
+    fn default() -> NamedBitString<N> {
+        NamedBitString::new()
+    }
+}
+
+impl<N: NamedBit> Clone for NamedBitString<N> {
+    fn clone(&self) -> NamedBitString<N> {
+        NamedBitString::from_bits(self.bits.clone())
+    }
+}
+
+impl<N: NamedBit> PartialEq for NamedBitString<N> {
+    fn eq(&self, other: &NamedBitString<N>) -> bool {
+        self.trimmed() == other.trimmed()
+    }
+}
+
+impl<N: NamedBit> Eq for NamedBitString<N> {}
+
+impl<N: NamedBit> Hash for NamedBitString<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trimmed().hash(state);
+    }
+}
+
+impl<N: NamedBit + fmt::Debug> fmt::Debug for NamedBitString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+impl<N: NamedBit> From<BitString> for NamedBitString<N> {
+    fn from(bits: BitString) -> NamedBitString<N> {
+        NamedBitString::from_bits(bits)
+    }
+}
+
+impl<N: NamedBit> APerElement for NamedBitString<N> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `NamedBitString` from an aligned PER encoding. With CANONICAL-PER, there must be no trailing 0 bits
+    /// beyond the lower bound of the size constraint.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let ret = BitString::from_aper(decoder, constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let bits = ret.unwrap();
+        let n = bits.get_num_bits();
+        let min = constraints.size.map_or(0, |c| c.size_bounds().0) as usize;
+        if decoder.is_canonical() && n > min && !bits.is_set(n - 1) {
+            return Err(DecodeError::TrailingZeroBits);
+        }
+        Ok(NamedBitString::from_bits(bits))
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        if constraints.size.is_none() {
+            return Err(EncodeError::MissingSizeConstraint);
+        }
+
+        let min = constraints.size.unwrap().size_bounds().0 as usize;
+        let mut bits = self.bits.clone();
+        bits.trim_trailing_zeros(min);
+        if bits.get_num_bits() < min {
+            bits.set_num_bits(min);
+        }
+        bits.to_aper(constraints)
+    }
+}
+
+impl<N: NamedBit> BerElement for NamedBitString<N> {
+    const TAG: Tag = Tag::BIT_STRING;
+
+    /// Read a `NamedBitString` from a BER encoding. With DER, there must be no trailing 0 bits.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = BitString::from_ber(decoder, tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let bits = ret.unwrap();
+        let n = bits.get_num_bits();
+        if decoder.rules() != Rules::Ber && n > 0 && !bits.is_set(n - 1) {
+            return Err(ber::DecodeError::TrailingZeroBits);
+        }
+        Ok(NamedBitString::from_bits(bits))
+    }
+
+    /// Trailing 0 bits are removed, as DER requires.
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        self.trimmed().to_ber(tag)
+    }
+}
+
+impl<N: NamedBit> OerElement for NamedBitString<N> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `NamedBitString` from an OER encoding. With COER, there must be no trailing 0 bits beyond the lower
+    /// bound of the size constraint, unless the size is fixed.
+    fn from_oer(decoder: &mut oer::Decoder, constraints: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = BitString::from_oer(decoder, constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let bits = ret.unwrap();
+        let n = bits.get_num_bits();
+        let min = constraints.size.map_or(0, |c| c.size_bounds().0) as usize;
+        if decoder.is_canonical() && constraints.fixed_size().is_none() && n > min && !bits.is_set(n - 1) {
+            return Err(oer::DecodeError::TrailingZeroBits);
+        }
+        Ok(NamedBitString::from_bits(bits))
+    }
+
+    /// Trailing 0 bits are removed down to the lower bound of the size constraint, as COER requires.
+    fn to_oer(&self, constraints: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        if constraints.fixed_size().is_some() {
+            return self.bits.to_oer(constraints);
+        }
+
+        let min = constraints.size.map_or(0, |c| c.size_bounds().0) as usize;
+        let mut bits = self.bits.clone();
+        bits.trim_trailing_zeros(min);
+        if bits.get_num_bits() < min {
+            bits.set_num_bits(min);
+        }
+        bits.to_oer(constraints)
+    }
+}
+
+impl<N: NamedBit> JerElement for NamedBitString<N> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `NamedBitString` from a JER encoding.
+    fn from_jer(value: &jer::Value, constraints: Constraints) -> Result<Self, jer::DecodeError> {
+        let ret = BitString::from_jer(value, constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(NamedBitString::from_bits(ret.unwrap()))
+    }
+
+    /// Trailing 0 bits are removed down to the lower bound of the size constraint, as with the other encodings.
+    fn to_jer(&self, constraints: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        if constraints.fixed_size().is_some() {
+            return self.bits.to_jer(constraints);
+        }
+
+        let min = constraints.size.map_or(0, |c| c.size_bounds().0) as usize;
+        let mut bits = self.bits.clone();
+        bits.trim_trailing_zeros(min);
+        if bits.get_num_bits() < min {
+            bits.set_num_bits(min);
+        }
+        bits.to_jer(constraints)
+    }
+}
+
+impl<N: NamedBit> XerElement for NamedBitString<N> {
+    const NAME: &'static str = "BIT_STRING";
+
+    /// Read a `NamedBitString` from an XER encoding. With CXER, there must be no trailing 0 bits.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        let ret = BitString::from_xer(decoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let bits = ret.unwrap();
+        let n = bits.get_num_bits();
+        if decoder.is_canonical() && n > 0 && !bits.is_set(n - 1) {
+            return Err(xer::DecodeError::TrailingZeroBits);
+        }
+        Ok(NamedBitString::from_bits(bits))
+    }
+
+    /// Trailing 0 bits are removed, as CXER requires.
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        let mut bits = self.bits.clone();
+        bits.trim_trailing_zeros(0);
+        bits.to_xer()
+    }
+}
+
+/// Declare an enum whose variants name the bits of a BIT STRING type, for use with
+/// [NamedBitString](struct.NamedBitString.html).
+///
+/// # Examples
+///
+/// ```
+/// // Flags ::= BIT STRING { flagA(0), flagB(1), flagC(2) } (SIZE(1..8))
+/// named_bits! {
+///     pub enum Flags {
+///         FlagA = 0,
+///         FlagB = 1,
+///         FlagC = 2,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! named_bits {
+    ($(#[$attr:meta])* pub enum $name:ident { $($variant:ident = $bit:expr),* $(,)* }) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl $crate::NamedBit for $name {
+            fn bit(self) -> usize {
+                match self {
+                    $($name::$variant => $bit),*
+                }
+            }
+
+            fn from_bit(bit: usize) -> Option<$name> {
+                $(if bit == $bit {
+                    return Some($name::$variant);
+                })*
+                None
+            }
+        }
+    };
+}
@@ -0,0 +1,421 @@
+use super::Position;
+
+/// An ASN.1 module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub name: String,
+    /// The object identifier of the module, if its header has one.
+    pub oid: Option<Value>,
+    pub tag_default: TagDefault,
+    /// Whether the header says `EXTENSIBILITY IMPLIED`.
+    pub extensibility_implied: bool,
+    /// The symbols listed by the EXPORTS clause, or `None` if the module exports everything.
+    pub exports: Option<Vec<String>>,
+    pub imports: Vec<Import>,
+    pub assignments: Vec<Assignment>,
+    pub position: Position,
+}
+
+impl Module {
+    /// Get the assignment of `name`.
+    pub fn assignment(&self, name: &str) -> Option<&Assignment> {
+        self.assignments.iter().find(|a| a.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TagDefault {
+    Explicit,
+    Implicit,
+    Automatic,
+}
+
+/// The symbols imported from a module. The symbols of parameterized assignments are listed without their `{}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub symbols: Vec<String>,
+    pub module: String,
+    /// The object identifier (or value reference to one) identifying the module, if there's one.
+    pub oid: Option<Value>,
+}
+
+/// A type, value, value set, object, object set or information object class assignment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub name: String,
+    /// The dummy parameters of a parameterized assignment.
+    pub parameters: Vec<Parameter>,
+    pub kind: AssignmentKind,
+    pub position: Position,
+}
+
+/// What an assignment assigns.
+///
+/// An object is assigned like a value, whose governor is a reference to its class, and an object set like a value set.
+/// Telling them apart needs to know what the governor refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignmentKind {
+    /// `Foo ::= Type`
+    Type(Type),
+    /// `foo Type ::= value`
+    Value { ty: Type, value: Value },
+    /// `Foo Type ::= { set }`
+    ValueSet { ty: Type, set: ElementSetSpecs },
+    /// `FOO ::= CLASS { ... }`
+    Class(Class),
+}
+
+/// A dummy parameter of a parameterized assignment, e.g. `CLASS : IEsSetParam` or `Type`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub governor: Option<Type>,
+    pub name: String,
+}
+
+/// A type with its constraints, in the order they're written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    pub kind: TypeKind,
+    pub constraints: Vec<Constraint>,
+}
+
+impl Type {
+    /// Construct an unconstrained `Type`.
+    pub fn new(kind: TypeKind) -> Type {
+        Type {
+            kind,
+            constraints: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind {
+    Boolean,
+    Null,
+    /// An INTEGER with its named numbers.
+    Integer(Vec<NamedNumber>),
+    Real,
+    /// A BIT STRING with its named bits.
+    BitString(Vec<NamedNumber>),
+    OctetString,
+    Enumerated {
+        root: Vec<NamedNumber>,
+        extensions: Option<Vec<NamedNumber>>,
+    },
+    ObjectIdentifier,
+    RelativeOid,
+    String(StringKind),
+    Time(TimeKind),
+    External,
+    EmbeddedPdv,
+    /// The unrestricted CHARACTER STRING type.
+    CharacterString,
+    Sequence(ComponentList),
+    Set(ComponentList),
+    /// A SEQUENCE OF, with the identifier of its element if it has one. A SIZE constraint written before OF is one of
+    /// the constraints of the SEQUENCE OF type.
+    SequenceOf {
+        name: Option<String>,
+        element: Box<Type>,
+    },
+    SetOf {
+        name: Option<String>,
+        element: Box<Type>,
+    },
+    Choice(ComponentList),
+    Tagged {
+        tag: Tag,
+        mode: Option<TagMode>,
+        ty: Box<Type>,
+    },
+    /// A reference to a type (or a class, value set or object set), with the actual parameters of a parameterized
+    /// one.
+    Reference {
+        reference: Reference,
+        arguments: Vec<Argument>,
+    },
+    /// A field of an information object class, e.g. `NGAP-PROTOCOL-IES.&Value`. `fields` has more than one item for
+    /// fields of objects held by fields.
+    ClassField {
+        class: Reference,
+        fields: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StringKind {
+    BMPString,
+    GeneralString,
+    GraphicString,
+    IA5String,
+    ISO646String,
+    NumericString,
+    PrintableString,
+    T61String,
+    TeletexString,
+    UniversalString,
+    UTF8String,
+    VideotexString,
+    VisibleString,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeKind {
+    Date,
+    DateTime,
+    Duration,
+    GeneralizedTime,
+    Time,
+    TimeOfDay,
+    UTCTime,
+}
+
+/// A possibly qualified reference, e.g. `maxnoofCells` or `NGAP-Constants.maxnoofCells`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub module: Option<String>,
+    pub name: String,
+}
+
+impl Reference {
+    /// Construct an unqualified `Reference`.
+    pub fn new(name: &str) -> Reference {
+        Reference {
+            module: None,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// An actual parameter of a parameterized reference.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    Type(Type),
+    Value(Value),
+    /// A value set or object set in braces, e.g. the `{IEsSetParam}` of `ProtocolIE-Field {{IEsSetParam}}`.
+    Set(ElementSetSpecs),
+}
+
+/// A named number of an INTEGER, a named bit of a BIT STRING or an item of an ENUMERATED type, whose number is only
+/// optional for the latter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedNumber {
+    pub name: String,
+    pub number: Option<Value>,
+}
+
+/// The components of a SEQUENCE or SET, or the alternatives of a CHOICE.
+///
+/// Root components after a second extension marker are appended to `root`, as PER encodes them with the others.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentList {
+    pub root: Vec<Component>,
+    /// The extension additions, if there's an extension marker.
+    pub extensions: Option<Vec<ExtensionAddition>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    pub name: String,
+    pub ty: Type,
+    pub presence: Presence,
+    pub position: Position,
+}
+
+/// Whether a component may be absent. The alternatives of a CHOICE are `Required`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Presence {
+    Required,
+    Optional,
+    Default(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionAddition {
+    Component(Component),
+    /// An extension addition group `[[ ... ]]`, with its version number if it has one.
+    Group {
+        version: Option<u64>,
+        components: Vec<Component>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub class: TagClass,
+    pub number: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TagClass {
+    Universal,
+    Application,
+    ContextSpecific,
+    Private,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TagMode {
+    Explicit,
+    Implicit,
+}
+
+/// A value, as far as it can be understood without its type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Boolean(bool),
+    Null,
+    Integer(i64),
+    /// A real number, including `PLUS-INFINITY`, `MINUS-INFINITY` and `NOT-A-NUMBER`.
+    Real(f64),
+    /// A character string, without its quotes.
+    CString(String),
+    /// The binary digits of a `'...'B` string.
+    BString(String),
+    /// The hexadecimal digits of a `'...'H` string.
+    HString(String),
+    /// A value reference, an identifier (e.g. of an ENUMERATED value), or a word of an object's defined syntax.
+    Reference(Reference),
+    /// A CHOICE value `identifier : value`.
+    Choice(String, Box<Value>),
+    /// An OBJECT IDENTIFIER arc in name and number form, e.g. `iso(1)`.
+    NamedNumber(String, Box<Value>),
+    /// A value in braces, whose meaning depends on its type: the items of each part separated by commas. For
+    /// example, `{ a 1, b 2 }` is a SEQUENCE value, `{ 1, 2 }` a SEQUENCE OF value, `{ iso member-body(2) 840 }` an
+    /// OBJECT IDENTIFIER value and `{ ID id-Cause CRITICALITY ignore TYPE Cause }` an object in defined syntax.
+    Braced(Vec<Vec<Value>>),
+    /// A type in an object's defined syntax, e.g. the `OCTET STRING` of `{ ... TYPE OCTET STRING }`.
+    Type(Box<Type>),
+}
+
+/// A parenthesized constraint.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constraint {
+    /// A subtype constraint, which is also how a simple table constraint like `({IEsSetParam})` is written.
+    Subtype(ElementSetSpecs),
+    /// A component relation constraint, e.g. `({IEsSetParam}{@id})`, with the component references without their
+    /// `@`.
+    Relation {
+        set: ElementSetSpecs,
+        components: Vec<String>,
+    },
+    /// A contents constraint `CONTAINING Type ENCODED BY value`, where either part may be missing.
+    Contents {
+        ty: Option<Type>,
+        encoded_by: Option<Value>,
+    },
+    /// A user-defined constraint `CONSTRAINED BY { ... }`, whose contents are skipped.
+    UserDefined,
+}
+
+/// The root element set of a constraint, and whether it's extensible with which additional elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementSetSpecs {
+    /// `None` for a constraint that's just an extension marker.
+    pub root: Option<ElementSet>,
+    pub extensible: bool,
+    pub additions: Option<ElementSet>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementSet {
+    Union(Vec<ElementSet>),
+    Intersection(Vec<ElementSet>),
+    Except(Box<ElementSet>, Box<ElementSet>),
+    /// The `ALL` of `ALL EXCEPT ...`.
+    All,
+    Value(Value),
+    Range {
+        lower: Endpoint,
+        upper: Endpoint,
+    },
+    Size(Box<Constraint>),
+    PermittedAlphabet(Box<Constraint>),
+    /// A contained subtype, or a reference to a value set or object set.
+    ContainedSubtype(Type),
+    Pattern(Value),
+    /// `WITH COMPONENT (...)`, for the elements of a SEQUENCE OF or SET OF.
+    WithComponent(Box<Constraint>),
+    /// `WITH COMPONENTS { ... }`, where `partial` is set if the list starts with `...`.
+    WithComponents {
+        partial: bool,
+        components: Vec<ComponentConstraint>,
+    },
+    /// An element set in parentheses.
+    Nested(Box<ElementSetSpecs>),
+    /// A value set or object set in braces.
+    Braced(Box<ElementSetSpecs>),
+}
+
+/// An endpoint of a range, which excludes its value if it's `open` (written `<`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    pub value: Bound,
+    pub open: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bound {
+    Min,
+    Max,
+    Value(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentConstraint {
+    pub name: String,
+    pub constraint: Option<Constraint>,
+    pub presence: Option<PresenceConstraint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PresenceConstraint {
+    Present,
+    Absent,
+    Optional,
+}
+
+/// An information object class.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Class {
+    pub fields: Vec<ClassField>,
+    /// The `WITH SYNTAX` of the class, if it has one.
+    pub syntax: Option<Vec<SyntaxToken>>,
+}
+
+/// A field of an information object class. The name keeps its `&`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassField {
+    pub name: String,
+    pub kind: FieldKind,
+    pub unique: bool,
+    pub optional: bool,
+    pub default: Option<Value>,
+}
+
+/// What a field of a class holds.
+///
+/// Like assignments, a value field and an object field look the same, as do a value set field and an object set
+/// field, and only differ by what their governor refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldKind {
+    /// `&Type`
+    Type,
+    /// `&value Type` or `&object CLASS`
+    Value(Type),
+    /// `&ValueSet Type` or `&ObjectSet CLASS`
+    ValueSet(Type),
+}
+
+/// An item of the defined syntax of a class.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxToken {
+    /// A literal word, e.g. `ID`.
+    Word(String),
+    Comma,
+    /// The field whose setting goes here.
+    Field(String),
+    /// An optional group `[ ... ]`.
+    Optional(Vec<SyntaxToken>),
+}
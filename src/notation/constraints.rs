@@ -0,0 +1,518 @@
+use aper::{self, Constraints};
+use {BMPString, IA5String, NumericString, PermittedAlphabet, PrintableString, UniversalString, VisibleString};
+use super::resolve::MAX_DEPTH;
+use super::*;
+
+/// The PER-visible constraints of a type (X.691 clause 10.3), i.e. the ones that affect how it's encoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerConstraints {
+    /// The range of an INTEGER type.
+    pub value: Option<aper::Constraint>,
+    /// Whether the range of an INTEGER type is extensible, so that a value outside it is encoded unconstrained.
+    pub value_extensible: bool,
+    /// The range of the number of bits, octets, characters or elements of a string or SEQUENCE OF type.
+    pub size: Option<aper::Constraint>,
+    pub size_extensible: bool,
+    /// The effective PermittedAlphabet of a known-multiplier character string type, which is its whole alphabet if it
+    /// has no PER-visible PermittedAlphabet constraint.
+    pub alphabet: Option<PermittedAlphabet>,
+}
+
+impl PerConstraints {
+    /// Get the `Constraints` to encode or decode a value of the type with.
+    pub fn constraints(&self) -> Constraints {
+        Constraints {
+            value: self.value,
+            size: self.size,
+        }
+    }
+}
+
+/// Compute the PER-visible constraints of `ty`, including the ones of the types it refers to, looking up references
+/// with `resolver`.
+///
+/// Constraints that aren't PER-visible are ignored, as X.691 requires: table and relation constraints, contents
+/// constraints, user-defined constraints, patterns, inner subtyping, single values of character strings, and
+/// extensible PermittedAlphabet constraints. A union with a constraint that isn't PER-visible isn't either, and only
+/// the first set of an EXCEPT is visible. Extension additions are ignored, but make the constraint extensible.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::Constraint;
+/// use asn1::notation::{self, AssignmentKind};
+///
+/// let modules = notation::parse("Foo DEFINITIONS ::= BEGIN
+///                                    maxnoofCells INTEGER ::= 16
+///                                    Cells ::= SEQUENCE (SIZE (1..maxnoofCells, ...)) OF INTEGER (0..4095)
+///                                END")
+///     .unwrap();
+/// let ty = match modules[0].assignment("Cells").unwrap().kind {
+///     AssignmentKind::Type(ref ty) => ty.clone(),
+///     _ => unreachable!(),
+/// };
+/// let per = notation::per_constraints(&ty, &modules[..]).unwrap();
+/// assert_eq!(per.size, Some(Constraint::new(Some(1), Some(16))));
+/// assert!(per.size_extensible);
+/// ```
+pub fn per_constraints<R: Resolver + ?Sized>(ty: &Type, resolver: &R) -> Result<PerConstraints, ResolveError> {
+    let ret = Evaluator { resolver }.type_constraints(ty, 0);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let (kind, effective) = ret.unwrap();
+
+    let (value, value_extensible) = match kind {
+        Kind::Integer => {
+            let ret = interval(effective.value);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap()
+        }
+        _ => (None, false),
+    };
+    let (size, size_extensible) = match kind {
+        Kind::Sized | Kind::KnownMultiplier => {
+            // A size can't be negative, and a size that's only at least 0 isn't a constraint
+            let sizes = effective.size.map(|b| {
+                                               Bounds {
+                                                   lb: Some(b.lb.unwrap_or(0).max(0)),
+                                                   ..b
+                                               }
+                                           });
+            let ret = interval(sizes.filter(|b| b.lb != Some(0) || b.ub.is_some()));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap()
+        }
+        _ => (None, false),
+    };
+    let alphabet = match kind {
+        Kind::KnownMultiplier => effective.alphabet,
+        _ => None,
+    };
+    if alphabet.as_ref().is_some_and(|a| a.is_empty()) {
+        return Err(ResolveError::EmptyConstraint);
+    }
+
+    Ok(PerConstraints {
+           value,
+           value_extensible,
+           size,
+           size_extensible,
+           alphabet,
+       })
+}
+
+/// Get the interval and extensibility of `bounds`.
+fn interval(bounds: Option<Bounds>) -> Result<(Option<aper::Constraint>, bool), ResolveError> {
+    match bounds {
+        Some(b) if b.is_empty() => Err(ResolveError::EmptyConstraint),
+        Some(b) if b.lb.is_some() || b.ub.is_some() => Ok((Some(aper::Constraint::new(b.lb, b.ub)), b.extensible)),
+        _ => Ok((None, false)),
+    }
+}
+
+/// What a constraint applies to, which decides which of its parts are PER-visible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Integer,
+    /// A BIT STRING, OCTET STRING, SEQUENCE OF or SET OF type.
+    Sized,
+    KnownMultiplier,
+    /// The characters of a PermittedAlphabet constraint.
+    Alphabet,
+    /// A type without PER-visible constraints.
+    Other,
+}
+
+/// An inclusive range, where a missing bound is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    lb: Option<i64>,
+    ub: Option<i64>,
+    extensible: bool,
+}
+
+impl Bounds {
+    fn is_empty(&self) -> bool {
+        match (self.lb, self.ub) {
+            (Some(lb), Some(ub)) => lb > ub,
+            _ => false,
+        }
+    }
+}
+
+// For every operation, `None` stands for every value, i.e. a dimension that isn't (visibly) constrained
+
+fn union_bounds(a: Option<Bounds>, b: Option<Bounds>) -> Option<Bounds> {
+    match (a, b) {
+        (Some(a), Some(b)) if a.is_empty() => Some(b),
+        (Some(a), Some(b)) if b.is_empty() => Some(a),
+        (Some(a), Some(b)) => {
+            Some(Bounds {
+                     lb: a.lb.and_then(|x| b.lb.map(|y| x.min(y))),
+                     ub: a.ub.and_then(|x| b.ub.map(|y| x.max(y))),
+                     extensible: a.extensible || b.extensible,
+                 })
+        }
+        _ => None,
+    }
+}
+
+fn intersect_bounds(a: Option<Bounds>, b: Option<Bounds>, extensible: fn(bool, bool) -> bool) -> Option<Bounds> {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            Some(Bounds {
+                     lb: a.lb.max(b.lb),
+                     ub: match (a.ub, b.ub) {
+                         (Some(x), Some(y)) => Some(x.min(y)),
+                         (x, None) | (None, x) => x,
+                     },
+                     extensible: extensible(a.extensible, b.extensible),
+                 })
+        }
+        (a, None) | (None, a) => a,
+    }
+}
+
+/// The constraints of a type so far, on each of the things PER-visible constraints can constrain.
+#[derive(Debug, Clone, Default)]
+struct Effective {
+    value: Option<Bounds>,
+    size: Option<Bounds>,
+    alphabet: Option<PermittedAlphabet>,
+}
+
+impl Effective {
+    fn union(self, other: Effective) -> Effective {
+        Effective {
+            value: union_bounds(self.value, other.value),
+            size: union_bounds(self.size, other.size),
+            alphabet: match (self.alphabet, other.alphabet) {
+                (Some(a), Some(b)) => Some(a.union(&b)),
+                _ => None,
+            },
+        }
+    }
+
+    fn intersection(self, other: Effective) -> Effective {
+        Effective {
+            value: intersect_bounds(self.value, other.value, |a, b| a && b),
+            size: intersect_bounds(self.size, other.size, |a, b| a && b),
+            alphabet: match (self.alphabet, other.alphabet) {
+                (Some(a), Some(b)) => Some(a.intersection(&b)),
+                (a, None) | (None, a) => a,
+            },
+        }
+    }
+
+    /// Apply `later` to a type with these constraints, where only the extensibility of `later` counts.
+    fn serial(self, later: Effective) -> Effective {
+        Effective {
+            value: intersect_bounds(self.value, later.value, |_, b| b),
+            size: intersect_bounds(self.size, later.size, |_, b| b),
+            alphabet: match (self.alphabet, later.alphabet) {
+                (Some(a), Some(b)) => Some(a.intersection(&b)),
+                (a, None) | (None, a) => a,
+            },
+        }
+    }
+
+    /// Mark the constraints as extensible, which makes a PermittedAlphabet constraint invisible.
+    fn extend(&mut self) {
+        if let Some(ref mut b) = self.value {
+            b.extensible = true;
+        }
+        if let Some(ref mut b) = self.size {
+            b.extensible = true;
+        }
+        self.alphabet = None;
+    }
+}
+
+struct Evaluator<'a, R: 'a + ?Sized> {
+    resolver: &'a R,
+}
+
+impl<'a, R: Resolver + ?Sized> Evaluator<'a, R> {
+    /// Get the kind of `ty` and its effective constraints.
+    fn type_constraints(&self, ty: &Type, depth: usize) -> Result<(Kind, Effective), ResolveError> {
+        if depth > MAX_DEPTH {
+            return Err(ResolveError::TooDeep);
+        }
+        let (kind, mut effective) = match ty.kind {
+            TypeKind::Reference { ref reference, ref arguments } => {
+                let ret = match self.resolver.resolve_type(reference, arguments) {
+                    Ok(referenced) => self.type_constraints(&referenced, depth + 1),
+                    Err(e) => Err(e),
+                };
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            }
+            TypeKind::Tagged { ref ty, .. } => {
+                let ret = self.type_constraints(ty, depth + 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            }
+            // The constraints of a field of a class are table constraints, which aren't PER-visible
+            TypeKind::ClassField { ref class, ref fields } => {
+                let ret = self.resolver.resolve_class(class);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let field = ret.unwrap().fields.into_iter().find(|f| fields.len() == 1 && f.name == fields[0]);
+                return match field.map(|f| f.kind) {
+                    Some(FieldKind::Value(ty)) | Some(FieldKind::ValueSet(ty)) => self.type_constraints(&ty, depth + 1),
+                    _ => Ok((Kind::Other, Effective::default())),
+                };
+            }
+            TypeKind::Integer(_) => (Kind::Integer, Effective::default()),
+            TypeKind::BitString(_) | TypeKind::OctetString | TypeKind::SequenceOf { .. } | TypeKind::SetOf { .. } => {
+                (Kind::Sized, Effective::default())
+            }
+            TypeKind::String(kind) => {
+                match known_multiplier_alphabet(kind) {
+                    Some(alphabet) => {
+                        (Kind::KnownMultiplier,
+                         Effective {
+                             alphabet: Some(alphabet),
+                             ..Effective::default()
+                         })
+                    }
+                    None => (Kind::Other, Effective::default()),
+                }
+            }
+            _ => (Kind::Other, Effective::default()),
+        };
+        if kind == Kind::Other {
+            return Ok((kind, effective));
+        }
+
+        for constraint in &ty.constraints {
+            let ret = self.constraint(constraint, kind, depth);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            effective = effective.serial(ret.unwrap());
+        }
+        Ok((kind, effective))
+    }
+
+    fn constraint(&self, constraint: &Constraint, kind: Kind, depth: usize) -> Result<Effective, ResolveError> {
+        match *constraint {
+            Constraint::Subtype(ref specs) => self.specs(specs, kind, depth),
+            _ => Ok(Effective::default()),
+        }
+    }
+
+    fn specs(&self, specs: &ElementSetSpecs, kind: Kind, depth: usize) -> Result<Effective, ResolveError> {
+        let mut effective = match specs.root {
+            Some(ref root) => {
+                let ret = self.element_set(root, kind, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            }
+            None => Effective::default(),
+        };
+        if specs.extensible {
+            effective.extend();
+        }
+        Ok(effective)
+    }
+
+    fn element_set(&self, set: &ElementSet, kind: Kind, depth: usize) -> Result<Effective, ResolveError> {
+        match *set {
+            ElementSet::Union(ref sets) | ElementSet::Intersection(ref sets) => {
+                let union = matches!(*set, ElementSet::Union(_));
+                let mut effective: Option<Effective> = None;
+                for set in sets {
+                    let ret = self.element_set(set, kind, depth);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    effective = Some(match effective {
+                                         Some(e) if union => e.union(ret.unwrap()),
+                                         Some(e) => e.intersection(ret.unwrap()),
+                                         None => ret.unwrap(),
+                                     });
+                }
+                Ok(effective.unwrap_or_default())
+            }
+            ElementSet::Except(ref set, _) => self.element_set(set, kind, depth),
+            ElementSet::Value(ref value) => {
+                match kind {
+                    Kind::Integer => {
+                        let ret = self.integer(value, depth);
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                        let v = ret.unwrap();
+                        Ok(Effective {
+                               value: Some(Bounds {
+                                               lb: Some(v),
+                                               ub: Some(v),
+                                               extensible: false,
+                                           }),
+                               ..Effective::default()
+                           })
+                    }
+                    Kind::Alphabet => {
+                        let ret = self.characters(value, depth);
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                        Ok(Effective {
+                               alphabet: Some(PermittedAlphabet::new(&ret.unwrap())),
+                               ..Effective::default()
+                           })
+                    }
+                    _ => Ok(Effective::default()),
+                }
+            }
+            ElementSet::Range { ref lower, ref upper } => self.range(lower, upper, kind, depth),
+            ElementSet::Size(ref constraint) if kind == Kind::Sized || kind == Kind::KnownMultiplier => {
+                let ret = self.constraint(constraint, Kind::Integer, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok(Effective {
+                       size: ret.unwrap().value,
+                       ..Effective::default()
+                   })
+            }
+            ElementSet::PermittedAlphabet(ref constraint) if kind == Kind::KnownMultiplier => {
+                let ret = self.constraint(constraint, Kind::Alphabet, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok(Effective {
+                       alphabet: ret.unwrap().alphabet,
+                       ..Effective::default()
+                   })
+            }
+            ElementSet::ContainedSubtype(ref ty) => {
+                let ret = self.type_constraints(ty, depth + 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok(ret.unwrap().1)
+            }
+            ElementSet::Nested(ref specs) | ElementSet::Braced(ref specs) => self.specs(specs, kind, depth),
+            _ => Ok(Effective::default()),
+        }
+    }
+
+    fn range(&self, lower: &Endpoint, upper: &Endpoint, kind: Kind, depth: usize) -> Result<Effective, ResolveError> {
+        match kind {
+            Kind::Integer => {
+                let mut bounds = Vec::with_capacity(2);
+                for endpoint in &[lower, upper] {
+                    bounds.push(match endpoint.value {
+                                    Bound::Value(ref value) => {
+                                        let ret = self.integer(value, depth);
+                                        if ret.is_err() {
+                                            return Err(ret.err().unwrap());
+                                        }
+                                        Some(ret.unwrap())
+                                    }
+                                    _ => None,
+                                });
+                }
+                Ok(Effective {
+                       value: Some(Bounds {
+                                       lb: bounds[0].map(|v| if lower.open { v.saturating_add(1) } else { v }),
+                                       ub: bounds[1].map(|v| if upper.open { v.saturating_sub(1) } else { v }),
+                                       extensible: false,
+                                   }),
+                       ..Effective::default()
+                   })
+            }
+            Kind::Alphabet => {
+                let mut codes = Vec::with_capacity(2);
+                for endpoint in &[lower, upper] {
+                    codes.push(match endpoint.value {
+                                   Bound::Min => 0,
+                                   Bound::Max => 0x10_ffff,
+                                   Bound::Value(ref value) => {
+                                       let ret = self.characters(value, depth);
+                                       if ret.is_err() {
+                                           return Err(ret.err().unwrap());
+                                       }
+                                       let s = ret.unwrap();
+                                       let mut chars = s.chars();
+                                       match (chars.next(), chars.next()) {
+                                           (Some(c), None) => c as u32,
+                                           _ => return Err(ResolveError::InvalidValue),
+                                       }
+                                   }
+                               });
+                }
+                let lb = if lower.open { codes[0] + 1 } else { codes[0] };
+                let ub = if upper.open { codes[1].saturating_sub(1) } else { codes[1] };
+                Ok(Effective {
+                       alphabet: Some(PermittedAlphabet::with_code_ranges(&[(lb, ub)])),
+                       ..Effective::default()
+                   })
+            }
+            _ => Ok(Effective::default()),
+        }
+    }
+
+    /// Get the integer `value` stands for.
+    fn integer(&self, value: &Value, depth: usize) -> Result<i64, ResolveError> {
+        match *value {
+            Value::Integer(v) => Ok(v),
+            Value::Reference(ref reference) => {
+                match self.referenced_value(reference, depth) {
+                    Ok(value) => self.integer(&value, depth + 1),
+                    Err(e) => Err(e),
+                }
+            }
+            _ => Err(ResolveError::InvalidValue),
+        }
+    }
+
+    /// Get the characters of the character string `value` stands for.
+    fn characters(&self, value: &Value, depth: usize) -> Result<String, ResolveError> {
+        match *value {
+            Value::CString(ref s) => Ok(s.clone()),
+            Value::Reference(ref reference) => {
+                match self.referenced_value(reference, depth) {
+                    Ok(value) => self.characters(&value, depth + 1),
+                    Err(e) => Err(e),
+                }
+            }
+            _ => Err(ResolveError::InvalidValue),
+        }
+    }
+
+    fn referenced_value(&self, reference: &Reference, depth: usize) -> Result<Value, ResolveError> {
+        if depth > MAX_DEPTH {
+            return Err(ResolveError::TooDeep);
+        }
+        self.resolver.resolve_value(reference)
+    }
+}
+
+/// Get the alphabet of a known-multiplier character string type, or `None` if `kind` isn't one.
+fn known_multiplier_alphabet(kind: StringKind) -> Option<PermittedAlphabet> {
+    match kind {
+        StringKind::BMPString => Some(BMPString::alphabet()),
+        StringKind::IA5String => Some(IA5String::alphabet()),
+        StringKind::NumericString => Some(NumericString::alphabet()),
+        StringKind::PrintableString => Some(PrintableString::alphabet()),
+        StringKind::UniversalString => Some(UniversalString::alphabet()),
+        StringKind::VisibleString | StringKind::ISO646String => Some(VisibleString::alphabet()),
+        _ => None,
+    }
+}
@@ -0,0 +1,272 @@
+use super::{ErrorKind, ParseError, Position};
+
+// Longest first, so that e.g. `::=` isn't lexed as `:`
+const SYMBOLS: [&str; 21] = ["::=", "...", "..", "[[", "]]", "{", "}", "(", ")", "[", "]", ",", ";", ":", "|", "^",
+                             "<", ".", "@", "!", "-"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    /// A reference, an identifier or a reserved word.
+    Word(String),
+    /// A field reference, with its `&`.
+    Field(String),
+    Number(String),
+    Real(String),
+    CString(String),
+    BString(String),
+    HString(String),
+    Symbol(&'static str),
+}
+
+impl Token {
+    /// Get the text of the token, for error messages.
+    pub(crate) fn text(&self) -> String {
+        match *self {
+            Token::Word(ref s) | Token::Field(ref s) | Token::Number(ref s) | Token::Real(ref s) => s.clone(),
+            Token::CString(ref s) => format!("\"{}\"", s.replace('"', "\"\"")),
+            Token::BString(ref s) => format!("'{}'B", s),
+            Token::HString(ref s) => format!("'{}'H", s),
+            Token::Symbol(s) => s.to_string(),
+        }
+    }
+}
+
+/// Split `text` into tokens and their positions, skipping whitespace and comments. Returns the tokens and the position
+/// of the end of the text.
+pub(crate) fn tokenize(text: &str) -> Result<(Vec<(Token, Position)>, Position), ParseError> {
+    let mut lexer = Lexer {
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut tokens = Vec::new();
+    loop {
+        let ret = lexer.skip_whitespace_and_comments();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let position = lexer.position();
+        let c = match lexer.peek(0) {
+            Some(c) => c,
+            None => return Ok((tokens, position)),
+        };
+
+        let ret = if c.is_ascii_alphabetic() {
+            Ok(Token::Word(lexer.word()))
+        } else if c == '&' && lexer.peek(1).is_some_and(|c| c.is_ascii_alphabetic()) {
+            lexer.bump();
+            Ok(Token::Field(format!("&{}", lexer.word())))
+        } else if c.is_ascii_digit() {
+            lexer.number()
+        } else if c == '"' {
+            lexer.cstring()
+        } else if c == '\'' {
+            lexer.quoted()
+        } else {
+            lexer.symbol()
+        };
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        tokens.push((ret.unwrap(), position));
+    }
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn peek(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).cloned()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0);
+        if let Some(c) = c {
+            self.pos += 1;
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        c
+    }
+
+    fn error(&self, position: Position, kind: ErrorKind) -> ParseError {
+        ParseError { position, kind }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), ParseError> {
+        loop {
+            match (self.peek(0), self.peek(1)) {
+                (Some(c), _) if c.is_whitespace() => {
+                    self.bump();
+                }
+                // A comment runs to the end of the line or to the next `--`
+                (Some('-'), Some('-')) => {
+                    self.bump();
+                    self.bump();
+                    loop {
+                        match (self.peek(0), self.peek(1)) {
+                            (None, _) | (Some('\n'), _) | (Some('\r'), _) => break,
+                            (Some('-'), Some('-')) => {
+                                self.bump();
+                                self.bump();
+                                break;
+                            }
+                            _ => {
+                                self.bump();
+                            }
+                        }
+                    }
+                }
+                // Block comments nest
+                (Some('/'), Some('*')) => {
+                    let start = self.position();
+                    let mut depth = 0;
+                    loop {
+                        match (self.peek(0), self.peek(1)) {
+                            (None, _) => return Err(self.error(start, ErrorKind::UnterminatedComment)),
+                            (Some('/'), Some('*')) => {
+                                depth += 1;
+                                self.bump();
+                                self.bump();
+                            }
+                            (Some('*'), Some('/')) => {
+                                depth -= 1;
+                                self.bump();
+                                self.bump();
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            _ => {
+                                self.bump();
+                            }
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Read a word, where hyphens may only be single and between other characters.
+    fn word(&mut self) -> String {
+        let mut s = String::new();
+        while let Some(c) = self.peek(0) {
+            let hyphen = c == '-' && self.peek(1).is_some_and(|c| c.is_ascii_alphanumeric());
+            if !c.is_ascii_alphanumeric() && !hyphen {
+                break;
+            }
+            s.push(c);
+            self.bump();
+        }
+        s
+    }
+
+    fn digits(&mut self, s: &mut String) {
+        while let Some(c) = self.peek(0).filter(|c| c.is_ascii_digit()) {
+            s.push(c);
+            self.bump();
+        }
+    }
+
+    /// Read a number, which is a real number if it has a fraction (but isn't the start of a range `1..2`) or an
+    /// exponent.
+    fn number(&mut self) -> Result<Token, ParseError> {
+        let position = self.position();
+        let mut s = String::new();
+        self.digits(&mut s);
+        let mut real = false;
+        if self.peek(0) == Some('.') && self.peek(1).is_some_and(|c| c.is_ascii_digit()) {
+            real = true;
+            s.push('.');
+            self.bump();
+            self.digits(&mut s);
+        }
+        if let Some(e) = self.peek(0).filter(|&c| c == 'e' || c == 'E') {
+            real = true;
+            s.push(e);
+            self.bump();
+            if let Some(sign) = self.peek(0).filter(|&c| c == '-' || c == '+') {
+                s.push(sign);
+                self.bump();
+            }
+            let len = s.len();
+            self.digits(&mut s);
+            if s.len() == len {
+                return Err(self.error(position, ErrorKind::InvalidNumber));
+            }
+        }
+        if self.peek(0).is_some_and(|c| c.is_ascii_alphabetic()) {
+            return Err(self.error(position, ErrorKind::InvalidNumber));
+        }
+        Ok(if real { Token::Real(s) } else { Token::Number(s) })
+    }
+
+    /// Read a character string, where `""` stands for a quote.
+    fn cstring(&mut self) -> Result<Token, ParseError> {
+        let position = self.position();
+        self.bump();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') if self.peek(0) == Some('"') => {
+                    s.push('"');
+                    self.bump();
+                }
+                Some('"') => return Ok(Token::CString(s)),
+                Some(c) => s.push(c),
+                None => return Err(self.error(position, ErrorKind::UnterminatedString)),
+            }
+        }
+    }
+
+    /// Read a binary or hexadecimal string, ignoring the whitespace in it.
+    fn quoted(&mut self) -> Result<Token, ParseError> {
+        let position = self.position();
+        self.bump();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('\'') => break,
+                Some(c) if c.is_whitespace() => {}
+                Some(c) => s.push(c),
+                None => return Err(self.error(position, ErrorKind::UnterminatedString)),
+            }
+        }
+        match self.bump() {
+            Some('B') if s.chars().all(|c| c == '0' || c == '1') => Ok(Token::BString(s)),
+            Some('H') if s.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_lowercase()) => Ok(Token::HString(s)),
+            _ => Err(self.error(position, ErrorKind::InvalidString)),
+        }
+    }
+
+    fn symbol(&mut self) -> Result<Token, ParseError> {
+        let position = self.position();
+        for symbol in SYMBOLS.iter() {
+            if symbol.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c)) {
+                for _ in 0..symbol.len() {
+                    self.bump();
+                }
+                return Ok(Token::Symbol(symbol));
+            }
+        }
+        Err(self.error(position, ErrorKind::InvalidCharacter(self.peek(0).unwrap())))
+    }
+}
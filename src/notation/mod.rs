@@ -0,0 +1,98 @@
+mod ast;
+mod constraints;
+mod lexer;
+mod parser;
+mod resolve;
+mod values;
+pub use self::ast::*;
+pub use self::constraints::{PerConstraints, per_constraints};
+pub use self::resolve::{ResolveError, Resolver};
+pub use self::values::{ValueError, dynamic_value, parse_element, parse_value, print_element, print_value, schema_type};
+
+use std::error;
+use std::fmt;
+
+/// A position in the text of a module, counting lines and characters from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// An error in the text of a module, at the position of the offending character or token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: Position,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    InvalidCharacter(char),
+    /// A number that doesn't fit in an `i64`, or a malformed real number.
+    InvalidNumber,
+    /// A binary or hexadecimal string with digits it can't have, or without its `B` or `H`.
+    InvalidString,
+    UnterminatedComment,
+    UnterminatedString,
+    /// The text ended where `expected` was expected.
+    UnexpectedEnd { expected: &'static str },
+    /// The token `found` is where `expected` was expected.
+    UnexpectedToken {
+        expected: &'static str,
+        found: String,
+    },
+    /// Notation this parser doesn't support, e.g. `COMPONENTS OF`.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            ErrorKind::InvalidCharacter(c) => format!("invalid character {:?}", c),
+            ErrorKind::InvalidNumber => "invalid number".to_string(),
+            ErrorKind::InvalidString => "invalid binary or hexadecimal string".to_string(),
+            ErrorKind::UnterminatedComment => "unterminated comment".to_string(),
+            ErrorKind::UnterminatedString => "unterminated string".to_string(),
+            ErrorKind::UnexpectedEnd { expected } => format!("expected {}, found the end of the text", expected),
+            ErrorKind::UnexpectedToken { expected, ref found } => format!("expected {}, found `{}`", expected, found),
+            ErrorKind::Unsupported(what) => format!("{} is not supported", what),
+        };
+        write!(f, "{}:{}: {}", self.position.line, self.position.column, what)
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Parse the ASN.1 modules (X.680 notation) in `text`.
+///
+/// Only the syntax is checked: references are kept as they're written, for a [Resolver](trait.Resolver.html) to
+/// resolve. Constructs that depend on what a reference refers to are kept in a form that covers all of them (see
+/// [AssignmentKind](enum.AssignmentKind.html) and [Value::Braced](enum.Value.html#variant.Braced)).
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::notation::{self, AssignmentKind, TypeKind};
+///
+/// let modules = notation::parse("Foo DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+///                                    Bar ::= SEQUENCE { a INTEGER (0..7), b BOOLEAN OPTIONAL }
+///                                END")
+///     .unwrap();
+/// match modules[0].assignment("Bar").unwrap().kind {
+///     AssignmentKind::Type(ref ty) => assert!(matches!(ty.kind, TypeKind::Sequence(_))),
+///     _ => unreachable!(),
+/// }
+///
+/// let err = notation::parse("Foo DEFINITIONS ::= BEGIN Bar ::= SEQUENCE { a } END").unwrap_err();
+/// println!("{}", err); // Prints 1:48: expected a type, found `}`
+/// ```
+pub fn parse(text: &str) -> Result<Vec<Module>, ParseError> {
+    let ret = lexer::tokenize(text);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let (tokens, end) = ret.unwrap();
+    parser::Parser::new(tokens, end).modules()
+}
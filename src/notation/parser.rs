@@ -0,0 +1,1530 @@
+use super::lexer::Token;
+use super::*;
+
+// The reserved words of X.680 that can't be references, other than those naming built-in types
+const RESERVED: [&str; 54] = ["ABSENT", "ALL", "APPLICATION", "AUTOMATIC", "BEGIN", "BY", "CLASS", "COMPONENT",
+                              "COMPONENTS", "CONSTRAINED", "CONTAINING", "DEFAULT", "DEFINITIONS", "ENCODED",
+                              "ENCODING-CONTROL", "END", "EXCEPT", "EXPLICIT", "EXPORTS", "EXTENSIBILITY", "FALSE",
+                              "FROM", "IDENTIFIER", "IMPLICIT", "IMPLIED", "IMPORTS", "INCLUDES", "INSTANCE",
+                              "INSTRUCTIONS", "INTERSECTION", "MAX", "MIN", "MINUS-INFINITY", "NOT-A-NUMBER", "OF",
+                              "OID-IRI", "OPTIONAL", "PATTERN", "PDV", "PLUS-INFINITY", "PRESENT", "PRIVATE",
+                              "RELATIVE-OID-IRI", "SETTINGS", "SIZE", "STRING", "SYNTAX", "TAGS", "TRUE", "UNION",
+                              "UNIQUE", "UNIVERSAL", "WITH", "ObjectDescriptor"];
+
+// The reserved words that start built-in types
+const BUILTIN_TYPES: [&str; 35] = ["BIT", "BMPString", "BOOLEAN", "CHARACTER", "CHOICE", "DATE", "DATE-TIME",
+                                   "DURATION", "EMBEDDED", "ENUMERATED", "EXTERNAL", "GeneralString",
+                                   "GeneralizedTime", "GraphicString", "IA5String", "INTEGER", "ISO646String", "NULL",
+                                   "NumericString", "OBJECT", "OCTET", "PrintableString", "REAL", "RELATIVE-OID",
+                                   "SEQUENCE", "SET", "T61String", "TeletexString", "TIME", "TIME-OF-DAY", "UTCTime",
+                                   "UTF8String", "UniversalString", "VideotexString", "VisibleString"];
+
+const STRING_TYPES: [(&str, StringKind); 13] = [("BMPString", StringKind::BMPString),
+                                                ("GeneralString", StringKind::GeneralString),
+                                                ("GraphicString", StringKind::GraphicString),
+                                                ("IA5String", StringKind::IA5String),
+                                                ("ISO646String", StringKind::ISO646String),
+                                                ("NumericString", StringKind::NumericString),
+                                                ("PrintableString", StringKind::PrintableString),
+                                                ("T61String", StringKind::T61String),
+                                                ("TeletexString", StringKind::TeletexString),
+                                                ("UniversalString", StringKind::UniversalString),
+                                                ("UTF8String", StringKind::UTF8String),
+                                                ("VideotexString", StringKind::VideotexString),
+                                                ("VisibleString", StringKind::VisibleString)];
+
+const TIME_TYPES: [(&str, TimeKind); 7] = [("DATE", TimeKind::Date),
+                                           ("DATE-TIME", TimeKind::DateTime),
+                                           ("DURATION", TimeKind::Duration),
+                                           ("GeneralizedTime", TimeKind::GeneralizedTime),
+                                           ("TIME", TimeKind::Time),
+                                           ("TIME-OF-DAY", TimeKind::TimeOfDay),
+                                           ("UTCTime", TimeKind::UTCTime)];
+
+fn is_reserved(word: &str) -> bool {
+    RESERVED.contains(&word) || BUILTIN_TYPES.contains(&word)
+}
+
+fn is_upper(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+pub(crate) struct Parser {
+    tokens: Vec<(Token, Position)>,
+    pos: usize,
+    end: Position,
+}
+
+impl Parser {
+    pub(crate) fn new(tokens: Vec<(Token, Position)>, end: Position) -> Parser {
+        Parser { tokens, pos: 0, end }
+    }
+
+    fn peek(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n).map(|t| &t.0)
+    }
+
+    fn peek_word(&self, n: usize) -> Option<&str> {
+        match self.peek(n) {
+            Some(Token::Word(w)) => Some(w),
+            _ => None,
+        }
+    }
+
+    fn is_word(&self, n: usize, word: &str) -> bool {
+        self.peek_word(n) == Some(word)
+    }
+
+    fn is_symbol(&self, n: usize, symbol: &str) -> bool {
+        match self.peek(n) {
+            Some(&Token::Symbol(s)) => s == symbol,
+            _ => false,
+        }
+    }
+
+    /// Tell if the `n`th next token is a reference (or an identifier) rather than a reserved word.
+    fn is_reference(&self, n: usize) -> bool {
+        self.peek_word(n).is_some_and(|w| !is_reserved(w))
+    }
+
+    fn position(&self) -> Position {
+        self.tokens.get(self.pos).map_or(self.end, |t| t.1)
+    }
+
+    fn unexpected(&self, expected: &'static str) -> ParseError {
+        let kind = match self.peek(0) {
+            Some(t) => ErrorKind::UnexpectedToken {
+                expected,
+                found: t.text(),
+            },
+            None => ErrorKind::UnexpectedEnd { expected },
+        };
+        ParseError {
+            position: self.position(),
+            kind,
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if self.is_word(0, word) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        if self.is_symbol(0, symbol) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_word(&mut self, word: &str, expected: &'static str) -> Result<(), ParseError> {
+        if self.eat_word(word) {
+            Ok(())
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str, expected: &'static str) -> Result<(), ParseError> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    /// Read a reference (or identifier), which must start with an uppercase letter if `upper` is set and with a
+    /// lowercase letter otherwise.
+    fn reference(&mut self, upper: bool, expected: &'static str) -> Result<String, ParseError> {
+        match self.peek_word(0) {
+            Some(w) if !is_reserved(w) && is_upper(w) == upper => {
+                let w = w.to_string();
+                self.pos += 1;
+                Ok(w)
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    /// Run `parse`, and go back to where it started if it fails.
+    fn attempt<T, F>(&mut self, parse: F) -> Option<T>
+        where F: FnOnce(&mut Self) -> Result<T, ParseError>
+    {
+        let pos = self.pos;
+        let ret = parse(self);
+        if ret.is_err() {
+            self.pos = pos;
+            return None;
+        }
+        Some(ret.unwrap())
+    }
+
+    /// Split a `[[` or `]]` token where it stands for two brackets.
+    fn split_brackets(&mut self) {
+        let single = if self.is_symbol(0, "[[") {
+            "["
+        } else if self.is_symbol(0, "]]") {
+            "]"
+        } else {
+            return;
+        };
+        let position = self.position();
+        self.tokens[self.pos].0 = Token::Symbol(single);
+        let next = Position {
+            line: position.line,
+            column: position.column + 1,
+        };
+        self.tokens.insert(self.pos + 1, (Token::Symbol(single), next));
+    }
+
+    pub(crate) fn modules(&mut self) -> Result<Vec<Module>, ParseError> {
+        let mut modules = Vec::new();
+        while self.peek(0).is_some() {
+            let ret = self.module();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            modules.push(ret.unwrap());
+        }
+        if modules.is_empty() {
+            return Err(self.unexpected("a module"));
+        }
+        Ok(modules)
+    }
+
+    /// Read a value that makes up the whole text.
+    pub(crate) fn whole_value(&mut self) -> Result<Value, ParseError> {
+        let ret = self.value();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if self.peek(0).is_some() {
+            return Err(self.unexpected("the end of the value"));
+        }
+        Ok(ret.unwrap())
+    }
+
+    fn module(&mut self) -> Result<Module, ParseError> {
+        let position = self.position();
+        let ret = self.reference(true, "a module name");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let name = ret.unwrap();
+        let mut oid = None;
+        if self.is_symbol(0, "{") {
+            let ret = self.braced_value();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            oid = Some(ret.unwrap());
+        }
+        let ret = self.expect_word("DEFINITIONS", "DEFINITIONS");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let mut tag_default = TagDefault::Explicit;
+        for &(word, default) in &[("EXPLICIT", TagDefault::Explicit),
+                                  ("IMPLICIT", TagDefault::Implicit),
+                                  ("AUTOMATIC", TagDefault::Automatic)] {
+            if self.eat_word(word) {
+                tag_default = default;
+                let ret = self.expect_word("TAGS", "TAGS");
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                break;
+            }
+        }
+        let extensibility_implied = self.eat_word("EXTENSIBILITY");
+        if extensibility_implied {
+            let ret = self.expect_word("IMPLIED", "IMPLIED");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        let ret = self.expect_symbol("::=", "`::=`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = self.expect_word("BEGIN", "BEGIN");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let mut exports = None;
+        if self.eat_word("EXPORTS") {
+            if !self.eat_word("ALL") {
+                let ret = self.symbols();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                exports = Some(ret.unwrap());
+            }
+            let ret = self.expect_symbol(";", "`;`");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        let mut imports = Vec::new();
+        if self.eat_word("IMPORTS") {
+            while !self.eat_symbol(";") {
+                let ret = self.import();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                imports.push(ret.unwrap());
+            }
+        }
+
+        let mut assignments = Vec::new();
+        while !self.eat_word("END") {
+            let ret = self.assignment();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            assignments.push(ret.unwrap());
+        }
+        Ok(Module {
+               name,
+               oid,
+               tag_default,
+               extensibility_implied,
+               exports,
+               imports,
+               assignments,
+               position,
+           })
+    }
+
+    /// Read a list of exported or imported symbols, dropping the `{}` of parameterized ones.
+    fn symbols(&mut self) -> Result<Vec<String>, ParseError> {
+        let mut symbols = Vec::new();
+        if self.is_symbol(0, ";") {
+            return Ok(symbols);
+        }
+        loop {
+            match self.peek_word(0) {
+                Some(w) if !is_reserved(w) => symbols.push(w.to_string()),
+                _ => return Err(self.unexpected("a symbol")),
+            }
+            self.pos += 1;
+            if self.eat_symbol("{") {
+                let ret = self.expect_symbol("}", "`}`");
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+            }
+            if !self.eat_symbol(",") {
+                return Ok(symbols);
+            }
+        }
+    }
+
+    fn import(&mut self) -> Result<Import, ParseError> {
+        let ret = self.symbols();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let symbols = ret.unwrap();
+        if symbols.is_empty() {
+            return Err(self.unexpected("a symbol"));
+        }
+        let ret = self.expect_word("FROM", "FROM");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = self.reference(true, "a module name");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let module = ret.unwrap();
+
+        // A value reference right after the module name identifies it, unless it's the first symbol of the next list
+        let mut oid = None;
+        if self.is_symbol(0, "{") {
+            let ret = self.braced_value();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            oid = Some(ret.unwrap());
+        } else if self.is_reference(0) &&
+                  !is_upper(self.peek_word(0).unwrap()) &&
+                  !self.is_symbol(1, ",") &&
+                  !self.is_symbol(1, "{") &&
+                  !self.is_word(1, "FROM") {
+            oid = Some(Value::Reference(Reference::new(self.peek_word(0).unwrap())));
+            self.pos += 1;
+        }
+        Ok(Import {
+               symbols,
+               module,
+               oid,
+           })
+    }
+
+    fn assignment(&mut self) -> Result<Assignment, ParseError> {
+        let position = self.position();
+        let name = match self.peek_word(0) {
+            Some(w) if !is_reserved(w) => w.to_string(),
+            _ => return Err(self.unexpected("an assignment")),
+        };
+        self.pos += 1;
+        let mut parameters = Vec::new();
+        if self.is_symbol(0, "{") {
+            let ret = self.parameters();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            parameters = ret.unwrap();
+        }
+
+        let kind = if is_upper(&name) && self.eat_symbol("::=") {
+            if self.eat_word("CLASS") {
+                self.class().map(AssignmentKind::Class)
+            } else {
+                self.parse_type().map(AssignmentKind::Type)
+            }
+        } else {
+            let ret = self.parse_type();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ty = ret.unwrap();
+            let ret = self.expect_symbol("::=", "`::=`");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if is_upper(&name) {
+                self.braced_set().map(|set| AssignmentKind::ValueSet { ty, set })
+            } else {
+                self.value().map(|value| AssignmentKind::Value { ty, value })
+            }
+        };
+        if kind.is_err() {
+            return Err(kind.err().unwrap());
+        }
+        Ok(Assignment {
+               name,
+               parameters,
+               kind: kind.unwrap(),
+               position,
+           })
+    }
+
+    fn parameters(&mut self) -> Result<Vec<Parameter>, ParseError> {
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut parameters = Vec::new();
+        loop {
+            let governor = self.attempt(|p| {
+                let ret = p.parse_type();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let ret2 = p.expect_symbol(":", "`:`");
+                if ret2.is_err() {
+                    return Err(ret2.err().unwrap());
+                }
+                Ok(ret.unwrap())
+            });
+            let name = match self.peek_word(0) {
+                Some(w) if !is_reserved(w) => w.to_string(),
+                _ => return Err(self.unexpected("a parameter")),
+            };
+            self.pos += 1;
+            parameters.push(Parameter { governor, name });
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        let ret = self.expect_symbol("}", "`}`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(parameters)
+    }
+
+    /// Tell if the next tokens start a type rather than a value.
+    fn starts_type(&self) -> bool {
+        if self.is_symbol(0, "[") {
+            return true;
+        }
+        match self.peek(0) {
+            Some(Token::Word(w)) if w == "NULL" => false,
+            Some(Token::Word(w)) if BUILTIN_TYPES.contains(&w.as_str()) => true,
+            // A reference qualified by its module is a value reference if its name is lowercase
+            Some(Token::Word(w)) if !is_reserved(w) && is_upper(w) => {
+                !(self.is_symbol(1, ".") && self.peek_word(2).is_some_and(|w| !is_upper(w)))
+            }
+            Some(Token::Field(f)) => is_upper(&f[1..]),
+            _ => false,
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<Type, ParseError> {
+        if self.eat_symbol("[") {
+            return self.tagged_type();
+        }
+        let position = self.position();
+        let word = match self.peek(0) {
+            Some(Token::Word(w)) => w.clone(),
+            Some(Token::Field(f)) if is_upper(&f[1..]) => {
+                let f = f.clone();
+                self.pos += 1;
+                return self.constraints(Type::new(TypeKind::Reference {
+                                                      reference: Reference::new(&f),
+                                                      arguments: vec![],
+                                                  }));
+            }
+            _ => return Err(self.unexpected("a type")),
+        };
+        self.pos += 1;
+
+        let kind = match word.as_str() {
+            "BOOLEAN" => Ok(TypeKind::Boolean),
+            "NULL" => Ok(TypeKind::Null),
+            "REAL" => Ok(TypeKind::Real),
+            "EXTERNAL" => Ok(TypeKind::External),
+            "RELATIVE-OID" => Ok(TypeKind::RelativeOid),
+            "INTEGER" => self.named_numbers().map(TypeKind::Integer),
+            "BIT" => self.expect_word("STRING", "STRING").and_then(|_| self.named_numbers()).map(TypeKind::BitString),
+            "OCTET" => self.expect_word("STRING", "STRING").map(|_| TypeKind::OctetString),
+            "CHARACTER" => self.expect_word("STRING", "STRING").map(|_| TypeKind::CharacterString),
+            "OBJECT" => self.expect_word("IDENTIFIER", "IDENTIFIER").map(|_| TypeKind::ObjectIdentifier),
+            "EMBEDDED" => self.expect_word("PDV", "PDV").map(|_| TypeKind::EmbeddedPdv),
+            "ENUMERATED" => self.enumeration(),
+            "CHOICE" => self.alternatives(),
+            "SEQUENCE" | "SET" => return self.sequence_or_set(word == "SEQUENCE"),
+            w if !is_reserved(w) && is_upper(w) => {
+                self.pos -= 1;
+                self.type_reference()
+            }
+            w => {
+                if let Some(&(_, kind)) = STRING_TYPES.iter().find(|t| t.0 == w) {
+                    Ok(TypeKind::String(kind))
+                } else if let Some(&(_, kind)) = TIME_TYPES.iter().find(|t| t.0 == w) {
+                    Ok(TypeKind::Time(kind))
+                } else {
+                    self.pos -= 1;
+                    Err(ParseError {
+                        position,
+                        kind: ErrorKind::UnexpectedToken {
+                            expected: "a type",
+                            found: word.clone(),
+                        },
+                    })
+                }
+            }
+        };
+        if kind.is_err() {
+            return Err(kind.err().unwrap());
+        }
+        self.constraints(Type::new(kind.unwrap()))
+    }
+
+    /// Add the constraints that follow a type to it.
+    fn constraints(&mut self, ty: Type) -> Result<Type, ParseError> {
+        let mut ty = ty;
+        while self.is_symbol(0, "(") {
+            let ret = self.constraint();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ty.constraints.push(ret.unwrap());
+        }
+        Ok(ty)
+    }
+
+    fn tagged_type(&mut self) -> Result<Type, ParseError> {
+        let mut class = TagClass::ContextSpecific;
+        for &(word, c) in &[("UNIVERSAL", TagClass::Universal),
+                            ("APPLICATION", TagClass::Application),
+                            ("PRIVATE", TagClass::Private)] {
+            if self.eat_word(word) {
+                class = c;
+            }
+        }
+        let ret = self.value();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let number = ret.unwrap();
+        let ret = self.expect_symbol("]", "`]`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mode = if self.eat_word("IMPLICIT") {
+            Some(TagMode::Implicit)
+        } else if self.eat_word("EXPLICIT") {
+            Some(TagMode::Explicit)
+        } else {
+            None
+        };
+        let ret = self.parse_type();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(Type::new(TypeKind::Tagged {
+                         tag: Tag { class, number },
+                         mode,
+                         ty: Box::new(ret.unwrap()),
+                     }))
+    }
+
+    /// Read a type reference, which may be qualified by its module, refer to a field of a class or have actual
+    /// parameters.
+    fn type_reference(&mut self) -> Result<TypeKind, ParseError> {
+        let ret = self.reference(true, "a type");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut reference = Reference::new(&ret.unwrap());
+        if self.is_symbol(0, ".") && self.peek_word(1).is_some_and(is_upper) {
+            self.pos += 1;
+            reference = Reference {
+                module: Some(reference.name),
+                name: self.peek_word(0).unwrap().to_string(),
+            };
+            self.pos += 1;
+        }
+
+        let mut fields = Vec::new();
+        while self.is_symbol(0, ".") {
+            match self.peek(1) {
+                Some(Token::Field(f)) => fields.push(f.clone()),
+                _ => break,
+            }
+            self.pos += 2;
+        }
+        if !fields.is_empty() {
+            return Ok(TypeKind::ClassField {
+                          class: reference,
+                          fields,
+                      });
+        }
+
+        let mut arguments = Vec::new();
+        if self.eat_symbol("{") {
+            loop {
+                let ret = self.argument();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                arguments.push(ret.unwrap());
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+            let ret = self.expect_symbol("}", "`}`");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(TypeKind::Reference {
+               reference,
+               arguments,
+           })
+    }
+
+    fn argument(&mut self) -> Result<Argument, ParseError> {
+        if self.is_symbol(0, "{") {
+            if let Some(set) = self.attempt(|p| p.braced_set()) {
+                return Ok(Argument::Set(set));
+            }
+            return self.value().map(Argument::Value);
+        }
+        if self.starts_type() {
+            return self.parse_type().map(Argument::Type);
+        }
+        self.value().map(Argument::Value)
+    }
+
+    fn sequence_or_set(&mut self, sequence: bool) -> Result<Type, ParseError> {
+        if self.is_symbol(0, "{") {
+            let ret = self.components();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let kind = if sequence {
+                TypeKind::Sequence(ret.unwrap())
+            } else {
+                TypeKind::Set(ret.unwrap())
+            };
+            return self.constraints(Type::new(kind));
+        }
+
+        // The constraint of a SEQUENCE OF or SET OF is written before OF, and can be a bare SIZE constraint
+        let mut constraints = Vec::new();
+        if self.is_symbol(0, "(") {
+            let ret = self.constraint();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            constraints.push(ret.unwrap());
+        } else if self.eat_word("SIZE") {
+            let ret = self.constraint();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            constraints.push(Constraint::Subtype(ElementSetSpecs {
+                                                     root: Some(ElementSet::Size(Box::new(ret.unwrap()))),
+                                                     extensible: false,
+                                                     additions: None,
+                                                 }));
+        }
+        let ret = self.expect_word("OF", "OF");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut name = None;
+        if self.is_reference(0) && !is_upper(self.peek_word(0).unwrap()) {
+            name = Some(self.peek_word(0).unwrap().to_string());
+            self.pos += 1;
+        }
+        let ret = self.parse_type();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let element = Box::new(ret.unwrap());
+        let kind = if sequence {
+            TypeKind::SequenceOf { name, element }
+        } else {
+            TypeKind::SetOf { name, element }
+        };
+        Ok(Type { kind, constraints })
+    }
+
+    /// Read the named numbers of an INTEGER or the named bits of a BIT STRING, if there are any.
+    fn named_numbers(&mut self) -> Result<Vec<NamedNumber>, ParseError> {
+        let mut numbers = Vec::new();
+        if !self.eat_symbol("{") {
+            return Ok(numbers);
+        }
+        loop {
+            let ret = self.named_number(true);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            numbers.push(ret.unwrap());
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        let ret = self.expect_symbol("}", "`}`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(numbers)
+    }
+
+    fn named_number(&mut self, numbered: bool) -> Result<NamedNumber, ParseError> {
+        let ret = self.reference(false, "an identifier");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let name = ret.unwrap();
+        if !numbered && !self.is_symbol(0, "(") {
+            return Ok(NamedNumber { name, number: None });
+        }
+        let ret = self.expect_symbol("(", "`(`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = self.value();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let number = Some(ret.unwrap());
+        let ret = self.expect_symbol(")", "`)`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(NamedNumber { name, number })
+    }
+
+    fn enumeration(&mut self) -> Result<TypeKind, ParseError> {
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut root = Vec::new();
+        let mut extensions: Option<Vec<NamedNumber>> = None;
+        loop {
+            if self.eat_symbol("...") {
+                if extensions.is_some() {
+                    return Err(self.unexpected("an identifier"));
+                }
+                let ret = self.exception();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                extensions = Some(Vec::new());
+            } else {
+                let ret = self.named_number(false);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match extensions {
+                    Some(ref mut additions) => additions.push(ret.unwrap()),
+                    None => root.push(ret.unwrap()),
+                }
+            }
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        let ret = self.expect_symbol("}", "`}`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(TypeKind::Enumerated { root, extensions })
+    }
+
+    /// Read the alternatives of a CHOICE, which has at least one in its root.
+    fn alternatives(&mut self) -> Result<TypeKind, ParseError> {
+        if self.is_symbol(0, "{") && (self.is_symbol(1, "}") || self.is_symbol(1, "...")) {
+            self.pos += 1;
+            return Err(self.unexpected("an identifier"));
+        }
+        self.components().map(TypeKind::Choice)
+    }
+
+    /// Read the components of a SEQUENCE or SET, or the alternatives of a CHOICE.
+    fn components(&mut self) -> Result<ComponentList, ParseError> {
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut list = ComponentList {
+            root: vec![],
+            extensions: None,
+        };
+        if self.eat_symbol("}") {
+            return Ok(list);
+        }
+
+        // How many extension markers have been read
+        let mut markers = 0;
+        loop {
+            if self.is_symbol(0, "...") {
+                if markers == 2 {
+                    return Err(self.unexpected("a component"));
+                }
+                self.pos += 1;
+                let ret = self.exception();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                markers += 1;
+                if markers == 1 {
+                    list.extensions = Some(vec![]);
+                }
+            } else if markers == 1 && self.eat_symbol("[[") {
+                let mut version = None;
+                if self.is_symbol(1, ":") {
+                    if let Some(Token::Number(n)) = self.peek(0) {
+                        version = n.parse().ok();
+                    }
+                    if version.is_none() {
+                        return Err(self.unexpected("a version number"));
+                    }
+                    self.pos += 2;
+                }
+                let mut components = Vec::new();
+                loop {
+                    let ret = self.component();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    components.push(ret.unwrap());
+                    if !self.eat_symbol(",") {
+                        break;
+                    }
+                }
+                let ret = self.expect_symbol("]]", "`]]`");
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                list.extensions.as_mut().unwrap().push(ExtensionAddition::Group {
+                                                           version,
+                                                           components,
+                                                       });
+            } else {
+                let ret = self.component();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let component = ret.unwrap();
+                if markers == 1 {
+                    list.extensions.as_mut().unwrap().push(ExtensionAddition::Component(component));
+                } else {
+                    list.root.push(component);
+                }
+            }
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        let ret = self.expect_symbol("}", "`}`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(list)
+    }
+
+    fn component(&mut self) -> Result<Component, ParseError> {
+        let position = self.position();
+        if self.is_word(0, "COMPONENTS") && self.is_word(1, "OF") {
+            return Err(ParseError {
+                           position,
+                           kind: ErrorKind::Unsupported("COMPONENTS OF"),
+                       });
+        }
+        let ret = self.reference(false, "a component");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let name = ret.unwrap();
+        let ret = self.parse_type();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ty = ret.unwrap();
+        let presence = if self.eat_word("OPTIONAL") {
+            Presence::Optional
+        } else if self.eat_word("DEFAULT") {
+            let ret = self.value();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            Presence::Default(ret.unwrap())
+        } else {
+            Presence::Required
+        };
+        Ok(Component {
+               name,
+               ty,
+               presence,
+               position,
+           })
+    }
+
+    /// Skip an exception specification `! ...`, which isn't kept.
+    fn exception(&mut self) -> Result<(), ParseError> {
+        if !self.eat_symbol("!") {
+            return Ok(());
+        }
+        let typed = self.attempt(|p| {
+            let ret = p.parse_type();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            p.expect_symbol(":", "`:`")
+        });
+        if typed.is_none() && self.starts_type() {
+            return self.parse_type().map(|_| ());
+        }
+        self.value().map(|_| ())
+    }
+
+    fn constraint(&mut self) -> Result<Constraint, ParseError> {
+        let ret = self.expect_symbol("(", "`(`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let constraint = if self.eat_word("CONTAINING") {
+            let ret = self.parse_type();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ty = Some(ret.unwrap());
+            self.encoded_by().map(|encoded_by| Constraint::Contents { ty, encoded_by })
+        } else if self.is_word(0, "ENCODED") {
+            self.encoded_by().map(|encoded_by| Constraint::Contents { ty: None, encoded_by })
+        } else if self.eat_word("CONSTRAINED") {
+            self.user_defined()
+        } else if let Some(set) = self.attempt(|p| p.relation_set()) {
+            self.relation_components().map(|components| Constraint::Relation { set, components })
+        } else {
+            self.element_set_specs().map(Constraint::Subtype)
+        };
+        if constraint.is_err() {
+            return Err(constraint.err().unwrap());
+        }
+        let ret = self.exception();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = self.expect_symbol(")", "`)`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(constraint.unwrap())
+    }
+
+    fn encoded_by(&mut self) -> Result<Option<Value>, ParseError> {
+        if !self.eat_word("ENCODED") {
+            return Ok(None);
+        }
+        let ret = self.expect_word("BY", "BY");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        self.value().map(Some)
+    }
+
+    /// Skip the contents of `CONSTRAINED BY { ... }`.
+    fn user_defined(&mut self) -> Result<Constraint, ParseError> {
+        let ret = self.expect_word("BY", "BY");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            if self.eat_symbol("{") {
+                depth += 1;
+            } else if self.eat_symbol("}") {
+                depth -= 1;
+            } else if self.peek(0).is_some() {
+                self.pos += 1;
+            } else {
+                return Err(self.unexpected("`}`"));
+            }
+        }
+        Ok(Constraint::UserDefined)
+    }
+
+    /// Read the object set of a component relation constraint, which must be followed by its `{@`.
+    fn relation_set(&mut self) -> Result<ElementSetSpecs, ParseError> {
+        let ret = self.braced_set();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if !self.is_symbol(0, "{") || !self.is_symbol(1, "@") {
+            return Err(self.unexpected("`{@`"));
+        }
+        Ok(ret.unwrap())
+    }
+
+    /// Read the `{@a, @.b}` of a component relation constraint.
+    fn relation_components(&mut self) -> Result<Vec<String>, ParseError> {
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut components = Vec::new();
+        loop {
+            let ret = self.expect_symbol("@", "`@`");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let mut path = String::new();
+            while self.eat_symbol(".") {
+                path.push('.');
+            }
+            loop {
+                let ret = self.reference(false, "a component");
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                path.push_str(&ret.unwrap());
+                if !self.eat_symbol(".") {
+                    break;
+                }
+                path.push('.');
+            }
+            components.push(path);
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        let ret = self.expect_symbol("}", "`}`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(components)
+    }
+
+    /// Read a value set or object set in braces.
+    fn braced_set(&mut self) -> Result<ElementSetSpecs, ParseError> {
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = self.element_set_specs();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret2 = self.expect_symbol("}", "`}`");
+        if ret2.is_err() {
+            return Err(ret2.err().unwrap());
+        }
+        ret
+    }
+
+    fn element_set_specs(&mut self) -> Result<ElementSetSpecs, ParseError> {
+        let mut specs = ElementSetSpecs {
+            root: None,
+            extensible: false,
+            additions: None,
+        };
+        if !self.is_symbol(0, "...") {
+            let ret = self.element_set();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            specs.root = Some(ret.unwrap());
+            if !self.eat_symbol(",") {
+                return Ok(specs);
+            }
+        }
+        let ret = self.expect_symbol("...", "`...`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        specs.extensible = true;
+        let ret = self.exception();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if self.eat_symbol(",") {
+            let ret = self.element_set();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            specs.additions = Some(ret.unwrap());
+        }
+        Ok(specs)
+    }
+
+    /// Read a union of intersections.
+    fn element_set(&mut self) -> Result<ElementSet, ParseError> {
+        let mut sets = Vec::new();
+        loop {
+            let ret = self.intersection();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            sets.push(ret.unwrap());
+            if !self.eat_symbol("|") && !self.eat_word("UNION") {
+                break;
+            }
+        }
+        Ok(if sets.len() == 1 {
+               sets.pop().unwrap()
+           } else {
+               ElementSet::Union(sets)
+           })
+    }
+
+    fn intersection(&mut self) -> Result<ElementSet, ParseError> {
+        let mut sets = Vec::new();
+        loop {
+            let ret = self.elements();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let mut set = ret.unwrap();
+            if self.eat_word("EXCEPT") {
+                let ret = self.elements();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                set = ElementSet::Except(Box::new(set), Box::new(ret.unwrap()));
+            }
+            sets.push(set);
+            if !self.eat_symbol("^") && !self.eat_word("INTERSECTION") {
+                break;
+            }
+        }
+        Ok(if sets.len() == 1 {
+               sets.pop().unwrap()
+           } else {
+               ElementSet::Intersection(sets)
+           })
+    }
+
+    fn elements(&mut self) -> Result<ElementSet, ParseError> {
+        if self.eat_symbol("(") {
+            let ret = self.element_set_specs();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret2 = self.expect_symbol(")", "`)`");
+            if ret2.is_err() {
+                return Err(ret2.err().unwrap());
+            }
+            return Ok(ElementSet::Nested(Box::new(ret.unwrap())));
+        }
+        if self.is_symbol(0, "{") {
+            if let Some(set) = self.attempt(|p| p.braced_set()) {
+                return Ok(ElementSet::Braced(Box::new(set)));
+            }
+        }
+        if self.eat_word("SIZE") {
+            return self.constraint().map(|c| ElementSet::Size(Box::new(c)));
+        }
+        if self.eat_word("FROM") {
+            return self.constraint().map(|c| ElementSet::PermittedAlphabet(Box::new(c)));
+        }
+        if self.eat_word("PATTERN") {
+            return self.value().map(ElementSet::Pattern);
+        }
+        if self.eat_word("INCLUDES") {
+            return self.parse_type().map(ElementSet::ContainedSubtype);
+        }
+        if self.eat_word("ALL") {
+            let ret = self.expect_word("EXCEPT", "EXCEPT");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            return self.elements().map(|e| ElementSet::Except(Box::new(ElementSet::All), Box::new(e)));
+        }
+        if self.eat_word("WITH") {
+            if self.eat_word("COMPONENT") {
+                return self.constraint().map(|c| ElementSet::WithComponent(Box::new(c)));
+            }
+            let ret = self.expect_word("COMPONENTS", "COMPONENTS");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            return self.with_components();
+        }
+        if self.starts_type() {
+            return self.parse_type().map(ElementSet::ContainedSubtype);
+        }
+
+        let lower = if self.eat_word("MIN") {
+            Bound::Min
+        } else {
+            let ret = self.value();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            Bound::Value(ret.unwrap())
+        };
+        let lower_open = self.eat_symbol("<");
+        if !self.eat_symbol("..") {
+            return match lower {
+                Bound::Value(value) if !lower_open => Ok(ElementSet::Value(value)),
+                _ => Err(self.unexpected("`..`")),
+            };
+        }
+        let upper_open = self.eat_symbol("<");
+        let upper = if self.eat_word("MAX") {
+            Bound::Max
+        } else {
+            let ret = self.value();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            Bound::Value(ret.unwrap())
+        };
+        Ok(ElementSet::Range {
+               lower: Endpoint {
+                   value: lower,
+                   open: lower_open,
+               },
+               upper: Endpoint {
+                   value: upper,
+                   open: upper_open,
+               },
+           })
+    }
+
+    /// Read the `{ ... }` of `WITH COMPONENTS`.
+    fn with_components(&mut self) -> Result<ElementSet, ParseError> {
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let partial = self.eat_symbol("...");
+        let mut components = Vec::new();
+        if !partial || self.eat_symbol(",") {
+            loop {
+                let ret = self.reference(false, "a component");
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let name = ret.unwrap();
+                let mut constraint = None;
+                if self.is_symbol(0, "(") {
+                    let ret = self.constraint();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    constraint = Some(ret.unwrap());
+                }
+                let mut presence = None;
+                for &(word, p) in &[("PRESENT", PresenceConstraint::Present),
+                                    ("ABSENT", PresenceConstraint::Absent),
+                                    ("OPTIONAL", PresenceConstraint::Optional)] {
+                    if self.eat_word(word) {
+                        presence = Some(p);
+                    }
+                }
+                components.push(ComponentConstraint {
+                                    name,
+                                    constraint,
+                                    presence,
+                                });
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+        let ret = self.expect_symbol("}", "`}`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(ElementSet::WithComponents {
+               partial,
+               components,
+           })
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        let position = self.position();
+        let token = match self.peek(0) {
+            Some(t) => t.clone(),
+            None => return Err(self.unexpected("a value")),
+        };
+        let value = match token {
+            Token::Number(ref n) => n.parse().map(Value::Integer).ok(),
+            Token::Real(ref r) => r.parse().map(Value::Real).ok(),
+            Token::CString(s) => Some(Value::CString(s)),
+            Token::BString(s) => Some(Value::BString(s)),
+            Token::HString(s) => Some(Value::HString(s)),
+            Token::Symbol("-") => {
+                let value = match self.peek(1) {
+                    Some(Token::Number(n)) => format!("-{}", n).parse().map(Value::Integer).ok(),
+                    Some(Token::Real(r)) => format!("-{}", r).parse().map(Value::Real).ok(),
+                    _ => return Err(self.unexpected("a value")),
+                };
+                self.pos += 1;
+                value
+            }
+            Token::Symbol("{") => return self.braced_value(),
+            Token::Word(ref w) => {
+                match w.as_str() {
+                    "TRUE" => Some(Value::Boolean(true)),
+                    "FALSE" => Some(Value::Boolean(false)),
+                    "NULL" => Some(Value::Null),
+                    "PLUS-INFINITY" => Some(Value::Real(f64::INFINITY)),
+                    "MINUS-INFINITY" => Some(Value::Real(f64::NEG_INFINITY)),
+                    "NOT-A-NUMBER" => Some(Value::Real(f64::NAN)),
+                    w if is_reserved(w) => return Err(self.unexpected("a value")),
+                    w => {
+                        self.pos += 1;
+                        if !is_upper(w) && self.eat_symbol(":") {
+                            return self.value().map(|v| Value::Choice(w.to_string(), Box::new(v)));
+                        }
+                        if is_upper(w) && self.is_symbol(0, ".") && self.is_reference(1) {
+                            let name = self.peek_word(1).unwrap().to_string();
+                            self.pos += 2;
+                            return Ok(Value::Reference(Reference {
+                                                           module: Some(w.to_string()),
+                                                           name,
+                                                       }));
+                        }
+                        return Ok(Value::Reference(Reference::new(w)));
+                    }
+                }
+            }
+            _ => return Err(self.unexpected("a value")),
+        };
+        match value {
+            Some(value) => {
+                self.pos += 1;
+                Ok(value)
+            }
+            None => Err(ParseError {
+                            position,
+                            kind: ErrorKind::InvalidNumber,
+                        }),
+        }
+    }
+
+    fn braced_value(&mut self) -> Result<Value, ParseError> {
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut parts = Vec::new();
+        if self.eat_symbol("}") {
+            return Ok(Value::Braced(parts));
+        }
+        loop {
+            let mut items = Vec::new();
+            while !self.is_symbol(0, ",") && !self.is_symbol(0, "}") {
+                let ret = self.braced_item();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                items.push(ret.unwrap());
+            }
+            if items.is_empty() {
+                return Err(self.unexpected("a value"));
+            }
+            parts.push(items);
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        let ret = self.expect_symbol("}", "`}`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(Value::Braced(parts))
+    }
+
+    /// Read an item of a value in braces, which may be an arc in name and number form or a type in an object's
+    /// defined syntax.
+    fn braced_item(&mut self) -> Result<Value, ParseError> {
+        if self.is_reference(0) && !is_upper(self.peek_word(0).unwrap()) && self.is_symbol(1, "(") {
+            let name = self.peek_word(0).unwrap().to_string();
+            self.pos += 2;
+            let ret = self.value();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret2 = self.expect_symbol(")", "`)`");
+            if ret2.is_err() {
+                return Err(ret2.err().unwrap());
+            }
+            return Ok(Value::NamedNumber(name, Box::new(ret.unwrap())));
+        }
+        let builtin = self.peek_word(0).is_some_and(|w| w != "NULL" && BUILTIN_TYPES.contains(&w));
+        let constrained = self.is_reference(0) && is_upper(self.peek_word(0).unwrap()) && self.is_symbol(1, "(");
+        if builtin || constrained || self.is_symbol(0, "[") {
+            return self.parse_type().map(|ty| Value::Type(Box::new(ty)));
+        }
+        self.value()
+    }
+
+    /// Read the fields and defined syntax of a class, after its CLASS.
+    fn class(&mut self) -> Result<Class, ParseError> {
+        let ret = self.expect_symbol("{", "`{`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut fields = Vec::new();
+        loop {
+            let ret = self.class_field();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            fields.push(ret.unwrap());
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        let ret = self.expect_symbol("}", "`}`");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let mut syntax = None;
+        if self.eat_word("WITH") {
+            let ret = self.expect_word("SYNTAX", "SYNTAX");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = self.expect_symbol("{", "`{`");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = self.syntax("}");
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            syntax = Some(ret.unwrap());
+        }
+        Ok(Class { fields, syntax })
+    }
+
+    fn class_field(&mut self) -> Result<ClassField, ParseError> {
+        let name = match self.peek(0) {
+            Some(Token::Field(f)) => f.clone(),
+            _ => return Err(self.unexpected("a field")),
+        };
+        self.pos += 1;
+        let ends = |p: &Self| {
+            p.is_symbol(0, ",") || p.is_symbol(0, "}") || p.is_word(0, "OPTIONAL") || p.is_word(0, "DEFAULT")
+        };
+        let kind = if is_upper(&name[1..]) && ends(self) {
+            FieldKind::Type
+        } else {
+            let ret = self.parse_type();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if is_upper(&name[1..]) {
+                FieldKind::ValueSet(ret.unwrap())
+            } else {
+                FieldKind::Value(ret.unwrap())
+            }
+        };
+        let unique = self.eat_word("UNIQUE");
+        let optional = self.eat_word("OPTIONAL");
+        let mut default = None;
+        if !optional && self.eat_word("DEFAULT") {
+            let ret = if self.is_symbol(0, "{") || !self.starts_type() {
+                self.value()
+            } else {
+                self.parse_type().map(|ty| Value::Type(Box::new(ty)))
+            };
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            default = Some(ret.unwrap());
+        }
+        Ok(ClassField {
+               name,
+               kind,
+               unique,
+               optional,
+               default,
+           })
+    }
+
+    /// Read the tokens of a defined syntax up to `close`.
+    fn syntax(&mut self, close: &str) -> Result<Vec<SyntaxToken>, ParseError> {
+        let mut tokens = Vec::new();
+        loop {
+            self.split_brackets();
+            if self.eat_symbol(close) {
+                return Ok(tokens);
+            }
+            let token = match self.peek(0) {
+                Some(Token::Word(w)) => SyntaxToken::Word(w.clone()),
+                Some(Token::Field(f)) => SyntaxToken::Field(f.clone()),
+                Some(&Token::Symbol(",")) => SyntaxToken::Comma,
+                Some(&Token::Symbol("[")) => {
+                    self.pos += 1;
+                    let ret = self.syntax("]");
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    tokens.push(SyntaxToken::Optional(ret.unwrap()));
+                    continue;
+                }
+                _ => return Err(self.unexpected("a word or a field")),
+            };
+            tokens.push(token);
+            self.pos += 1;
+        }
+    }
+}
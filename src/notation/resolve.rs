@@ -0,0 +1,449 @@
+use std::error;
+use std::fmt;
+use super::*;
+
+// How many references deep resolving may go, which is how cycles like `A ::= B (1..4)  B ::= A` are caught
+pub(crate) const MAX_DEPTH: usize = 32;
+
+/// Something that knows what references refer to, e.g. the modules of a specification.
+///
+/// The types and values it returns are as if they were written where the reference is: the dummy parameters of a
+/// parameterized type are replaced by the actual ones, and references in them are qualified with the module that
+/// defines what they refer to, if the resolver knows it.
+pub trait Resolver {
+    /// Get the type assigned to `reference`, instantiated with `arguments` if it's parameterized. A value set
+    /// assignment `Foo INTEGER ::= { ... }` is the type it constrains, e.g. `INTEGER ({ ... })`.
+    fn resolve_type(&self, reference: &Reference, arguments: &[Argument]) -> Result<Type, ResolveError>;
+
+    /// Get the value assigned to `reference`.
+    fn resolve_value(&self, reference: &Reference) -> Result<Value, ResolveError>;
+
+    /// Get the information object class assigned to `reference`.
+    fn resolve_class(&self, reference: &Reference) -> Result<Class, ResolveError> {
+        Err(ResolveError::UnknownReference(reference_name(reference)))
+    }
+}
+
+/// A module resolves its own assignments, and qualifies the references to what it imports with the module it imports
+/// them from.
+impl Resolver for Module {
+    fn resolve_type(&self, reference: &Reference, arguments: &[Argument]) -> Result<Type, ResolveError> {
+        let ret = self.lookup(reference);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let assignment = ret.unwrap();
+        let mut ty = match assignment.kind {
+            AssignmentKind::Type(ref ty) => ty.clone(),
+            AssignmentKind::ValueSet { ref ty, ref set } => {
+                let mut ty = ty.clone();
+                ty.constraints.push(Constraint::Subtype(set.clone()));
+                ty
+            }
+            _ => return Err(ResolveError::UnknownReference(reference_name(reference))),
+        };
+        if assignment.parameters.len() != arguments.len() {
+            return Err(ResolveError::InvalidArguments(reference_name(reference)));
+        }
+        let rewriter = Rewriter {
+            module: self,
+            parameters: &assignment.parameters,
+            arguments,
+        };
+        rewriter.ty(&mut ty);
+        Ok(ty)
+    }
+
+    fn resolve_value(&self, reference: &Reference) -> Result<Value, ResolveError> {
+        let ret = self.lookup(reference);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let assignment = ret.unwrap();
+        let mut value = match assignment.kind {
+            AssignmentKind::Value { ref value, .. } if assignment.parameters.is_empty() => value.clone(),
+            _ => return Err(ResolveError::UnknownReference(reference_name(reference))),
+        };
+        let rewriter = Rewriter {
+            module: self,
+            parameters: &[],
+            arguments: &[],
+        };
+        rewriter.value(&mut value);
+        Ok(value)
+    }
+
+    fn resolve_class(&self, reference: &Reference) -> Result<Class, ResolveError> {
+        let ret = self.lookup(reference);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let assignment = ret.unwrap();
+        let mut class = match assignment.kind {
+            AssignmentKind::Class(ref class) if assignment.parameters.is_empty() => class.clone(),
+            _ => return Err(ResolveError::UnknownReference(reference_name(reference))),
+        };
+        let rewriter = Rewriter {
+            module: self,
+            parameters: &[],
+            arguments: &[],
+        };
+        rewriter.class(&mut class);
+        Ok(class)
+    }
+}
+
+impl Module {
+    fn lookup(&self, reference: &Reference) -> Result<&Assignment, ResolveError> {
+        if reference.module.as_ref().is_some_and(|m| *m != self.name) {
+            return Err(ResolveError::UnknownReference(reference_name(reference)));
+        }
+        match self.assignment(&reference.name) {
+            Some(assignment) => Ok(assignment),
+            None => Err(ResolveError::UnknownReference(reference_name(reference))),
+        }
+    }
+}
+
+/// A qualified reference is looked up in the module it names, following its imports, and an unqualified one in every
+/// module in turn.
+impl Resolver for [Module] {
+    fn resolve_type(&self, reference: &Reference, arguments: &[Argument]) -> Result<Type, ResolveError> {
+        match defining_module(self, reference) {
+            Some(module) => module.resolve_type(&qualify(reference, module), arguments),
+            None => Err(ResolveError::UnknownReference(reference_name(reference))),
+        }
+    }
+
+    fn resolve_value(&self, reference: &Reference) -> Result<Value, ResolveError> {
+        match defining_module(self, reference) {
+            Some(module) => module.resolve_value(&qualify(reference, module)),
+            None => Err(ResolveError::UnknownReference(reference_name(reference))),
+        }
+    }
+
+    fn resolve_class(&self, reference: &Reference) -> Result<Class, ResolveError> {
+        match defining_module(self, reference) {
+            Some(module) => module.resolve_class(&qualify(reference, module)),
+            None => Err(ResolveError::UnknownReference(reference_name(reference))),
+        }
+    }
+}
+
+/// Get the module among `modules` with the assignment `reference` refers to.
+fn defining_module<'a>(modules: &'a [Module], reference: &Reference) -> Option<&'a Module> {
+    let name = &reference.name;
+    let mut module_name = match reference.module {
+        Some(ref module_name) => module_name.clone(),
+        None => return modules.iter().find(|m| m.assignment(name).is_some()),
+    };
+    // Each step follows an import, so there can't be more steps than modules unless the imports go round in a circle
+    for _ in 0..modules.len() {
+        let import = match modules.iter().find(|m| m.name == module_name) {
+            Some(module) if module.assignment(name).is_some() => return Some(module),
+            Some(module) => module.imports.iter().find(|i| i.symbols.contains(name)),
+            None => None,
+        };
+        match import {
+            Some(import) => module_name = import.module.clone(),
+            None => return None,
+        }
+    }
+    None
+}
+
+fn qualify(reference: &Reference, module: &Module) -> Reference {
+    Reference {
+        module: Some(module.name.clone()),
+        name: reference.name.clone(),
+    }
+}
+
+fn reference_name(reference: &Reference) -> String {
+    match reference.module {
+        Some(ref module) => format!("{}.{}", module, reference.name),
+        None => reference.name.clone(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveError {
+    /// A reference the resolver doesn't know, with its module if it names one.
+    UnknownReference(String),
+    /// A reference to a parameterized assignment with the wrong number of actual parameters, or to one that isn't
+    /// parameterized with some.
+    InvalidArguments(String),
+    /// A value that can't be used where it is, e.g. a string as the bound of an INTEGER range.
+    InvalidValue,
+    /// Constraints that no value satisfies, e.g. `INTEGER (1..4 ^ 6..8)`.
+    EmptyConstraint,
+    /// References nested too deeply, which is usually a cycle.
+    TooDeep,
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResolveError::UnknownReference(ref name) => write!(f, "unknown reference {}", name),
+            ResolveError::InvalidArguments(ref name) => write!(f, "wrong actual parameters for {}", name),
+            ResolveError::InvalidValue => write!(f, "invalid value in a constraint"),
+            ResolveError::EmptyConstraint => write!(f, "the constraints permit no value"),
+            ResolveError::TooDeep => write!(f, "references are nested too deeply"),
+        }
+    }
+}
+
+impl error::Error for ResolveError {}
+
+/// Rewrites what's assigned in `module` as if it were written where it's referred to: replaces the dummy parameters
+/// with the actual ones, and qualifies the references to the module's assignments and imports.
+struct Rewriter<'a> {
+    module: &'a Module,
+    parameters: &'a [Parameter],
+    arguments: &'a [Argument],
+}
+
+impl<'a> Rewriter<'a> {
+    /// Get the dummy parameter `reference` stands for, and its actual parameter.
+    fn argument(&self, reference: &Reference) -> Option<(&'a Parameter, &'a Argument)> {
+        if reference.module.is_some() {
+            return None;
+        }
+        self.parameters.iter().zip(self.arguments.iter()).find(|&(p, _)| p.name == reference.name)
+    }
+
+    fn reference(&self, reference: &mut Reference) {
+        if reference.module.is_some() {
+            return;
+        }
+        if self.module.assignment(&reference.name).is_some() {
+            reference.module = Some(self.module.name.clone());
+        } else if let Some(import) = self.module.imports.iter().find(|i| i.symbols.contains(&reference.name)) {
+            reference.module = Some(import.module.clone());
+        }
+    }
+
+    fn ty(&self, ty: &mut Type) {
+        for constraint in &mut ty.constraints {
+            self.constraint(constraint);
+        }
+
+        // A dummy type, or a dummy value set used as a type, is replaced with its constraints applied after those of
+        // the actual parameter
+        let replacement = match ty.kind {
+            TypeKind::Reference { ref reference, ref arguments } if arguments.is_empty() => {
+                match self.argument(reference) {
+                    Some((_, Argument::Type(actual))) => Some(actual.clone()),
+                    Some((Parameter { governor: Some(governor), .. }, Argument::Set(set))) => {
+                        let mut actual = governor.clone();
+                        actual.constraints.push(Constraint::Subtype(set.clone()));
+                        Some(actual)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(mut actual) = replacement {
+            actual.constraints.append(&mut ty.constraints);
+            *ty = actual;
+            return;
+        }
+
+        match ty.kind {
+            TypeKind::Integer(ref mut numbers) | TypeKind::BitString(ref mut numbers) => self.named_numbers(numbers),
+            TypeKind::Enumerated { ref mut root, ref mut extensions } => {
+                self.named_numbers(root);
+                if let Some(ref mut extensions) = *extensions {
+                    self.named_numbers(extensions);
+                }
+            }
+            TypeKind::Sequence(ref mut components) |
+            TypeKind::Set(ref mut components) |
+            TypeKind::Choice(ref mut components) => self.components(components),
+            TypeKind::SequenceOf { ref mut element, .. } | TypeKind::SetOf { ref mut element, .. } => self.ty(element),
+            TypeKind::Tagged { ref mut tag, ref mut ty, .. } => {
+                self.value(&mut tag.number);
+                self.ty(ty);
+            }
+            TypeKind::Reference { ref mut reference, ref mut arguments } => {
+                self.reference(reference);
+                for argument in arguments {
+                    match *argument {
+                        Argument::Type(ref mut ty) => self.ty(ty),
+                        Argument::Value(ref mut value) => self.value(value),
+                        Argument::Set(ref mut set) => self.specs(set),
+                    }
+                }
+            }
+            TypeKind::ClassField { ref mut class, .. } => {
+                let actual = match self.argument(class) {
+                    Some((_, Argument::Type(Type { kind: TypeKind::Reference { reference, .. }, .. }))) => {
+                        Some(reference.clone())
+                    }
+                    _ => None,
+                };
+                match actual {
+                    Some(actual) => *class = actual,
+                    None => self.reference(class),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn class(&self, class: &mut Class) {
+        for field in &mut class.fields {
+            match field.kind {
+                FieldKind::Value(ref mut ty) | FieldKind::ValueSet(ref mut ty) => self.ty(ty),
+                FieldKind::Type => {}
+            }
+            if let Some(ref mut value) = field.default {
+                self.value(value);
+            }
+        }
+    }
+
+    fn named_numbers(&self, numbers: &mut [NamedNumber]) {
+        for number in numbers {
+            if let Some(ref mut value) = number.number {
+                self.value(value);
+            }
+        }
+    }
+
+    fn components(&self, components: &mut ComponentList) {
+        for component in &mut components.root {
+            self.component(component);
+        }
+        for addition in components.extensions.iter_mut().flat_map(|e| e.iter_mut()) {
+            match *addition {
+                ExtensionAddition::Component(ref mut component) => self.component(component),
+                ExtensionAddition::Group { ref mut components, .. } => {
+                    for component in components {
+                        self.component(component);
+                    }
+                }
+            }
+        }
+    }
+
+    fn component(&self, component: &mut Component) {
+        self.ty(&mut component.ty);
+        if let Presence::Default(ref mut value) = component.presence {
+            self.value(value);
+        }
+    }
+
+    fn constraint(&self, constraint: &mut Constraint) {
+        match *constraint {
+            Constraint::Subtype(ref mut set) | Constraint::Relation { ref mut set, .. } => self.specs(set),
+            Constraint::Contents { ref mut ty, ref mut encoded_by } => {
+                if let Some(ref mut ty) = *ty {
+                    self.ty(ty);
+                }
+                if let Some(ref mut value) = *encoded_by {
+                    self.value(value);
+                }
+            }
+            Constraint::UserDefined => {}
+        }
+    }
+
+    fn specs(&self, specs: &mut ElementSetSpecs) {
+        if let Some(ref mut root) = specs.root {
+            self.element_set(root);
+        }
+        if let Some(ref mut additions) = specs.additions {
+            self.element_set(additions);
+        }
+    }
+
+    fn element_set(&self, set: &mut ElementSet) {
+        // A dummy value set or object set is replaced with the elements of the actual parameter, which need no
+        // parentheses if the actual parameter has no extension marker
+        let replacement = match *set {
+            ElementSet::ContainedSubtype(ref ty) if ty.constraints.is_empty() => {
+                match ty.kind {
+                    TypeKind::Reference { ref reference, ref arguments } if arguments.is_empty() => {
+                        match self.argument(reference) {
+                            Some((_, Argument::Set(actual))) if !actual.extensible && actual.additions.is_none() => {
+                                actual.root.clone()
+                            }
+                            Some((_, Argument::Set(actual))) => Some(ElementSet::Nested(Box::new(actual.clone()))),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(actual) = replacement {
+            *set = actual;
+            return;
+        }
+
+        match *set {
+            ElementSet::Union(ref mut sets) | ElementSet::Intersection(ref mut sets) => {
+                for set in sets {
+                    self.element_set(set);
+                }
+            }
+            ElementSet::Except(ref mut set, ref mut excluded) => {
+                self.element_set(set);
+                self.element_set(excluded);
+            }
+            ElementSet::Value(ref mut value) | ElementSet::Pattern(ref mut value) => self.value(value),
+            ElementSet::Range { ref mut lower, ref mut upper } => {
+                for endpoint in &mut [lower, upper] {
+                    if let Bound::Value(ref mut value) = endpoint.value {
+                        self.value(value);
+                    }
+                }
+            }
+            ElementSet::Size(ref mut constraint) |
+            ElementSet::PermittedAlphabet(ref mut constraint) |
+            ElementSet::WithComponent(ref mut constraint) => self.constraint(constraint),
+            ElementSet::ContainedSubtype(ref mut ty) => self.ty(ty),
+            ElementSet::WithComponents { ref mut components, .. } => {
+                for component in components {
+                    if let Some(ref mut constraint) = component.constraint {
+                        self.constraint(constraint);
+                    }
+                }
+            }
+            ElementSet::Nested(ref mut specs) | ElementSet::Braced(ref mut specs) => self.specs(specs),
+            ElementSet::All => {}
+        }
+    }
+
+    fn value(&self, value: &mut Value) {
+        let replacement = match *value {
+            Value::Reference(ref reference) => {
+                match self.argument(reference) {
+                    Some((_, Argument::Value(actual))) => Some(actual.clone()),
+                    Some((_, Argument::Type(actual))) => Some(Value::Type(Box::new(actual.clone()))),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(actual) = replacement {
+            *value = actual;
+            return;
+        }
+
+        match *value {
+            Value::Reference(ref mut reference) => self.reference(reference),
+            Value::Choice(_, ref mut value) | Value::NamedNumber(_, ref mut value) => self.value(value),
+            Value::Braced(ref mut items) => {
+                for value in items.iter_mut().flat_map(|i| i.iter_mut()) {
+                    self.value(value);
+                }
+            }
+            Value::Type(ref mut ty) => self.ty(ty),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,1118 @@
+use aper::{APerElement, Decoder, DecodeError, EncodeError};
+use schema;
+use {BitString, ObjectIdentifier, RelativeOid, VisibleString};
+use std::error;
+use std::fmt;
+use super::lexer;
+use super::parser::Parser;
+use super::resolve::MAX_DEPTH;
+use super::*;
+
+/// An error in converting a value between value notation and its dynamic or typed form.
+#[derive(Debug, PartialEq)]
+pub enum ValueError {
+    /// The text isn't a value in value notation.
+    Parse(ParseError),
+    Resolve(ResolveError),
+    /// A value that isn't a value of its type, with the path to it from the outermost value, e.g.
+    /// `protocolIEs.0.value`.
+    InvalidValue(String),
+    /// A type whose values this module can't handle, e.g. EXTERNAL.
+    Unsupported(&'static str),
+    Encode(EncodeError),
+    Decode(DecodeError),
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ValueError::Parse(ref e) => write!(f, "{}", e),
+            ValueError::Resolve(ref e) => write!(f, "{}", e),
+            ValueError::InvalidValue(ref path) if path.is_empty() => write!(f, "invalid value"),
+            ValueError::InvalidValue(ref path) => write!(f, "invalid value at {}", path),
+            ValueError::Unsupported(what) => write!(f, "{} is not supported", what),
+            ValueError::Encode(ref e) => write!(f, "encoding failed: {:?}", e),
+            ValueError::Decode(ref e) => write!(f, "decoding failed: {:?}", e),
+        }
+    }
+}
+
+impl error::Error for ValueError {}
+
+/// Get the runtime schema of `ty`, to decode and encode its values with the [schema](../schema/index.html) module.
+///
+/// The components of a SET and the alternatives of a CHOICE are taken in the order they're written, which is their
+/// canonical order with AUTOMATIC TAGS. Open types, i.e. type fields of classes, are `Type::Octets`.
+pub fn schema_type<R: Resolver + ?Sized>(ty: &Type, resolver: &R) -> Result<schema::Type, ValueError> {
+    Values { resolver }.schema_type(ty, 0)
+}
+
+/// Convert `value`, a value of `ty` in value notation, to a dynamic value.
+pub fn dynamic_value<R: Resolver + ?Sized>(value: &Value, ty: &Type, resolver: &R)
+                                           -> Result<schema::Value, ValueError> {
+    Values { resolver }.dynamic(value, ty, "", &[], 0)
+}
+
+/// Parse `text`, a value of `ty` in value notation, into a dynamic value.
+///
+/// The value of an open type whose type is given by a component relation constraint, like the `value` of a
+/// `ProtocolIE-Field`, is written as a value of the type of the object it selects, and is kept as its encoding. It may
+/// also be written as that encoding, in hexadecimal.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::notation::{self, AssignmentKind};
+///
+/// let modules = notation::parse("Foo DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+///                                    Bar ::= SEQUENCE { a INTEGER (0..7), b ENUMERATED { on, off } OPTIONAL }
+///                                END")
+///     .unwrap();
+/// let ty = match modules[0].assignment("Bar").unwrap().kind {
+///     AssignmentKind::Type(ref ty) => ty.clone(),
+///     _ => unreachable!(),
+/// };
+/// let value = notation::parse_value("{ a 5, b off }", &ty, &modules[..]).unwrap();
+/// println!("{}", notation::print_value(&value, &ty, &modules[..]).unwrap());
+/// ```
+pub fn parse_value<R: Resolver + ?Sized>(text: &str, ty: &Type, resolver: &R) -> Result<schema::Value, ValueError> {
+    let ret = lexer::tokenize(text);
+    if ret.is_err() {
+        return Err(ValueError::Parse(ret.err().unwrap()));
+    }
+    let (tokens, end) = ret.unwrap();
+    let ret = Parser::new(tokens, end).whole_value();
+    if ret.is_err() {
+        return Err(ValueError::Parse(ret.err().unwrap()));
+    }
+    dynamic_value(&ret.unwrap(), ty, resolver)
+}
+
+/// Parse `text`, a value of `ty` in value notation, into a value of `T`, the Rust type of `ty`.
+pub fn parse_element<T: APerElement, R: Resolver + ?Sized>(text: &str, ty: &Type, resolver: &R)
+                                                           -> Result<T, ValueError> {
+    let ret = parse_value(text, ty, resolver);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let value = ret.unwrap();
+    let ret = schema_type(ty, resolver);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let ret = schema::encode(&value, &ret.unwrap());
+    if ret.is_err() {
+        return Err(ValueError::Encode(ret.err().unwrap()));
+    }
+    let enc = ret.unwrap();
+    let ret = per_constraints(ty, resolver);
+    if ret.is_err() {
+        return Err(ValueError::Resolve(ret.err().unwrap()));
+    }
+    T::from_aper(&mut Decoder::new(enc.bytes()), ret.unwrap().constraints()).map_err(ValueError::Decode)
+}
+
+/// Print `value`, a value of `ty`, in value notation, with one component or element per line.
+///
+/// The value of an open type whose type is given by a component relation constraint is printed as a value of the type
+/// of the object it selects. Values that can't be written in value notation, like the open type value of an unknown
+/// extension addition, are printed in hexadecimal.
+pub fn print_value<R: Resolver + ?Sized>(value: &schema::Value, ty: &Type, resolver: &R)
+                                         -> Result<String, ValueError> {
+    let mut output = String::new();
+    let ret = Values { resolver }.print(&mut output, value, ty, "", 0, 0);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(output)
+}
+
+/// Print `element`, whose ASN.1 type is `ty`, in value notation. See [print_value](fn.print_value.html).
+pub fn print_element<T: APerElement, R: Resolver + ?Sized>(element: &T, ty: &Type, resolver: &R)
+                                                           -> Result<String, ValueError> {
+    let ret = per_constraints(ty, resolver);
+    if ret.is_err() {
+        return Err(ValueError::Resolve(ret.err().unwrap()));
+    }
+    let ret = element.to_aper(ret.unwrap().constraints());
+    if ret.is_err() {
+        return Err(ValueError::Encode(ret.err().unwrap()));
+    }
+    let enc = ret.unwrap();
+    let ret = schema_type(ty, resolver);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let ret = schema::decode(&mut Decoder::new(enc.bytes()), &ret.unwrap());
+    if ret.is_err() {
+        return Err(ValueError::Decode(ret.err().unwrap()));
+    }
+    print_value(&ret.unwrap(), ty, resolver)
+}
+
+/// The components of a SEQUENCE value converted so far, which open types may depend on.
+type Siblings = [(String, Type, schema::Value)];
+
+struct Values<'a, R: 'a + ?Sized> {
+    resolver: &'a R,
+}
+
+impl<'a, R: Resolver + ?Sized> Values<'a, R> {
+    /// Get the builtin type `ty` refers to, following references and tags. A type field of a class is left as it is.
+    fn builtin(&self, ty: &Type, depth: usize) -> Result<Type, ValueError> {
+        if depth > MAX_DEPTH {
+            return Err(ValueError::Resolve(ResolveError::TooDeep));
+        }
+        match ty.kind {
+            TypeKind::Reference { ref reference, ref arguments } => {
+                match self.resolver.resolve_type(reference, arguments) {
+                    Ok(ty) => self.builtin(&ty, depth + 1),
+                    Err(e) => Err(ValueError::Resolve(e)),
+                }
+            }
+            TypeKind::Tagged { ref ty, .. } => self.builtin(ty, depth + 1),
+            TypeKind::ClassField { ref class, ref fields } => {
+                let ret = self.field(class, fields);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match ret.unwrap().kind {
+                    FieldKind::Value(ref ty) | FieldKind::ValueSet(ref ty) => self.builtin(ty, depth + 1),
+                    FieldKind::Type => Ok(ty.clone()),
+                }
+            }
+            _ => Ok(ty.clone()),
+        }
+    }
+
+    fn field(&self, class: &Reference, fields: &[String]) -> Result<ClassField, ValueError> {
+        if fields.len() != 1 {
+            return Err(ValueError::Unsupported("fields of objects held by fields"));
+        }
+        let ret = self.resolver.resolve_class(class);
+        if ret.is_err() {
+            return Err(ValueError::Resolve(ret.err().unwrap()));
+        }
+        match ret.unwrap().fields.into_iter().find(|f| f.name == fields[0]) {
+            Some(field) => Ok(field),
+            None => Err(ValueError::Resolve(ResolveError::UnknownReference(format!("{}.{}", class.name, fields[0])))),
+        }
+    }
+
+    fn schema_type(&self, ty: &Type, depth: usize) -> Result<schema::Type, ValueError> {
+        let ret = self.builtin(ty, depth);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let builtin = ret.unwrap();
+        let ret = per_constraints(ty, self.resolver);
+        if ret.is_err() {
+            return Err(ValueError::Resolve(ret.err().unwrap()));
+        }
+        let per = ret.unwrap();
+
+        let ty = match builtin.kind {
+            TypeKind::Boolean => schema::Type::Boolean,
+            TypeKind::Null => schema::Type::Null,
+            TypeKind::Integer(_) => {
+                schema::Type::Integer {
+                    value: per.value,
+                    extensible: per.value_extensible,
+                }
+            }
+            TypeKind::Enumerated { ref root, ref extensions } => {
+                schema::Type::Enumerated {
+                    root: root.len() as u64,
+                    extensible: extensions.is_some(),
+                }
+            }
+            TypeKind::BitString(_) => {
+                schema::Type::BitString {
+                    size: per.size,
+                    extensible: per.size_extensible,
+                }
+            }
+            TypeKind::OctetString => {
+                schema::Type::OctetString {
+                    size: per.size,
+                    extensible: per.size_extensible,
+                }
+            }
+            TypeKind::String(StringKind::UTF8String) => schema::Type::Utf8String,
+            TypeKind::String(_) => {
+                match per.alphabet {
+                    Some(alphabet) => {
+                        schema::Type::CharString {
+                            alphabet,
+                            size: per.size,
+                            extensible: per.size_extensible,
+                        }
+                    }
+                    None => schema::Type::Octets,
+                }
+            }
+            TypeKind::Time(TimeKind::GeneralizedTime) | TypeKind::Time(TimeKind::UTCTime) => {
+                schema::Type::CharString {
+                    alphabet: VisibleString::alphabet(),
+                    size: None,
+                    extensible: false,
+                }
+            }
+            TypeKind::Time(_) => return Err(ValueError::Unsupported("the TIME type and its subtypes")),
+            TypeKind::ObjectIdentifier => schema::Type::ObjectIdentifier,
+            TypeKind::RelativeOid => schema::Type::RelativeOid,
+            TypeKind::Real => schema::Type::Real,
+            TypeKind::External => return Err(ValueError::Unsupported("EXTERNAL")),
+            TypeKind::EmbeddedPdv => return Err(ValueError::Unsupported("EMBEDDED PDV")),
+            TypeKind::CharacterString => return Err(ValueError::Unsupported("CHARACTER STRING")),
+            TypeKind::Sequence(ref components) | TypeKind::Set(ref components) => {
+                let mut root = Vec::with_capacity(components.root.len());
+                for component in &components.root {
+                    let ret = self.schema_type(&component.ty, depth + 1);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    root.push(schema::Component {
+                                  ty: ret.unwrap(),
+                                  optional: component.presence != Presence::Required,
+                              });
+                }
+                let mut extensions = None;
+                if let Some(ref additions) = components.extensions {
+                    let mut types = Vec::with_capacity(additions.len());
+                    for addition in additions {
+                        let ret = match *addition {
+                            ExtensionAddition::Component(ref component) => self.schema_type(&component.ty, depth + 1),
+                            ExtensionAddition::Group { ref components, .. } => {
+                                let group = ComponentList {
+                                    root: components.clone(),
+                                    extensions: None,
+                                };
+                                self.schema_type(&Type::new(TypeKind::Sequence(group)), depth + 1)
+                            }
+                        };
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                        types.push(ret.unwrap());
+                    }
+                    extensions = Some(types);
+                }
+                schema::Type::Sequence { root, extensions }
+            }
+            TypeKind::SequenceOf { ref element, .. } | TypeKind::SetOf { ref element, .. } => {
+                let ret = self.schema_type(element, depth + 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                schema::Type::SequenceOf {
+                    element: Box::new(ret.unwrap()),
+                    size: per.size,
+                    extensible: per.size_extensible,
+                }
+            }
+            TypeKind::Choice(ref components) => {
+                let mut types = Vec::new();
+                for component in components.root.iter().chain(extension_components(components)) {
+                    let ret = self.schema_type(&component.ty, depth + 1);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    types.push(ret.unwrap());
+                }
+                let extensions = components.extensions.as_ref().map(|_| types.split_off(components.root.len()));
+                schema::Type::Choice {
+                    root: types,
+                    extensions,
+                }
+            }
+            // An open type, whose value is kept as its encoding
+            TypeKind::ClassField { .. } => schema::Type::Octets,
+            TypeKind::Reference { .. } | TypeKind::Tagged { .. } => unreachable!(),
+        };
+        Ok(ty)
+    }
+
+    /// Get the integer `value` stands for.
+    fn integer(&self, value: &Value, path: &str, depth: usize) -> Result<i64, ValueError> {
+        match *value {
+            Value::Integer(v) => Ok(v),
+            Value::Reference(ref reference) if depth <= MAX_DEPTH => {
+                match self.resolver.resolve_value(reference) {
+                    Ok(value) => self.integer(&value, path, depth + 1),
+                    Err(e) => Err(ValueError::Resolve(e)),
+                }
+            }
+            Value::Reference(_) => Err(ValueError::Resolve(ResolveError::TooDeep)),
+            _ => Err(ValueError::InvalidValue(path.to_string())),
+        }
+    }
+
+    /// Get the identifiers and numbers of named numbers.
+    fn named_numbers(&self, numbers: &[NamedNumber], depth: usize) -> Result<Vec<(String, i64)>, ValueError> {
+        let mut named = Vec::with_capacity(numbers.len());
+        for number in numbers {
+            if let Some(ref value) = number.number {
+                let ret = self.integer(value, &number.name, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                named.push((number.name.clone(), ret.unwrap()));
+            }
+        }
+        Ok(named)
+    }
+
+    /// Get the identifiers of an ENUMERATED type in the order of their indexes: the root items sorted by their
+    /// numbers, where an item without a number has the smallest one that no other item has, and then the extension
+    /// additions.
+    fn enumeration(&self, root: &[NamedNumber], extensions: &Option<Vec<NamedNumber>>, depth: usize)
+                   -> Result<Vec<String>, ValueError> {
+        let ret = self.named_numbers(root, depth);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut used: Vec<i64> = ret.unwrap().into_iter().map(|(_, n)| n).collect();
+        let mut numbered = Vec::with_capacity(root.len());
+        let mut explicit = used.clone().into_iter();
+        let mut next = 0;
+        for item in root {
+            let n = match item.number {
+                Some(_) => explicit.next().unwrap(),
+                None => {
+                    while used.contains(&next) {
+                        next += 1;
+                    }
+                    used.push(next);
+                    next
+                }
+            };
+            numbered.push((n, item.name.clone()));
+        }
+        numbered.sort();
+
+        let mut names: Vec<String> = numbered.into_iter().map(|(_, name)| name).collect();
+        if let Some(ref extensions) = *extensions {
+            names.extend(extensions.iter().map(|item| item.name.clone()));
+        }
+        Ok(names)
+    }
+
+    /// Convert `value` to a dynamic value of `ty`, where `siblings` are the components before it if it's a component.
+    fn dynamic(&self, value: &Value, ty: &Type, path: &str, siblings: &Siblings, depth: usize)
+               -> Result<schema::Value, ValueError> {
+        let ret = self.builtin(ty, depth);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let builtin = ret.unwrap();
+        let invalid = || Err(ValueError::InvalidValue(path.to_string()));
+
+        match (&builtin.kind, value) {
+            (TypeKind::Boolean, &Value::Boolean(b)) => Ok(schema::Value::Boolean(b)),
+            (TypeKind::Null, Value::Null) => Ok(schema::Value::Null),
+            (TypeKind::Integer(_), &Value::Integer(v)) => Ok(schema::Value::Integer(v)),
+            (TypeKind::Integer(numbers), Value::Reference(reference)) if reference.module.is_none() &&
+                                                                        numbers.iter()
+                                                                               .any(|n| n.name == reference.name) => {
+                let ret = self.named_numbers(numbers, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let number = ret.unwrap().into_iter().find(|(name, _)| *name == reference.name).map(|(_, n)| n);
+                Ok(schema::Value::Integer(number.unwrap()))
+            }
+            (TypeKind::Enumerated { root, extensions }, Value::Reference(reference))
+                if root.iter().chain(extensions.iter().flatten()).any(|i| i.name == reference.name) => {
+                let ret = self.enumeration(root, extensions, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let index = ret.unwrap().iter().position(|name| *name == reference.name).unwrap();
+                Ok(schema::Value::Enumerated(index as u64))
+            }
+            (TypeKind::BitString(_), Value::BString(s)) => Ok(schema::Value::BitString(bits(s, 1))),
+            (TypeKind::BitString(_), Value::HString(s)) => Ok(schema::Value::BitString(bits(s, 4))),
+            (TypeKind::BitString(numbers), Value::Braced(parts)) => {
+                let ret = self.named_numbers(numbers, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let named = ret.unwrap();
+                let mut positions = Vec::new();
+                for part in parts {
+                    let position = match part.as_slice() {
+                        [Value::Reference(reference)] => {
+                            named.iter().find(|&(name, _)| *name == reference.name).map(|&(_, n)| n)
+                        }
+                        _ => None,
+                    };
+                    match position {
+                        Some(n) if n >= 0 => positions.push(n as usize),
+                        _ => return invalid(),
+                    }
+                }
+                // Named bits are as many as needed for the last one that's set, or the least the size allows
+                let ret = per_constraints(ty, self.resolver);
+                if ret.is_err() {
+                    return Err(ValueError::Resolve(ret.err().unwrap()));
+                }
+                let min = ret.unwrap().size.and_then(|c| c.min()).unwrap_or(0) as usize;
+                let mut bits = BitString::with_len(positions.iter().map(|&n| n + 1).max().unwrap_or(0).max(min));
+                for n in positions {
+                    bits.set(n, true);
+                }
+                Ok(schema::Value::BitString(bits))
+            }
+            (TypeKind::OctetString, Value::HString(s)) => Ok(schema::Value::OctetString(bits(s, 4).to_bytes())),
+            (TypeKind::OctetString, Value::BString(s)) => Ok(schema::Value::OctetString(bits(s, 1).to_bytes())),
+            (TypeKind::String(kind), Value::CString(s)) => {
+                match *kind {
+                    StringKind::UTF8String => Ok(schema::Value::String(s.clone())),
+                    _ if is_known_multiplier(*kind) => Ok(schema::Value::String(s.clone())),
+                    _ => Ok(schema::Value::Octets(s.as_bytes().to_vec())),
+                }
+            }
+            (TypeKind::Time(TimeKind::GeneralizedTime), Value::CString(s)) |
+            (TypeKind::Time(TimeKind::UTCTime), Value::CString(s)) => Ok(schema::Value::String(s.clone())),
+            (TypeKind::ObjectIdentifier, Value::Braced(parts)) => {
+                let ret = self.arcs(parts, ty, path, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match ObjectIdentifier::new(&ret.unwrap()) {
+                    Ok(oid) => Ok(schema::Value::ObjectIdentifier(oid)),
+                    Err(_) => invalid(),
+                }
+            }
+            (TypeKind::RelativeOid, Value::Braced(parts)) => {
+                let ret = self.arcs(parts, ty, path, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match RelativeOid::new(&ret.unwrap()) {
+                    Ok(oid) => Ok(schema::Value::RelativeOid(oid)),
+                    Err(_) => invalid(),
+                }
+            }
+            (TypeKind::Real, &Value::Real(v)) => Ok(schema::Value::Real(v)),
+            (TypeKind::Real, &Value::Integer(v)) => Ok(schema::Value::Real(v as f64)),
+            (TypeKind::Real, Value::Braced(parts)) => {
+                // { mantissa m, base b, exponent e }
+                let mut numbers = Vec::with_capacity(3);
+                for (part, name) in parts.iter().zip(["mantissa", "base", "exponent"].iter()) {
+                    match part.as_slice() {
+                        [Value::Reference(reference), value] if reference.name == *name => {
+                            let ret = self.integer(value, path, depth);
+                            if ret.is_err() {
+                                return Err(ret.err().unwrap());
+                            }
+                            numbers.push(ret.unwrap());
+                        }
+                        _ => return invalid(),
+                    }
+                }
+                if numbers.len() != 3 || parts.len() != 3 || (numbers[1] != 2 && numbers[1] != 10) {
+                    return invalid();
+                }
+                Ok(schema::Value::Real(numbers[0] as f64 * (numbers[1] as f64).powi(numbers[2] as i32)))
+            }
+            (TypeKind::Sequence(components), Value::Braced(parts)) |
+            (TypeKind::Set(components), Value::Braced(parts)) => self.sequence(components, parts, path, depth),
+            (TypeKind::SequenceOf { element, .. }, Value::Braced(parts)) |
+            (TypeKind::SetOf { element, .. }, Value::Braced(parts)) => {
+                let mut elements = Vec::with_capacity(parts.len());
+                for (i, part) in parts.iter().enumerate() {
+                    if part.len() != 1 {
+                        return invalid();
+                    }
+                    let ret = self.dynamic(&part[0], element, &child_path(path, &i.to_string()), &[], depth + 1);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    elements.push(ret.unwrap());
+                }
+                Ok(schema::Value::SequenceOf(elements))
+            }
+            (TypeKind::Choice(components), Value::Choice(name, value)) => {
+                let alternatives: Vec<&Component> = components.root
+                                                              .iter()
+                                                              .chain(extension_components(components))
+                                                              .collect();
+                let index = match alternatives.iter().position(|a| a.name == *name) {
+                    Some(index) => index,
+                    None => return invalid(),
+                };
+                let ret = self.dynamic(value, &alternatives[index].ty, &child_path(path, name), &[], depth + 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok(schema::Value::Choice {
+                       index,
+                       value: Box::new(ret.unwrap()),
+                   })
+            }
+            (TypeKind::ClassField { .. }, Value::HString(s)) => Ok(schema::Value::Octets(bits(s, 4).to_bytes())),
+            (TypeKind::ClassField { .. }, _) => {
+                let ret = self.open_type(ty, siblings, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let actual = match ret.unwrap() {
+                    Some(actual) => actual,
+                    None => return invalid(),
+                };
+                let ret = self.dynamic(value, &actual, path, &[], depth + 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let value = ret.unwrap();
+                let ret = self.schema_type(&actual, depth + 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match schema::encode(&value, &ret.unwrap()) {
+                    Ok(ref enc) if enc.num_bits() == 0 => Ok(schema::Value::Octets(vec![0])),
+                    Ok(enc) => Ok(schema::Value::Octets(enc.bytes().clone())),
+                    Err(e) => Err(ValueError::Encode(e)),
+                }
+            }
+            (_, Value::Reference(reference)) if depth <= MAX_DEPTH => {
+                match self.resolver.resolve_value(reference) {
+                    Ok(value) => self.dynamic(&value, ty, path, siblings, depth + 1),
+                    Err(e) => Err(ValueError::Resolve(e)),
+                }
+            }
+            _ => invalid(),
+        }
+    }
+
+    /// Get the arcs of an object identifier value, which may start with a reference to another one.
+    fn arcs(&self, parts: &[Vec<Value>], ty: &Type, path: &str, depth: usize) -> Result<Vec<u64>, ValueError> {
+        let mut arcs = Vec::new();
+        for (i, item) in parts.iter().flat_map(|p| p.iter()).enumerate() {
+            let ret = match *item {
+                Value::Reference(ref reference) if i == 0 && reference.module.is_none() &&
+                                                   well_known_arc(&reference.name).is_some() => {
+                    Ok(well_known_arc(&reference.name).unwrap())
+                }
+                Value::Reference(ref reference) if i == 0 => {
+                    let ret = self.dynamic(item, ty, path, &[], depth + 1);
+                    if let Ok(schema::Value::ObjectIdentifier(ref oid)) = ret {
+                        arcs.extend_from_slice(oid.arcs());
+                        continue;
+                    }
+                    if let Ok(schema::Value::RelativeOid(ref oid)) = ret {
+                        arcs.extend_from_slice(oid.arcs());
+                        continue;
+                    }
+                    self.integer(&Value::Reference(reference.clone()), path, depth)
+                }
+                Value::NamedNumber(_, ref value) => self.integer(value, path, depth),
+                _ => self.integer(item, path, depth),
+            };
+            match ret {
+                Ok(arc) if arc >= 0 => arcs.push(arc as u64),
+                Ok(_) => return Err(ValueError::InvalidValue(path.to_string())),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(arcs)
+    }
+
+    fn sequence(&self, components: &ComponentList, parts: &[Vec<Value>], path: &str, depth: usize)
+                -> Result<schema::Value, ValueError> {
+        let mut named: Vec<(&str, &Value)> = Vec::with_capacity(parts.len());
+        for part in parts {
+            match part.as_slice() {
+                [Value::Reference(Reference { module: None, name }), value]
+                    if !named.iter().any(|&(n, _)| n == name) => named.push((name, value)),
+                _ => return Err(ValueError::InvalidValue(path.to_string())),
+            }
+        }
+        let known = components.root.iter().chain(extension_components(components));
+        let known_names: Vec<&str> = known.map(|c| c.name.as_str()).collect();
+        if let Some(&(name, _)) = named.iter().find(|&&(name, _)| !known_names.contains(&name)) {
+            return Err(ValueError::InvalidValue(child_path(path, name)));
+        }
+
+        // Components are converted in the order of the type, so that an open type's component comes before it
+        let mut siblings: Vec<(String, Type, schema::Value)> = Vec::new();
+        let mut convert = |component: &Component| -> Result<Option<schema::Value>, ValueError> {
+            let value = match named.iter().find(|&&(name, _)| name == component.name) {
+                Some(&(_, value)) => value,
+                None if component.presence == Presence::Required => {
+                    return Err(ValueError::InvalidValue(child_path(path, &component.name)));
+                }
+                None => return Ok(None),
+            };
+            let ret = self.dynamic(value, &component.ty, &child_path(path, &component.name), &siblings, depth + 1);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let value = ret.unwrap();
+            siblings.push((component.name.clone(), component.ty.clone(), value.clone()));
+            Ok(Some(value))
+        };
+
+        let mut root = Vec::with_capacity(components.root.len());
+        for component in &components.root {
+            let ret = convert(component);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            root.push(ret.unwrap());
+        }
+        let mut extensions = Vec::new();
+        for addition in components.extensions.iter().flat_map(|e| e.iter()) {
+            match *addition {
+                ExtensionAddition::Component(ref component) => {
+                    let ret = convert(&Component {
+                                          presence: Presence::Optional,
+                                          ..component.clone()
+                                      });
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    extensions.push(ret.unwrap());
+                }
+                // A group is present if any of its components is, and then has all its mandatory ones
+                ExtensionAddition::Group { ref components, .. } => {
+                    if !components.iter().any(|c| named.iter().any(|&(name, _)| name == c.name)) {
+                        extensions.push(None);
+                        continue;
+                    }
+                    let mut group = Vec::with_capacity(components.len());
+                    for component in components {
+                        let ret = convert(component);
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                        group.push(ret.unwrap());
+                    }
+                    extensions.push(Some(schema::Value::Sequence {
+                                             root: group,
+                                             extensions: vec![],
+                                         }));
+                }
+            }
+        }
+        if extensions.iter().all(|e| e.is_none()) {
+            extensions.clear();
+        }
+        Ok(schema::Value::Sequence { root, extensions })
+    }
+
+    /// Get the type of the value of the open type `ty` that its component relation constraint selects, given the
+    /// components before it. Returns `None` if `ty` has no such constraint, or it selects no object.
+    fn open_type(&self, ty: &Type, siblings: &Siblings, depth: usize) -> Result<Option<Type>, ValueError> {
+        let (class, field) = match ty.kind {
+            TypeKind::ClassField { ref class, ref fields } if fields.len() == 1 => (class, &fields[0]),
+            _ => return Ok(None),
+        };
+        // Only a relation to a component of the same SEQUENCE, like `{@id}`, is supported
+        let (set, at) = match ty.constraints.iter().find(|c| matches!(**c, Constraint::Relation { .. })) {
+            Some(Constraint::Relation { set, components }) if components.len() == 1 => {
+                (set, components[0].trim_start_matches('.'))
+            }
+            _ => return Ok(None),
+        };
+        let (key_field, key_type, key) = match siblings.iter().find(|&(name, _, _)| name == at) {
+            Some((_, Type { kind: TypeKind::ClassField { fields, .. }, .. }, value)) if fields.len() == 1 => {
+                (&fields[0], Type::new(TypeKind::ClassField {
+                                           class: class.clone(),
+                                           fields: fields.clone(),
+                                       }), value)
+            }
+            _ => return Ok(None),
+        };
+
+        let ret = self.resolver.resolve_class(class);
+        if ret.is_err() {
+            return Err(ValueError::Resolve(ret.err().unwrap()));
+        }
+        let syntax = match ret.unwrap().syntax {
+            Some(syntax) => syntax,
+            None => return Err(ValueError::Unsupported("objects of classes without a defined syntax")),
+        };
+        let mut objects = Vec::new();
+        let ret = self.objects(set, &mut objects, depth);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        for object in objects {
+            let mut settings = Vec::new();
+            let mut pos = 0;
+            if !object_settings(&object, &mut pos, &syntax, &mut settings) || pos != object.len() {
+                continue;
+            }
+            let setting = |name: &str| settings.iter().find(|&(n, _)| n == name).map(|(_, v)| v);
+            let matches = match setting(key_field) {
+                Some(value) => self.dynamic(value, &key_type, "", &[], depth + 1).ok().as_ref() == Some(key),
+                None => false,
+            };
+            if !matches {
+                continue;
+            }
+            return Ok(match setting(field) {
+                          Some(Value::Type(ty)) => Some((**ty).clone()),
+                          Some(Value::Reference(reference)) => {
+                              Some(Type::new(TypeKind::Reference {
+                                                 reference: reference.clone(),
+                                                 arguments: Vec::new(),
+                                             }))
+                          }
+                          _ => None,
+                      });
+        }
+        Ok(None)
+    }
+
+    /// Collect the objects of an object set, each as the items of its definition in braces.
+    fn objects(&self, specs: &ElementSetSpecs, objects: &mut Vec<Vec<Value>>, depth: usize) -> Result<(), ValueError> {
+        for set in specs.root.iter().chain(specs.additions.iter()) {
+            let ret = self.object_elements(set, objects, depth);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(())
+    }
+
+    fn object_elements(&self, set: &ElementSet, objects: &mut Vec<Vec<Value>>, depth: usize)
+                       -> Result<(), ValueError> {
+        if depth > MAX_DEPTH {
+            return Err(ValueError::Resolve(ResolveError::TooDeep));
+        }
+        match *set {
+            ElementSet::Union(ref sets) | ElementSet::Intersection(ref sets) => {
+                for set in sets {
+                    let ret = self.object_elements(set, objects, depth);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+                Ok(())
+            }
+            ElementSet::Except(ref set, _) => self.object_elements(set, objects, depth),
+            ElementSet::Nested(ref specs) | ElementSet::Braced(ref specs) => self.objects(specs, objects, depth),
+            ElementSet::Value(Value::Braced(ref parts)) => {
+                objects.push(parts.iter().flat_map(|p| p.iter().cloned()).collect());
+                Ok(())
+            }
+            ElementSet::Value(Value::Reference(ref reference)) => {
+                match self.resolver.resolve_value(reference) {
+                    Ok(Value::Braced(parts)) => {
+                        objects.push(parts.into_iter().flatten().collect());
+                        Ok(())
+                    }
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(ValueError::Resolve(e)),
+                }
+            }
+            ElementSet::ContainedSubtype(Type { kind: TypeKind::Reference { ref reference, ref arguments }, .. }) => {
+                let ret = self.resolver.resolve_type(reference, arguments);
+                if ret.is_err() {
+                    return Err(ValueError::Resolve(ret.err().unwrap()));
+                }
+                for constraint in &ret.unwrap().constraints {
+                    if let Constraint::Subtype(ref specs) = *constraint {
+                        let ret = self.objects(specs, objects, depth + 1);
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn print(&self, output: &mut String, value: &schema::Value, ty: &Type, path: &str, indent: usize, depth: usize)
+             -> Result<(), ValueError> {
+        let ret = self.builtin(ty, depth);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let builtin = ret.unwrap();
+
+        match (&builtin.kind, value) {
+            (_, schema::Value::Unknown(bytes)) => output.push_str(&hex(bytes)),
+            (TypeKind::Boolean, &schema::Value::Boolean(b)) => output.push_str(if b { "TRUE" } else { "FALSE" }),
+            (TypeKind::Null, schema::Value::Null) => output.push_str("NULL"),
+            (TypeKind::Integer(numbers), &schema::Value::Integer(v)) => {
+                let ret = self.named_numbers(numbers, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match ret.unwrap().into_iter().find(|&(_, n)| n == v) {
+                    Some((name, _)) => output.push_str(&name),
+                    None => output.push_str(&v.to_string()),
+                }
+            }
+            (TypeKind::Enumerated { root, extensions }, &schema::Value::Enumerated(index)) => {
+                let ret = self.enumeration(root, extensions, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                // An extension value this type doesn't know about has no identifier
+                match ret.unwrap().get(index as usize) {
+                    Some(name) => output.push_str(name),
+                    None => output.push_str(&index.to_string()),
+                }
+            }
+            (TypeKind::BitString(numbers), schema::Value::BitString(bits)) => {
+                let ret = self.named_numbers(numbers, depth);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let named = ret.unwrap();
+                let set: Vec<usize> = (0..bits.get_num_bits()).filter(|&i| bits.is_set(i)).collect();
+                let names: Vec<&str> = set.iter()
+                                          .filter_map(|&i| named.iter().find(|&&(_, n)| n == i as i64))
+                                          .map(|(name, _)| name.as_str())
+                                          .collect();
+                if !named.is_empty() && names.len() == set.len() {
+                    output.push_str(&format!("{{ {} }}", names.join(", ")).replace("{  }", "{}"));
+                } else {
+                    output.push('\'');
+                    output.extend(bits.iter().map(|b| if b { '1' } else { '0' }));
+                    output.push_str("'B");
+                }
+            }
+            (TypeKind::OctetString, schema::Value::OctetString(bytes)) => output.push_str(&hex(bytes)),
+            (TypeKind::String(_), schema::Value::String(s)) | (TypeKind::Time(_), schema::Value::String(s)) => {
+                output.push_str(&format!("\"{}\"", s.replace('"', "\"\"")))
+            }
+            (TypeKind::String(_), schema::Value::Octets(bytes)) => {
+                match String::from_utf8(bytes.clone()) {
+                    Ok(s) => output.push_str(&format!("\"{}\"", s.replace('"', "\"\""))),
+                    Err(_) => output.push_str(&hex(bytes)),
+                }
+            }
+            (TypeKind::ObjectIdentifier, schema::Value::ObjectIdentifier(oid)) => arcs_notation(output, oid.arcs()),
+            (TypeKind::RelativeOid, schema::Value::RelativeOid(oid)) => arcs_notation(output, oid.arcs()),
+            (TypeKind::Real, &schema::Value::Real(v)) => {
+                if v.is_nan() {
+                    output.push_str("NOT-A-NUMBER");
+                } else if v == f64::INFINITY {
+                    output.push_str("PLUS-INFINITY");
+                } else if v == f64::NEG_INFINITY {
+                    output.push_str("MINUS-INFINITY");
+                } else {
+                    output.push_str(&format!("{:?}", v));
+                }
+            }
+            (TypeKind::Sequence(components), schema::Value::Sequence { root, extensions }) |
+            (TypeKind::Set(components), schema::Value::Sequence { root, extensions }) => {
+                if root.len() != components.root.len() {
+                    return Err(ValueError::InvalidValue(path.to_string()));
+                }
+                // The components of an extension addition group are written like the others
+                let mut present: Vec<(&Component, &schema::Value)> = Vec::new();
+                for (component, value) in components.root.iter().zip(root.iter()) {
+                    if let Some(ref value) = *value {
+                        present.push((component, value));
+                    }
+                }
+                let additions = components.extensions.iter().flat_map(|e| e.iter());
+                for (addition, value) in additions.zip(extensions.iter()) {
+                    match (addition, value) {
+                        (_, None) => {}
+                        (ExtensionAddition::Component(component), Some(value)) => present.push((component, value)),
+                        (ExtensionAddition::Group { components, .. },
+                         Some(schema::Value::Sequence { root: group, .. })) if group.len() == components.len() => {
+                            for (component, value) in components.iter().zip(group.iter()) {
+                                if let Some(ref value) = *value {
+                                    present.push((component, value));
+                                }
+                            }
+                        }
+                        _ => return Err(ValueError::InvalidValue(path.to_string())),
+                    }
+                }
+                if present.is_empty() {
+                    output.push_str("{}");
+                    return Ok(());
+                }
+
+                output.push_str("{\n");
+                let mut siblings: Vec<(String, Type, schema::Value)> = Vec::new();
+                for (i, &(component, value)) in present.iter().enumerate() {
+                    push_indent(output, indent + 1);
+                    output.push_str(&component.name);
+                    output.push(' ');
+                    let ret = self.print_component(output, value, component, &siblings, path, indent + 1, depth);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    siblings.push((component.name.clone(), component.ty.clone(), value.clone()));
+                    output.push_str(if i + 1 < present.len() { ",\n" } else { "\n" });
+                }
+                push_indent(output, indent);
+                output.push('}');
+            }
+            (TypeKind::SequenceOf { element, .. }, schema::Value::SequenceOf(elements)) |
+            (TypeKind::SetOf { element, .. }, schema::Value::SequenceOf(elements)) => {
+                if elements.is_empty() {
+                    output.push_str("{}");
+                    return Ok(());
+                }
+                output.push_str("{\n");
+                for (i, value) in elements.iter().enumerate() {
+                    push_indent(output, indent + 1);
+                    let ret = self.print(output, value, element, &child_path(path, &i.to_string()), indent + 1,
+                                         depth + 1);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    output.push_str(if i + 1 < elements.len() { ",\n" } else { "\n" });
+                }
+                push_indent(output, indent);
+                output.push('}');
+            }
+            (TypeKind::Choice(components), schema::Value::Choice { index, value }) => {
+                let alternative = components.root.iter().chain(extension_components(components)).nth(*index);
+                match alternative {
+                    Some(alternative) => {
+                        output.push_str(&alternative.name);
+                        output.push_str(" : ");
+                        let ret = self.print(output, value, &alternative.ty, &child_path(path, &alternative.name),
+                                             indent, depth + 1);
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                    }
+                    None => return Err(ValueError::InvalidValue(path.to_string())),
+                }
+            }
+            (TypeKind::ClassField { .. }, schema::Value::Octets(bytes)) => output.push_str(&hex(bytes)),
+            _ => return Err(ValueError::InvalidValue(path.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Print the value of a component, which is printed as a value of the type its component relation constraint
+    /// selects if it's an open type with one, and can be decoded as such.
+    #[allow(clippy::too_many_arguments)]
+    fn print_component(&self, output: &mut String, value: &schema::Value, component: &Component,
+                       siblings: &Siblings, path: &str, indent: usize, depth: usize)
+                       -> Result<(), ValueError> {
+        let path = child_path(path, &component.name);
+        if let schema::Value::Octets(ref bytes) = *value {
+            let ret = self.open_type(&component.ty, siblings, depth);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if let Some(actual) = ret.unwrap() {
+                let ret = self.schema_type(&actual, depth + 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                if let Ok(value) = schema::decode(&mut Decoder::new(bytes), &ret.unwrap()) {
+                    return self.print(output, &value, &actual, &path, indent, depth + 1);
+                }
+            }
+        }
+        self.print(output, value, &component.ty, &path, indent, depth + 1)
+    }
+}
+
+/// Match the items of an object's definition from `pos` against the defined syntax of its class, collecting the
+/// settings of its fields.
+fn object_settings(items: &[Value], pos: &mut usize, syntax: &[SyntaxToken], settings: &mut Vec<(String, Value)>)
+                   -> bool {
+    for token in syntax {
+        match *token {
+            SyntaxToken::Word(ref word) => {
+                match items.get(*pos) {
+                    Some(Value::Reference(reference)) if reference.name == *word => *pos += 1,
+                    _ => return false,
+                }
+            }
+            SyntaxToken::Comma => {}
+            SyntaxToken::Field(ref field) => {
+                match items.get(*pos) {
+                    Some(value) => {
+                        settings.push((field.clone(), value.clone()));
+                        *pos += 1;
+                    }
+                    None => return false,
+                }
+            }
+            SyntaxToken::Optional(ref group) => {
+                let (start, len) = (*pos, settings.len());
+                if !object_settings(items, pos, group, settings) {
+                    *pos = start;
+                    settings.truncate(len);
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Get the components of the extension additions, including those in groups.
+fn extension_components(components: &ComponentList) -> Vec<&Component> {
+    let mut list = Vec::new();
+    for addition in components.extensions.iter().flat_map(|e| e.iter()) {
+        match *addition {
+            ExtensionAddition::Component(ref component) => list.push(component),
+            ExtensionAddition::Group { ref components, .. } => list.extend(components.iter()),
+        }
+    }
+    list
+}
+
+fn is_known_multiplier(kind: StringKind) -> bool {
+    matches!(kind,
+             StringKind::BMPString | StringKind::IA5String | StringKind::ISO646String | StringKind::NumericString |
+             StringKind::PrintableString | StringKind::UniversalString | StringKind::VisibleString)
+}
+
+fn well_known_arc(name: &str) -> Option<i64> {
+    match name {
+        "itu-t" | "ccitt" => Some(0),
+        "iso" => Some(1),
+        "joint-iso-itu-t" | "joint-iso-ccitt" => Some(2),
+        _ => None,
+    }
+}
+
+/// Get the bits of a binary (`digit_bits` 1) or hexadecimal (`digit_bits` 4) string.
+fn bits(digits: &str, digit_bits: usize) -> BitString {
+    let mut bits = BitString::new();
+    for d in digits.chars() {
+        let v = d.to_digit(16).unwrap_or(0);
+        for i in (0..digit_bits).rev() {
+            bits.push(v >> i & 1 == 1);
+        }
+    }
+    bits
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let digits: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+    format!("'{}'H", digits.concat())
+}
+
+fn arcs_notation(output: &mut String, arcs: &[u64]) {
+    let arcs: Vec<String> = arcs.iter().map(|a| a.to_string()).collect();
+    output.push_str(&format!("{{ {} }}", arcs.join(" ")));
+}
+
+fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn push_indent(output: &mut String, indent: usize) {
+    for _ in 0..indent {
+        output.push_str("  ");
+    }
+}
@@ -1,4 +1,8 @@
 use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError};
+use ber::{self, BerElement, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
 
 impl APerElement for () {
     const CONSTRAINTS: Constraints = Constraints {
@@ -15,3 +19,71 @@ impl APerElement for () {
         Ok(Encoding::new())
     }
 }
+
+impl BerElement for () {
+    const TAG: Tag = Tag::NULL;
+
+    /// Read `()` from the BER encoding of a NULL, which has no contents octets.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_primitive(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if !ret.unwrap().is_empty() {
+            return Err(ber::DecodeError::InvalidLength);
+        }
+        Ok(())
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::primitive(tag, Vec::new()))
+    }
+}
+
+impl OerElement for () {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read `()` from an OER encoding, which is empty.
+    fn from_oer(_: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        Ok(())
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        Ok(oer::Encoding::new())
+    }
+}
+
+impl XerElement for () {
+    const NAME: &'static str = "NULL";
+
+    /// Read `()` from an XER encoding, which is an empty element.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        decoder.finish()
+    }
+
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        Ok(xer::Encoding::new())
+    }
+}
+
+impl JerElement for () {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read `()` from a JER encoding, which is `null`.
+    fn from_jer(value: &jer::Value, _: Constraints) -> Result<Self, jer::DecodeError> {
+        match *value {
+            jer::Value::Null => Ok(()),
+            _ => Err(jer::DecodeError::UnexpectedType),
+        }
+    }
+
+    fn to_jer(&self, _: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        Ok(jer::Value::Null)
+    }
+}
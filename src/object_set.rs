@@ -0,0 +1,344 @@
+use aper::{APerElement, Constraint, Constraints, Decoder, DecodeError, Encoding, EncodeError, UNCONSTRAINED, encode_int,
+           encode_open_type};
+
+/// The ASN.1 `Criticality ::= ENUMERATED { reject, ignore, notify }` of the 3GPP application protocols, telling the
+/// receiver what to do with an IE or procedure it doesn't understand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Criticality {
+    Reject,
+    Ignore,
+    Notify,
+}
+
+impl APerElement for Criticality {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `Criticality` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = decoder.decode_int(Some(0), Some(2));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ret.unwrap() {
+            0 => Ok(Criticality::Reject),
+            1 => Ok(Criticality::Ignore),
+            2 => Ok(Criticality::Notify),
+            _ => Err(DecodeError::InvalidChoice),
+        }
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        encode_int(*self as i64, Some(0), Some(2))
+    }
+}
+
+/// An object of an information object set: its id and the criticality it's defined with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectSetEntry {
+    pub id: i64,
+    pub criticality: Criticality,
+}
+
+/// Trait for the values of the open type field of an information object set, e.g. the `&Value` of the IEs in
+/// `S1AP-PROTOCOL-IES`, usually implemented by an enum declared with [object_set!](macro.object_set.html).
+pub trait ObjectSet: Sized {
+    /// The limits on the id field, e.g. `ProtocolIE-ID ::= INTEGER (0..65535)`.
+    const ID_RANGE: Constraint;
+
+    /// The objects of the set.
+    const OBJECTS: &'static [ObjectSetEntry];
+
+    /// Get the id of the object the value belongs to.
+    fn id(&self) -> i64;
+
+    /// Decode the value of the object identified by `id` from the contents of an open type. Returns `None` if there
+    /// is no such object in the set.
+    fn decode_value(id: i64, decoder: &mut Decoder) -> Option<Result<Self, DecodeError>>;
+
+    /// Encode the value, not including the open type length.
+    fn encode_value(&self) -> Result<Encoding, EncodeError>;
+
+    /// Get the criticality of the object identified by `id`, if there is one.
+    fn criticality(id: i64) -> Option<Criticality> {
+        Self::OBJECTS.iter().find(|o| o.id == id).map(|o| o.criticality)
+    }
+}
+
+/// The value of an open type constrained by an information object set. Values with ids that aren't in the set are
+/// kept as the raw octets of the open type so they can be passed through unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpenValue<S: ObjectSet> {
+    Known(S),
+    Unknown(Vec<u8>),
+}
+
+/// A `SEQUENCE { id, criticality, value }` whose value is an open type constrained by an information object set, as
+/// in `ProtocolIE-Field`, `ProtocolExtensionField` and the `InitiatingMessage`, `SuccessfulOutcome` and
+/// `UnsuccessfulOutcome` of elementary procedures.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate asn1;
+/// use asn1::{Criticality, OpenValue, ProtocolField};
+/// use asn1::aper::{APerElement, UNCONSTRAINED};
+///
+/// object_set! {
+///     pub enum ProtocolIes: 0..65535 {
+///         MmeUeS1apId(u32) = 0 => Reject,
+///         EnbUeS1apId(u16) = 8 => Ignore,
+///     }
+/// }
+///
+/// let field = ProtocolField::new(ProtocolIes::EnbUeS1apId(42));
+/// println!("{:?}", field.to_aper(UNCONSTRAINED).unwrap().bytes()); // Prints [0, 8, 64, 2, 0, 42]
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolField<S: ObjectSet> {
+    pub id: i64,
+    pub criticality: Criticality,
+    pub value: OpenValue<S>,
+}
+
+impl<S: ObjectSet> ProtocolField<S> {
+    /// Construct a `ProtocolField` with the id and criticality of the object `value` belongs to.
+    pub fn new(value: S) -> ProtocolField<S> {
+        let id = value.id();
+        ProtocolField {
+            id,
+            criticality: S::criticality(id).unwrap_or(Criticality::Reject),
+            value: OpenValue::Known(value),
+        }
+    }
+
+    /// Get the value if its id is in the set.
+    pub fn known(&self) -> Option<&S> {
+        match self.value {
+            OpenValue::Known(ref v) => Some(v),
+            OpenValue::Unknown(_) => None,
+        }
+    }
+}
+
+impl<S: ObjectSet> APerElement for ProtocolField<S> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `ProtocolField` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let id = decoder.decode_int(S::ID_RANGE.min(), S::ID_RANGE.max());
+        if id.is_err() {
+            return Err(id.err().unwrap());
+        }
+        let id = id.unwrap();
+        let criticality = Criticality::from_aper(decoder, UNCONSTRAINED);
+        if criticality.is_err() {
+            return Err(criticality.err().unwrap());
+        }
+        let bytes = decoder.decode_open_type_bytes();
+        if bytes.is_err() {
+            return Err(bytes.err().unwrap());
+        }
+        let bytes = bytes.unwrap();
+
+        let value = match S::decode_value(id, &mut decoder.sub_decoder(&bytes)) {
+            Some(Ok(v)) => OpenValue::Known(v),
+            Some(Err(e)) => return Err(e),
+            None => OpenValue::Unknown(bytes),
+        };
+        Ok(ProtocolField {
+            id,
+            criticality: criticality.unwrap(),
+            value,
+        })
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        let ret = encode_int(self.id, S::ID_RANGE.min(), S::ID_RANGE.max());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut enc = ret.unwrap();
+        let ret = self.criticality.to_aper(UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = enc.append(&ret.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let inner = match self.value {
+            OpenValue::Known(ref v) => v.encode_value(),
+            OpenValue::Unknown(ref bytes) => Ok(Encoding::with_bytes(bytes.clone())),
+        };
+        if inner.is_err() {
+            return Err(inner.err().unwrap());
+        }
+        let ret = encode_open_type(&inner.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = enc.append(&ret.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(enc)
+    }
+}
+
+/// A `SEQUENCE (SIZE(lb..ub)) OF ProtocolField`, as in `ProtocolIE-Container` and `ProtocolExtensionContainer`. The
+/// size constraint is required when encoding and decoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolContainer<S: ObjectSet> {
+    pub fields: Vec<ProtocolField<S>>,
+}
+
+impl<S: ObjectSet> ProtocolContainer<S> {
+    /// Construct a `ProtocolContainer` with `fields`.
+    pub fn new(fields: Vec<ProtocolField<S>>) -> ProtocolContainer<S> {
+        ProtocolContainer { fields }
+    }
+
+    /// Get the first field with `id`.
+    pub fn get(&self, id: i64) -> Option<&ProtocolField<S>> {
+        self.fields.iter().find(|f| f.id == id)
+    }
+}
+
+impl<S: ObjectSet> APerElement for ProtocolContainer<S> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `ProtocolContainer` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let (lb, ub) = match constraints.size.map(|sz_constr| sz_constr.size_bounds()) {
+            Some((lb, Some(ub))) => (lb, ub),
+            _ => return Err(DecodeError::MissingSizeConstraint),
+        };
+        let ret = decoder.decode_int(Some(lb), Some(ub));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let mut fields = Vec::new();
+        for _ in 0..ret.unwrap() {
+            let ret = ProtocolField::from_aper(decoder, UNCONSTRAINED);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            fields.push(ret.unwrap());
+        }
+        Ok(ProtocolContainer { fields })
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        let (lb, ub) = match constraints.size.map(|sz_constr| sz_constr.size_bounds()) {
+            Some((lb, Some(ub))) => (lb, ub),
+            _ => return Err(EncodeError::MissingSizeConstraint),
+        };
+        let n = self.fields.len() as i64;
+        if n < lb || n > ub {
+            return Err(EncodeError::InvalidSize);
+        }
+
+        let ret = encode_int(n, Some(lb), Some(ub));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut enc = ret.unwrap();
+        for field in &self.fields {
+            let ret = field.to_aper(UNCONSTRAINED);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = enc.append(&ret.unwrap());
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(enc)
+    }
+}
+
+/// Declare an enum for the values of an information object set, with one variant per object, and its
+/// [ObjectSet](trait.ObjectSet.html) implementation. Each object gives the type of its value, its id and its
+/// criticality; the range of the id field follows the name of the enum. Values are encoded with the `CONSTRAINTS` of
+/// their type, unless other constraints follow the type.
+///
+/// # Examples
+///
+/// ```
+/// // S1AP-PROTOCOL-IES S1AP-PROTOCOL-IES ::= {
+/// //     { ID id-MME-UE-S1AP-ID CRITICALITY reject TYPE MME-UE-S1AP-ID PRESENCE mandatory } |
+/// //     { ID id-eNB-UE-S1AP-ID CRITICALITY ignore TYPE ENB-UE-S1AP-ID PRESENCE mandatory },
+/// //     ...
+/// // }
+/// object_set! {
+///     pub enum ProtocolIes: 0..65535 {
+///         MmeUeS1apId(u32) = 0 => Reject,
+///         EnbUeS1apId(u16) = 8 => Ignore,
+///         NasPdu(Vec<u8>, NAS_PDU) = 26 => Reject,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! object_set {
+    (@constraints $t:ty) => {
+        <$t as $crate::aper::APerElement>::CONSTRAINTS
+    };
+    (@constraints $t:ty, $constraints:expr) => {
+        $constraints
+    };
+    ($(#[$attr:meta])* pub enum $name:ident: $min:tt..$max:tt {
+        $($variant:ident($t:ty $(, $constraints:expr)*) = $id:expr => $crit:ident),* $(,)*
+    }) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum $name {
+            $($variant($t)),*
+        }
+
+        impl $crate::ObjectSet for $name {
+            const ID_RANGE: $crate::aper::Constraint = $crate::aper::Constraint::new(Some($min), Some($max));
+
+            const OBJECTS: &'static [$crate::ObjectSetEntry] = &[
+                $($crate::ObjectSetEntry { id: $id, criticality: $crate::Criticality::$crit }),*
+            ];
+
+            fn id(&self) -> i64 {
+                match *self {
+                    $($name::$variant(_) => $id),*
+                }
+            }
+
+            fn decode_value(id: i64, decoder: &mut $crate::aper::Decoder)
+                            -> Option<Result<$name, $crate::aper::DecodeError>> {
+                $(if id == $id {
+                    let constraints = $crate::object_set!(@constraints $t $(, $constraints)*);
+                    return Some(match <$t as $crate::aper::APerElement>::from_aper(decoder, constraints) {
+                        Ok(v) => Ok($name::$variant(v)),
+                        Err(e) => Err(e),
+                    });
+                })*
+                None
+            }
+
+            fn encode_value(&self) -> Result<$crate::aper::Encoding, $crate::aper::EncodeError> {
+                match *self {
+                    $($name::$variant(ref v) => {
+                        $crate::aper::APerElement::to_aper(v, $crate::object_set!(@constraints $t $(, $constraints)*))
+                    }),*
+                }
+            }
+        }
+    };
+}
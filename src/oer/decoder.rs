@@ -0,0 +1,429 @@
+use super::*;
+use super::encoding::int_width;
+use ber::{Class, Tag};
+use bit_string::BitString;
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    DefaultValueEncoded,
+    InvalidBool,
+    InvalidCharacter,
+    InvalidLength,
+    InvalidOid,
+    InvalidReal,
+    InvalidSize,
+    InvalidTime,
+    InvalidUnusedBits,
+    InvalidUtf8,
+    MalformedInt,
+    MalformedLength,
+    MalformedTag,
+    NonCanonicalReal,
+    NonCanonicalTime,
+    NonMinimalInt,
+    NonMinimalLength,
+    NonZeroPadding,
+    NonZeroUnusedBits,
+    NotEnoughBytes,
+    TrailingData,
+    TrailingZeroBits,
+}
+
+/// A cursor used to decode OER messages.
+///
+/// A `Decoder` accepts anything basic OER allows unless it is set to check the input against the canonical OER
+/// (COER) restrictions (see [set_canonical()](#method.set_canonical)), in which case an encoding that isn't canonical
+/// is an `Err` naming the problem.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::oer::{self, OerElement, UNCONSTRAINED};
+///
+/// let data = b"\x00\x2b"; // 43
+/// let mut d = oer::Decoder::new(data);
+/// let x = i16::from_oer(&mut d, UNCONSTRAINED).unwrap();
+/// println!("x = {}", x); // Prints x = 43
+/// ```
+pub struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    canonical: bool,
+    lossless: bool,
+}
+
+impl<'a> Decoder<'a> {
+    /// Construct a new `Decoder` with an array of bytes.
+    pub fn new(data: &'a [u8]) -> Decoder<'a> {
+        Decoder {
+            data,
+            pos: 0,
+            canonical: false,
+            lossless: false,
+        }
+    }
+
+    /// Construct a `Decoder` for the contents of a nested encoding (e.g. an open type) found by this one, with the
+    /// same settings.
+    pub fn sub_decoder<'b>(&self, data: &'b [u8]) -> Decoder<'b> {
+        let mut decoder = Decoder::new(data);
+        decoder.canonical = self.canonical;
+        decoder.lossless = self.lossless;
+        decoder
+    }
+
+    /// Enable or disable the checks of the canonical OER restrictions.
+    pub fn set_canonical(&mut self, canonical: bool) {
+        self.canonical = canonical;
+    }
+
+    /// Check if the input is checked against the canonical OER restrictions.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Enable or disable lossless mode, in which the extension additions a type doesn't know about are kept (see
+    /// [aper::Decoder::set_lossless](../aper/struct.Decoder.html#method.set_lossless)).
+    pub fn set_lossless(&mut self, lossless: bool) {
+        self.lossless = lossless;
+    }
+
+    /// Check if lossless mode is enabled.
+    pub fn is_lossless(&self) -> bool {
+        self.lossless
+    }
+
+    /// Get the number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Check if all of the bytes have been read.
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    /// Returns an `Err` if there are bytes left to read, e.g. after the value of an open type.
+    pub fn finish(&self) -> Result<(), DecodeError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::TrailingData)
+        }
+    }
+
+    /// Read a byte.
+    pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        if self.pos >= self.data.len() {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        self.pos += 1;
+        Ok(self.data[self.pos - 1])
+    }
+
+    /// Read `n` bytes.
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if n > self.data.len() - self.pos {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        self.pos += n;
+        Ok(&self.data[self.pos - n..self.pos])
+    }
+
+    // Read `n` bytes as a big-endian unsigned number that fits in a `u64`
+    fn read_uint(&mut self, n: usize) -> Result<u64, DecodeError> {
+        let ret = self.read_bytes(n);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(ret.unwrap().iter().fold(0, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    /// Decode a length determinant. The length is checked against the number of bytes left.
+    pub fn decode_length(&mut self) -> Result<usize, DecodeError> {
+        let ret = self.read_u8();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let b = ret.unwrap();
+        let len = if b < 0x80 {
+            b as usize
+        } else {
+            let n = (b & 0x7f) as usize;
+            if n == 0 || n > 8 {
+                return Err(DecodeError::MalformedLength);
+            }
+            let ret = self.read_uint(n);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let len = ret.unwrap();
+            // The short form must be used if possible, and the long form can't have a leading zero octet
+            if self.canonical && (len < 0x80 || (64 - len.leading_zeros() as usize).div_ceil(8) != n) {
+                return Err(DecodeError::NonMinimalLength);
+            }
+            if len > usize::MAX as u64 {
+                return Err(DecodeError::MalformedLength);
+            }
+            len as usize
+        };
+        if len > self.data.len() - self.pos {
+            return Err(DecodeError::NotEnoughBytes);
+        }
+        Ok(len)
+    }
+
+    /// Decode the quantity field of a SEQUENCE OF or SET OF value, i.e. the number of elements.
+    pub fn decode_quantity(&mut self) -> Result<usize, DecodeError> {
+        let ret = self.read_u8();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let n = ret.unwrap() as usize;
+        if n == 0 || n > 8 {
+            return Err(DecodeError::MalformedLength);
+        }
+        let ret = self.read_uint(n);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let quantity = ret.unwrap();
+        if self.canonical && n > 1 && (64 - quantity.leading_zeros() as usize).div_ceil(8) != n {
+            return Err(DecodeError::NonMinimalLength);
+        }
+        if quantity > usize::MAX as u64 {
+            return Err(DecodeError::MalformedLength);
+        }
+        Ok(quantity as usize)
+    }
+
+    /// Decode an OER integer between `min` and `max` (see [encode_int](fn.encode_int.html)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\x01\xf5\x02\x01\xf5";
+    /// let mut d = oer::Decoder::new(data);
+    /// let x = d.decode_int(Some(0), Some(1000)).unwrap();
+    /// let y = d.decode_int(Some(0), None).unwrap();
+    /// println!("x = {}, y = {}", x, y); // Prints x = 501, y = 501
+    /// ```
+    pub fn decode_int(&mut self, min: Option<i64>, max: Option<i64>) -> Result<i64, DecodeError> {
+        let unsigned = min.is_some_and(|l| l >= 0);
+        let ret = match int_width(min, max) {
+            Some(n) => {
+                let ret = self.read_bytes(n);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                self.read_int_contents(ret.unwrap(), unsigned, false)
+            }
+            None => {
+                let ret = self.decode_length();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let ret = self.read_bytes(ret.unwrap());
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                self.read_int_contents(ret.unwrap(), unsigned, true)
+            }
+        };
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        if min.is_some_and(|l| value < l) || max.is_some_and(|h| value > h) {
+            return Err(DecodeError::MalformedInt);
+        }
+        Ok(value)
+    }
+
+    // Read the octets of an integer, which must be in as few octets as possible with COER if it has a length
+    // determinant
+    fn read_int_contents(&self, bytes: &[u8], unsigned: bool, has_length: bool) -> Result<i64, DecodeError> {
+        if bytes.is_empty() || bytes.len() > 8 {
+            return Err(DecodeError::MalformedInt);
+        }
+        if self.canonical && has_length && bytes.len() > 1 &&
+           if unsigned {
+            bytes[0] == 0x00
+        } else {
+            bytes[0] == 0x00 && bytes[1] < 0x80 || bytes[0] == 0xff && bytes[1] >= 0x80
+        } {
+            return Err(DecodeError::NonMinimalInt);
+        }
+
+        if unsigned {
+            let value = bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+            if value > i64::MAX as u64 {
+                return Err(DecodeError::MalformedInt);
+            }
+            Ok(value as i64)
+        } else {
+            let first = if bytes[0] >= 0x80 { -1i64 } else { 0 };
+            Ok(bytes.iter().fold(first, |acc, &b| (acc << 8) | b as i64))
+        }
+    }
+
+    /// Decode the value of an ENUMERATED type (see [encode_enumerated](fn.encode_enumerated.html)).
+    pub fn decode_enumerated(&mut self) -> Result<i64, DecodeError> {
+        let ret = self.read_u8();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let b = ret.unwrap();
+        if b < 0x80 {
+            return Ok(b as i64);
+        }
+
+        let ret = self.read_bytes((b & 0x7f) as usize);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = self.read_int_contents(ret.unwrap(), false, true);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        if self.canonical && (0..0x80).contains(&value) {
+            return Err(DecodeError::NonMinimalInt);
+        }
+        Ok(value)
+    }
+
+    /// Decode the presence bitmap at the start of a SEQUENCE value, which has `n` bits (see
+    /// [encode_preamble](fn.encode_preamble.html)).
+    pub fn decode_preamble(&mut self, n: usize) -> Result<BitString, DecodeError> {
+        let ret = self.read_bytes(n.div_ceil(8));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let bytes = ret.unwrap();
+        if self.canonical && !n.is_multiple_of(8) && bytes[bytes.len() - 1] & (0xff >> (n % 8)) != 0 {
+            return Err(DecodeError::NonZeroPadding);
+        }
+        Ok(BitString::with_bytes_and_len(bytes, n))
+    }
+
+    /// Decode the tag that identifies the alternative of a CHOICE value (see [encode_tag](fn.encode_tag.html)).
+    pub fn decode_tag(&mut self) -> Result<Tag, DecodeError> {
+        let ret = self.read_u8();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let b = ret.unwrap();
+        let class = match b >> 6 {
+            0 => Class::Universal,
+            1 => Class::Application,
+            2 => Class::ContextSpecific,
+            _ => Class::Private,
+        };
+        if b & 0x3f != 0x3f {
+            return Ok(Tag::new(class, (b & 0x3f) as u32));
+        }
+
+        // Base 128, most significant group first, without leading zero groups
+        let mut number: u32 = 0;
+        loop {
+            let ret = self.read_u8();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let b = ret.unwrap();
+            if number == 0 && b == 0x80 || number > u32::MAX >> 7 {
+                return Err(DecodeError::MalformedTag);
+            }
+            number = (number << 7) | (b & 0x7f) as u32;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        if number < 0x3f {
+            return Err(DecodeError::MalformedTag);
+        }
+        Ok(Tag::new(class, number))
+    }
+
+    /// Decode an OCTET STRING with a size constraint (see [encode_string](fn.encode_string.html)).
+    pub fn decode_string(&mut self, size: Option<Constraint>) -> Result<Vec<u8>, DecodeError> {
+        let len = match size.and_then(|sz_constr| sz_constr.fixed_size()) {
+            Some(n) => n as usize,
+            None => {
+                let ret = self.decode_length();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            }
+        };
+        let ret = self.read_bytes(len);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if size.is_some_and(|sz_constr| !sz_constr.allows_size(len)) {
+            return Err(DecodeError::InvalidSize);
+        }
+        Ok(ret.unwrap().to_vec())
+    }
+
+    /// Decode the octets of an open type value.
+    pub fn decode_open_type_bytes(&mut self) -> Result<Vec<u8>, DecodeError> {
+        self.decode_string(None)
+    }
+
+    /// Decode an open type value as a `T`, using a decoder bounded to the octets of the value. Unlike with aligned
+    /// PER, the value must use all of the octets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\x02\x00\x2b";
+    /// let mut d = oer::Decoder::new(data);
+    /// println!("{}", d.decode_open_type::<i16>(UNCONSTRAINED).unwrap()); // Prints 43
+    /// ```
+    pub fn decode_open_type<T: OerElement>(&mut self, constraints: Constraints) -> Result<T, DecodeError> {
+        let ret = self.decode_length();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = self.read_bytes(ret.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut decoder = self.sub_decoder(ret.unwrap());
+        let ret = T::from_oer(&mut decoder, constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        let ret = decoder.finish();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(value)
+    }
+
+    /// Decode a component with a DEFAULT value, given its bit in the presence bitmap. Returns `default` if it's
+    /// absent. With COER, an encoded value equal to the default is an `Err`.
+    pub fn decode_default<T: OerElement + PartialEq>(&mut self,
+                                                      present: bool,
+                                                      default: T,
+                                                      constraints: Constraints)
+                                                      -> Result<T, DecodeError> {
+        if !present {
+            return Ok(default);
+        }
+        let ret = T::from_oer(self, constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        if self.canonical && value == default {
+            return Err(DecodeError::DefaultValueEncoded);
+        }
+        Ok(value)
+    }
+}
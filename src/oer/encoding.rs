@@ -0,0 +1,228 @@
+use super::*;
+use ber::{Class, Tag};
+use bit_string::BitString;
+
+#[derive(Debug, PartialEq)]
+pub enum EncodeError {
+    InvalidSize,
+    InvalidValue,
+}
+
+/// A wrapper for an OER encoding, which is always a whole number of octets.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::oer::{Encoding, OerElement, UNCONSTRAINED};
+///
+/// let mut enc = Encoding::new();
+/// enc.append(&true.to_oer(UNCONSTRAINED).unwrap());
+/// enc.append(&500u16.to_oer(UNCONSTRAINED).unwrap());
+/// println!("enc = {:x?}", enc.bytes()); // Prints enc = [ff, 1, f4]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Encoding {
+    bytes: Vec<u8>,
+}
+
+impl Encoding {
+    /// Construct a new, empty `Encoding`.
+    pub fn new() -> Encoding {
+        Encoding { bytes: Vec::new() }
+    }
+
+    /// Construct a new `Encoding` with `bytes`.
+    pub fn with_bytes(bytes: Vec<u8>) -> Encoding {
+        Encoding { bytes }
+    }
+
+    /// Append `other` to the end of `self`.
+    pub fn append(&mut self, other: &Encoding) {
+        self.bytes.extend_from_slice(&other.bytes);
+    }
+
+    /// Get the bytes of the encoding.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Take the bytes of the encoding.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Encode a length determinant: a single octet if `len` is less than 128, otherwise `0x80` plus the number of
+/// octets of `len` followed by those octets.
+pub fn encode_length(len: usize) -> Encoding {
+    if len < 0x80 {
+        return Encoding::with_bytes(vec![len as u8]);
+    }
+    let bytes = len.to_be_bytes();
+    let start = len.leading_zeros() as usize / 8;
+    let mut enc = vec![0x80 | (bytes.len() - start) as u8];
+    enc.extend_from_slice(&bytes[start..]);
+    Encoding::with_bytes(enc)
+}
+
+/// Encode the quantity field of a SEQUENCE OF or SET OF value: the number of octets of `n`, then those octets.
+pub fn encode_quantity(n: usize) -> Encoding {
+    let bytes = n.to_be_bytes();
+    let start = (n.leading_zeros() as usize / 8).min(bytes.len() - 1);
+    let mut enc = vec![(bytes.len() - start) as u8];
+    enc.extend_from_slice(&bytes[start..]);
+    Encoding::with_bytes(enc)
+}
+
+/// Encode an OER integer between `min` and `max`.
+///
+/// If both bounds are known and fit in the range of a 1, 2, 4 or 8-octet integer (unsigned if `min` is not
+/// negative), the value is encoded in that many octets. Otherwise it is encoded in as few octets as possible, preceded
+/// by a length determinant: unsigned if `min` is not negative, two's complement otherwise.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::oer::encode_int;
+///
+/// println!("{:?}", encode_int(501, Some(0), Some(1000)).unwrap().bytes()); // Prints [1, 245]
+/// println!("{:?}", encode_int(501, Some(0), None).unwrap().bytes()); // Prints [2, 1, 245]
+/// println!("{:?}", encode_int(-1, None, None).unwrap().bytes()); // Prints [1, 255]
+/// ```
+pub fn encode_int(value: i64, min: Option<i64>, max: Option<i64>) -> Result<Encoding, EncodeError> {
+    if min.is_some_and(|l| value < l) || max.is_some_and(|h| value > h) {
+        return Err(EncodeError::InvalidValue);
+    }
+    let bytes = value.to_be_bytes();
+    if let Some(n) = int_width(min, max) {
+        return Ok(Encoding::with_bytes(bytes[8 - n..].to_vec()));
+    }
+
+    let unsigned = min.is_some_and(|l| l >= 0);
+    let mut start = 0;
+    while start < 7 && if unsigned {
+        bytes[start] == 0x00
+    } else {
+        bytes[start] == 0x00 && bytes[start + 1] < 0x80 || bytes[start] == 0xff && bytes[start + 1] >= 0x80
+    } {
+        start += 1;
+    }
+    let mut enc = encode_length(8 - start);
+    enc.append(&Encoding::with_bytes(bytes[start..].to_vec()));
+    Ok(enc)
+}
+
+/// Get the number of octets of an integer between `min` and `max` if it has a fixed size.
+pub(crate) fn int_width(min: Option<i64>, max: Option<i64>) -> Option<usize> {
+    let (l, h) = match (min, max) {
+        (Some(l), Some(h)) => (l, h),
+        _ => return None,
+    };
+    if l >= 0 {
+        if h <= u8::MAX as i64 {
+            Some(1)
+        } else if h <= u16::MAX as i64 {
+            Some(2)
+        } else if h <= u32::MAX as i64 {
+            Some(4)
+        } else {
+            Some(8)
+        }
+    } else if l >= i8::MIN as i64 && h <= i8::MAX as i64 {
+        Some(1)
+    } else if l >= i16::MIN as i64 && h <= i16::MAX as i64 {
+        Some(2)
+    } else if l >= i32::MIN as i64 && h <= i32::MAX as i64 {
+        Some(4)
+    } else {
+        Some(8)
+    }
+}
+
+/// Encode the value of an ENUMERATED type: a single octet if it's between 0 and 127, otherwise `0x80` plus the
+/// number of octets of the value followed by the value in two's complement form.
+pub fn encode_enumerated(value: i64) -> Encoding {
+    if (0..0x80).contains(&value) {
+        return Encoding::with_bytes(vec![value as u8]);
+    }
+    let mut enc = encode_int(value, None, None).unwrap().into_bytes();
+    enc[0] |= 0x80;
+    Encoding::with_bytes(enc)
+}
+
+/// Encode the presence bitmap at the start of a SEQUENCE value (the extension bit, if the type is extensible, then
+/// one bit for each OPTIONAL or DEFAULT component), padded with 0s to a whole number of octets.
+pub fn encode_preamble(bits: &BitString) -> Encoding {
+    Encoding::with_bytes(bits.to_bytes())
+}
+
+/// Encode the tag that identifies the alternative of a CHOICE value: the class in the two most significant bits,
+/// then the number in the other 6 bits if it's less than 63, or in base 128 in the octets that follow otherwise.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::ber::Tag;
+/// use asn1::oer::encode_tag;
+///
+/// println!("{:x?}", encode_tag(Tag::context(1)).bytes()); // Prints [81]
+/// println!("{:x?}", encode_tag(Tag::context(100)).bytes()); // Prints [bf, 64]
+/// ```
+pub fn encode_tag(tag: Tag) -> Encoding {
+    let class = match tag.class {
+        Class::Universal => 0x00,
+        Class::Application => 0x40,
+        Class::ContextSpecific => 0x80,
+        Class::Private => 0xc0,
+    };
+    if tag.number < 0x3f {
+        return Encoding::with_bytes(vec![class | tag.number as u8]);
+    }
+
+    let mut bytes = vec![class | 0x3f];
+    let n = (32 - tag.number.leading_zeros() as usize).div_ceil(7);
+    for i in (0..n).rev() {
+        let more = if i > 0 { 0x80 } else { 0x00 };
+        bytes.push(more | ((tag.number >> (7 * i)) & 0x7f) as u8);
+    }
+    Encoding::with_bytes(bytes)
+}
+
+/// Encode an OCTET STRING with a size constraint. A string with a fixed size has no length determinant.
+pub fn encode_string(bytes: &[u8], size: Option<Constraint>) -> Result<Encoding, EncodeError> {
+    if size.is_some_and(|sz_constr| !sz_constr.allows_size(bytes.len())) {
+        return Err(EncodeError::InvalidSize);
+    }
+    if size.and_then(|sz_constr| sz_constr.fixed_size()).is_some() {
+        return Ok(Encoding::with_bytes(bytes.to_vec()));
+    }
+    let mut enc = encode_length(bytes.len());
+    enc.append(&Encoding::with_bytes(bytes.to_vec()));
+    Ok(enc)
+}
+
+/// Encode an open type value from its complete encoding `inner`, which is preceded by a length determinant.
+pub fn encode_open_type(inner: &Encoding) -> Encoding {
+    let mut enc = encode_length(inner.bytes().len());
+    enc.append(inner);
+    enc
+}
+
+/// Encode a component with a DEFAULT value. Returns `None` if `value` is the default, in which case it is omitted
+/// and its bit in the presence bitmap is clear, as COER requires.
+pub fn encode_default<T: OerElement + PartialEq>(value: &T,
+                                                 default: &T,
+                                                 constraints: Constraints)
+                                                 -> Result<Option<Encoding>, EncodeError> {
+    if value == default {
+        return Ok(None);
+    }
+    let ret = value.to_oer(constraints);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(Some(ret.unwrap()))
+}
@@ -0,0 +1,133 @@
+mod decoder;
+mod encoding;
+pub use self::decoder::{Decoder, DecodeError};
+pub use self::encoding::{Encoding, EncodeError, encode_default, encode_enumerated, encode_int, encode_length,
+                         encode_open_type, encode_preamble, encode_quantity, encode_string, encode_tag};
+pub use aper::{Constraint, Constraints, UNCONSTRAINED};
+
+/// Trait for OER encoding/decoding.
+///
+/// The constraints are the same PER-visible [Constraints](struct.Constraints.html) as for aligned PER: OER uses the
+/// value constraint of an INTEGER to choose a fixed number of octets, and a size constraint with equal bounds to
+/// leave out the length determinant of a string. The presence bitmap of a SEQUENCE is read with
+/// [Decoder::decode_preamble](struct.Decoder.html#method.decode_preamble), the tag of the alternative of a CHOICE
+/// with [Decoder::decode_tag](struct.Decoder.html#method.decode_tag) and the extension additions of a SEQUENCE with
+/// [ExtensionAdditions](../struct.ExtensionAdditions.html).
+///
+/// # Examples
+///
+/// ```
+/// // Foo ::= SEQUENCE {
+/// //     a INTEGER (0..1000),
+/// //     b BOOLEAN OPTIONAL,
+/// //     ...
+/// // }
+/// extern crate asn1;
+/// use asn1::{BitString, ExtensionAdditions};
+/// use asn1::oer::{self, Constraints, Encoding, OerElement, UNCONSTRAINED};
+///
+/// struct Foo {
+///     a: i64,
+///     b: Option<bool>,
+/// }
+///
+/// impl OerElement for Foo {
+///     const CONSTRAINTS: Constraints = UNCONSTRAINED;
+///
+///     fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+///         let preamble = decoder.decode_preamble(2);
+///         if preamble.is_err() {
+///             return Err(preamble.err().unwrap());
+///         }
+///         let preamble = preamble.unwrap();
+///
+///         let a = decoder.decode_int(Some(0), Some(1000));
+///         if a.is_err() {
+///             return Err(a.err().unwrap());
+///         }
+///         let mut b = None;
+///         if preamble.is_set(1) {
+///             let ret = bool::from_oer(decoder, UNCONSTRAINED);
+///             if ret.is_err() {
+///                 return Err(ret.err().unwrap());
+///             }
+///             b = Some(ret.unwrap());
+///         }
+///         if preamble.is_set(0) {
+///             // Skip the extension additions
+///             let ret = ExtensionAdditions::from_oer(decoder, UNCONSTRAINED);
+///             if ret.is_err() {
+///                 return Err(ret.err().unwrap());
+///             }
+///         }
+///         Ok(Foo { a: a.unwrap(), b })
+///     }
+///
+///     fn to_oer(&self, _: Constraints) -> Result<Encoding, oer::EncodeError> {
+///         let mut enc = oer::encode_preamble(&BitString::from_bits(&[false, self.b.is_some()]));
+///         enc.append(&oer::encode_int(self.a, Some(0), Some(1000)).unwrap());
+///         if let Some(b) = self.b {
+///             enc.append(&b.to_oer(UNCONSTRAINED).unwrap());
+///         }
+///         Ok(enc)
+///     }
+/// }
+/// ```
+pub trait OerElement: Sized {
+    /// PER-visible Constraints
+    const CONSTRAINTS: Constraints;
+
+    /// Read a value with constraints `constraints`.
+    fn from_oer(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError>;
+
+    /// Encode the value, which must satisfy `constraints`.
+    fn to_oer(&self, constraints: Constraints) -> Result<Encoding, EncodeError>;
+}
+
+/// Encode `value` with OER.
+///
+/// The types of the crate encode their values canonically, so the encoding is also valid COER as long as
+/// implementations of `OerElement` for SEQUENCE types omit components equal to their DEFAULT value (see
+/// [encode_default](fn.encode_default.html)).
+pub fn encode<T: OerElement>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    let ret = value.to_oer(T::CONSTRAINTS);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(ret.unwrap().into_bytes())
+}
+
+/// Decode a `T` from `data`, which must be its OER encoding and nothing else.
+pub fn decode<T: OerElement>(data: &[u8]) -> Result<T, DecodeError> {
+    decode_with(Decoder::new(data))
+}
+
+/// Decode a `T` from `data`, which must be its canonical OER (COER) encoding and nothing else.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::oer;
+///
+/// assert_eq!(Ok(true), oer::decode_canonical::<bool>(b"\xff"));
+/// assert_eq!(Err(oer::DecodeError::InvalidBool), oer::decode_canonical::<bool>(b"\x01"));
+/// ```
+pub fn decode_canonical<T: OerElement>(data: &[u8]) -> Result<T, DecodeError> {
+    let mut decoder = Decoder::new(data);
+    decoder.set_canonical(true);
+    decode_with(decoder)
+}
+
+fn decode_with<T: OerElement>(mut decoder: Decoder) -> Result<T, DecodeError> {
+    let ret = T::from_oer(&mut decoder, T::CONSTRAINTS);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let value = ret.unwrap();
+    let ret = decoder.finish();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(value)
+}
@@ -0,0 +1,488 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_fragmented};
+use ber::{self, BerElement, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
+use std::fmt;
+use std::str::FromStr;
+
+/// The reasons an OBJECT IDENTIFIER or RELATIVE-OID value can be invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OidError {
+    /// An arc isn't a non-negative integer that fits in a `u64`.
+    InvalidArc,
+    /// The first arc isn't 0, 1 or 2, or the second arc is 40 or more under 0 or 1.
+    InvalidRootArcs,
+    /// The contents octets end in the middle of an arc, or an arc has leading 0x80 octets.
+    MalformedContents,
+    /// There are too few arcs (2 for an OBJECT IDENTIFIER, 1 for a RELATIVE-OID).
+    TooFewArcs,
+}
+
+fn parse_arcs(s: &str) -> Result<Vec<u64>, OidError> {
+    let mut arcs = Vec::new();
+    for arc in s.split('.') {
+        if arc.is_empty() || !arc.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(OidError::InvalidArc);
+        }
+        match arc.parse::<u64>() {
+            Ok(a) => arcs.push(a),
+            Err(_) => return Err(OidError::InvalidArc),
+        }
+    }
+    Ok(arcs)
+}
+
+// Read the arcs of an OBJECT IDENTIFIER or RELATIVE-OID from an XER encoding, in dotted form. With CXER, the arcs
+// can't have leading zeros.
+fn decode_xer_arcs(decoder: &mut xer::Decoder) -> Result<Vec<u64>, xer::DecodeError> {
+    let ret = decoder.decode_token();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let s = ret.unwrap();
+    let ret = parse_arcs(&s);
+    if ret.is_err() {
+        return Err(xer::DecodeError::InvalidOid);
+    }
+    let arcs = ret.unwrap();
+    if decoder.is_canonical() && s.split('.').any(|arc| arc.len() > 1 && arc.starts_with('0')) {
+        return Err(xer::DecodeError::NonCanonicalOid);
+    }
+    Ok(arcs)
+}
+
+fn fmt_arcs(arcs: &[u64], f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, arc) in arcs.iter().enumerate() {
+        if i > 0 {
+            f.write_str(".")?;
+        }
+        write!(f, "{}", arc)?;
+    }
+    Ok(())
+}
+
+fn write_subidentifier(bytes: &mut Vec<u8>, value: u64) {
+    let mut n = 1;
+    while n < 10 && value >> (7 * n) > 0 {
+        n += 1;
+    }
+    for i in (0..n).rev() {
+        let more = if i > 0 { 0x80 } else { 0x00 };
+        bytes.push(more | ((value >> (7 * i)) & 0x7F) as u8);
+    }
+}
+
+fn read_subidentifiers(bytes: &[u8]) -> Result<Vec<u64>, OidError> {
+    let mut ret = Vec::new();
+    let mut value: u64 = 0;
+    let mut in_arc = false;
+    for &b in bytes {
+        if !in_arc && b == 0x80 {
+            return Err(OidError::MalformedContents);
+        }
+        if value >> 57 > 0 {
+            return Err(OidError::InvalidArc);
+        }
+        value = (value << 7) | (b & 0x7F) as u64;
+        in_arc = b & 0x80 > 0;
+        if !in_arc {
+            ret.push(value);
+            value = 0;
+        }
+    }
+    if in_arc {
+        return Err(OidError::MalformedContents);
+    }
+    Ok(ret)
+}
+
+fn decode_contents(decoder: &mut Decoder) -> Result<Vec<u8>, DecodeError> {
+    let mut content: Vec<u8> = Vec::new();
+    let ret = decoder.decode_fragmented(|d, n| d.read_to_vec(&mut content, n * 8));
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(content)
+}
+
+fn encode_contents(bytes: &[u8]) -> Result<Encoding, EncodeError> {
+    encode_fragmented(bytes.len(), |start, end| Ok(Encoding::with_bytes(bytes[start..end].to_vec())))
+}
+
+/// An ASN.1 OBJECT IDENTIFIER, e.g. `1.2.840.113549`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::ObjectIdentifier;
+///
+/// let rsadsi: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+/// println!("{:?}", rsadsi.arcs()); // Prints [1, 2, 840, 113549]
+/// println!("{}", rsadsi); // Prints 1.2.840.113549
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectIdentifier {
+    arcs: Vec<u64>,
+}
+
+impl ObjectIdentifier {
+    /// Construct an `ObjectIdentifier` from its arcs.
+    /// Returns an `Err` if there are fewer than two arcs or the first two arcs are out of range.
+    pub fn new(arcs: &[u64]) -> Result<ObjectIdentifier, OidError> {
+        if arcs.len() < 2 {
+            return Err(OidError::TooFewArcs);
+        }
+        if arcs[0] > 2 || arcs[0] < 2 && arcs[1] >= 40 || arcs[1] > u64::MAX - 80 {
+            return Err(OidError::InvalidRootArcs);
+        }
+        Ok(ObjectIdentifier { arcs: arcs.to_vec() })
+    }
+
+    /// Get the arcs.
+    pub fn arcs(&self) -> &[u64] {
+        &self.arcs
+    }
+
+    /// Get the contents octets of the BER encoding (X.690 8.19), where the first two arcs share a subidentifier.
+    pub fn to_contents_octets(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_subidentifier(&mut bytes, self.arcs[0] * 40 + self.arcs[1]);
+        for &arc in &self.arcs[2..] {
+            write_subidentifier(&mut bytes, arc);
+        }
+        bytes
+    }
+
+    /// Construct an `ObjectIdentifier` from the contents octets of its BER encoding.
+    pub fn from_contents_octets(bytes: &[u8]) -> Result<ObjectIdentifier, OidError> {
+        let ret = read_subidentifiers(bytes);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let subids = ret.unwrap();
+        if subids.is_empty() {
+            return Err(OidError::TooFewArcs);
+        }
+
+        let first = match subids[0] {
+            x if x < 40 => vec![0, x],
+            x if x < 80 => vec![1, x - 40],
+            x => vec![2, x - 80],
+        };
+        Ok(ObjectIdentifier { arcs: first.into_iter().chain(subids[1..].iter().cloned()).collect() })
+    }
+}
+
+impl FromStr for ObjectIdentifier {
+    type Err = OidError;
+
+    /// Parse an `ObjectIdentifier` in dotted form, e.g. `"1.2.840.113549"`.
+    fn from_str(s: &str) -> Result<ObjectIdentifier, OidError> {
+        let ret = parse_arcs(s);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        ObjectIdentifier::new(&ret.unwrap())
+    }
+}
+
+impl fmt::Display for ObjectIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_arcs(&self.arcs, f)
+    }
+}
+
+impl APerElement for ObjectIdentifier {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read an `ObjectIdentifier` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = decode_contents(decoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ObjectIdentifier::from_contents_octets(&ret.unwrap()) {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        encode_contents(&self.to_contents_octets())
+    }
+}
+
+impl BerElement for ObjectIdentifier {
+    const TAG: Tag = Tag::OBJECT_IDENTIFIER;
+
+    /// Read an `ObjectIdentifier` from a BER encoding.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_primitive(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ObjectIdentifier::from_contents_octets(ret.unwrap()) {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(ber::DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::primitive(tag, self.to_contents_octets()))
+    }
+}
+
+impl OerElement for ObjectIdentifier {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read an `ObjectIdentifier` from an OER encoding, which is its BER contents octets preceded by a length
+    /// determinant.
+    fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = decoder.decode_string(None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ObjectIdentifier::from_contents_octets(&ret.unwrap()) {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(oer::DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        oer::encode_string(&self.to_contents_octets(), None)
+    }
+}
+
+impl JerElement for ObjectIdentifier {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read an `ObjectIdentifier` from a JER encoding, which is a string in dotted form.
+    fn from_jer(value: &jer::Value, _: Constraints) -> Result<Self, jer::DecodeError> {
+        let ret = value.as_str();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ret.unwrap().parse() {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(jer::DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_jer(&self, _: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        Ok(jer::Value::String(self.to_string()))
+    }
+}
+
+impl XerElement for ObjectIdentifier {
+    const NAME: &'static str = "OBJECT_IDENTIFIER";
+
+    /// Read an `ObjectIdentifier` from an XER encoding, which is its dotted form.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        let ret = decode_xer_arcs(decoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ObjectIdentifier::new(&ret.unwrap()) {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(xer::DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        Ok(xer::Encoding::text(&self.to_string()))
+    }
+}
+
+/// An ASN.1 RELATIVE-OID, i.e. the arcs of an OBJECT IDENTIFIER relative to some known prefix.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::{ObjectIdentifier, RelativeOid};
+///
+/// let rsadsi: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+/// let pkcs1: RelativeOid = "1.1".parse().unwrap();
+/// println!("{}", pkcs1.resolve(&rsadsi)); // Prints 1.2.840.113549.1.1
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RelativeOid {
+    arcs: Vec<u64>,
+}
+
+impl RelativeOid {
+    /// Construct a `RelativeOid` from its arcs. Returns an `Err` if there are no arcs.
+    pub fn new(arcs: &[u64]) -> Result<RelativeOid, OidError> {
+        if arcs.is_empty() {
+            return Err(OidError::TooFewArcs);
+        }
+        Ok(RelativeOid { arcs: arcs.to_vec() })
+    }
+
+    /// Get the arcs.
+    pub fn arcs(&self) -> &[u64] {
+        &self.arcs
+    }
+
+    /// Append the arcs to `base`.
+    pub fn resolve(&self, base: &ObjectIdentifier) -> ObjectIdentifier {
+        ObjectIdentifier { arcs: base.arcs.iter().chain(self.arcs.iter()).cloned().collect() }
+    }
+
+    /// Get the contents octets of the BER encoding (X.690 8.20).
+    pub fn to_contents_octets(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for &arc in &self.arcs {
+            write_subidentifier(&mut bytes, arc);
+        }
+        bytes
+    }
+
+    /// Construct a `RelativeOid` from the contents octets of its BER encoding.
+    pub fn from_contents_octets(bytes: &[u8]) -> Result<RelativeOid, OidError> {
+        let ret = read_subidentifiers(bytes);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        RelativeOid::new(&ret.unwrap())
+    }
+}
+
+impl FromStr for RelativeOid {
+    type Err = OidError;
+
+    /// Parse a `RelativeOid` in dotted form, e.g. `"8571.3.2"`.
+    fn from_str(s: &str) -> Result<RelativeOid, OidError> {
+        let ret = parse_arcs(s);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        RelativeOid::new(&ret.unwrap())
+    }
+}
+
+impl fmt::Display for RelativeOid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_arcs(&self.arcs, f)
+    }
+}
+
+impl APerElement for RelativeOid {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `RelativeOid` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = decode_contents(decoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match RelativeOid::from_contents_octets(&ret.unwrap()) {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        encode_contents(&self.to_contents_octets())
+    }
+}
+
+impl BerElement for RelativeOid {
+    const TAG: Tag = Tag::RELATIVE_OID;
+
+    /// Read a `RelativeOid` from a BER encoding.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_primitive(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match RelativeOid::from_contents_octets(ret.unwrap()) {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(ber::DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::primitive(tag, self.to_contents_octets()))
+    }
+}
+
+impl OerElement for RelativeOid {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `RelativeOid` from an OER encoding, which is its BER contents octets preceded by a length determinant.
+    fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = decoder.decode_string(None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match RelativeOid::from_contents_octets(&ret.unwrap()) {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(oer::DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        oer::encode_string(&self.to_contents_octets(), None)
+    }
+}
+
+impl JerElement for RelativeOid {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `RelativeOid` from a JER encoding, which is a string in dotted form.
+    fn from_jer(value: &jer::Value, _: Constraints) -> Result<Self, jer::DecodeError> {
+        let ret = value.as_str();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ret.unwrap().parse() {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(jer::DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_jer(&self, _: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        Ok(jer::Value::String(self.to_string()))
+    }
+}
+
+impl XerElement for RelativeOid {
+    const NAME: &'static str = "RELATIVE_OID";
+
+    /// Read a `RelativeOid` from an XER encoding, which is its dotted form.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        let ret = decode_xer_arcs(decoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match RelativeOid::new(&ret.unwrap()) {
+            Ok(oid) => Ok(oid),
+            Err(_) => Err(xer::DecodeError::InvalidOid),
+        }
+    }
+
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        Ok(xer::Encoding::text(&self.to_string()))
+    }
+}
@@ -0,0 +1,128 @@
+use aper::{Decoder, DecodeError, Encoding, LENGTH_DET_FRAG, LENGTH_DET_LONG, LENGTH_MASK_LONG, LENGTH_MASK_SHORT};
+
+/// Get the number of bits needed to encode `range` different values.
+pub(crate) fn bits_for(range: u128) -> usize {
+    let mut b = 0;
+    while (1u128 << b) < range {
+        b += 1;
+    }
+    b
+}
+
+/// Get the number of octets needed to encode `range` different values, which is at least 1.
+pub(crate) fn octets_for(range: u128) -> usize {
+    let n = bits_for(range).div_ceil(8);
+    if n == 0 { 1 } else { n }
+}
+
+/// Read a constrained whole number from aligned PER if `aligned` is set, and from unaligned PER otherwise. In aligned
+/// PER, it is octet-aligned if the range is larger than 255 and takes a length determinant if the range is larger than
+/// 64K.
+pub(crate) fn read_constrained(decoder: &mut Decoder, lb: i64, ub: i64, aligned: bool) -> Result<i64, DecodeError> {
+    if ub < lb {
+        return Err(DecodeError::MalformedInt);
+    }
+    let range = (ub as i128 - lb as i128 + 1) as u128;
+    let ret = if !aligned || range < 256 {
+        decoder.read_uint(bits_for(range))
+    } else if range <= 65536 {
+        decoder.align();
+        decoder.read_uint(if range == 256 { 8 } else { 16 })
+    } else {
+        let ret = decoder.read_uint(bits_for(octets_for(range) as u128));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let len = ret.unwrap() as usize + 1;
+        if len > octets_for(range) {
+            return Err(DecodeError::MalformedInt);
+        }
+        decoder.align();
+        decoder.read_uint(8 * len)
+    };
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let value = lb as i128 + ret.unwrap() as i128;
+    if value > ub as i128 {
+        return Err(DecodeError::MalformedInt);
+    }
+    Ok(value as i64)
+}
+
+/// Write a constrained whole number in aligned PER if `aligned` is set, and in unaligned PER otherwise. See
+/// `read_constrained`.
+pub(crate) fn write_constrained(output: &mut Encoding, value: i64, lb: i64, ub: i64, aligned: bool) {
+    let range = (ub as i128 - lb as i128 + 1) as u128;
+    let offset = (value as i128 - lb as i128) as u64;
+    if !aligned || range < 256 {
+        output.append_uint(offset, bits_for(range));
+    } else if range <= 65536 {
+        output.align();
+        output.append_uint(offset, if range == 256 { 8 } else { 16 });
+    } else {
+        let len = octets_for(offset as u128 + 1);
+        output.append_uint(len as u64 - 1, bits_for(octets_for(range) as u128));
+        output.align();
+        output.append_uint(offset, 8 * len);
+    }
+}
+
+/// Read an unconstrained length determinant, which is octet-aligned in aligned PER. Returns the length and whether it
+/// is the length of a fragment.
+pub(crate) fn read_length(decoder: &mut Decoder, aligned: bool) -> Result<(usize, bool), DecodeError> {
+    if aligned {
+        decoder.align();
+    }
+    let ret = decoder.read_u8();
+    if ret.is_err() {
+        return Err(DecodeError::MalformedLength);
+    }
+
+    let b = ret.unwrap();
+    if b & LENGTH_DET_FRAG == LENGTH_DET_FRAG {
+        let m = (b & LENGTH_MASK_LONG) as usize;
+        if !(1..=4).contains(&m) {
+            return Err(DecodeError::MalformedLength);
+        }
+        return Ok((m * 16384, true));
+    }
+    if b & LENGTH_DET_LONG > 0 {
+        let ret = decoder.read_u8();
+        if ret.is_err() {
+            return Err(DecodeError::MalformedLength);
+        }
+        // CANONICAL-PER requires the short form for lengths below 128
+        let len = (((b & LENGTH_MASK_LONG) as usize) << 8) + ret.unwrap() as usize;
+        if decoder.is_canonical() && len < 128 {
+            return Err(DecodeError::NonMinimalLength);
+        }
+        return Ok((len, false));
+    }
+    Ok(((b & LENGTH_MASK_SHORT) as usize, false))
+}
+
+/// Write an unconstrained length determinant. See `read_length`.
+pub(crate) fn write_length(output: &mut Encoding, len: usize, fragment: bool, aligned: bool) {
+    if aligned {
+        output.align();
+    }
+    if fragment {
+        output.append_uint((LENGTH_DET_FRAG as usize | (len / 16384)) as u64, 8);
+    } else if len < 128 {
+        output.append_uint(len as u64, 8);
+    } else {
+        output.append_uint((((LENGTH_DET_LONG as usize) << 8) | len) as u64, 16);
+    }
+}
+
+/// Get the length of the next fragment of `left` items: 16K, 32K, 48K or 64K items while there are at least 16K left,
+/// and then all of them. Returns whether it is a fragment.
+pub(crate) fn fragment_length(left: usize) -> (usize, bool) {
+    if left >= 16384 {
+        let m = if left >= 65536 { 4 } else { left / 16384 };
+        (m * 16384, true)
+    } else {
+        (left, false)
+    }
+}
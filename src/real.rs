@@ -0,0 +1,370 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_fragmented};
+use ber::{self, BerElement, Rules, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
+
+const PLUS_INFINITY: u8 = 0x40;
+const MINUS_INFINITY: u8 = 0x41;
+const NOT_A_NUMBER: u8 = 0x42;
+const MINUS_ZERO: u8 = 0x43;
+
+/// The reasons the contents octets of a REAL can be invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RealError {
+    /// A decimal encoding isn't valid ISO 6093 NR1, NR2 or NR3 text.
+    InvalidDecimal,
+    /// The contents octets end too early or use a reserved form.
+    MalformedContents,
+}
+
+/// Get the contents octets of the BER encoding of `value` (X.690 8.5), in the canonical form required by CER and DER
+/// (X.690 11.3.1): base 2, no scaling factor, an odd mantissa and a minimal exponent.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::real;
+///
+/// println!("{:?}", real::to_contents_octets(10.0)); // Prints [128, 1, 5], i.e. 5 * 2^1
+/// ```
+pub fn to_contents_octets(value: f64) -> Vec<u8> {
+    if value.is_nan() {
+        return vec![NOT_A_NUMBER];
+    }
+    if value.is_infinite() {
+        return vec![if value > 0. { PLUS_INFINITY } else { MINUS_INFINITY }];
+    }
+    if value == 0. {
+        return if value.is_sign_negative() { vec![MINUS_ZERO] } else { Vec::new() };
+    }
+
+    let bits = value.to_bits();
+    let biased = ((bits >> 52) & 0x7FF) as i64;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mut mantissa, mut exponent) = if biased == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | (1 << 52), biased - 1075)
+    };
+    while mantissa & 1 == 0 {
+        mantissa >>= 1;
+        exponent += 1;
+    }
+
+    let mut exp_bytes = exponent.to_be_bytes().to_vec();
+    while exp_bytes.len() > 1 && (exp_bytes[0] == 0x00 && exp_bytes[1] & 0x80 == 0 ||
+                                  exp_bytes[0] == 0xFF && exp_bytes[1] & 0x80 > 0) {
+        exp_bytes.remove(0);
+    }
+    let mut man_bytes = mantissa.to_be_bytes().to_vec();
+    while man_bytes[0] == 0 {
+        man_bytes.remove(0);
+    }
+
+    let sign = if value < 0. { 0x40 } else { 0x00 };
+    let mut bytes = vec![0x80 | sign | (exp_bytes.len() - 1) as u8];
+    bytes.append(&mut exp_bytes);
+    bytes.append(&mut man_bytes);
+    bytes
+}
+
+/// Construct an `f64` from the contents octets of the BER encoding of a REAL (X.690 8.5). Binary encodings with any
+/// base and scaling factor and decimal encodings in any of the NR1, NR2 and NR3 forms are accepted. Values that are
+/// too large or too small for an `f64` become infinities or zeros.
+pub fn from_contents_octets(bytes: &[u8]) -> Result<f64, RealError> {
+    if bytes.is_empty() {
+        return Ok(0.);
+    }
+
+    let first = bytes[0];
+    if first & 0x80 > 0 {
+        return from_binary(bytes);
+    }
+    if first & 0x40 > 0 {
+        if bytes.len() > 1 {
+            return Err(RealError::MalformedContents);
+        }
+        return match first {
+            PLUS_INFINITY => Ok(f64::INFINITY),
+            MINUS_INFINITY => Ok(f64::NEG_INFINITY),
+            NOT_A_NUMBER => Ok(f64::NAN),
+            MINUS_ZERO => Ok(-0.),
+            _ => Err(RealError::MalformedContents),
+        };
+    }
+    from_decimal(first & 0x3F, &bytes[1..])
+}
+
+fn from_binary(bytes: &[u8]) -> Result<f64, RealError> {
+    let first = bytes[0];
+    let negative = first & 0x40 > 0;
+    let log2_base = match (first >> 4) & 0x03 {
+        0 => 1,
+        1 => 3,
+        2 => 4,
+        _ => return Err(RealError::MalformedContents),
+    };
+    let scale = ((first >> 2) & 0x03) as i64;
+
+    let (exp_start, exp_len) = match first & 0x03 {
+        3 => {
+            if bytes.len() < 2 || bytes[1] == 0 {
+                return Err(RealError::MalformedContents);
+            }
+            (2, bytes[1] as usize)
+        }
+        n => (1, n as usize + 1),
+    };
+    if bytes.len() < exp_start + exp_len || exp_len > 8 {
+        return Err(RealError::MalformedContents);
+    }
+
+    let mut exponent: i64 = if bytes[exp_start] & 0x80 > 0 { -1 } else { 0 };
+    for &b in &bytes[exp_start..exp_start + exp_len] {
+        exponent = (exponent << 8) | b as i64;
+    }
+
+    let mut mantissa: f64 = 0.;
+    for &b in &bytes[exp_start + exp_len..] {
+        mantissa = mantissa * 256. + b as f64;
+    }
+
+    let power = exponent.saturating_mul(log2_base).saturating_add(scale);
+    let value = scale_by_power_of_two(mantissa, power);
+    Ok(if negative { -value } else { value })
+}
+
+fn scale_by_power_of_two(mut value: f64, mut power: i64) -> f64 {
+    // Scale in steps that can't overflow an f64 on their own
+    while power > 1000 && value.is_finite() && value != 0. {
+        value *= 2f64.powi(1000);
+        power -= 1000;
+    }
+    while power < -1000 && value != 0. {
+        value *= 2f64.powi(-1000);
+        power += 1000;
+    }
+    value * 2f64.powi(power as i32)
+}
+
+fn from_decimal(form: u8, bytes: &[u8]) -> Result<f64, RealError> {
+    let ret = ::std::str::from_utf8(bytes);
+    if ret.is_err() {
+        return Err(RealError::InvalidDecimal);
+    }
+    let text = ret.unwrap().trim_start_matches(' ').replace(',', ".");
+
+    let digits_only = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let unsigned = text.trim_start_matches(['+', '-']);
+    if text.len() - unsigned.len() > 1 {
+        return Err(RealError::InvalidDecimal);
+    }
+    let valid = match form {
+        1 => digits_only(unsigned),
+        2 => {
+            let mut parts = unsigned.splitn(2, '.');
+            let int = parts.next().unwrap();
+            let frac = parts.next();
+            frac.is_some() && (digits_only(int) || int.is_empty()) &&
+            (digits_only(frac.unwrap()) || frac.unwrap().is_empty()) && !(int.is_empty() && frac.unwrap().is_empty())
+        }
+        3 => unsigned.contains(['E', 'e']),
+        _ => return Err(RealError::MalformedContents),
+    };
+    if !valid {
+        return Err(RealError::InvalidDecimal);
+    }
+
+    match text.parse::<f64>() {
+        Ok(value) => Ok(value),
+        Err(_) => Err(RealError::InvalidDecimal),
+    }
+}
+
+/// REAL is encoded as the contents octets of its CER encoding, preceded by an unconstrained length determinant.
+impl APerElement for f64 {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read an `f64` from the aligned PER encoding of a REAL. With CANONICAL-PER, the contents octets must be in the
+    /// canonical form.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let mut content: Vec<u8> = Vec::new();
+        let ret = decoder.decode_fragmented(|d, n| d.read_to_vec(&mut content, n * 8));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = match from_contents_octets(&content) {
+            Ok(value) => value,
+            Err(_) => return Err(DecodeError::InvalidReal),
+        };
+        if decoder.is_canonical() && to_contents_octets(value) != content {
+            return Err(DecodeError::NonCanonicalReal);
+        }
+        Ok(value)
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        let bytes = to_contents_octets(*self);
+        encode_fragmented(bytes.len(), |start, end| Ok(Encoding::with_bytes(bytes[start..end].to_vec())))
+    }
+}
+
+impl BerElement for f64 {
+    const TAG: Tag = Tag::REAL;
+
+    /// Read an `f64` from the BER encoding of a REAL. With DER, the contents octets must be in the canonical form
+    /// (see [to_contents_octets](real/fn.to_contents_octets.html)).
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_primitive(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let contents = ret.unwrap();
+        let value = match from_contents_octets(contents) {
+            Ok(value) => value,
+            Err(_) => return Err(ber::DecodeError::InvalidReal),
+        };
+        if decoder.rules() != Rules::Ber && to_contents_octets(value) != contents {
+            return Err(ber::DecodeError::NonCanonicalReal);
+        }
+        Ok(value)
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::primitive(tag, to_contents_octets(*self)))
+    }
+}
+
+impl OerElement for f64 {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read an `f64` from the OER encoding of a REAL, which is its BER contents octets preceded by a length
+    /// determinant. With COER, the contents octets must be in the canonical form.
+    fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = decoder.decode_string(None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let contents = ret.unwrap();
+        let value = match from_contents_octets(&contents) {
+            Ok(value) => value,
+            Err(_) => return Err(oer::DecodeError::InvalidReal),
+        };
+        if decoder.is_canonical() && to_contents_octets(value) != contents {
+            return Err(oer::DecodeError::NonCanonicalReal);
+        }
+        Ok(value)
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        oer::encode_string(&to_contents_octets(*self), None)
+    }
+}
+
+/// A REAL is encoded as a number, except for -0 and the special values, which are the strings `"-0"`, `"INF"`,
+/// `"-INF"` and `"NaN"`.
+impl JerElement for f64 {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read an `f64` from the JER encoding of a REAL.
+    fn from_jer(value: &jer::Value, _: Constraints) -> Result<Self, jer::DecodeError> {
+        match *value {
+            jer::Value::Number(ref n) => match n.parse() {
+                Ok(value) => Ok(value),
+                Err(_) => Err(jer::DecodeError::InvalidReal),
+            },
+            jer::Value::String(ref s) => match s.as_str() {
+                "-0" => Ok(-0.),
+                "INF" => Ok(f64::INFINITY),
+                "-INF" => Ok(f64::NEG_INFINITY),
+                "NaN" => Ok(f64::NAN),
+                _ => Err(jer::DecodeError::InvalidReal),
+            },
+            _ => Err(jer::DecodeError::UnexpectedType),
+        }
+    }
+
+    fn to_jer(&self, _: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        let special = if self.is_nan() {
+            "NaN"
+        } else if self.is_infinite() {
+            if *self > 0. { "INF" } else { "-INF" }
+        } else if *self == 0. && self.is_sign_negative() {
+            "-0"
+        } else {
+            // `Debug` writes valid JSON numbers, using an exponent for very large or small values
+            return Ok(jer::Value::Number(format!("{:?}", self)));
+        };
+        Ok(jer::Value::String(special.to_string()))
+    }
+}
+
+/// A REAL is written as a number in scientific notation (e.g. `1.5E2`), as `0` or `-0` for the zeros, or as one of
+/// the empty elements `<PLUS-INFINITY/>`, `<MINUS-INFINITY/>` and `<NOT-A-NUMBER/>`.
+impl XerElement for f64 {
+    const NAME: &'static str = "REAL";
+
+    /// Read an `f64` from the XER encoding of a REAL. With CXER, the number must be in the form written by
+    /// [to_xer](#method.to_xer): a mantissa without trailing zeros and a minimal exponent.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        if decoder.peek_name().is_some() {
+            let ret = decoder.decode_empty_element();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            return match ret.unwrap() {
+                "PLUS-INFINITY" => Ok(f64::INFINITY),
+                "MINUS-INFINITY" => Ok(f64::NEG_INFINITY),
+                "NOT-A-NUMBER" => Ok(f64::NAN),
+                _ => Err(xer::DecodeError::InvalidReal),
+            };
+        }
+
+        let ret = decoder.decode_token();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let s = ret.unwrap();
+        // Rust also parses words like "inf", which aren't valid here
+        if !s.bytes().all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b)) {
+            return Err(xer::DecodeError::InvalidReal);
+        }
+        let value: f64 = match s.parse() {
+            Ok(value) => value,
+            Err(_) => return Err(xer::DecodeError::InvalidReal),
+        };
+        if decoder.is_canonical() && xer_text(value) != s {
+            return Err(xer::DecodeError::NonCanonicalReal);
+        }
+        Ok(value)
+    }
+
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        if self.is_nan() {
+            return Ok(xer::Encoding::empty_element("NOT-A-NUMBER"));
+        }
+        if self.is_infinite() {
+            return Ok(xer::Encoding::empty_element(if *self > 0. { "PLUS-INFINITY" } else { "MINUS-INFINITY" }));
+        }
+        Ok(xer::Encoding::text(&xer_text(*self)))
+    }
+}
+
+// The text of a finite REAL in its CXER form
+fn xer_text(value: f64) -> String {
+    if value == 0. {
+        return if value.is_sign_negative() { "-0".to_string() } else { "0".to_string() };
+    }
+    format!("{:E}", value)
+}
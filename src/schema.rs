@@ -0,0 +1,836 @@
+use aper::{encode_fragmented, encode_normally_small, encode_open_type, APerElement, Constraint, Decoder, DecodeError,
+           EncodeError, Encoding, UNCONSTRAINED};
+use char_string::PermittedAlphabet;
+use oid::{ObjectIdentifier, RelativeOid};
+use per;
+use BitString;
+
+/// The PER-visible description of an ASN.1 type, which is all that's needed to [decode](fn.decode.html) and
+/// [encode](fn.encode.html) its values, or to transcode them between aligned and unaligned PER.
+///
+/// A SET is described like a SEQUENCE with its components in canonical order, an extension addition group like a
+/// SEQUENCE without an extension marker, and a SET OF like a SEQUENCE OF. DATE, TIME-OF-DAY, DATE-TIME and DURATION
+/// are described by the SEQUENCE types X.691 encodes them as, and UTCTime and GeneralizedTime as `VisibleString`s.
+///
+/// # Examples
+///
+/// ```
+/// // Foo ::= SEQUENCE {
+/// //     a INTEGER (0..7),
+/// //     b IA5String (SIZE (1..8)) OPTIONAL,
+/// //     ...,
+/// //     c BOOLEAN
+/// // }
+/// extern crate asn1;
+/// use asn1::IA5String;
+/// use asn1::aper::Constraint;
+/// use asn1::schema::{Component, Type};
+///
+/// let foo = Type::Sequence {
+///     root: vec![Component::new(Type::Integer {
+///                                   value: Some(Constraint::new(Some(0), Some(7))),
+///                                   extensible: false,
+///                               }),
+///                Component::optional(Type::CharString {
+///                    alphabet: IA5String::alphabet(),
+///                    size: Some(Constraint::new(Some(1), Some(8))),
+///                    extensible: false,
+///                })],
+///     extensions: Some(vec![Type::Boolean]),
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub enum Type {
+    Null,
+    Boolean,
+    /// An INTEGER with its value constraint, which may have an extension marker.
+    Integer {
+        value: Option<Constraint>,
+        extensible: bool,
+    },
+    /// An ENUMERATED type with `root` values in its root.
+    Enumerated { root: u64, extensible: bool },
+    BitString {
+        size: Option<Constraint>,
+        extensible: bool,
+    },
+    OctetString {
+        size: Option<Constraint>,
+        extensible: bool,
+    },
+    /// A known-multiplier character string type with its effective PermittedAlphabet.
+    CharString {
+        alphabet: PermittedAlphabet,
+        size: Option<Constraint>,
+        extensible: bool,
+    },
+    Utf8String,
+    ObjectIdentifier,
+    RelativeOid,
+    Real,
+    /// Any other type whose values are encoded as octets preceded by an unconstrained length determinant, which are
+    /// kept as they are.
+    Octets,
+    /// An open type holding a value of the given type.
+    Open(Box<Type>),
+    /// A SEQUENCE with its root components and, if it's extensible, the types of its extension additions.
+    Sequence {
+        root: Vec<Component>,
+        extensions: Option<Vec<Type>>,
+    },
+    SequenceOf {
+        element: Box<Type>,
+        size: Option<Constraint>,
+        extensible: bool,
+    },
+    /// A CHOICE with its root alternatives and, if it's extensible, its extension alternatives.
+    Choice {
+        root: Vec<Type>,
+        extensions: Option<Vec<Type>>,
+    },
+}
+
+/// A root component of a SEQUENCE type. Components that are OPTIONAL or have a DEFAULT value have a bit in the
+/// preamble.
+#[derive(Debug, Clone)]
+pub struct Component {
+    pub ty: Type,
+    pub optional: bool,
+}
+
+impl Component {
+    /// Construct a mandatory `Component`.
+    pub fn new(ty: Type) -> Component {
+        Component {
+            ty,
+            optional: false,
+        }
+    }
+
+    /// Construct an OPTIONAL (or DEFAULT) `Component`.
+    pub fn optional(ty: Type) -> Component {
+        Component { ty, optional: true }
+    }
+}
+
+
+/// A value of a [Type](enum.Type.html), for messages whose types are only known at runtime.
+///
+/// Components and alternatives are identified by their position in the type, as they are in PER.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{self, Constraint};
+/// use asn1::schema::{self, Component, Type, Value};
+///
+/// // SEQUENCE { a INTEGER (0..7), b BOOLEAN OPTIONAL }
+/// let ty = Type::Sequence {
+///     root: vec![Component::new(Type::Integer {
+///                                   value: Some(Constraint::new(Some(0), Some(7))),
+///                                   extensible: false,
+///                               }),
+///                Component::optional(Type::Boolean)],
+///     extensions: None,
+/// };
+/// let value = Value::Sequence {
+///     root: vec![Some(Value::Integer(5)), None],
+///     extensions: vec![],
+/// };
+/// let enc = schema::encode(&value, &ty).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [80]
+/// assert_eq!(Ok(value), schema::decode(&mut aper::Decoder::new(enc.bytes()), &ty));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    /// The index of an ENUMERATED value, where the indexes of the extension values follow those of the root values.
+    Enumerated(u64),
+    BitString(BitString),
+    OctetString(Vec<u8>),
+    /// A value of a known-multiplier character string type or a UTF8String.
+    String(String),
+    ObjectIdentifier(ObjectIdentifier),
+    RelativeOid(RelativeOid),
+    Real(f64),
+    /// The octets of a value of a `Type::Octets`.
+    Octets(Vec<u8>),
+    /// The value held by an open type.
+    Open(Box<Value>),
+    /// A SEQUENCE value: its root components, `None` for those that are absent, and its extension additions, `None` for
+    /// those that are absent. `extensions` has as many items as the extension bitmap has bits, which may be more or
+    /// fewer than the type has extension additions.
+    Sequence {
+        root: Vec<Option<Value>>,
+        extensions: Vec<Option<Value>>,
+    },
+    SequenceOf(Vec<Value>),
+    /// A CHOICE value: the index of its alternative, where the indexes of the extension alternatives follow those of
+    /// the root alternatives, and the value of that alternative.
+    Choice { index: usize, value: Box<Value> },
+    /// The octets of the open type value of an extension addition or alternative that the type doesn't know about.
+    Unknown(Vec<u8>),
+}
+
+/// Decode a value of type `ty` from its aligned PER encoding, read by `decoder`.
+///
+/// Extension additions and alternatives that `ty` doesn't know about are decoded as `Value::Unknown`.
+pub fn decode(decoder: &mut Decoder, ty: &Type) -> Result<Value, DecodeError> {
+    match *ty {
+        Type::Null => Ok(Value::Null),
+        Type::Boolean => decoder.read(1).map(|b| Value::Boolean(b > 0)),
+        Type::Integer { value, extensible } => {
+            let mut value = value;
+            if extensible {
+                let ret = decoder.read(1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                if ret.unwrap() > 0 {
+                    value = None;
+                }
+            }
+            let (lb, ub) = value.map_or((None, None), |c| (c.min(), c.max()));
+            if let (Some(lb), Some(ub)) = (lb, ub) {
+                return per::read_constrained(decoder, lb, ub, true).map(Value::Integer);
+            }
+
+            let ret = per::read_length(decoder, true);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let (len, fragment) = ret.unwrap();
+            if fragment || len == 0 || len > 8 {
+                return Err(DecodeError::MalformedInt);
+            }
+            let ret = decoder.read_uint(8 * len);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let n = ret.unwrap();
+            match lb {
+                Some(lb) => {
+                    let value = lb as i128 + n as i128;
+                    if value > i64::MAX as i128 {
+                        return Err(DecodeError::MalformedInt);
+                    }
+                    Ok(Value::Integer(value as i64))
+                }
+                None => {
+                    let shift = 64 - 8 * len;
+                    Ok(Value::Integer(((n << shift) as i64) >> shift))
+                }
+            }
+        }
+        Type::Enumerated { root, extensible } => {
+            if extensible {
+                let ret = decoder.read(1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                if ret.unwrap() > 0 {
+                    return decoder.decode_normally_small().map(|i| Value::Enumerated(root + i));
+                }
+            }
+            per::read_constrained(decoder, 0, root as i64 - 1, true).map(|i| Value::Enumerated(i as u64))
+        }
+        Type::BitString { size, extensible } => {
+            let mut bits = BitString::new();
+            let ret = decode_counted(decoder, size, extensible, |lb, ub| lb != ub || ub > 16, |d, n| {
+                let mut left = n;
+                while left > 0 {
+                    let m = if left > 64 { 64 } else { left };
+                    let ret = d.read_uint(m);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    let value = ret.unwrap();
+                    for i in (0..m).rev() {
+                        bits.push((value >> i) & 1 == 1);
+                    }
+                    left -= m;
+                }
+                Ok(())
+            });
+            ret.map(|_| Value::BitString(bits))
+        }
+        Type::OctetString { size, extensible } => {
+            let mut bytes = Vec::new();
+            let ret = decode_counted(decoder,
+                                     size,
+                                     extensible,
+                                     |lb, ub| lb != ub || ub > 2,
+                                     |d, n| d.read_to_vec(&mut bytes, 8 * n));
+            ret.map(|_| Value::OctetString(bytes))
+        }
+        Type::CharString { ref alphabet, size, extensible } => {
+            let b = alphabet.char_bits() as i64;
+            let mut s = String::new();
+            let ret = decode_counted(decoder,
+                                     size,
+                                     extensible,
+                                     |_, ub| ub * b > 16,
+                                     |d, n| read_chars(d, alphabet, n, &mut s));
+            ret.map(|_| Value::String(s))
+        }
+        Type::Utf8String => String::from_aper(decoder, UNCONSTRAINED).map(Value::String),
+        Type::ObjectIdentifier => ObjectIdentifier::from_aper(decoder, UNCONSTRAINED).map(Value::ObjectIdentifier),
+        Type::RelativeOid => RelativeOid::from_aper(decoder, UNCONSTRAINED).map(Value::RelativeOid),
+        Type::Real => f64::from_aper(decoder, UNCONSTRAINED).map(Value::Real),
+        Type::Octets => decoder.decode_open_type_bytes().map(Value::Octets),
+        Type::Open(ref ty) => decode_open(decoder, Some(ty)).map(|v| Value::Open(Box::new(v))),
+        Type::Sequence { ref root, ref extensions } => {
+            let mut is_ext = false;
+            if extensions.is_some() {
+                let ret = decoder.read(1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                is_ext = ret.unwrap() > 0;
+            }
+
+            let mut present = Vec::with_capacity(root.len());
+            for c in root {
+                if !c.optional {
+                    present.push(true);
+                    continue;
+                }
+                let ret = decoder.read(1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                present.push(ret.unwrap() > 0);
+            }
+            let mut values = Vec::with_capacity(root.len());
+            for (c, p) in root.iter().zip(present) {
+                if !p {
+                    values.push(None);
+                    continue;
+                }
+                let ret = decode(decoder, &c.ty);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                values.push(Some(ret.unwrap()));
+            }
+            if !is_ext {
+                return Ok(Value::Sequence {
+                              root: values,
+                              extensions: vec![],
+                          });
+            }
+
+            let ret = decode_normally_small_length(decoder);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let n = ret.unwrap();
+            let mut present = Vec::with_capacity(n);
+            for _ in 0..n {
+                let ret = decoder.read(1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                present.push(ret.unwrap() > 0);
+            }
+            let additions = extensions.as_ref().unwrap();
+            let mut ext_values = Vec::with_capacity(n);
+            for (i, p) in present.into_iter().enumerate() {
+                if !p {
+                    ext_values.push(None);
+                    continue;
+                }
+                let ret = decode_open(decoder, additions.get(i));
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ext_values.push(Some(ret.unwrap()));
+            }
+            Ok(Value::Sequence {
+                   root: values,
+                   extensions: ext_values,
+               })
+        }
+        Type::SequenceOf { ref element, size, extensible } => {
+            let mut elements = Vec::new();
+            let ret = decode_counted(decoder, size, extensible, |_, _| false, |d, n| {
+                for _ in 0..n {
+                    let ret = decode(d, element);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    elements.push(ret.unwrap());
+                }
+                Ok(())
+            });
+            ret.map(|_| Value::SequenceOf(elements))
+        }
+        Type::Choice { ref root, ref extensions } => {
+            if extensions.is_some() {
+                let ret = decoder.read(1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                if ret.unwrap() > 0 {
+                    let ret = decoder.decode_normally_small();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    let i = ret.unwrap() as usize;
+                    let ret = decode_open(decoder, extensions.as_ref().unwrap().get(i));
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    return Ok(Value::Choice {
+                                  index: root.len() + i,
+                                  value: Box::new(ret.unwrap()),
+                              });
+                }
+            }
+            if root.is_empty() {
+                return Err(DecodeError::InvalidChoice);
+            }
+            let ret = per::read_constrained(decoder, 0, root.len() as i64 - 1, true);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let index = ret.unwrap() as usize;
+            let ret = decode(decoder, &root[index]);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            Ok(Value::Choice {
+                   index,
+                   value: Box::new(ret.unwrap()),
+               })
+        }
+    }
+}
+
+/// Decode a count of items limited by `size` and the items it counts. `aligned_items(lb, ub)` tells if the items are
+/// octet-aligned when the count is a constrained whole number. `items(decoder, n)` is called to decode each run of `n`
+/// items.
+fn decode_counted<A, F>(decoder: &mut Decoder,
+                        size: Option<Constraint>,
+                        extensible: bool,
+                        aligned_items: A,
+                        mut items: F)
+                        -> Result<(), DecodeError>
+    where A: Fn(i64, i64) -> bool,
+          F: FnMut(&mut Decoder, usize) -> Result<(), DecodeError>
+{
+    let mut size = size;
+    if extensible {
+        let ret = decoder.read(1);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if ret.unwrap() > 0 {
+            size = None;
+        }
+    }
+    let (lb, ub) = match size {
+        Some(sz_constr) => sz_constr.size_bounds(),
+        None => (0, None),
+    };
+
+    if let Some(ub) = ub.filter(|&ub| ub < 65536) {
+        let n = if lb == ub {
+            ub
+        } else {
+            let ret = per::read_constrained(decoder, lb, ub, true);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            ret.unwrap()
+        };
+        if aligned_items(lb, ub) {
+            decoder.align();
+        }
+        return items(decoder, n as usize);
+    }
+
+    let mut total = 0;
+    loop {
+        let ret = per::read_length(decoder, true);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let (n, fragment) = ret.unwrap();
+        let ret = items(decoder, n);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        total += n as i64;
+        if !fragment {
+            break;
+        }
+    }
+    if total < lb || ub.is_some_and(|ub| total > ub) {
+        return Err(DecodeError::InvalidSize);
+    }
+    Ok(())
+}
+
+/// Decode `n` characters of a known-multiplier character string and push them onto `s`.
+fn read_chars(decoder: &mut Decoder,
+              alphabet: &PermittedAlphabet,
+              n: usize,
+              s: &mut String)
+              -> Result<(), DecodeError> {
+    let b = alphabet.char_bits();
+    let remapped = alphabet.is_remapped(b);
+    for _ in 0..n {
+        let ret = decoder.read_uint(b);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+
+        let code = if remapped {
+            alphabet.code_at(value)
+        } else if value <= u32::MAX as u64 && alphabet.index_of(value as u32).is_some() {
+            Some(value as u32)
+        } else {
+            None
+        };
+        match code.and_then(char::from_u32) {
+            Some(c) => s.push(c),
+            None => return Err(DecodeError::InvalidCharacter),
+        }
+    }
+    Ok(())
+}
+
+/// Decode the normally small length of the extension bitmap of a SEQUENCE.
+fn decode_normally_small_length(decoder: &mut Decoder) -> Result<usize, DecodeError> {
+    let ret = decoder.read(1);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    if ret.unwrap() == 0 {
+        return decoder.read_uint(6).map(|n| n as usize + 1);
+    }
+    let ret = per::read_length(decoder, true);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    match ret.unwrap() {
+        (n, false) if n > 0 => Ok(n),
+        _ => Err(DecodeError::MalformedLength),
+    }
+}
+
+/// Decode an open type holding a value of type `ty`, or a `Value::Unknown` if `ty` is `None`.
+fn decode_open(decoder: &mut Decoder, ty: Option<&Type>) -> Result<Value, DecodeError> {
+    let ret = decoder.decode_open_type_bytes();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let content = ret.unwrap();
+    match ty {
+        Some(ty) => decode(&mut decoder.sub_decoder(&content), ty),
+        None => Ok(Value::Unknown(content)),
+    }
+}
+
+/// Encode `value`, a value of type `ty`, in aligned PER.
+///
+/// Returns `EncodeError::InvalidValue` if `value` isn't a value of `ty`, including when it's a `Value::Unknown`
+/// anywhere but in place of an extension addition or alternative.
+pub fn encode(value: &Value, ty: &Type) -> Result<Encoding, EncodeError> {
+    let mut output = Encoding::new();
+    let ret = encode_into(&mut output, value, ty);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(output)
+}
+
+fn encode_into(output: &mut Encoding, value: &Value, ty: &Type) -> Result<(), EncodeError> {
+    match (ty, value) {
+        (&Type::Null, &Value::Null) => Ok(()),
+        (&Type::Boolean, &Value::Boolean(b)) => {
+            output.append_uint(b as u64, 1);
+            Ok(())
+        }
+        (&Type::Integer { value: constraint, extensible }, &Value::Integer(v)) => {
+            let (lb, ub) = constraint.map_or((None, None), |c| (c.min(), c.max()));
+            let in_root = lb.is_none_or(|lb| v >= lb) && ub.is_none_or(|ub| v <= ub);
+            if extensible {
+                output.append_uint(!in_root as u64, 1);
+            } else if !in_root {
+                return Err(EncodeError::InvalidValue);
+            }
+            let (lb, ub) = if in_root { (lb, ub) } else { (None, None) };
+            if let (Some(lb), Some(ub)) = (lb, ub) {
+                per::write_constrained(output, v, lb, ub, true);
+                return Ok(());
+            }
+
+            // The offset from the lower bound if there's one, and the two's complement value otherwise
+            let (n, len) = match lb {
+                Some(lb) => {
+                    let offset = (v as i128 - lb as i128) as u64;
+                    (offset, per::octets_for(offset as u128 + 1))
+                }
+                None => {
+                    let mut len = 1;
+                    while len < 8 && (v < -(1i64 << (8 * len - 1)) || v >= 1i64 << (8 * len - 1)) {
+                        len += 1;
+                    }
+                    (v as u64, len)
+                }
+            };
+            per::write_length(output, len, false, true);
+            output.append_uint(n, 8 * len);
+            Ok(())
+        }
+        (&Type::Enumerated { root, extensible }, &Value::Enumerated(i)) => {
+            if i < root {
+                if extensible {
+                    output.append_uint(0, 1);
+                }
+                per::write_constrained(output, i as i64, 0, root as i64 - 1, true);
+                return Ok(());
+            }
+            if !extensible {
+                return Err(EncodeError::InvalidValue);
+            }
+            output.append_uint(1, 1);
+            let ret = encode_normally_small(i - root);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            output.append(&ret.unwrap())
+        }
+        (&Type::BitString { size, extensible }, Value::BitString(bits)) => {
+            encode_counted(output,
+                           bits.get_num_bits(),
+                           size,
+                           extensible,
+                           |lb, ub| lb != ub || ub > 16,
+                           |o, start, end| {
+                               for i in start..end {
+                                   o.append_uint(bits.is_set(i) as u64, 1);
+                               }
+                               Ok(())
+                           })
+        }
+        (&Type::OctetString { size, extensible }, Value::OctetString(bytes)) => {
+            encode_counted(output,
+                           bytes.len(),
+                           size,
+                           extensible,
+                           |lb, ub| lb != ub || ub > 2,
+                           |o, start, end| {
+                               for b in &bytes[start..end] {
+                                   o.append_uint(*b as u64, 8);
+                               }
+                               Ok(())
+                           })
+        }
+        (&Type::CharString { ref alphabet, size, extensible }, Value::String(s)) => {
+            let b = alphabet.char_bits() as i64;
+            let chars: Vec<char> = s.chars().collect();
+            encode_counted(output,
+                           chars.len(),
+                           size,
+                           extensible,
+                           |_, ub| ub * b > 16,
+                           |o, start, end| write_chars(o, alphabet, &chars[start..end]))
+        }
+        (&Type::Utf8String, Value::String(s)) => append_element(output, s),
+        (&Type::ObjectIdentifier, Value::ObjectIdentifier(oid)) => append_element(output, oid),
+        (&Type::RelativeOid, Value::RelativeOid(oid)) => append_element(output, oid),
+        (&Type::Real, Value::Real(x)) => append_element(output, x),
+        (&Type::Octets, Value::Octets(bytes)) => {
+            let ret = encode_fragmented(bytes.len(), |start, end| Ok(Encoding::with_bytes(bytes[start..end].to_vec())));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            output.append(&ret.unwrap())
+        }
+        (Type::Open(ty), Value::Open(value)) => encode_open(output, value, Some(ty)),
+        (Type::Sequence { root, extensions },
+         Value::Sequence { root: values, extensions: ext_values }) => {
+            if values.len() != root.len() {
+                return Err(EncodeError::InvalidValue);
+            }
+            let is_ext = ext_values.iter().any(|v| v.is_some());
+            if extensions.is_some() {
+                output.append_uint(is_ext as u64, 1);
+            } else if is_ext {
+                return Err(EncodeError::InvalidValue);
+            }
+
+            for (c, v) in root.iter().zip(values) {
+                if c.optional {
+                    output.append_uint(v.is_some() as u64, 1);
+                } else if v.is_none() {
+                    return Err(EncodeError::InvalidValue);
+                }
+            }
+            for (c, v) in root.iter().zip(values) {
+                if let Some(ref v) = *v {
+                    let ret = encode_into(output, v, &c.ty);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+            }
+            if !is_ext {
+                return Ok(());
+            }
+
+            let n = ext_values.len();
+            if n <= 64 {
+                output.append_uint(0, 1);
+                output.append_uint(n as u64 - 1, 6);
+            } else {
+                output.append_uint(1, 1);
+                per::write_length(output, n, false, true);
+            }
+            for v in ext_values {
+                output.append_uint(v.is_some() as u64, 1);
+            }
+            let additions = extensions.as_ref().unwrap();
+            for (i, v) in ext_values.iter().enumerate() {
+                if let Some(ref v) = *v {
+                    let ret = encode_open(output, v, additions.get(i));
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+            }
+            Ok(())
+        }
+        (&Type::SequenceOf { ref element, size, extensible }, Value::SequenceOf(elements)) => {
+            encode_counted(output, elements.len(), size, extensible, |_, _| false, |o, start, end| {
+                for e in &elements[start..end] {
+                    let ret = encode_into(o, e, element);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+                Ok(())
+            })
+        }
+        (Type::Choice { root, extensions }, &Value::Choice { index, ref value }) => {
+            if index < root.len() {
+                if extensions.is_some() {
+                    output.append_uint(0, 1);
+                }
+                per::write_constrained(output, index as i64, 0, root.len() as i64 - 1, true);
+                return encode_into(output, value, &root[index]);
+            }
+            let additions = match *extensions {
+                Some(ref additions) => additions,
+                None => return Err(EncodeError::InvalidValue),
+            };
+            output.append_uint(1, 1);
+            let ret = encode_normally_small((index - root.len()) as u64);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = output.append(&ret.unwrap());
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            encode_open(output, value, additions.get(index - root.len()))
+        }
+        _ => Err(EncodeError::InvalidValue),
+    }
+}
+
+/// Encode a count of `n` items limited by `size` and the items it counts. See `decode_counted`. `items(output, start,
+/// end)` is called to encode each run of items.
+fn encode_counted<A, F>(output: &mut Encoding,
+                        n: usize,
+                        size: Option<Constraint>,
+                        extensible: bool,
+                        aligned_items: A,
+                        mut items: F)
+                        -> Result<(), EncodeError>
+    where A: Fn(i64, i64) -> bool,
+          F: FnMut(&mut Encoding, usize, usize) -> Result<(), EncodeError>
+{
+    let (lb, ub) = match size {
+        Some(sz_constr) => sz_constr.size_bounds(),
+        None => (0, None),
+    };
+    let in_root = n as i64 >= lb && ub.is_none_or(|ub| n as i64 <= ub);
+    if extensible {
+        output.append_uint(!in_root as u64, 1);
+    } else if !in_root {
+        return Err(EncodeError::InvalidSize);
+    }
+    let (lb, ub) = if in_root { (lb, ub) } else { (0, None) };
+
+    if let Some(ub) = ub.filter(|&ub| ub < 65536) {
+        if lb != ub {
+            per::write_constrained(output, n as i64, lb, ub, true);
+        }
+        if aligned_items(lb, ub) {
+            output.align();
+        }
+        return items(output, 0, n);
+    }
+
+    let mut start = 0;
+    loop {
+        let (len, fragment) = per::fragment_length(n - start);
+        per::write_length(output, len, fragment, true);
+        let ret = items(output, start, start + len);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        start += len;
+        if !fragment {
+            return Ok(());
+        }
+    }
+}
+
+/// Encode the characters of a known-multiplier character string.
+fn write_chars(output: &mut Encoding, alphabet: &PermittedAlphabet, chars: &[char]) -> Result<(), EncodeError> {
+    let b = alphabet.char_bits();
+    let remapped = alphabet.is_remapped(b);
+    for &c in chars {
+        match alphabet.index_of(c as u32) {
+            Some(index) if remapped => output.append_uint(index, b),
+            Some(_) => output.append_uint(c as u64, b),
+            None => return Err(EncodeError::InvalidCharacter),
+        }
+    }
+    Ok(())
+}
+
+/// Encode `value` as an open type holding a value of type `ty`. A `Value::Unknown` is written as it is, whether or
+/// not there's a `ty`.
+fn encode_open(output: &mut Encoding, value: &Value, ty: Option<&Type>) -> Result<(), EncodeError> {
+    let ret = match (value, ty) {
+        (Value::Unknown(bytes), _) => Ok(Encoding::with_bytes(bytes.clone())),
+        (_, Some(ty)) => encode(value, ty),
+        (_, None) => Err(EncodeError::InvalidValue),
+    };
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let ret = encode_open_type(&ret.unwrap());
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    output.append(&ret.unwrap())
+}
+
+fn append_element<T: APerElement>(output: &mut Encoding, value: &T) -> Result<(), EncodeError> {
+    let ret = value.to_aper(UNCONSTRAINED);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    output.append(&ret.unwrap())
+}
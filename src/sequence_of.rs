@@ -1,4 +1,8 @@
 use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_length};
+use ber::{self, BerElement, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
 
 impl<T: APerElement> APerElement for Vec<T> {
     const CONSTRAINTS: Constraints = Constraints {
@@ -7,6 +11,7 @@ impl<T: APerElement> APerElement for Vec<T> {
     };
 
     /// Read a `Vec[T]` from an aligned PER encoding.
+    #[allow(clippy::needless_late_init)]
     fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
         if constraints.size.is_none() {
             return Err(DecodeError::MissingSizeConstraint);
@@ -73,3 +78,190 @@ impl<T: APerElement> APerElement for Vec<T> {
         Ok(enc)
     }
 }
+
+/// `Vec<T>` is encoded as a SEQUENCE OF `T`. Note that this makes `Vec<u8>` a SEQUENCE OF INTEGER; an OCTET STRING
+/// is read with [ber::Decoder::decode_string](ber/struct.Decoder.html#method.decode_string) and encoded with
+/// [ber::Encoding::string](ber/struct.Encoding.html#method.string).
+impl<T: BerElement> BerElement for Vec<T> {
+    const TAG: Tag = Tag::SEQUENCE;
+
+    /// Read a `Vec[T]` from a BER encoding.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_constructed(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut d = ret.unwrap();
+
+        let mut content: Vec<T> = Vec::new();
+        while !d.is_empty() {
+            let ret = T::from_ber(&mut d, T::TAG);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            content.push(ret.unwrap());
+        }
+        Ok(content)
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        let mut elements = Vec::with_capacity(self.len());
+        for x in self {
+            let ret = x.to_ber(T::TAG);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            elements.push(ret.unwrap());
+        }
+        Ok(ber::Encoding::constructed(tag, elements))
+    }
+}
+
+/// As with aligned PER, the value constraint is used as the size constraint of the elements.
+impl<T: OerElement> OerElement for Vec<T> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `Vec[T]` from an OER encoding: the number of elements, then the elements.
+    fn from_oer(decoder: &mut oer::Decoder, constraints: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = decoder.decode_quantity();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let len = ret.unwrap();
+        if !constraints.check_size(len) {
+            return Err(oer::DecodeError::InvalidSize);
+        }
+
+        let el_constrs = Constraints {
+            value: None,
+            size: constraints.value,
+        };
+        // Each element takes at least one byte unless it's empty, so don't trust `len` for the capacity
+        let mut content: Vec<T> = Vec::new();
+        for _ in 0..len {
+            let ret = T::from_oer(decoder, el_constrs);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            content.push(ret.unwrap());
+        }
+        Ok(content)
+    }
+
+    fn to_oer(&self, constraints: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        if !constraints.check_size(self.len()) {
+            return Err(oer::EncodeError::InvalidSize);
+        }
+        let mut enc = oer::encode_quantity(self.len());
+        for x in self {
+            let ret = x.to_oer(Constraints {
+                value: None,
+                size: constraints.value,
+            });
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            enc.append(&ret.unwrap());
+        }
+        Ok(enc)
+    }
+}
+
+/// An item of a SEQUENCE OF is held in an element named after its type (e.g. `<INTEGER>5</INTEGER>`), unless its
+/// encoding is an empty element of its own, like `<true/>` for a BOOLEAN.
+impl<T: XerElement> XerElement for Vec<T> {
+    const NAME: &'static str = "SEQUENCE_OF";
+
+    /// Read a `Vec[T]` from an XER encoding.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        let mut content: Vec<T> = Vec::new();
+        while !decoder.is_empty() {
+            let ret = decoder.decode_item(T::NAME);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let mut item = ret.unwrap();
+            let ret = T::from_xer(&mut item);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            content.push(ret.unwrap());
+            let ret = item.finish();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(content)
+    }
+
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        let mut enc = xer::Encoding::new();
+        for x in self {
+            let ret = x.to_xer();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let item = ret.unwrap();
+            if item.is_empty_element() {
+                enc.append(&item);
+            } else {
+                enc.push(T::NAME, item);
+            }
+        }
+        Ok(enc)
+    }
+}
+
+impl<T: JerElement> JerElement for Vec<T> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `Vec[T]` from a JER encoding, which is an array.
+    fn from_jer(value: &jer::Value, constraints: Constraints) -> Result<Self, jer::DecodeError> {
+        let ret = value.as_array();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let items = ret.unwrap();
+        if !constraints.check_size(items.len()) {
+            return Err(jer::DecodeError::InvalidSize);
+        }
+
+        let el_constrs = Constraints {
+            value: None,
+            size: constraints.value,
+        };
+        let mut content: Vec<T> = Vec::with_capacity(items.len());
+        for item in items {
+            let ret = T::from_jer(item, el_constrs);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            content.push(ret.unwrap());
+        }
+        Ok(content)
+    }
+
+    fn to_jer(&self, constraints: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        if !constraints.check_size(self.len()) {
+            return Err(jer::EncodeError::InvalidSize);
+        }
+        let mut items = Vec::with_capacity(self.len());
+        for x in self {
+            let ret = x.to_jer(Constraints {
+                value: None,
+                size: constraints.value,
+            });
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            items.push(ret.unwrap());
+        }
+        Ok(jer::Value::Array(items))
+    }
+}
@@ -0,0 +1,830 @@
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, EnumAccess, Expected, MapAccess, SeqAccess,
+                Unexpected, VariantAccess, Visitor};
+use serde::ser::{Serialize, SerializeStruct, SerializeTuple, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+use {BMPString, BitString, Criticality, Date, DateTime, Duration, ExtensionAdditions, GeneralizedTime, IA5String,
+     NamedBit, NamedBitString, NumericString, ObjectIdentifier, ObjectSet, OpenValue, PrintableString,
+     ProtocolContainer, ProtocolField, RelativeOid, TimeOfDay, UTCTime, UniversalString, UnknownAlternative,
+     UnknownExtensions, VisibleString};
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok()).collect()
+}
+
+/// Octets, as a string of hex digits in human-readable formats.
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&to_hex(self.0))
+        } else {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+}
+
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ByteBuf, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = ByteBuf;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("octets as a string of hex digits")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<ByteBuf, E> {
+        match from_hex(v) {
+            Some(bytes) => Ok(ByteBuf(bytes)),
+            None => Err(E::invalid_value(Unexpected::Str(v), &self)),
+        }
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<ByteBuf, E> {
+        Ok(ByteBuf(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<ByteBuf, E> {
+        Ok(ByteBuf(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ByteBuf, A::Error> {
+        let mut bytes = Vec::new();
+        loop {
+            let ret = seq.next_element();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            match ret.unwrap() {
+                Some(b) => bytes.push(b),
+                None => return Ok(ByteBuf(bytes)),
+            }
+        }
+    }
+}
+
+/// Parses a value from its text.
+struct StrVisitor<T> {
+    expecting: &'static str,
+    parse: fn(&str) -> Option<T>,
+}
+
+impl<'de, T> Visitor<'de> for StrVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        match (self.parse)(v) {
+            Some(value) => Ok(value),
+            None => Err(E::invalid_value(Unexpected::Str(v), &self)),
+        }
+    }
+}
+
+/// The position of a field or variant in `names`, read from its name or its position.
+struct Identifier {
+    names: &'static [&'static str],
+    variant: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for Identifier {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Identifier {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "one of {:?}", self.names)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<usize, E> {
+        if v < self.names.len() as u64 {
+            Ok(v as usize)
+        } else {
+            Err(E::invalid_value(Unexpected::Unsigned(v), &self))
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<usize, E> {
+        match self.names.iter().position(|name| *name == v) {
+            Some(i) => Ok(i),
+            None if self.variant => Err(E::unknown_variant(v, self.names)),
+            None => Err(E::unknown_field(v, self.names)),
+        }
+    }
+}
+
+// Read the `i`th element of a sequence that should have more
+fn next_element<'de, A: SeqAccess<'de>, T: Deserialize<'de>>(seq: &mut A,
+                                                             i: usize,
+                                                             expected: &dyn Expected)
+                                                             -> Result<T, A::Error> {
+    match seq.next_element() {
+        Ok(Some(value)) => Ok(value),
+        Ok(None) => Err(de::Error::invalid_length(i, expected)),
+        Err(e) => Err(e),
+    }
+}
+
+// Read the value of the field `name` of a map into `slot`
+fn next_value<'de, A: MapAccess<'de>, T: Deserialize<'de>>(map: &mut A,
+                                                           slot: &mut Option<T>,
+                                                           name: &'static str)
+                                                           -> Result<(), A::Error> {
+    if slot.is_some() {
+        return Err(de::Error::duplicate_field(name));
+    }
+    let ret = map.next_value();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    *slot = Some(ret.unwrap());
+    Ok(())
+}
+
+fn required<T, E: de::Error>(slot: Option<T>, name: &'static str) -> Result<T, E> {
+    match slot {
+        Some(value) => Ok(value),
+        None => Err(E::missing_field(name)),
+    }
+}
+
+// A value whose text is its representation in every format
+macro_rules! string_impl {
+    ($name:ident, $expecting:expr, $parse:expr) => {
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                deserializer.deserialize_str(StrVisitor {
+                    expecting: $expecting,
+                    parse: $parse,
+                })
+            }
+        }
+    };
+}
+
+string_impl!(NumericString, "a NumericString", NumericString::new);
+string_impl!(PrintableString, "a PrintableString", PrintableString::new);
+string_impl!(VisibleString, "a VisibleString", VisibleString::new);
+string_impl!(IA5String, "an IA5String", IA5String::new);
+string_impl!(BMPString, "a BMPString", BMPString::new);
+string_impl!(UniversalString, "a UniversalString", UniversalString::new);
+string_impl!(Date, "a DATE, e.g. \"2024-02-29\"", |s| s.parse().ok());
+string_impl!(TimeOfDay, "a TIME-OF-DAY, e.g. \"12:30:00\"", |s| s.parse().ok());
+string_impl!(DateTime, "a DATE-TIME, e.g. \"2024-02-29T12:30:00\"", |s| s.parse().ok());
+string_impl!(Duration, "a DURATION, e.g. \"P1DT2H\"", |s| s.parse().ok());
+string_impl!(GeneralizedTime, "a GeneralizedTime, e.g. \"20240229123000Z\"", |s| s.parse().ok());
+string_impl!(UTCTime, "a UTCTime, e.g. \"240229123000Z\"", |s| s.parse().ok());
+
+// An OID is written in dotted form in human-readable formats and as its arcs otherwise
+macro_rules! oid_impl {
+    ($name:ident, $expecting:expr) => {
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    serializer.collect_str(self)
+                } else {
+                    self.arcs().serialize(serializer)
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                if deserializer.is_human_readable() {
+                    return deserializer.deserialize_str(StrVisitor {
+                        expecting: $expecting,
+                        parse: |s| s.parse().ok(),
+                    });
+                }
+                let ret = Vec::<u64>::deserialize(deserializer);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match $name::new(&ret.unwrap()) {
+                    Ok(oid) => Ok(oid),
+                    Err(e) => Err(de::Error::custom(format_args!("invalid {}: {:?}", stringify!($name), e))),
+                }
+            }
+        }
+    };
+}
+
+oid_impl!(ObjectIdentifier, "an OBJECT IDENTIFIER in dotted form, e.g. \"1.2.840.113549\"");
+oid_impl!(RelativeOid, "a RELATIVE-OID in dotted form, e.g. \"1.2\"");
+
+/// A `BitString` is a string of `0` and `1` characters in human-readable formats, and a tuple of its length and its
+/// octets otherwise.
+impl Serialize for BitString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let bits: String = self.iter().map(|b| if b { '1' } else { '0' }).collect();
+            return serializer.serialize_str(&bits);
+        }
+        let ret = serializer.serialize_tuple(2);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut tuple = ret.unwrap();
+        let ret = tuple.serialize_element(&(self.get_num_bits() as u64));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = tuple.serialize_element(&Bytes(&self.to_bytes()));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        tuple.end()
+    }
+}
+
+fn parse_bits(s: &str) -> Option<BitString> {
+    let mut bits = BitString::with_len(0);
+    for c in s.chars() {
+        match c {
+            '0' => bits.push(false),
+            '1' => bits.push(true),
+            _ => return None,
+        }
+    }
+    Some(bits)
+}
+
+impl<'de> Deserialize<'de> for BitString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BitString, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(StrVisitor {
+                expecting: "a string of 0 and 1 bits",
+                parse: parse_bits,
+            })
+        } else {
+            deserializer.deserialize_tuple(2, BitStringVisitor)
+        }
+    }
+}
+
+struct BitStringVisitor;
+
+impl<'de> Visitor<'de> for BitStringVisitor {
+    type Value = BitString;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the length and octets of a BIT STRING")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<BitString, A::Error> {
+        let ret = next_element::<A, u64>(&mut seq, 0, &self);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let n = ret.unwrap();
+        let ret = next_element::<A, ByteBuf>(&mut seq, 1, &self);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let bytes = ret.unwrap().0;
+        if (bytes.len() as u64) * 8 < n {
+            return Err(de::Error::invalid_length(bytes.len(), &self));
+        }
+        Ok(BitString::with_bytes_and_len(&bytes, n as usize))
+    }
+}
+
+/// A `NamedBitString` is represented like its `BitString`.
+impl<N: NamedBit> Serialize for NamedBitString<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.bits().serialize(serializer)
+    }
+}
+
+impl<'de, N: NamedBit> Deserialize<'de> for NamedBitString<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<NamedBitString<N>, D::Error> {
+        BitString::deserialize(deserializer).map(NamedBitString::from_bits)
+    }
+}
+
+const CRITICALITIES: &[&str] = &["reject", "ignore", "notify"];
+
+/// A `Criticality` is a unit variant named like its ASN.1 identifier, e.g. `"reject"`.
+impl Serialize for Criticality {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit_variant("Criticality", *self as u32, CRITICALITIES[*self as usize])
+    }
+}
+
+impl<'de> Deserialize<'de> for Criticality {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Criticality, D::Error> {
+        deserializer.deserialize_enum("Criticality", CRITICALITIES, CriticalityVisitor)
+    }
+}
+
+struct CriticalityVisitor;
+
+impl<'de> Visitor<'de> for CriticalityVisitor {
+    type Value = Criticality;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Criticality")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Criticality, A::Error> {
+        let ret = data.variant_seed(Identifier {
+            names: CRITICALITIES,
+            variant: true,
+        });
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let (i, variant) = ret.unwrap();
+        let ret = variant.unit_variant();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok([Criticality::Reject, Criticality::Ignore, Criticality::Notify][i])
+    }
+}
+
+const OPEN_VALUES: &[&str] = &["known", "unknown"];
+
+/// An `OpenValue` is a `"known"` variant holding the value or an `"unknown"` one holding the octets.
+impl<S: ObjectSet + Serialize> Serialize for OpenValue<S> {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        match *self {
+            OpenValue::Known(ref value) => serializer.serialize_newtype_variant("OpenValue", 0, "known", value),
+            OpenValue::Unknown(ref bytes) => {
+                serializer.serialize_newtype_variant("OpenValue", 1, "unknown", &Bytes(bytes))
+            }
+        }
+    }
+}
+
+impl<'de, S: ObjectSet + Deserialize<'de>> Deserialize<'de> for OpenValue<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<OpenValue<S>, D::Error> {
+        deserializer.deserialize_enum("OpenValue", OPEN_VALUES, OpenValueVisitor(PhantomData))
+    }
+}
+
+struct OpenValueVisitor<S>(PhantomData<S>);
+
+impl<'de, S: ObjectSet + Deserialize<'de>> Visitor<'de> for OpenValueVisitor<S> {
+    type Value = OpenValue<S>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an OpenValue")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<OpenValue<S>, A::Error> {
+        let ret = data.variant_seed(Identifier {
+            names: OPEN_VALUES,
+            variant: true,
+        });
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match ret.unwrap() {
+            (0, variant) => variant.newtype_variant().map(OpenValue::Known),
+            (_, variant) => variant.newtype_variant::<ByteBuf>().map(|bytes| OpenValue::Unknown(bytes.0)),
+        }
+    }
+}
+
+const PROTOCOL_FIELD: &[&str] = &["id", "criticality", "value"];
+
+impl<S: ObjectSet + Serialize> Serialize for ProtocolField<S> {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        let ret = serializer.serialize_struct("ProtocolField", 3);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut s = ret.unwrap();
+        let ret = s.serialize_field("id", &self.id);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = s.serialize_field("criticality", &self.criticality);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = s.serialize_field("value", &self.value);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        s.end()
+    }
+}
+
+impl<'de, S: ObjectSet + Deserialize<'de>> Deserialize<'de> for ProtocolField<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ProtocolField<S>, D::Error> {
+        deserializer.deserialize_struct("ProtocolField", PROTOCOL_FIELD, ProtocolFieldVisitor(PhantomData))
+    }
+}
+
+struct ProtocolFieldVisitor<S>(PhantomData<S>);
+
+impl<'de, S: ObjectSet + Deserialize<'de>> Visitor<'de> for ProtocolFieldVisitor<S> {
+    type Value = ProtocolField<S>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a ProtocolField")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ProtocolField<S>, A::Error> {
+        let id = next_element(&mut seq, 0, &self);
+        if id.is_err() {
+            return Err(id.err().unwrap());
+        }
+        let criticality = next_element(&mut seq, 1, &self);
+        if criticality.is_err() {
+            return Err(criticality.err().unwrap());
+        }
+        let value = next_element(&mut seq, 2, &self);
+        if value.is_err() {
+            return Err(value.err().unwrap());
+        }
+        Ok(ProtocolField {
+            id: id.unwrap(),
+            criticality: criticality.unwrap(),
+            value: value.unwrap(),
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ProtocolField<S>, A::Error> {
+        let (mut id, mut criticality, mut value) = (None, None, None);
+        loop {
+            let ret = map.next_key_seed(Identifier {
+                names: PROTOCOL_FIELD,
+                variant: false,
+            });
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = match ret.unwrap() {
+                Some(0) => next_value(&mut map, &mut id, "id"),
+                Some(1) => next_value(&mut map, &mut criticality, "criticality"),
+                Some(_) => next_value(&mut map, &mut value, "value"),
+                None => break,
+            };
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        let id = required(id, "id");
+        if id.is_err() {
+            return Err(id.err().unwrap());
+        }
+        let criticality = required(criticality, "criticality");
+        if criticality.is_err() {
+            return Err(criticality.err().unwrap());
+        }
+        let value = required(value, "value");
+        if value.is_err() {
+            return Err(value.err().unwrap());
+        }
+        Ok(ProtocolField {
+            id: id.unwrap(),
+            criticality: criticality.unwrap(),
+            value: value.unwrap(),
+        })
+    }
+}
+
+/// A `ProtocolContainer` is the sequence of its fields.
+impl<S: ObjectSet + Serialize> Serialize for ProtocolContainer<S> {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        self.fields.serialize(serializer)
+    }
+}
+
+impl<'de, S: ObjectSet + Deserialize<'de>> Deserialize<'de> for ProtocolContainer<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ProtocolContainer<S>, D::Error> {
+        Vec::deserialize(deserializer).map(ProtocolContainer::new)
+    }
+}
+
+const UNKNOWN_ALTERNATIVE: &[&str] = &["index", "value"];
+
+impl Serialize for UnknownAlternative {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ret = serializer.serialize_struct("UnknownAlternative", 2);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut s = ret.unwrap();
+        let ret = s.serialize_field("index", &self.index);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = s.serialize_field("value", &Bytes(&self.value));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownAlternative {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<UnknownAlternative, D::Error> {
+        deserializer.deserialize_struct("UnknownAlternative", UNKNOWN_ALTERNATIVE, UnknownAlternativeVisitor)
+    }
+}
+
+struct UnknownAlternativeVisitor;
+
+impl<'de> Visitor<'de> for UnknownAlternativeVisitor {
+    type Value = UnknownAlternative;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an UnknownAlternative")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<UnknownAlternative, A::Error> {
+        let index = next_element(&mut seq, 0, &self);
+        if index.is_err() {
+            return Err(index.err().unwrap());
+        }
+        let value = next_element::<A, ByteBuf>(&mut seq, 1, &self);
+        if value.is_err() {
+            return Err(value.err().unwrap());
+        }
+        Ok(UnknownAlternative {
+            index: index.unwrap(),
+            value: value.unwrap().0,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<UnknownAlternative, A::Error> {
+        let (mut index, mut value) = (None, None::<ByteBuf>);
+        loop {
+            let ret = map.next_key_seed(Identifier {
+                names: UNKNOWN_ALTERNATIVE,
+                variant: false,
+            });
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = match ret.unwrap() {
+                Some(0) => next_value(&mut map, &mut index, "index"),
+                Some(_) => next_value(&mut map, &mut value, "value"),
+                None => break,
+            };
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        let index = required(index, "index");
+        if index.is_err() {
+            return Err(index.err().unwrap());
+        }
+        let value = required(value, "value");
+        if value.is_err() {
+            return Err(value.err().unwrap());
+        }
+        Ok(UnknownAlternative {
+            index: index.unwrap(),
+            value: value.unwrap().0,
+        })
+    }
+}
+
+/// The positions and octets of the present unknown additions.
+struct UnknownValues<'a>(&'a [(usize, Vec<u8>)]);
+
+impl<'a> Serialize for UnknownValues<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|&(i, ref bytes)| (i as u64, Bytes(bytes))))
+    }
+}
+
+const UNKNOWN_EXTENSIONS: &[&str] = &["count", "values"];
+
+/// `UnknownExtensions` are the size of the received presence bitmap and the positions and octets of the present
+/// additions.
+impl Serialize for UnknownExtensions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ret = serializer.serialize_struct("UnknownExtensions", 2);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut s = ret.unwrap();
+        let ret = s.serialize_field("count", &(self.count as u64));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = s.serialize_field("values", &UnknownValues(&self.values));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownExtensions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<UnknownExtensions, D::Error> {
+        deserializer.deserialize_struct("UnknownExtensions", UNKNOWN_EXTENSIONS, UnknownExtensionsVisitor)
+    }
+}
+
+struct UnknownExtensionsVisitor;
+
+impl UnknownExtensionsVisitor {
+    fn build<E: de::Error>(&self, count: u64, values: Vec<(u64, ByteBuf)>) -> Result<UnknownExtensions, E> {
+        if values.iter().any(|&(i, _)| i >= count) {
+            return Err(E::invalid_value(Unexpected::Unsigned(count), self));
+        }
+        Ok(UnknownExtensions {
+            count: count as usize,
+            values: values.into_iter().map(|(i, bytes)| (i as usize, bytes.0)).collect(),
+        })
+    }
+}
+
+impl<'de> Visitor<'de> for UnknownExtensionsVisitor {
+    type Value = UnknownExtensions;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("UnknownExtensions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<UnknownExtensions, A::Error> {
+        let count = next_element(&mut seq, 0, &self);
+        if count.is_err() {
+            return Err(count.err().unwrap());
+        }
+        let values = next_element(&mut seq, 1, &self);
+        if values.is_err() {
+            return Err(values.err().unwrap());
+        }
+        self.build(count.unwrap(), values.unwrap())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<UnknownExtensions, A::Error> {
+        let (mut count, mut values) = (None, None);
+        loop {
+            let ret = map.next_key_seed(Identifier {
+                names: UNKNOWN_EXTENSIONS,
+                variant: false,
+            });
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = match ret.unwrap() {
+                Some(0) => next_value(&mut map, &mut count, "count"),
+                Some(_) => next_value(&mut map, &mut values, "values"),
+                None => break,
+            };
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        let count = required(count, "count");
+        if count.is_err() {
+            return Err(count.err().unwrap());
+        }
+        let values = required(values, "values");
+        if values.is_err() {
+            return Err(values.err().unwrap());
+        }
+        self.build(count.unwrap(), values.unwrap())
+    }
+}
+
+/// The octets of the known additions, `None` for the absent ones.
+struct AdditionValues<'a>(&'a [Option<Vec<u8>>]);
+
+impl<'a> Serialize for AdditionValues<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|value| value.as_ref().map(|bytes| Bytes(bytes))))
+    }
+}
+
+const EXTENSION_ADDITIONS: &[&str] = &["values", "lossless", "canonical"];
+
+/// `ExtensionAdditions` are the octets of the additions and the decoder settings they were read with, so that they
+/// decode the same way after a round trip.
+impl Serialize for ExtensionAdditions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ret = serializer.serialize_struct("ExtensionAdditions", 3);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut s = ret.unwrap();
+        let ret = s.serialize_field("values", &AdditionValues(&self.values));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = s.serialize_field("lossless", &self.lossless);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = s.serialize_field("canonical", &self.canonical);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ExtensionAdditions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ExtensionAdditions, D::Error> {
+        deserializer.deserialize_struct("ExtensionAdditions", EXTENSION_ADDITIONS, ExtensionAdditionsVisitor)
+    }
+}
+
+struct ExtensionAdditionsVisitor;
+
+fn addition_values(values: Vec<Option<ByteBuf>>) -> Vec<Option<Vec<u8>>> {
+    values.into_iter().map(|value| value.map(|bytes| bytes.0)).collect()
+}
+
+impl<'de> Visitor<'de> for ExtensionAdditionsVisitor {
+    type Value = ExtensionAdditions;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ExtensionAdditions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ExtensionAdditions, A::Error> {
+        let values = next_element(&mut seq, 0, &self);
+        if values.is_err() {
+            return Err(values.err().unwrap());
+        }
+        let lossless = next_element(&mut seq, 1, &self);
+        if lossless.is_err() {
+            return Err(lossless.err().unwrap());
+        }
+        let canonical = next_element(&mut seq, 2, &self);
+        if canonical.is_err() {
+            return Err(canonical.err().unwrap());
+        }
+        Ok(ExtensionAdditions {
+            values: addition_values(values.unwrap()),
+            lossless: lossless.unwrap(),
+            canonical: canonical.unwrap(),
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ExtensionAdditions, A::Error> {
+        let (mut values, mut lossless, mut canonical) = (None, None, None);
+        loop {
+            let ret = map.next_key_seed(Identifier {
+                names: EXTENSION_ADDITIONS,
+                variant: false,
+            });
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let ret = match ret.unwrap() {
+                Some(0) => next_value(&mut map, &mut values, "values"),
+                Some(1) => next_value(&mut map, &mut lossless, "lossless"),
+                Some(_) => next_value(&mut map, &mut canonical, "canonical"),
+                None => break,
+            };
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        let values = required(values, "values");
+        if values.is_err() {
+            return Err(values.err().unwrap());
+        }
+        Ok(ExtensionAdditions {
+            values: addition_values(values.unwrap()),
+            lossless: lossless.unwrap_or(false),
+            canonical: canonical.unwrap_or(false),
+        })
+    }
+}
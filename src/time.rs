@@ -0,0 +1,1569 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_int};
+use ber::{self, BerElement, Rules, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
+use char_string::{VisibleString, decode_chars, encode_chars};
+use std::fmt;
+use std::str::FromStr;
+
+/// The reasons a time value can be invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeError {
+    /// The string form doesn't follow the syntax of the type.
+    InvalidFormat,
+    /// A field is out of range, e.g. a month of 13 or a 31st of April.
+    InvalidValue,
+    /// The value can't be represented by the type it is being converted to.
+    Unrepresentable,
+}
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn parse_digits(s: &[u8]) -> Result<u32, TimeError> {
+    if s.is_empty() || s.len() > 9 || !s.iter().all(|b| b.is_ascii_digit()) {
+        return Err(TimeError::InvalidFormat);
+    }
+    Ok(s.iter().fold(0, |acc, &b| acc * 10 + (b - b'0') as u32))
+}
+
+/// Parse a UTC offset: nothing (local time), `Z`, or a sign followed by `hhmm` (or just `hh` if `hours_only`).
+fn parse_utc_offset(s: &[u8], hours_only: bool) -> Result<Option<i16>, TimeError> {
+    if s.is_empty() {
+        return Ok(None);
+    }
+    if s == b"Z" {
+        return Ok(Some(0));
+    }
+    let sign = match s[0] {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return Err(TimeError::InvalidFormat),
+    };
+    if s.len() != 5 && !(hours_only && s.len() == 3) {
+        return Err(TimeError::InvalidFormat);
+    }
+    let ret = parse_digits(&s[1..3]);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let hours = ret.unwrap();
+    let minutes = if s.len() == 5 {
+        let ret = parse_digits(&s[3..5]);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        ret.unwrap()
+    } else {
+        0
+    };
+    if hours > 23 || minutes > 59 {
+        return Err(TimeError::InvalidValue);
+    }
+    Ok(Some(sign * (hours * 60 + minutes) as i16))
+}
+
+fn fmt_utc_offset(offset: Option<i16>) -> String {
+    match offset {
+        None => String::new(),
+        Some(0) => "Z".to_string(),
+        Some(m) => {
+            let sign = if m < 0 { '-' } else { '+' };
+            format!("{}{:02}{:02}", sign, m.abs() / 60, m.abs() % 60)
+        }
+    }
+}
+
+fn check_utc_offset(offset: Option<i16>) -> Result<(), TimeError> {
+    match offset {
+        Some(m) if m.abs() >= 24 * 60 => Err(TimeError::InvalidValue),
+        _ => Ok(()),
+    }
+}
+
+fn append(enc: &mut Encoding, other: Result<Encoding, EncodeError>) -> Result<(), EncodeError> {
+    if other.is_err() {
+        return Err(other.err().unwrap());
+    }
+    enc.append(&other.unwrap())
+}
+
+/// The year of a DATE or DATE-TIME, encoded as the YEAR-ENCODING CHOICE of X.691 clause 32.
+fn encode_year(year: i32) -> Result<Encoding, EncodeError> {
+    let (choice, min, max) = match year {
+        2005..=2020 => (0, Some(2005), Some(2020)),
+        2021..=2276 => (1, Some(2021), Some(2276)),
+        1749..=2004 => (2, Some(1749), Some(2004)),
+        _ => (3, None, None),
+    };
+    let ret = encode_int(choice, Some(0), Some(3));
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let mut enc = ret.unwrap();
+    let ret = append(&mut enc, encode_int(year as i64, min, max));
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(enc)
+}
+
+fn decode_year(decoder: &mut Decoder) -> Result<i32, DecodeError> {
+    let ret = decoder.decode_int(Some(0), Some(3));
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let choice = ret.unwrap();
+    let (min, max) = match choice {
+        0 => (Some(2005), Some(2020)),
+        1 => (Some(2021), Some(2276)),
+        2 => (Some(1749), Some(2004)),
+        _ => (None, None),
+    };
+    let ret = decoder.decode_int(min, max);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let year = ret.unwrap();
+    if year < i32::MIN as i64 || year > i32::MAX as i64 {
+        return Err(DecodeError::InvalidTime);
+    }
+    Ok(year as i32)
+}
+
+/// An ASN.1 DATE, i.e. a calendar date like `2024-02-29`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::Date;
+///
+/// let date: Date = "2024-02-29".parse().unwrap();
+/// println!("{}", date.day()); // Prints 29
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// Construct a `Date`. Returns an `Err` if the month or day is out of range.
+    pub fn new(year: i32, month: u8, day: u8) -> Result<Date, TimeError> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(TimeError::InvalidValue);
+        }
+        Ok(Date { year, month, day })
+    }
+
+    /// Get the year.
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// Get the month, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Get the day of the month, from 1 to 31.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl FromStr for Date {
+    type Err = TimeError;
+
+    /// Parse a `Date` of the form `YYYY-MM-DD`. Years beyond 9999 or before 0 need a sign, e.g. `-0044-03-15`.
+    fn from_str(s: &str) -> Result<Date, TimeError> {
+        let (sign, unsigned) = match s.as_bytes().first() {
+            Some(&b'-') => (-1, &s[1..]),
+            Some(&b'+') => (1, &s[1..]),
+            _ => (1, s),
+        };
+        let b = unsigned.as_bytes();
+        if b.len() < 10 || b[b.len() - 3] != b'-' || b[b.len() - 6] != b'-' {
+            return Err(TimeError::InvalidFormat);
+        }
+        let fields: Vec<Result<u32, TimeError>> = vec![parse_digits(&b[..b.len() - 6]),
+                                                       parse_digits(&b[b.len() - 5..b.len() - 3]),
+                                                       parse_digits(&b[b.len() - 2..])];
+        if let Some(err) = fields.iter().find(|f| f.is_err()) {
+            return Err(err.unwrap_err());
+        }
+        let fields: Vec<u32> = fields.into_iter().map(|f| f.unwrap()).collect();
+        if fields[0] > i32::MAX as u32 {
+            return Err(TimeError::InvalidValue);
+        }
+        Date::new(sign * fields[0] as i32, fields[1] as u8, fields[2] as u8)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.year < 0 {
+            f.write_str("-")?;
+        } else if self.year > 9999 {
+            f.write_str("+")?;
+        }
+        write!(f, "{:04}-{:02}-{:02}", self.year.unsigned_abs(), self.month, self.day)
+    }
+}
+
+impl APerElement for Date {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `Date` from an aligned PER encoding (a YEAR-MONTH-DAY-ENCODING).
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let year = decode_year(decoder);
+        if year.is_err() {
+            return Err(year.err().unwrap());
+        }
+        let month = decoder.decode_int(Some(1), Some(12));
+        if month.is_err() {
+            return Err(month.err().unwrap());
+        }
+        let day = decoder.decode_int(Some(1), Some(31));
+        if day.is_err() {
+            return Err(day.err().unwrap());
+        }
+        match Date::new(year.unwrap(), month.unwrap() as u8, day.unwrap() as u8) {
+            Ok(date) => Ok(date),
+            Err(_) => Err(DecodeError::InvalidTime),
+        }
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        let ret = encode_year(self.year);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut enc = ret.unwrap();
+        let ret = append(&mut enc, encode_int(self.month as i64, Some(1), Some(12)));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = append(&mut enc, encode_int(self.day as i64, Some(1), Some(31)));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(enc)
+    }
+}
+
+/// An ASN.1 TIME-OF-DAY, i.e. a local time like `13:45:30`, to the second.
+///
+/// A second of 60 is allowed for leap seconds, and `24:00:00` for the end of a day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay {
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl TimeOfDay {
+    /// Construct a `TimeOfDay`. Returns an `Err` if a field is out of range.
+    pub fn new(hour: u8, minute: u8, second: u8) -> Result<TimeOfDay, TimeError> {
+        if hour > 24 || minute > 59 || second > 60 || hour == 24 && (minute > 0 || second > 0) {
+            return Err(TimeError::InvalidValue);
+        }
+        Ok(TimeOfDay { hour, minute, second })
+    }
+
+    /// Get the hour, from 0 to 24.
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Get the minute, from 0 to 59.
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Get the second, from 0 to 60.
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = TimeError;
+
+    /// Parse a `TimeOfDay` of the form `hh:mm:ss`.
+    fn from_str(s: &str) -> Result<TimeOfDay, TimeError> {
+        let b = s.as_bytes();
+        if b.len() != 8 || b[2] != b':' || b[5] != b':' {
+            return Err(TimeError::InvalidFormat);
+        }
+        let mut fields = [0u8; 3];
+        for (i, field) in fields.iter_mut().enumerate() {
+            let ret = parse_digits(&b[i * 3..i * 3 + 2]);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            *field = ret.unwrap() as u8;
+        }
+        TimeOfDay::new(fields[0], fields[1], fields[2])
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+impl APerElement for TimeOfDay {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `TimeOfDay` from an aligned PER encoding (an HOURS-MINUTES-SECONDS-ENCODING).
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let hour = decoder.decode_int(Some(0), Some(24));
+        if hour.is_err() {
+            return Err(hour.err().unwrap());
+        }
+        let minute = decoder.decode_int(Some(0), Some(59));
+        if minute.is_err() {
+            return Err(minute.err().unwrap());
+        }
+        let second = decoder.decode_int(Some(0), Some(60));
+        if second.is_err() {
+            return Err(second.err().unwrap());
+        }
+        match TimeOfDay::new(hour.unwrap() as u8, minute.unwrap() as u8, second.unwrap() as u8) {
+            Ok(time) => Ok(time),
+            Err(_) => Err(DecodeError::InvalidTime),
+        }
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        let ret = encode_int(self.hour as i64, Some(0), Some(24));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut enc = ret.unwrap();
+        let ret = append(&mut enc, encode_int(self.minute as i64, Some(0), Some(59)));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = append(&mut enc, encode_int(self.second as i64, Some(0), Some(60)));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(enc)
+    }
+}
+
+/// An ASN.1 DATE-TIME, i.e. a local date and time like `2024-02-29T13:45:30`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    date: Date,
+    time: TimeOfDay,
+}
+
+impl DateTime {
+    /// Construct a `DateTime` from its date and time of day.
+    pub fn new(date: Date, time: TimeOfDay) -> DateTime {
+        DateTime { date, time }
+    }
+
+    /// Get the date.
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    /// Get the time of day.
+    pub fn time(&self) -> TimeOfDay {
+        self.time
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = TimeError;
+
+    /// Parse a `DateTime` of the form `YYYY-MM-DDThh:mm:ss`.
+    fn from_str(s: &str) -> Result<DateTime, TimeError> {
+        let mut parts = s.splitn(2, 'T');
+        let date = parts.next().unwrap().parse::<Date>();
+        if date.is_err() {
+            return Err(date.err().unwrap());
+        }
+        let time = match parts.next() {
+            Some(t) => t.parse::<TimeOfDay>(),
+            None => Err(TimeError::InvalidFormat),
+        };
+        if time.is_err() {
+            return Err(time.err().unwrap());
+        }
+        Ok(DateTime::new(date.unwrap(), time.unwrap()))
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)
+    }
+}
+
+impl APerElement for DateTime {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `DateTime` from an aligned PER encoding (a DATE-TIME-ENCODING).
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let date = Date::from_aper(decoder, constraints);
+        if date.is_err() {
+            return Err(date.err().unwrap());
+        }
+        let time = TimeOfDay::from_aper(decoder, constraints);
+        if time.is_err() {
+            return Err(time.err().unwrap());
+        }
+        Ok(DateTime::new(date.unwrap(), time.unwrap()))
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        let ret = self.date.to_aper(constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let mut enc = ret.unwrap();
+        let ret = append(&mut enc, self.time.to_aper(constraints));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(enc)
+    }
+}
+
+const DATE_DESIGNATORS: [char; 4] = ['Y', 'M', 'W', 'D'];
+const TIME_DESIGNATORS: [char; 3] = ['H', 'M', 'S'];
+
+/// An ASN.1 DURATION, i.e. an ISO 8601 duration like `P1Y2M10DT2H30M` or `PT0.5S`.
+///
+/// Each component is optional. A decimal fraction may be attached to the last component that is present, and is kept
+/// as its number of digits and their value so that e.g. `.50` and `.5` stay distinct.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::Duration;
+///
+/// let d: Duration = "PT1.25S".parse().unwrap();
+/// println!("{:?} {:?}", d.seconds(), d.fraction()); // Prints Some(1) Some((2, 25))
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Duration {
+    // years, months, weeks, days, hours, minutes, seconds
+    components: [Option<u64>; 7],
+    fraction: Option<(u32, u64)>,
+}
+
+macro_rules! duration_component {
+    ($get:ident, $set:ident, $i:expr, $name:expr) => {
+        #[doc = concat!("Get the number of ", $name, ".")]
+        pub fn $get(&self) -> Option<u64> {
+            self.components[$i]
+        }
+
+        #[doc = concat!("Set the number of ", $name, ", or `None` to leave them out.")]
+        pub fn $set(&mut self, n: Option<u64>) {
+            self.components[$i] = n;
+        }
+    };
+}
+
+impl Duration {
+    /// Construct a `Duration` with no components.
+    pub fn new() -> Duration {
+        Duration::default()
+    }
+
+    duration_component!(years, set_years, 0, "years");
+    duration_component!(months, set_months, 1, "months");
+    duration_component!(weeks, set_weeks, 2, "weeks");
+    duration_component!(days, set_days, 3, "days");
+    duration_component!(hours, set_hours, 4, "hours");
+    duration_component!(minutes, set_minutes, 5, "minutes");
+    duration_component!(seconds, set_seconds, 6, "seconds");
+
+    /// Get the decimal fraction of the last component as `(number of digits, value)`.
+    pub fn fraction(&self) -> Option<(u32, u64)> {
+        self.fraction
+    }
+
+    /// Set the decimal fraction of the last component as `(number of digits, value)`, e.g. `(3, 5)` for `.005`.
+    pub fn set_fraction(&mut self, fraction: Option<(u32, u64)>) {
+        self.fraction = fraction;
+    }
+}
+
+impl FromStr for Duration {
+    type Err = TimeError;
+
+    /// Parse a `Duration` in the ISO 8601 form `PnYnMnWnDTnHnMnS`, where each component is optional.
+    fn from_str(s: &str) -> Result<Duration, TimeError> {
+        if !s.starts_with('P') {
+            return Err(TimeError::InvalidFormat);
+        }
+        let mut duration = Duration::new();
+        let mut next = 0;
+        let mut in_time = false;
+        let mut rest = &s[1..];
+        while !rest.is_empty() {
+            if rest.starts_with('T') && !in_time {
+                in_time = true;
+                next = DATE_DESIGNATORS.len();
+                rest = &rest[1..];
+                if rest.is_empty() {
+                    return Err(TimeError::InvalidFormat);
+                }
+                continue;
+            }
+            if duration.fraction.is_some() {
+                return Err(TimeError::InvalidFormat);
+            }
+
+            let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.' && c != ',').unwrap_or(rest.len());
+            let mut number = rest[..end].splitn(2, ['.', ',']);
+            let int = number.next().unwrap();
+            if int.is_empty() || int.len() > 19 || end == rest.len() {
+                return Err(TimeError::InvalidFormat);
+            }
+            let designator = rest[end..].chars().next().unwrap();
+            let (designators, offset) = if in_time {
+                (&TIME_DESIGNATORS[..], DATE_DESIGNATORS.len())
+            } else {
+                (&DATE_DESIGNATORS[..], 0)
+            };
+            let i = match designators.iter().position(|&d| d == designator) {
+                Some(i) if i + offset >= next => i + offset,
+                _ => return Err(TimeError::InvalidFormat),
+            };
+            match int.parse::<u64>() {
+                Ok(n) => duration.components[i] = Some(n),
+                Err(_) => return Err(TimeError::InvalidValue),
+            }
+            if let Some(frac) = number.next() {
+                if frac.is_empty() || frac.len() > 19 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(TimeError::InvalidFormat);
+                }
+                duration.fraction = Some((frac.len() as u32, frac.parse().unwrap()));
+            }
+            next = i + 1;
+            rest = &rest[end + designator.len_utf8()..];
+        }
+        if duration.components.iter().all(|c| c.is_none()) {
+            return Err(TimeError::InvalidFormat);
+        }
+        Ok(duration)
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let last = match self.components.iter().rposition(|c| c.is_some()) {
+            Some(last) => last,
+            None => return f.write_str("PT0S"),
+        };
+        f.write_str("P")?;
+        let designators = DATE_DESIGNATORS.iter().chain(TIME_DESIGNATORS.iter());
+        for (i, (component, designator)) in self.components.iter().zip(designators).enumerate() {
+            if i == DATE_DESIGNATORS.len() && self.components[i..].iter().any(|c| c.is_some()) {
+                f.write_str("T")?;
+            }
+            if let Some(n) = *component {
+                write!(f, "{}", n)?;
+                if let (true, Some((digits, value))) = (i == last, self.fraction) {
+                    write!(f, ".{:0width$}", value, width = digits as usize)?;
+                }
+                write!(f, "{}", designator)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl APerElement for Duration {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `Duration` from an aligned PER encoding (a DURATION-INTERVAL-ENCODING).
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = decoder.read(8);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let present = ret.unwrap();
+
+        let read_component = |decoder: &mut Decoder, min: i64| {
+            let ret = decoder.decode_int(Some(min), None);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            Ok(ret.unwrap() as u64)
+        };
+
+        let mut duration = Duration::new();
+        for i in 0..duration.components.len() {
+            if present & (0x80 >> i) > 0 {
+                let ret = read_component(decoder, 0);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                duration.components[i] = Some(ret.unwrap());
+            }
+        }
+        if present & 0x01 > 0 {
+            let digits = read_component(decoder, 1);
+            if digits.is_err() {
+                return Err(digits.err().unwrap());
+            }
+            let value = read_component(decoder, 0);
+            if value.is_err() {
+                return Err(value.err().unwrap());
+            }
+            let digits = digits.unwrap();
+            if digits > u32::MAX as u64 {
+                return Err(DecodeError::InvalidTime);
+            }
+            duration.fraction = Some((digits as u32, value.unwrap()));
+        }
+        Ok(duration)
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        let mut present: u64 = 0;
+        for (i, component) in self.components.iter().enumerate() {
+            if component.is_some() {
+                present |= 0x80 >> i;
+            }
+        }
+        if self.fraction.is_some() {
+            present |= 0x01;
+        }
+        let mut enc = Encoding::with_uint(present, 8);
+
+        let mut values: Vec<(u64, i64)> = self.components.iter().filter_map(|c| c.map(|n| (n, 0))).collect();
+        if let Some((digits, value)) = self.fraction {
+            // The number of digits is at least 1, but the value of the fraction may be 0
+            values.push((digits as u64, 1));
+            values.push((value, 0));
+        }
+        for (value, min) in values {
+            if value > i64::MAX as u64 || (value as i64) < min {
+                return Err(EncodeError::InvalidValue);
+            }
+            let ret = append(&mut enc, encode_int(value as i64, Some(min), None));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(enc)
+    }
+}
+
+/// Split the fraction of the last of `units` (in seconds) into whole seconds and nanoseconds.
+fn parse_fraction(digits: &[u8], unit: u64) -> (u64, u32) {
+    let digits = &digits[..digits.len().min(18)];
+    let value = digits.iter().fold(0u128, |acc, &b| acc * 10 + (b - b'0') as u128);
+    let nanos = value * unit as u128 * 1_000_000_000 / 10u128.pow(digits.len() as u32);
+    ((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32)
+}
+
+fn check_time(time: TimeOfDay, nanosecond: u32, utc_offset: Option<i16>) -> Result<(), TimeError> {
+    if time.hour() > 23 || nanosecond >= 1_000_000_000 {
+        return Err(TimeError::InvalidValue);
+    }
+    check_utc_offset(utc_offset)
+}
+
+/// An ASN.1 GeneralizedTime, e.g. `20240229134530.25Z`.
+///
+/// The minutes, seconds and fraction are optional, and the UTC offset is either `Z`, `+hh[mm]`, `-hh[mm]` or left out
+/// for a local time. The original string is kept so it can be encoded exactly as it was received.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::GeneralizedTime;
+///
+/// let t: GeneralizedTime = "20240229134530.25+0100".parse().unwrap();
+/// println!("{} {:?}", t.date_time(), t.utc_offset()); // Prints 2024-02-29T13:45:30 Some(60)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GeneralizedTime {
+    text: String,
+    date_time: DateTime,
+    nanosecond: u32,
+    utc_offset: Option<i16>,
+}
+
+impl GeneralizedTime {
+    /// Construct a `GeneralizedTime` from its string form.
+    pub fn new(s: &str) -> Result<GeneralizedTime, TimeError> {
+        let b = s.as_bytes();
+        if !s.is_ascii() || b.len() < 10 {
+            return Err(TimeError::InvalidFormat);
+        }
+        let mut fields = [0u32; 6];
+        let mut n = 0;
+        let mut pos = 0;
+        while n < 6 && pos + 2 <= b.len() && b[pos].is_ascii_digit() {
+            let width = if n == 0 { 4 } else { 2 };
+            if pos + width > b.len() {
+                return Err(TimeError::InvalidFormat);
+            }
+            let ret = parse_digits(&b[pos..pos + width]);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            fields[n] = ret.unwrap();
+            n += 1;
+            pos += width;
+        }
+        if n < 4 {
+            return Err(TimeError::InvalidFormat);
+        }
+
+        let mut extra_seconds = 0;
+        let mut nanosecond = 0;
+        if pos < b.len() && (b[pos] == b'.' || b[pos] == b',') {
+            let start = pos + 1;
+            pos = start;
+            while pos < b.len() && b[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            if pos == start {
+                return Err(TimeError::InvalidFormat);
+            }
+            let unit = [3600, 60, 1][n - 4];
+            let (s, ns) = parse_fraction(&b[start..pos], unit);
+            extra_seconds = s as u32;
+            nanosecond = ns;
+        }
+        let utc_offset = parse_utc_offset(&b[pos..], true);
+        if utc_offset.is_err() {
+            return Err(utc_offset.err().unwrap());
+        }
+
+        let date = Date::new(fields[0] as i32, fields[1] as u8, fields[2] as u8);
+        if date.is_err() {
+            return Err(date.err().unwrap());
+        }
+        let minute = fields[4] + extra_seconds / 60;
+        let second = fields[5] + extra_seconds % 60;
+        let time = TimeOfDay::new(fields[3] as u8, minute as u8, second as u8);
+        if time.is_err() {
+            return Err(time.err().unwrap());
+        }
+        let ret = check_time(time.unwrap(), nanosecond, utc_offset.unwrap());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(GeneralizedTime {
+            text: s.to_string(),
+            date_time: DateTime::new(date.unwrap(), time.unwrap()),
+            nanosecond,
+            utc_offset: utc_offset.unwrap(),
+        })
+    }
+
+    /// Construct a `GeneralizedTime` from its parts, with `utc_offset` in minutes east of UTC (`None` for local time).
+    /// The string form always has seconds, has only as many fraction digits as needed and uses `Z` for UTC.
+    pub fn from_parts(date_time: DateTime,
+                      nanosecond: u32,
+                      utc_offset: Option<i16>)
+                      -> Result<GeneralizedTime, TimeError> {
+        let (date, time) = (date_time.date(), date_time.time());
+        if date.year() < 0 || date.year() > 9999 {
+            return Err(TimeError::InvalidValue);
+        }
+        let ret = check_time(time, nanosecond, utc_offset);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let mut text = format!("{:04}{:02}{:02}{:02}{:02}{:02}",
+                               date.year(),
+                               date.month(),
+                               date.day(),
+                               time.hour(),
+                               time.minute(),
+                               time.second());
+        if nanosecond > 0 {
+            text.push_str(format!(".{:09}", nanosecond).trim_end_matches('0'));
+        }
+        text.push_str(&fmt_utc_offset(utc_offset));
+        Ok(GeneralizedTime {
+            text,
+            date_time,
+            nanosecond,
+            utc_offset,
+        })
+    }
+
+    /// Get the string form.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Check if the string form is the one DER and CER require: UTC, with seconds, and with a fraction (after a `.`)
+    /// only if it isn't 0 and without trailing 0s.
+    pub fn is_canonical(&self) -> bool {
+        let b = self.text.as_bytes();
+        if b.len() < 15 || !b[..14].iter().all(|c| c.is_ascii_digit()) || b[b.len() - 1] != b'Z' {
+            return false;
+        }
+        let fraction = &b[14..b.len() - 1];
+        fraction.is_empty() ||
+        fraction.len() > 1 && fraction[0] == b'.' && fraction[1..].iter().all(|c| c.is_ascii_digit()) &&
+        fraction[fraction.len() - 1] != b'0'
+    }
+
+    /// Get the date and time, as they appear in the string (i.e. in the local time of `utc_offset()`).
+    pub fn date_time(&self) -> DateTime {
+        self.date_time
+    }
+
+    /// Get the fraction of the second in nanoseconds.
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+
+    /// Get the UTC offset in minutes east of UTC, or `None` for a local time.
+    pub fn utc_offset(&self) -> Option<i16> {
+        self.utc_offset
+    }
+}
+
+impl FromStr for GeneralizedTime {
+    type Err = TimeError;
+
+    fn from_str(s: &str) -> Result<GeneralizedTime, TimeError> {
+        GeneralizedTime::new(s)
+    }
+}
+
+impl fmt::Display for GeneralizedTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// GeneralizedTime is encoded as an unconstrained VisibleString.
+impl APerElement for GeneralizedTime {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `GeneralizedTime` from an aligned PER encoding. With CANONICAL-PER, the string must be in the canonical
+    /// form, with the seconds, a `Z` and no trailing 0s in the fraction.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = decode_chars(decoder, &VisibleString::alphabet(), None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let t = match GeneralizedTime::new(&ret.unwrap()) {
+            Ok(t) => t,
+            Err(_) => return Err(DecodeError::InvalidTime),
+        };
+        if decoder.is_canonical() && !t.is_canonical() {
+            return Err(DecodeError::NonCanonicalTime);
+        }
+        Ok(t)
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        encode_chars(&self.text, &VisibleString::alphabet(), None)
+    }
+}
+
+impl BerElement for GeneralizedTime {
+    const TAG: Tag = Tag::GENERALIZED_TIME;
+
+    /// Read a `GeneralizedTime` from a BER encoding. With DER, the string must be in the canonical form, with the
+    /// seconds, a `Z` and no trailing 0s in the fraction.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_string(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let t = match String::from_utf8(ret.unwrap()) {
+            Ok(s) => match GeneralizedTime::new(&s) {
+                Ok(t) => t,
+                Err(_) => return Err(ber::DecodeError::InvalidTime),
+            },
+            Err(_) => return Err(ber::DecodeError::InvalidTime),
+        };
+        if decoder.rules() != Rules::Ber && !t.is_canonical() {
+            return Err(ber::DecodeError::NonCanonicalTime);
+        }
+        Ok(t)
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::string(tag, self.text.as_bytes().to_vec()))
+    }
+}
+
+/// An ASN.1 UTCTime, e.g. `240229134530Z`.
+///
+/// The seconds are optional and a UTC offset (`Z`, `+hhmm` or `-hhmm`) is required. Two-digit years from 50 to 99
+/// are in the 1900s and the rest in the 2000s, as in RFC 5280. The original string is kept so it can be encoded
+/// exactly as it was received.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UTCTime {
+    text: String,
+    date_time: DateTime,
+    utc_offset: i16,
+}
+
+impl UTCTime {
+    /// Construct a `UTCTime` from its string form.
+    pub fn new(s: &str) -> Result<UTCTime, TimeError> {
+        let b = s.as_bytes();
+        if !s.is_ascii() || b.len() < 11 {
+            return Err(TimeError::InvalidFormat);
+        }
+        let n = if b.len() >= 13 && b[10].is_ascii_digit() { 6 } else { 5 };
+        let mut fields = [0u32; 6];
+        for (i, field) in fields.iter_mut().enumerate().take(n) {
+            let ret = parse_digits(&b[i * 2..i * 2 + 2]);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            *field = ret.unwrap();
+        }
+        let utc_offset = match parse_utc_offset(&b[n * 2..], false) {
+            Ok(Some(offset)) => offset,
+            Ok(None) => return Err(TimeError::InvalidFormat),
+            Err(e) => return Err(e),
+        };
+
+        let year = if fields[0] >= 50 { 1900 } else { 2000 } + fields[0] as i32;
+        let date = Date::new(year, fields[1] as u8, fields[2] as u8);
+        if date.is_err() {
+            return Err(date.err().unwrap());
+        }
+        let time = TimeOfDay::new(fields[3] as u8, fields[4] as u8, fields[5] as u8);
+        if time.is_err() {
+            return Err(time.err().unwrap());
+        }
+        let ret = check_time(time.unwrap(), 0, Some(utc_offset));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(UTCTime {
+            text: s.to_string(),
+            date_time: DateTime::new(date.unwrap(), time.unwrap()),
+            utc_offset,
+        })
+    }
+
+    /// Construct a `UTCTime` from its parts, with `utc_offset` in minutes east of UTC. The year must be from 1950 to
+    /// 2049. The string form always has seconds and uses `Z` for UTC.
+    pub fn from_parts(date_time: DateTime, utc_offset: i16) -> Result<UTCTime, TimeError> {
+        let (date, time) = (date_time.date(), date_time.time());
+        if date.year() < 1950 || date.year() > 2049 {
+            return Err(TimeError::InvalidValue);
+        }
+        let ret = check_time(time, 0, Some(utc_offset));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let text = format!("{:02}{:02}{:02}{:02}{:02}{:02}{}",
+                           date.year() % 100,
+                           date.month(),
+                           date.day(),
+                           time.hour(),
+                           time.minute(),
+                           time.second(),
+                           fmt_utc_offset(Some(utc_offset)));
+        Ok(UTCTime {
+            text,
+            date_time,
+            utc_offset,
+        })
+    }
+
+    /// Get the string form.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Check if the string form is the one DER and CER require: UTC, with seconds.
+    pub fn is_canonical(&self) -> bool {
+        self.text.len() == 13 && self.text.ends_with('Z')
+    }
+
+    /// Get the date and time, as they appear in the string (i.e. in the local time of `utc_offset()`).
+    pub fn date_time(&self) -> DateTime {
+        self.date_time
+    }
+
+    /// Get the UTC offset in minutes east of UTC.
+    pub fn utc_offset(&self) -> i16 {
+        self.utc_offset
+    }
+}
+
+impl FromStr for UTCTime {
+    type Err = TimeError;
+
+    fn from_str(s: &str) -> Result<UTCTime, TimeError> {
+        UTCTime::new(s)
+    }
+}
+
+impl fmt::Display for UTCTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// UTCTime is encoded as an unconstrained VisibleString.
+impl APerElement for UTCTime {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `UTCTime` from an aligned PER encoding. With CANONICAL-PER, the string must be in the canonical form,
+    /// with the seconds and a `Z`.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = decode_chars(decoder, &VisibleString::alphabet(), None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let t = match UTCTime::new(&ret.unwrap()) {
+            Ok(t) => t,
+            Err(_) => return Err(DecodeError::InvalidTime),
+        };
+        if decoder.is_canonical() && !t.is_canonical() {
+            return Err(DecodeError::NonCanonicalTime);
+        }
+        Ok(t)
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
+        encode_chars(&self.text, &VisibleString::alphabet(), None)
+    }
+}
+
+impl BerElement for UTCTime {
+    const TAG: Tag = Tag::UTC_TIME;
+
+    /// Read a `UTCTime` from a BER encoding. With DER, the string must be in the canonical form, with the seconds and a
+    /// `Z`.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_string(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let t = match String::from_utf8(ret.unwrap()) {
+            Ok(s) => match UTCTime::new(&s) {
+                Ok(t) => t,
+                Err(_) => return Err(ber::DecodeError::InvalidTime),
+            },
+            Err(_) => return Err(ber::DecodeError::InvalidTime),
+        };
+        if decoder.rules() != Rules::Ber && !t.is_canonical() {
+            return Err(ber::DecodeError::NonCanonicalTime);
+        }
+        Ok(t)
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::string(tag, self.text.as_bytes().to_vec()))
+    }
+}
+
+/// GeneralizedTime is encoded as an unconstrained VisibleString.
+impl OerElement for GeneralizedTime {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `GeneralizedTime` from an OER encoding. With COER, the string must be in the canonical form, with the
+    /// seconds, a `Z` and no trailing 0s in the fraction.
+    fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = VisibleString::from_oer(decoder, oer::UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(match ret.err().unwrap() {
+                oer::DecodeError::InvalidCharacter => oer::DecodeError::InvalidTime,
+                e => e,
+            });
+        }
+        let t = match GeneralizedTime::new(ret.unwrap().as_str()) {
+            Ok(t) => t,
+            Err(_) => return Err(oer::DecodeError::InvalidTime),
+        };
+        if decoder.is_canonical() && !t.is_canonical() {
+            return Err(oer::DecodeError::NonCanonicalTime);
+        }
+        Ok(t)
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        oer::encode_string(self.text.as_bytes(), None)
+    }
+}
+
+/// UTCTime is encoded as an unconstrained VisibleString.
+impl OerElement for UTCTime {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `UTCTime` from an OER encoding. With COER, the string must be in the canonical form, with the seconds and
+    /// a `Z`.
+    fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = VisibleString::from_oer(decoder, oer::UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(match ret.err().unwrap() {
+                oer::DecodeError::InvalidCharacter => oer::DecodeError::InvalidTime,
+                e => e,
+            });
+        }
+        let t = match UTCTime::new(ret.unwrap().as_str()) {
+            Ok(t) => t,
+            Err(_) => return Err(oer::DecodeError::InvalidTime),
+        };
+        if decoder.is_canonical() && !t.is_canonical() {
+            return Err(oer::DecodeError::NonCanonicalTime);
+        }
+        Ok(t)
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        oer::encode_string(self.text.as_bytes(), None)
+    }
+}
+
+// The JER encoding of a time type is a string holding its text
+macro_rules! jer_time_impl {
+    ($name:ident) => {
+        impl JerElement for $name {
+            const CONSTRAINTS: Constraints = Constraints {
+                value: None,
+                size: None,
+            };
+
+            /// Read a value from a JER encoding.
+            fn from_jer(value: &jer::Value, _: Constraints) -> Result<Self, jer::DecodeError> {
+                let ret = value.as_str();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match ret.unwrap().parse() {
+                    Ok(t) => Ok(t),
+                    Err(_) => Err(jer::DecodeError::InvalidTime),
+                }
+            }
+
+            fn to_jer(&self, _: Constraints) -> Result<jer::Value, jer::EncodeError> {
+                Ok(jer::Value::String(self.to_string()))
+            }
+        }
+    };
+}
+
+jer_time_impl!(Date);
+jer_time_impl!(TimeOfDay);
+jer_time_impl!(DateTime);
+jer_time_impl!(Duration);
+jer_time_impl!(GeneralizedTime);
+jer_time_impl!(UTCTime);
+
+// The XER encoding of a time type is its text. `$is_canonical` checks a value against the text it was read from for
+// CXER.
+macro_rules! xer_time_impl {
+    ($name:ident, $xml_name:expr, $is_canonical:expr) => {
+        impl XerElement for $name {
+            const NAME: &'static str = $xml_name;
+
+            /// Read a value from an XER encoding. With CXER, the text must be in the canonical form.
+            fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+                let ret = decoder.decode_token();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let s = ret.unwrap();
+                let t: $name = match s.parse() {
+                    Ok(t) => t,
+                    Err(_) => return Err(xer::DecodeError::InvalidTime),
+                };
+                let is_canonical: fn(&$name, &str) -> bool = $is_canonical;
+                if decoder.is_canonical() && !is_canonical(&t, &s) {
+                    return Err(xer::DecodeError::NonCanonicalTime);
+                }
+                Ok(t)
+            }
+
+            fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+                Ok(xer::Encoding::text(&self.to_string()))
+            }
+        }
+    };
+}
+
+xer_time_impl!(Date, "DATE", |t, s| t.to_string() == s);
+xer_time_impl!(TimeOfDay, "TIME-OF-DAY", |t, s| t.to_string() == s);
+xer_time_impl!(DateTime, "DATE-TIME", |t, s| t.to_string() == s);
+xer_time_impl!(Duration, "DURATION", |t, s| t.to_string() == s);
+xer_time_impl!(GeneralizedTime, "GeneralizedTime", |t, _| t.is_canonical());
+xer_time_impl!(UTCTime, "UTCTime", |t, _| t.is_canonical());
+
+/// Conversions to and from the types of the `chrono` crate.
+#[cfg(feature = "chrono")]
+mod chrono_conversions {
+    use super::*;
+    use chrono::{Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike};
+    use std::convert::TryFrom;
+
+    fn offset_minutes(offset: FixedOffset) -> Result<i16, TimeError> {
+        let seconds = offset.local_minus_utc();
+        if seconds % 60 != 0 {
+            return Err(TimeError::Unrepresentable);
+        }
+        Ok((seconds / 60) as i16)
+    }
+
+    fn fixed_offset(minutes: i16) -> Result<FixedOffset, TimeError> {
+        match FixedOffset::east_opt(minutes as i32 * 60) {
+            Some(offset) => Ok(offset),
+            None => Err(TimeError::Unrepresentable),
+        }
+    }
+
+    /// Split a chrono time into a `TimeOfDay` and nanoseconds, turning chrono's leap seconds into a second of 60.
+    fn split_time(time: &NaiveTime) -> (TimeOfDay, u32) {
+        let (second, nanosecond) = if time.nanosecond() >= 1_000_000_000 {
+            (60, time.nanosecond() - 1_000_000_000)
+        } else {
+            (time.second(), time.nanosecond())
+        };
+        (TimeOfDay::new(time.hour() as u8, time.minute() as u8, second as u8).unwrap(), nanosecond)
+    }
+
+    fn join_time(time: TimeOfDay, nanosecond: u32) -> Result<NaiveTime, TimeError> {
+        let (second, nanosecond) = if time.second() == 60 {
+            (59, nanosecond + 1_000_000_000)
+        } else {
+            (time.second(), nanosecond)
+        };
+        match NaiveTime::from_hms_nano_opt(time.hour() as u32, time.minute() as u32, second as u32, nanosecond) {
+            Some(t) => Ok(t),
+            None => Err(TimeError::Unrepresentable),
+        }
+    }
+
+    impl From<NaiveDate> for Date {
+        fn from(date: NaiveDate) -> Date {
+            Date::new(date.year(), date.month() as u8, date.day() as u8).unwrap()
+        }
+    }
+
+    impl TryFrom<Date> for NaiveDate {
+        type Error = TimeError;
+
+        fn try_from(date: Date) -> Result<NaiveDate, TimeError> {
+            match NaiveDate::from_ymd_opt(date.year(), date.month() as u32, date.day() as u32) {
+                Some(d) => Ok(d),
+                None => Err(TimeError::Unrepresentable),
+            }
+        }
+    }
+
+    /// Fractions of a second are dropped.
+    impl From<NaiveTime> for TimeOfDay {
+        fn from(time: NaiveTime) -> TimeOfDay {
+            split_time(&time).0
+        }
+    }
+
+    impl TryFrom<TimeOfDay> for NaiveTime {
+        type Error = TimeError;
+
+        fn try_from(time: TimeOfDay) -> Result<NaiveTime, TimeError> {
+            join_time(time, 0)
+        }
+    }
+
+    /// Fractions of a second are dropped.
+    impl From<NaiveDateTime> for DateTime {
+        fn from(dt: NaiveDateTime) -> DateTime {
+            DateTime::new(Date::from(dt.date()), TimeOfDay::from(dt.time()))
+        }
+    }
+
+    impl TryFrom<DateTime> for NaiveDateTime {
+        type Error = TimeError;
+
+        fn try_from(dt: DateTime) -> Result<NaiveDateTime, TimeError> {
+            let date = NaiveDate::try_from(dt.date());
+            if date.is_err() {
+                return Err(date.err().unwrap());
+            }
+            let time = NaiveTime::try_from(dt.time());
+            if time.is_err() {
+                return Err(time.err().unwrap());
+            }
+            Ok(date.unwrap().and_time(time.unwrap()))
+        }
+    }
+
+    impl<Tz: TimeZone> TryFrom<::chrono::DateTime<Tz>> for GeneralizedTime {
+        type Error = TimeError;
+
+        fn try_from(dt: ::chrono::DateTime<Tz>) -> Result<GeneralizedTime, TimeError> {
+            let offset = offset_minutes(dt.offset().fix());
+            if offset.is_err() {
+                return Err(offset.err().unwrap());
+            }
+            let local = dt.naive_local();
+            let (time, nanosecond) = split_time(&local.time());
+            GeneralizedTime::from_parts(DateTime::new(Date::from(local.date()), time),
+                                        nanosecond,
+                                        Some(offset.unwrap()))
+        }
+    }
+
+    /// Converts a GeneralizedTime with a UTC offset. Local times are `Unrepresentable`.
+    impl<'a> TryFrom<&'a GeneralizedTime> for ::chrono::DateTime<FixedOffset> {
+        type Error = TimeError;
+
+        fn try_from(t: &'a GeneralizedTime) -> Result<::chrono::DateTime<FixedOffset>, TimeError> {
+            if t.utc_offset().is_none() {
+                return Err(TimeError::Unrepresentable);
+            }
+            let offset = fixed_offset(t.utc_offset().unwrap());
+            if offset.is_err() {
+                return Err(offset.err().unwrap());
+            }
+            let date = NaiveDate::try_from(t.date_time().date());
+            if date.is_err() {
+                return Err(date.err().unwrap());
+            }
+            let time = join_time(t.date_time().time(), t.nanosecond());
+            if time.is_err() {
+                return Err(time.err().unwrap());
+            }
+            match offset.unwrap().from_local_datetime(&date.unwrap().and_time(time.unwrap())).single() {
+                Some(dt) => Ok(dt),
+                None => Err(TimeError::Unrepresentable),
+            }
+        }
+    }
+
+    impl<Tz: TimeZone> TryFrom<::chrono::DateTime<Tz>> for UTCTime {
+        type Error = TimeError;
+
+        /// Fractions of a second are dropped.
+        fn try_from(dt: ::chrono::DateTime<Tz>) -> Result<UTCTime, TimeError> {
+            let offset = offset_minutes(dt.offset().fix());
+            if offset.is_err() {
+                return Err(offset.err().unwrap());
+            }
+            UTCTime::from_parts(DateTime::from(dt.naive_local()), offset.unwrap())
+        }
+    }
+
+    impl<'a> TryFrom<&'a UTCTime> for ::chrono::DateTime<FixedOffset> {
+        type Error = TimeError;
+
+        fn try_from(t: &'a UTCTime) -> Result<::chrono::DateTime<FixedOffset>, TimeError> {
+            let offset = fixed_offset(t.utc_offset());
+            if offset.is_err() {
+                return Err(offset.err().unwrap());
+            }
+            let local = NaiveDateTime::try_from(t.date_time());
+            if local.is_err() {
+                return Err(local.err().unwrap());
+            }
+            match offset.unwrap().from_local_datetime(&local.unwrap()).single() {
+                Some(dt) => Ok(dt),
+                None => Err(TimeError::Unrepresentable),
+            }
+        }
+    }
+}
+
+/// Conversions to and from the types of the `time` crate.
+#[cfg(feature = "time")]
+mod time_conversions {
+    use super::*;
+    use std::convert::TryFrom;
+    use time_crate::{Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+    fn utc_offset(minutes: i16) -> Result<UtcOffset, TimeError> {
+        match UtcOffset::from_whole_seconds(minutes as i32 * 60) {
+            Ok(offset) => Ok(offset),
+            Err(_) => Err(TimeError::Unrepresentable),
+        }
+    }
+
+    fn offset_minutes(offset: UtcOffset) -> Result<i16, TimeError> {
+        if offset.seconds_past_minute() != 0 {
+            return Err(TimeError::Unrepresentable);
+        }
+        Ok(offset.whole_minutes())
+    }
+
+    impl From<time_crate::Date> for Date {
+        fn from(date: time_crate::Date) -> Date {
+            Date::new(date.year(), date.month() as u8, date.day()).unwrap()
+        }
+    }
+
+    impl TryFrom<Date> for time_crate::Date {
+        type Error = TimeError;
+
+        fn try_from(date: Date) -> Result<time_crate::Date, TimeError> {
+            let month = Month::try_from(date.month()).unwrap();
+            match time_crate::Date::from_calendar_date(date.year(), month, date.day()) {
+                Ok(d) => Ok(d),
+                Err(_) => Err(TimeError::Unrepresentable),
+            }
+        }
+    }
+
+    /// Fractions of a second are dropped.
+    impl From<Time> for TimeOfDay {
+        fn from(time: Time) -> TimeOfDay {
+            TimeOfDay::new(time.hour(), time.minute(), time.second()).unwrap()
+        }
+    }
+
+    /// Leap seconds and `24:00:00` are `Unrepresentable`.
+    impl TryFrom<TimeOfDay> for Time {
+        type Error = TimeError;
+
+        fn try_from(time: TimeOfDay) -> Result<Time, TimeError> {
+            match Time::from_hms(time.hour(), time.minute(), time.second()) {
+                Ok(t) => Ok(t),
+                Err(_) => Err(TimeError::Unrepresentable),
+            }
+        }
+    }
+
+    /// Fractions of a second are dropped.
+    impl From<PrimitiveDateTime> for DateTime {
+        fn from(dt: PrimitiveDateTime) -> DateTime {
+            DateTime::new(Date::from(dt.date()), TimeOfDay::from(dt.time()))
+        }
+    }
+
+    impl TryFrom<DateTime> for PrimitiveDateTime {
+        type Error = TimeError;
+
+        fn try_from(dt: DateTime) -> Result<PrimitiveDateTime, TimeError> {
+            let date = time_crate::Date::try_from(dt.date());
+            if date.is_err() {
+                return Err(date.err().unwrap());
+            }
+            let time = Time::try_from(dt.time());
+            if time.is_err() {
+                return Err(time.err().unwrap());
+            }
+            Ok(PrimitiveDateTime::new(date.unwrap(), time.unwrap()))
+        }
+    }
+
+    impl TryFrom<OffsetDateTime> for GeneralizedTime {
+        type Error = TimeError;
+
+        fn try_from(dt: OffsetDateTime) -> Result<GeneralizedTime, TimeError> {
+            let offset = offset_minutes(dt.offset());
+            if offset.is_err() {
+                return Err(offset.err().unwrap());
+            }
+            let local = DateTime::new(Date::from(dt.date()), TimeOfDay::from(dt.time()));
+            GeneralizedTime::from_parts(local, dt.nanosecond(), Some(offset.unwrap()))
+        }
+    }
+
+    /// Converts a GeneralizedTime with a UTC offset. Local times are `Unrepresentable`.
+    impl<'a> TryFrom<&'a GeneralizedTime> for OffsetDateTime {
+        type Error = TimeError;
+
+        fn try_from(t: &'a GeneralizedTime) -> Result<OffsetDateTime, TimeError> {
+            if t.utc_offset().is_none() {
+                return Err(TimeError::Unrepresentable);
+            }
+            let offset = utc_offset(t.utc_offset().unwrap());
+            if offset.is_err() {
+                return Err(offset.err().unwrap());
+            }
+            let local = PrimitiveDateTime::try_from(t.date_time());
+            if local.is_err() {
+                return Err(local.err().unwrap());
+            }
+            match local.unwrap().replace_nanosecond(t.nanosecond()) {
+                Ok(local) => Ok(local.assume_offset(offset.unwrap())),
+                Err(_) => Err(TimeError::Unrepresentable),
+            }
+        }
+    }
+
+    impl TryFrom<OffsetDateTime> for UTCTime {
+        type Error = TimeError;
+
+        /// Fractions of a second are dropped.
+        fn try_from(dt: OffsetDateTime) -> Result<UTCTime, TimeError> {
+            let offset = offset_minutes(dt.offset());
+            if offset.is_err() {
+                return Err(offset.err().unwrap());
+            }
+            let local = DateTime::new(Date::from(dt.date()), TimeOfDay::from(dt.time()));
+            UTCTime::from_parts(local, offset.unwrap())
+        }
+    }
+
+    impl<'a> TryFrom<&'a UTCTime> for OffsetDateTime {
+        type Error = TimeError;
+
+        fn try_from(t: &'a UTCTime) -> Result<OffsetDateTime, TimeError> {
+            let offset = utc_offset(t.utc_offset());
+            if offset.is_err() {
+                return Err(offset.err().unwrap());
+            }
+            let local = PrimitiveDateTime::try_from(t.date_time());
+            if local.is_err() {
+                return Err(local.err().unwrap());
+            }
+            Ok(local.unwrap().assume_offset(offset.unwrap()))
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use aper::{Constraint, Decoder, DecodeError, Encoding};
+use char_string::PermittedAlphabet;
+use per;
+
+pub use schema::{Component, Type};
+
+/// Transcode a value of type `ty` from its aligned PER encoding, read by `decoder`, to unaligned PER.
+///
+/// The values themselves are never built: each field is read and written again with the other variant's alignment,
+/// field widths and character encodings. Open types (including extension additions and alternatives) are transcoded
+/// too, so an extension the schema doesn't know about is an `Err`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{self, Constraint};
+/// use asn1::transcode::{self, Type};
+///
+/// // INTEGER (0..1000) is octet-aligned in aligned PER only
+/// let ty = Type::Integer {
+///     value: Some(Constraint::new(Some(0), Some(1000))),
+///     extensible: false,
+/// };
+/// let enc = transcode::aper_to_uper(&mut aper::Decoder::new(b"\x01\xf4"), &ty).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [125, 0], i.e. 500 in 10 bits
+/// ```
+pub fn aper_to_uper(decoder: &mut Decoder, ty: &Type) -> Result<Encoding, DecodeError> {
+    transcode(decoder, ty, true)
+}
+
+/// Transcode a value of type `ty` from its unaligned PER encoding, read by `decoder`, to aligned PER. See
+/// [aper_to_uper](fn.aper_to_uper.html).
+pub fn uper_to_aper(decoder: &mut Decoder, ty: &Type) -> Result<Encoding, DecodeError> {
+    transcode(decoder, ty, false)
+}
+
+fn transcode(decoder: &mut Decoder, ty: &Type, aligned: bool) -> Result<Encoding, DecodeError> {
+    let mut t = Transcoder {
+        input: decoder,
+        output: Encoding::new(),
+        aligned,
+    };
+    let ret = t.value(ty);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(t.output)
+}
+
+struct Transcoder<'a, 'b: 'a> {
+    input: &'a mut Decoder<'b>,
+    output: Encoding,
+    // Whether the input is aligned PER, in which case the output is unaligned PER, and vice versa
+    aligned: bool,
+}
+
+impl<'a, 'b> Transcoder<'a, 'b> {
+    /// Skip the padding before an octet-aligned field in whichever of the input and output is aligned PER.
+    fn align(&mut self) {
+        if self.aligned {
+            self.input.align();
+        } else {
+            self.output.align();
+        }
+    }
+
+    fn copy_bits(&mut self, n: usize) -> Result<(), DecodeError> {
+        let mut left = n;
+        while left > 0 {
+            let m = if left > 64 { 64 } else { left };
+            let ret = self.input.read_uint(m);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            self.output.append_uint(ret.unwrap(), m);
+            left -= m;
+        }
+        Ok(())
+    }
+
+    fn copy_bit(&mut self) -> Result<bool, DecodeError> {
+        let ret = self.input.read(1);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let bit = ret.unwrap();
+        self.output.append_uint(bit as u64, 1);
+        Ok(bit > 0)
+    }
+
+    fn constrained(&mut self, lb: i64, ub: i64) -> Result<i64, DecodeError> {
+        let ret = per::read_constrained(self.input, lb, ub, self.aligned);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        per::write_constrained(&mut self.output, value, lb, ub, !self.aligned);
+        Ok(value)
+    }
+
+    /// Transcode a length determinant that isn't a fragment.
+    fn length(&mut self) -> Result<usize, DecodeError> {
+        let ret = per::read_length(self.input, self.aligned);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let (len, fragment) = ret.unwrap();
+        if fragment {
+            return Err(DecodeError::MalformedLength);
+        }
+        per::write_length(&mut self.output, len, false, !self.aligned);
+        Ok(len)
+    }
+
+    /// Transcode a normally small non-negative whole number.
+    fn normally_small(&mut self) -> Result<u64, DecodeError> {
+        let ret = self.copy_bit();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if !ret.unwrap() {
+            let ret = self.input.read_uint(6);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let value = ret.unwrap();
+            self.output.append_uint(value, 6);
+            return Ok(value);
+        }
+
+        let ret = self.length();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let len = ret.unwrap();
+        if len == 0 || len > 8 {
+            return Err(DecodeError::MalformedInt);
+        }
+        let ret = self.input.read_uint(8 * len);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let value = ret.unwrap();
+        self.output.append_uint(value, 8 * len);
+        Ok(value)
+    }
+
+    /// Transcode a normally small length, as used for the size of the presence bitmap of extension additions.
+    fn normally_small_length(&mut self) -> Result<usize, DecodeError> {
+        let ret = self.copy_bit();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if ret.unwrap() {
+            return self.length();
+        }
+        let ret = self.input.read_uint(6);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let n = ret.unwrap();
+        self.output.append_uint(n, 6);
+        Ok(n as usize + 1)
+    }
+
+    /// Transcode a count of items limited by `size` and the items it counts. `aligned_items(lb, ub)` tells if the items
+    /// are octet-aligned in aligned PER when the count is a constrained whole number. `items(transcoder, n)` is called
+    /// to transcode each run of `n` items.
+    fn counted<A, F>(&mut self,
+                     size: Option<Constraint>,
+                     extensible: bool,
+                     aligned_items: A,
+                     mut items: F)
+                     -> Result<(), DecodeError>
+        where A: Fn(i64, i64) -> bool,
+              F: FnMut(&mut Self, usize) -> Result<(), DecodeError>
+    {
+        let mut size = size;
+        if extensible {
+            let ret = self.copy_bit();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if ret.unwrap() {
+                size = None;
+            }
+        }
+        let (lb, ub) = match size {
+            Some(sz_constr) => sz_constr.size_bounds(),
+            None => (0, None),
+        };
+
+        if let Some(ub) = ub.filter(|&ub| ub < 65536) {
+            let n = if lb == ub {
+                ub
+            } else {
+                let ret = self.constrained(lb, ub);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            };
+            if aligned_items(lb, ub) {
+                self.align();
+            }
+            return items(self, n as usize);
+        }
+
+        let mut total = 0;
+        loop {
+            let ret = per::read_length(self.input, self.aligned);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let (n, fragment) = ret.unwrap();
+            per::write_length(&mut self.output, n, fragment, !self.aligned);
+            let ret = items(self, n);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            total += n as i64;
+            if !fragment {
+                break;
+            }
+        }
+        if total < lb || ub.is_some_and(|ub| total > ub) {
+            return Err(DecodeError::InvalidSize);
+        }
+        Ok(())
+    }
+
+    /// Transcode the characters of a known-multiplier character string.
+    fn chars(&mut self, alphabet: &PermittedAlphabet, n: usize) -> Result<(), DecodeError> {
+        let (b_in, b_out) = if self.aligned {
+            (alphabet.char_bits(), alphabet.unaligned_char_bits())
+        } else {
+            (alphabet.unaligned_char_bits(), alphabet.char_bits())
+        };
+        let (remapped_in, remapped_out) = (alphabet.is_remapped(b_in), alphabet.is_remapped(b_out));
+        for _ in 0..n {
+            let ret = self.input.read_uint(b_in);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let value = ret.unwrap();
+
+            let code = if remapped_in {
+                alphabet.code_at(value)
+            } else if value <= u32::MAX as u64 && alphabet.index_of(value as u32).is_some() {
+                Some(value as u32)
+            } else {
+                None
+            };
+            match code {
+                Some(code) if remapped_out => self.output.append_uint(alphabet.index_of(code).unwrap(), b_out),
+                Some(code) => self.output.append_uint(code as u64, b_out),
+                None => return Err(DecodeError::InvalidCharacter),
+            }
+        }
+        Ok(())
+    }
+
+    /// Transcode an open type holding a value of type `ty`.
+    fn open(&mut self, ty: &Type) -> Result<(), DecodeError> {
+        let mut content: Vec<u8> = Vec::new();
+        loop {
+            let ret = per::read_length(self.input, self.aligned);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let (n, fragment) = ret.unwrap();
+            let ret = self.input.read_to_vec(&mut content, 8 * n);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if !fragment {
+                break;
+            }
+        }
+
+        let mut decoder = self.input.sub_decoder(&content);
+        let ret = transcode(&mut decoder, ty, self.aligned);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let inner = ret.unwrap();
+        let bytes = if inner.num_bits() == 0 {
+            vec![0x00]
+        } else {
+            inner.bytes().clone()
+        };
+
+        let mut start = 0;
+        loop {
+            let (n, fragment) = per::fragment_length(bytes.len() - start);
+            per::write_length(&mut self.output, n, fragment, !self.aligned);
+            for b in &bytes[start..start + n] {
+                self.output.append_uint(*b as u64, 8);
+            }
+            start += n;
+            if !fragment {
+                return Ok(());
+            }
+        }
+    }
+
+    fn value(&mut self, ty: &Type) -> Result<(), DecodeError> {
+        match *ty {
+            Type::Null => Ok(()),
+            Type::Boolean => self.copy_bit().map(|_| ()),
+            Type::Integer { value, extensible } => {
+                let mut value = value;
+                if extensible {
+                    let ret = self.copy_bit();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    if ret.unwrap() {
+                        value = None;
+                    }
+                }
+                if let Some((Some(lb), Some(ub))) = value.map(|c| (c.min(), c.max())) {
+                    return self.constrained(lb, ub).map(|_| ());
+                }
+
+                // Semi-constrained and unconstrained values are the same octets with a length determinant
+                let ret = self.length();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let len = ret.unwrap();
+                if len == 0 {
+                    return Err(DecodeError::MalformedInt);
+                }
+                self.copy_bits(8 * len)
+            }
+            Type::Enumerated { root, extensible } => {
+                if extensible {
+                    let ret = self.copy_bit();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    if ret.unwrap() {
+                        return self.normally_small().map(|_| ());
+                    }
+                }
+                self.constrained(0, root as i64 - 1).map(|_| ())
+            }
+            Type::BitString { size, extensible } => {
+                self.counted(size, extensible, |lb, ub| lb != ub || ub > 16, |t, n| t.copy_bits(n))
+            }
+            Type::OctetString { size, extensible } => {
+                self.counted(size, extensible, |lb, ub| lb != ub || ub > 2, |t, n| t.copy_bits(8 * n))
+            }
+            Type::CharString { ref alphabet, size, extensible } => {
+                let b = alphabet.char_bits() as i64;
+                self.counted(size, extensible, |_, ub| ub * b > 16, |t, n| t.chars(alphabet, n))
+            }
+            Type::Utf8String | Type::ObjectIdentifier | Type::RelativeOid | Type::Real | Type::Octets => {
+                self.counted(None, false, |_, _| false, |t, n| t.copy_bits(8 * n))
+            }
+            Type::Open(ref ty) => self.open(ty),
+            Type::Sequence { ref root, ref extensions } => {
+                let mut is_ext = false;
+                if extensions.is_some() {
+                    let ret = self.copy_bit();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    is_ext = ret.unwrap();
+                }
+
+                let mut present = Vec::with_capacity(root.len());
+                for c in root {
+                    if !c.optional {
+                        present.push(true);
+                        continue;
+                    }
+                    let ret = self.copy_bit();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    present.push(ret.unwrap());
+                }
+                for (c, p) in root.iter().zip(present) {
+                    if !p {
+                        continue;
+                    }
+                    let ret = self.value(&c.ty);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+                if !is_ext {
+                    return Ok(());
+                }
+
+                let ret = self.normally_small_length();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let n = ret.unwrap();
+                let mut present = Vec::with_capacity(n);
+                for _ in 0..n {
+                    let ret = self.copy_bit();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    present.push(ret.unwrap());
+                }
+                let additions = extensions.as_ref().unwrap();
+                for (i, p) in present.into_iter().enumerate() {
+                    if !p {
+                        continue;
+                    }
+                    let ret = match additions.get(i) {
+                        Some(ty) => self.open(ty),
+                        None => Err(DecodeError::UnknownExtension),
+                    };
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+                Ok(())
+            }
+            Type::SequenceOf { ref element, size, extensible } => {
+                self.counted(size, extensible, |_, _| false, |t, n| {
+                    for _ in 0..n {
+                        let ret = t.value(element);
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                    }
+                    Ok(())
+                })
+            }
+            Type::Choice { ref root, ref extensions } => {
+                if extensions.is_some() {
+                    let ret = self.copy_bit();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    if ret.unwrap() {
+                        let ret = self.normally_small();
+                        if ret.is_err() {
+                            return Err(ret.err().unwrap());
+                        }
+                        return match extensions.as_ref().unwrap().get(ret.unwrap() as usize) {
+                            Some(ty) => self.open(ty),
+                            None => Err(DecodeError::UnknownExtension),
+                        };
+                    }
+                }
+
+                if root.is_empty() {
+                    return Err(DecodeError::InvalidChoice);
+                }
+                let ret = self.constrained(0, root.len() as i64 - 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                self.value(&root[ret.unwrap() as usize])
+            }
+        }
+    }
+}
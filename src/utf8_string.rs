@@ -0,0 +1,135 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_fragmented};
+use ber::{self, BerElement, Tag};
+use oer::{self, OerElement};
+use jer::{self, JerElement};
+use xer::{self, XerElement};
+
+/// `UTF8String` is not a known-multiplier character string type, so its encoding is just the UTF-8 octets preceded by
+/// an unconstrained length determinant. A size constraint is not PER-visible, but it is still checked against the
+/// number of characters.
+impl APerElement for String {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `String` from the aligned PER encoding of a `UTF8String`.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let mut content: Vec<u8> = Vec::new();
+        let ret = decoder.decode_fragmented(|d, n| d.read_to_vec(&mut content, n * 8));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let ret = String::from_utf8(content);
+        if ret.is_err() {
+            return Err(DecodeError::InvalidUtf8);
+        }
+        let s = ret.unwrap();
+        if !constraints.check_size(s.chars().count()) {
+            return Err(DecodeError::InvalidSize);
+        }
+        Ok(s)
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        if !constraints.check_size(self.chars().count()) {
+            return Err(EncodeError::InvalidSize);
+        }
+        let bytes = self.as_bytes();
+        encode_fragmented(bytes.len(), |start, end| Ok(Encoding::with_bytes(bytes[start..end].to_vec())))
+    }
+}
+
+impl BerElement for String {
+    const TAG: Tag = Tag::UTF8_STRING;
+
+    /// Read a `String` from the BER encoding of a `UTF8String`.
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let ret = decoder.decode_string(tag);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match String::from_utf8(ret.unwrap()) {
+            Ok(s) => Ok(s),
+            Err(_) => Err(ber::DecodeError::InvalidUtf8),
+        }
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<ber::Encoding, ber::EncodeError> {
+        Ok(ber::Encoding::string(tag, self.as_bytes().to_vec()))
+    }
+}
+
+/// As with aligned PER, a size constraint is checked against the number of characters but doesn't change the
+/// encoding, which is the UTF-8 octets preceded by a length determinant.
+impl OerElement for String {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `String` from the OER encoding of a `UTF8String`.
+    fn from_oer(decoder: &mut oer::Decoder, constraints: Constraints) -> Result<Self, oer::DecodeError> {
+        let ret = decoder.decode_string(None);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = String::from_utf8(ret.unwrap());
+        if ret.is_err() {
+            return Err(oer::DecodeError::InvalidUtf8);
+        }
+        let s = ret.unwrap();
+        if !constraints.check_size(s.chars().count()) {
+            return Err(oer::DecodeError::InvalidSize);
+        }
+        Ok(s)
+    }
+
+    fn to_oer(&self, constraints: Constraints) -> Result<oer::Encoding, oer::EncodeError> {
+        if !constraints.check_size(self.chars().count()) {
+            return Err(oer::EncodeError::InvalidSize);
+        }
+        oer::encode_string(self.as_bytes(), None)
+    }
+}
+
+impl XerElement for String {
+    const NAME: &'static str = "UTF8String";
+
+    /// Read a `String` from the XER encoding of a `UTF8String`, which is its characters as text.
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+        decoder.decode_text()
+    }
+
+    fn to_xer(&self) -> Result<xer::Encoding, xer::EncodeError> {
+        Ok(xer::Encoding::text(self))
+    }
+}
+
+impl JerElement for String {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `String` from the JER encoding of a `UTF8String`, which is a string.
+    fn from_jer(value: &jer::Value, constraints: Constraints) -> Result<Self, jer::DecodeError> {
+        let ret = value.as_str();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let s = ret.unwrap();
+        if !constraints.check_size(s.chars().count()) {
+            return Err(jer::DecodeError::InvalidSize);
+        }
+        Ok(s.to_string())
+    }
+
+    fn to_jer(&self, constraints: Constraints) -> Result<jer::Value, jer::EncodeError> {
+        if !constraints.check_size(self.chars().count()) {
+            return Err(jer::EncodeError::InvalidSize);
+        }
+        Ok(jer::Value::String(self.clone()))
+    }
+}
@@ -0,0 +1,518 @@
+use super::*;
+use super::encoding::{CONTROL_NAMES, Node};
+
+// Limits the recursion needed to parse nested elements
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, PartialEq)]
+pub enum DecodeError {
+    InvalidBits,
+    InvalidBool,
+    InvalidCharacter,
+    InvalidHex,
+    InvalidOid,
+    InvalidReal,
+    InvalidTime,
+    MalformedInt,
+    MalformedXml,
+    MissingElement,
+    NestingTooDeep,
+    NonCanonicalHex,
+    NonCanonicalInt,
+    NonCanonicalOid,
+    NonCanonicalReal,
+    NonCanonicalTime,
+    TrailingData,
+    TrailingZeroBits,
+    UnexpectedElement,
+    UnexpectedText,
+    UnexpectedWhitespace,
+}
+
+/// Parse an XML document into the name of its root element and the [Encoding](struct.Encoding.html) of the value it
+/// holds. An XML declaration, comments and processing instructions are skipped, and so are attributes (e.g.
+/// namespace declarations).
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::xer::{self, XerElement};
+///
+/// let (name, content) = xer::parse("<?xml version=\"1.0\"?>\n<INTEGER>43</INTEGER>").unwrap();
+/// let mut d = xer::Decoder::new(&content);
+/// println!("{} = {}", name, i32::from_xer(&mut d).unwrap()); // Prints INTEGER = 43
+/// ```
+pub fn parse(xml: &str) -> Result<(String, Encoding), DecodeError> {
+    let mut parser = Parser {
+        xml: xml.trim_start_matches('\u{feff}'),
+        pos: 0,
+    };
+    let ret = parser.skip_misc();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let ret = parser.parse_element(0);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let root = ret.unwrap();
+    let ret = parser.skip_misc();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    if parser.pos < parser.xml.len() {
+        return Err(DecodeError::TrailingData);
+    }
+    match root {
+        Node::Element(name, content) => Ok((name, content)),
+        Node::Empty(name) => Ok((name, Encoding::new())),
+        Node::Text(_) => Err(DecodeError::MalformedXml),
+    }
+}
+
+struct Parser<'a> {
+    xml: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.xml[self.pos..]
+    }
+
+    // Skip past `end`, which must come before the end of the input
+    fn skip_past(&mut self, end: &str) -> Result<(), DecodeError> {
+        match self.rest().find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                Ok(())
+            }
+            None => Err(DecodeError::MalformedXml),
+        }
+    }
+
+    // Skip whitespace, comments and processing instructions outside of the root element
+    fn skip_misc(&mut self) -> Result<(), DecodeError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            let ret = if trimmed.starts_with("<?") {
+                self.skip_past("?>")
+            } else if trimmed.starts_with("<!--") {
+                self.skip_past("-->")
+            } else {
+                return Ok(());
+            };
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<String, DecodeError> {
+        let rest = self.rest();
+        let n = rest.find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=').unwrap_or(rest.len());
+        if n == 0 {
+            return Err(DecodeError::MalformedXml);
+        }
+        self.pos += n;
+        Ok(rest[..n].to_string())
+    }
+
+    // Parse an element, starting at its `<`
+    fn parse_element(&mut self, depth: usize) -> Result<Node, DecodeError> {
+        if depth >= MAX_DEPTH {
+            return Err(DecodeError::NestingTooDeep);
+        }
+        if !self.rest().starts_with('<') {
+            return Err(DecodeError::MalformedXml);
+        }
+        self.pos += 1;
+        let ret = self.parse_name();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let name = ret.unwrap();
+
+        // Skip any attributes
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("/>") {
+                self.pos += 2;
+                return Ok(Node::Empty(name));
+            }
+            if trimmed.starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let ret = self.parse_name();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let rest = self.rest().trim_start();
+            let quote = match rest.strip_prefix('=').map(|r| r.trim_start()) {
+                Some(r) if r.starts_with('"') || r.starts_with('\'') => {
+                    self.pos = self.xml.len() - r.len() + 1;
+                    &r[..1]
+                }
+                _ => return Err(DecodeError::MalformedXml),
+            };
+            let ret = self.skip_past(quote);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+
+        let ret = self.parse_content(depth);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let content = ret.unwrap();
+        let end = format!("</{}", name);
+        if !self.rest().starts_with(&end) {
+            return Err(DecodeError::MalformedXml);
+        }
+        self.pos += end.len();
+        let rest = self.rest();
+        let trimmed = rest.trim_start();
+        if !trimmed.starts_with('>') {
+            return Err(DecodeError::MalformedXml);
+        }
+        self.pos += rest.len() - trimmed.len() + 1;
+        Ok(Node::Element(name, content))
+    }
+
+    // Parse the contents of an element, up to its end tag
+    fn parse_content(&mut self, depth: usize) -> Result<Encoding, DecodeError> {
+        let mut content = Encoding::new();
+        let mut text = String::new();
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(DecodeError::MalformedXml);
+            }
+            if rest.starts_with("</") {
+                break;
+            }
+            if rest.starts_with("<!--") {
+                let ret = self.skip_past("-->");
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                continue;
+            }
+            if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                match cdata.find("]]>") {
+                    Some(i) => {
+                        text.push_str(&cdata[..i]);
+                        self.pos += 9 + i + 3;
+                    }
+                    None => return Err(DecodeError::MalformedXml),
+                }
+                continue;
+            }
+            if rest.starts_with('<') {
+                if !text.is_empty() {
+                    content.nodes.push(Node::Text(text.clone()));
+                    text.clear();
+                }
+                let ret = self.parse_element(depth + 1);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                content.nodes.push(ret.unwrap());
+                continue;
+            }
+            if let Some(reference) = rest.strip_prefix('&') {
+                let end = match reference.find(';') {
+                    Some(end) => end,
+                    None => return Err(DecodeError::MalformedXml),
+                };
+                let c = match &reference[..end] {
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "amp" => Some('&'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    r if r.starts_with("#x") => u32::from_str_radix(&r[2..], 16).ok().and_then(::std::char::from_u32),
+                    r if r.starts_with('#') => r[1..].parse().ok().and_then(::std::char::from_u32),
+                    _ => None,
+                };
+                match c {
+                    Some(c) => text.push(c),
+                    None => return Err(DecodeError::MalformedXml),
+                }
+                self.pos += end + 2;
+                continue;
+            }
+            let n = rest.find(['<', '&']).unwrap_or(rest.len());
+            text.push_str(&rest[..n]);
+            self.pos += n;
+        }
+        if !text.is_empty() {
+            content.nodes.push(Node::Text(text));
+        }
+        Ok(content)
+    }
+}
+
+fn is_whitespace(node: &Node) -> bool {
+    match *node {
+        Node::Text(ref text) => text.chars().all(|c| c == ' ' || c == '\t' || c == '\n' || c == '\r'),
+        _ => false,
+    }
+}
+
+/// A cursor over the contents of an element of an XER document, used to decode the value it holds.
+///
+/// A `Decoder` accepts anything basic XER allows unless it is set to check the input against the canonical XER
+/// (CXER) restrictions (see [set_canonical()](#method.set_canonical)), in which case an encoding that isn't canonical
+/// is an `Err` naming the problem.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::xer::{self, XerElement};
+///
+/// // Foo ::= SEQUENCE { a INTEGER, b BOOLEAN }
+/// let (_, content) = xer::parse("<Foo><a>43</a><b><true/></b></Foo>").unwrap();
+/// let mut d = xer::Decoder::new(&content);
+/// let a = i32::from_xer(&mut d.decode_element("a").unwrap()).unwrap();
+/// let b = bool::from_xer(&mut d.decode_element("b").unwrap()).unwrap();
+/// d.finish().unwrap();
+/// println!("a = {}, b = {}", a, b); // Prints a = 43, b = true
+/// ```
+pub struct Decoder<'a> {
+    nodes: &'a [Node],
+    pos: usize,
+    canonical: bool,
+}
+
+impl<'a> Decoder<'a> {
+    /// Construct a new `Decoder` over the contents of an element.
+    pub fn new(content: &'a Encoding) -> Decoder<'a> {
+        Decoder {
+            nodes: &content.nodes,
+            pos: 0,
+            canonical: false,
+        }
+    }
+
+    fn sub_decoder<'b>(&self, content: &'b Encoding) -> Decoder<'b> {
+        let mut decoder = Decoder::new(content);
+        decoder.canonical = self.canonical;
+        decoder
+    }
+
+    /// Enable or disable the checks of the canonical XER restrictions.
+    pub fn set_canonical(&mut self, canonical: bool) {
+        self.canonical = canonical;
+    }
+
+    /// Check if the input is checked against the canonical XER restrictions.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    // Skip whitespace between elements, which CXER doesn't allow
+    fn skip_whitespace(&mut self) -> Result<(), DecodeError> {
+        while self.pos < self.nodes.len() && is_whitespace(&self.nodes[self.pos]) {
+            if self.canonical {
+                return Err(DecodeError::UnexpectedWhitespace);
+            }
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    /// Check if everything has been read, apart from whitespace.
+    pub fn is_empty(&self) -> bool {
+        self.nodes[self.pos..].iter().all(is_whitespace)
+    }
+
+    /// Returns an `Err` if there is anything left to read, e.g. after the last component of a SEQUENCE.
+    pub fn finish(&mut self) -> Result<(), DecodeError> {
+        let ret = self.skip_whitespace();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if self.pos < self.nodes.len() {
+            return Err(DecodeError::TrailingData);
+        }
+        Ok(())
+    }
+
+    /// Get the name of the next element without reading it, e.g. to check if an OPTIONAL component is present.
+    pub fn peek_name(&self) -> Option<&'a str> {
+        match self.nodes[self.pos..].iter().find(|n| !is_whitespace(n)) {
+            Some(Node::Element(name, _)) | Some(Node::Empty(name)) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Read the next element, which holds a value, and return its name and a decoder for its contents, e.g. for the
+    /// chosen alternative of a CHOICE.
+    pub fn decode_any_element(&mut self) -> Result<(&'a str, Decoder<'a>), DecodeError> {
+        let ret = self.skip_whitespace();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let nodes = self.nodes;
+        match nodes.get(self.pos) {
+            Some(Node::Element(name, content)) => {
+                self.pos += 1;
+                Ok((name, self.sub_decoder(content)))
+            }
+            Some(Node::Empty(name)) => {
+                self.pos += 1;
+                Ok((name,
+                    Decoder {
+                        nodes: &[],
+                        pos: 0,
+                        canonical: self.canonical,
+                    }))
+            }
+            Some(Node::Text(_)) => Err(DecodeError::UnexpectedText),
+            None => Err(DecodeError::MissingElement),
+        }
+    }
+
+    /// Read the next element, which must be named `name`, and return a decoder for its contents, e.g. for a component
+    /// of a SEQUENCE.
+    pub fn decode_element(&mut self, name: &str) -> Result<Decoder<'a>, DecodeError> {
+        if self.peek_name().is_some_and(|n| n != name) {
+            return Err(DecodeError::UnexpectedElement);
+        }
+        let ret = self.decode_any_element();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(ret.unwrap().1)
+    }
+
+    /// Read the next item of a SEQUENCE OF, held in an element named `name` unless it's an empty element of its own
+    /// (e.g. `<true/>`), and return a decoder for it.
+    pub fn decode_item(&mut self, name: &str) -> Result<Decoder<'a>, DecodeError> {
+        let ret = self.skip_whitespace();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        match self.nodes.get(self.pos) {
+            Some(Node::Empty(n)) if n != name => {
+                self.pos += 1;
+                Ok(Decoder {
+                    nodes: &self.nodes[self.pos - 1..self.pos],
+                    pos: 0,
+                    canonical: self.canonical,
+                })
+            }
+            _ => self.decode_element(name),
+        }
+    }
+
+    /// Read a value written as a single empty element (e.g. `<true/>`) and return its name.
+    pub fn decode_empty_element(&mut self) -> Result<&'a str, DecodeError> {
+        let ret = self.skip_whitespace();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let nodes = self.nodes;
+        let name = match nodes.get(self.pos) {
+            Some(Node::Empty(name)) => name,
+            Some(Node::Element(..)) => return Err(DecodeError::UnexpectedElement),
+            Some(Node::Text(_)) => return Err(DecodeError::UnexpectedText),
+            None => return Err(DecodeError::MissingElement),
+        };
+        self.pos += 1;
+        let ret = self.finish();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(name)
+    }
+
+    /// Read the rest of the contents as text, e.g. the characters of a character string. Empty elements naming
+    /// control characters (e.g. `<cr/>`) are replaced by those characters.
+    pub fn decode_text(&mut self) -> Result<String, DecodeError> {
+        let mut s = String::new();
+        for node in &self.nodes[self.pos..] {
+            match *node {
+                Node::Text(ref text) => s.push_str(text),
+                Node::Empty(ref name) => match CONTROL_NAMES.iter().position(|n| n == name) {
+                    Some(i) => s.push(i as u8 as char),
+                    None => return Err(DecodeError::UnexpectedElement),
+                },
+                Node::Element(..) => return Err(DecodeError::UnexpectedElement),
+            }
+        }
+        self.pos = self.nodes.len();
+        Ok(s)
+    }
+
+    /// Read the rest of the contents as a token in which whitespace has no meaning, e.g. the digits of an INTEGER.
+    /// Whitespace around it is removed, except with CXER, where it's an `Err`.
+    pub fn decode_token(&mut self) -> Result<String, DecodeError> {
+        let ret = self.decode_text();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let text = ret.unwrap();
+        let trimmed = text.trim_matches(|c| c == ' ' || c == '\t' || c == '\n' || c == '\r');
+        if self.canonical && trimmed.len() != text.len() {
+            return Err(DecodeError::UnexpectedWhitespace);
+        }
+        Ok(trimmed.to_string())
+    }
+
+    /// Read the rest of the contents as an INTEGER, i.e. decimal digits with an optional sign. With CXER, there can
+    /// be no `+` sign or leading zeros, and 0 has no sign.
+    pub fn decode_int(&mut self) -> Result<i64, DecodeError> {
+        let ret = self.decode_token();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let token = ret.unwrap();
+        let digits = token.strip_prefix(['+', '-']).unwrap_or(&token);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(DecodeError::MalformedInt);
+        }
+        if self.canonical && (token.starts_with('+') || digits.len() > 1 && digits.starts_with('0') || token == "-0") {
+            return Err(DecodeError::NonCanonicalInt);
+        }
+        match token.parse() {
+            Ok(value) => Ok(value),
+            Err(_) => Err(DecodeError::MalformedInt),
+        }
+    }
+
+    /// Read the rest of the contents as an OCTET STRING, i.e. hexadecimal digits. With CXER, the digits must be
+    /// uppercase and there can be no whitespace.
+    pub fn decode_octet_string(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let ret = self.decode_text();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let text = ret.unwrap();
+        let mut digits = Vec::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                ' ' | '\t' | '\n' | '\r' if self.canonical => return Err(DecodeError::UnexpectedWhitespace),
+                ' ' | '\t' | '\n' | '\r' => continue,
+                'a'..='f' if self.canonical => return Err(DecodeError::NonCanonicalHex),
+                c => match c.to_digit(16) {
+                    Some(d) => digits.push(d as u8),
+                    None => return Err(DecodeError::InvalidHex),
+                },
+            }
+        }
+        if digits.len() % 2 != 0 {
+            return Err(DecodeError::InvalidHex);
+        }
+        Ok(digits.chunks(2).map(|d| (d[0] << 4) | d[1]).collect())
+    }
+}
@@ -0,0 +1,141 @@
+#[derive(Debug, PartialEq)]
+pub enum EncodeError {
+    InvalidValue,
+}
+
+// The names of the empty elements that stand for the control characters U+0000 to U+001F in character strings
+pub(super) const CONTROL_NAMES: [&str; 32] = ["nul", "soh", "stx", "etx", "eot", "enq", "ack", "bel", "bs", "ht", "lf",
+                                              "vt", "ff", "cr", "so", "si", "dle", "dc1", "dc2", "dc3", "dc4", "nak",
+                                              "syn", "etb", "can", "em", "sub", "esc", "is4", "is3", "is2", "is1"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Node {
+    Text(String),
+    // An empty element that is part of a value, e.g. `<true/>`
+    Empty(String),
+    // An element holding a value, e.g. a component of a SEQUENCE
+    Element(String, Encoding),
+}
+
+/// The XER encoding of a value: the contents of the element that holds it, which is text, empty elements such as
+/// `<true/>` or elements holding the values of components. The name of the element that holds the value itself
+/// depends on where it is, so it is only given when the encoding is written out.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::xer::{Encoding, XerElement};
+///
+/// // Foo ::= SEQUENCE { a INTEGER, b BOOLEAN }
+/// let mut enc = Encoding::new();
+/// enc.push("a", 5.to_xer().unwrap());
+/// enc.push("b", true.to_xer().unwrap());
+/// println!("{}", enc.to_xml("Foo", true)); // Prints <Foo><a>5</a><b><true/></b></Foo>
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Encoding {
+    pub(super) nodes: Vec<Node>,
+}
+
+impl Encoding {
+    /// Construct a new, empty `Encoding`, e.g. for a NULL.
+    pub fn new() -> Encoding {
+        Encoding { nodes: Vec::new() }
+    }
+
+    /// Construct the encoding of a value written as text. Control characters become empty elements (e.g. `<cr/>`).
+    pub fn text(s: &str) -> Encoding {
+        let mut enc = Encoding::new();
+        let mut text = String::new();
+        for c in s.chars() {
+            if (c as u32) < 0x20 {
+                if !text.is_empty() {
+                    enc.nodes.push(Node::Text(text.clone()));
+                    text.clear();
+                }
+                enc.nodes.push(Node::Empty(CONTROL_NAMES[c as usize].to_string()));
+            } else {
+                text.push(c);
+            }
+        }
+        if !text.is_empty() {
+            enc.nodes.push(Node::Text(text));
+        }
+        enc
+    }
+
+    /// Construct the encoding of a value written as an empty element, e.g. `<true/>` or the identifier of an
+    /// ENUMERATED value.
+    pub fn empty_element(name: &str) -> Encoding {
+        Encoding { nodes: vec![Node::Empty(name.to_string())] }
+    }
+
+    /// Construct the encoding of an OCTET STRING, which is its octets in uppercase hexadecimal.
+    pub fn octet_string(bytes: &[u8]) -> Encoding {
+        let hex: String = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        Encoding::text(&hex)
+    }
+
+    /// Append an element named `name` holding `value`, e.g. a component of a SEQUENCE or the chosen alternative of a
+    /// CHOICE.
+    pub fn push(&mut self, name: &str, value: Encoding) {
+        self.nodes.push(Node::Element(name.to_string(), value));
+    }
+
+    /// Append the contents of `other` to the end of `self`.
+    pub fn append(&mut self, other: &Encoding) {
+        self.nodes.extend_from_slice(&other.nodes);
+    }
+
+    /// Check if the encoding is a single empty element, like those of BOOLEAN values. Such values aren't wrapped in
+    /// an element of their own in a SEQUENCE OF.
+    pub fn is_empty_element(&self) -> bool {
+        matches!(self.nodes[..], [Node::Empty(_)])
+    }
+
+    /// Write the encoding as an XML element named `name`. With `canonical` set, it's written the way CXER requires,
+    /// without any whitespace between elements; otherwise elements holding other elements are indented, one per line.
+    pub fn to_xml(&self, name: &str, canonical: bool) -> String {
+        let mut xml = String::new();
+        self.write_to(&mut xml, name, if canonical { None } else { Some(0) });
+        xml
+    }
+
+    fn write_to(&self, xml: &mut String, name: &str, indent: Option<usize>) {
+        if self.nodes.is_empty() {
+            xml.push_str(&format!("<{}/>", name));
+            return;
+        }
+        xml.push_str(&format!("<{}>", name));
+
+        let nested = self.nodes.iter().any(|n| matches!(*n, Node::Element(..)));
+        let child_indent = indent.map(|i| i + 1);
+        for node in &self.nodes {
+            if let (true, Some(i)) = (nested, child_indent) {
+                xml.push('\n');
+                xml.push_str(&"  ".repeat(i));
+            }
+            match *node {
+                Node::Text(ref text) => {
+                    for c in text.chars() {
+                        match c {
+                            '&' => xml.push_str("&amp;"),
+                            '<' => xml.push_str("&lt;"),
+                            '>' => xml.push_str("&gt;"),
+                            c => xml.push(c),
+                        }
+                    }
+                }
+                Node::Empty(ref name) => xml.push_str(&format!("<{}/>", name)),
+                Node::Element(ref name, ref value) => value.write_to(xml, name, child_indent),
+            }
+        }
+
+        if let (true, Some(i)) = (nested, indent) {
+            xml.push('\n');
+            xml.push_str(&"  ".repeat(i));
+        }
+        xml.push_str(&format!("</{}>", name));
+    }
+}
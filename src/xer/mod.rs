@@ -0,0 +1,149 @@
+mod decoder;
+mod encoding;
+pub use self::decoder::{Decoder, DecodeError, parse};
+pub use self::encoding::{Encoding, EncodeError};
+
+/// Trait for XER encoding/decoding.
+///
+/// XER doesn't depend on constraints: values are written as text (e.g. the digits of an INTEGER), as empty elements
+/// (e.g. `<true/>`) or as elements named after the components of a SEQUENCE or the alternative of a CHOICE.
+/// `NAME` is the name of the element that holds a value of the type when it isn't a component, e.g. at the root of a
+/// document or as an item of a SEQUENCE OF.
+///
+/// # Examples
+///
+/// ```
+/// // Foo ::= SEQUENCE {
+/// //     a INTEGER,
+/// //     b BOOLEAN OPTIONAL
+/// // }
+/// extern crate asn1;
+/// use asn1::xer::{self, Encoding, XerElement};
+///
+/// struct Foo {
+///     a: i32,
+///     b: Option<bool>,
+/// }
+///
+/// impl XerElement for Foo {
+///     const NAME: &'static str = "Foo";
+///
+///     fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, xer::DecodeError> {
+///         let ret = decoder.decode_element("a");
+///         if ret.is_err() {
+///             return Err(ret.err().unwrap());
+///         }
+///         let a = i32::from_xer(&mut ret.unwrap());
+///         if a.is_err() {
+///             return Err(a.err().unwrap());
+///         }
+///         let mut b = None;
+///         if decoder.peek_name() == Some("b") {
+///             let ret = bool::from_xer(&mut decoder.decode_element("b").unwrap());
+///             if ret.is_err() {
+///                 return Err(ret.err().unwrap());
+///             }
+///             b = Some(ret.unwrap());
+///         }
+///         let ret = decoder.finish();
+///         if ret.is_err() {
+///             return Err(ret.err().unwrap());
+///         }
+///         Ok(Foo { a: a.unwrap(), b })
+///     }
+///
+///     fn to_xer(&self) -> Result<Encoding, xer::EncodeError> {
+///         let mut enc = Encoding::new();
+///         enc.push("a", self.a.to_xer().unwrap());
+///         if let Some(b) = self.b {
+///             enc.push("b", b.to_xer().unwrap());
+///         }
+///         Ok(enc)
+///     }
+/// }
+/// ```
+pub trait XerElement: Sized {
+    /// The name of the element holding a value of the type, e.g. `INTEGER` or the name of a type reference.
+    const NAME: &'static str;
+
+    /// Read a value from the contents of its element.
+    fn from_xer(decoder: &mut Decoder) -> Result<Self, DecodeError>;
+
+    /// Encode the value as the contents of its element.
+    fn to_xer(&self) -> Result<Encoding, EncodeError>;
+}
+
+/// Encode `value` with basic XER, as a document whose root element is named `T::NAME`. Elements holding other
+/// elements are indented to make the document easier to read.
+pub fn encode<T: XerElement>(value: &T) -> Result<String, EncodeError> {
+    let ret = value.to_xer();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(ret.unwrap().to_xml(T::NAME, false))
+}
+
+/// Encode `value` with canonical XER (CXER).
+///
+/// The types of the crate encode their values canonically, so the encoding is valid CXER as long as
+/// implementations of `XerElement` for SEQUENCE types omit components equal to their DEFAULT value.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::xer;
+///
+/// println!("{}", xer::encode_canonical(&vec![true, false]).unwrap());
+/// // Prints <SEQUENCE_OF><true/><false/></SEQUENCE_OF>
+/// ```
+pub fn encode_canonical<T: XerElement>(value: &T) -> Result<String, EncodeError> {
+    let ret = value.to_xer();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(ret.unwrap().to_xml(T::NAME, true))
+}
+
+/// Decode a `T` from the XER document `xml`, whose root element must be named `T::NAME`.
+pub fn decode<T: XerElement>(xml: &str) -> Result<T, DecodeError> {
+    decode_with(xml, false)
+}
+
+/// Decode a `T` from the XER document `xml`, which must be its canonical XER (CXER) encoding.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::xer;
+///
+/// assert_eq!(Ok(5), xer::decode_canonical::<i32>("<INTEGER>5</INTEGER>"));
+/// assert_eq!(Err(xer::DecodeError::NonCanonicalInt), xer::decode_canonical::<i32>("<INTEGER>+5</INTEGER>"));
+/// ```
+pub fn decode_canonical<T: XerElement>(xml: &str) -> Result<T, DecodeError> {
+    decode_with(xml, true)
+}
+
+fn decode_with<T: XerElement>(xml: &str, canonical: bool) -> Result<T, DecodeError> {
+    let ret = parse(xml);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let (name, content) = ret.unwrap();
+    if name != T::NAME {
+        return Err(DecodeError::UnexpectedElement);
+    }
+    let mut decoder = Decoder::new(&content);
+    decoder.set_canonical(canonical);
+    let ret = T::from_xer(&mut decoder);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let value = ret.unwrap();
+    let ret = decoder.finish();
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(value)
+}
@@ -0,0 +1,299 @@
+extern crate asn1;
+use asn1::{BitString, BMPString, GeneralizedTime, IA5String, ObjectIdentifier, UTCTime};
+use asn1::ber::{self, BerElement, Class, Encoding, Length, Tag};
+
+fn encode<T: BerElement>(x: &T) -> Vec<u8> {
+    x.to_ber(T::TAG).unwrap().bytes()
+}
+
+fn decode<T: BerElement>(data: &[u8]) -> Result<T, ber::DecodeError> {
+    let mut d = ber::Decoder::new(data);
+    T::from_ber(&mut d, T::TAG)
+}
+
+// Foo ::= SEQUENCE {
+//     a INTEGER,
+//     b [0] IMPLICIT BOOLEAN OPTIONAL,
+//     c [APPLICATION 40] EXPLICIT UTF8String
+// }
+#[derive(Debug, PartialEq)]
+struct Foo {
+    a: i32,
+    b: Option<bool>,
+    c: String,
+}
+
+impl BerElement for Foo {
+    const TAG: Tag = Tag::SEQUENCE;
+
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let d = decoder.decode_constructed(tag);
+        if d.is_err() {
+            return Err(d.err().unwrap());
+        }
+        let mut d = d.unwrap();
+
+        let a = i32::from_ber(&mut d, i32::TAG);
+        if a.is_err() {
+            return Err(a.err().unwrap());
+        }
+        let mut b = None;
+        if d.peek_tag() == Ok(Tag::context(0)) {
+            let ret = bool::from_ber(&mut d, Tag::context(0));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            b = Some(ret.unwrap());
+        }
+        let c = d.decode_explicit::<String>(Tag::application(40));
+        if c.is_err() {
+            return Err(c.err().unwrap());
+        }
+
+        let ret = d.finish();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(Foo {
+            a: a.unwrap(),
+            b,
+            c: c.unwrap(),
+        })
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<Encoding, ber::EncodeError> {
+        let mut elements = vec![self.a.to_ber(i32::TAG).unwrap()];
+        if let Some(b) = self.b {
+            elements.push(b.to_ber(Tag::context(0)).unwrap());
+        }
+        elements.push(Encoding::explicit(Tag::application(40), self.c.to_ber(String::TAG).unwrap()));
+        Ok(Encoding::constructed(tag, elements))
+    }
+}
+
+#[test]
+fn identifiers() {
+    let mut bytes = Vec::new();
+    ber::write_identifier(&mut bytes, Tag::context(3), true);
+    ber::write_identifier(&mut bytes, Tag::application(31), false);
+    ber::write_identifier(&mut bytes, Tag::private(201), false);
+    assert_eq!(vec![0xa3, 0x5f, 0x1f, 0xdf, 0x81, 0x49], bytes);
+
+    let mut d = ber::Decoder::new(&bytes);
+    assert_eq!(Ok((Tag::context(3), true)), d.read_identifier());
+    assert_eq!(Ok((Tag::new(Class::Application, 31), false)), d.read_identifier());
+    assert_eq!(Ok((Tag::private(201), false)), d.read_identifier());
+
+    // Leading zero group
+    let mut d = ber::Decoder::new(b"\x1f\x80\x01");
+    assert_eq!(Err(ber::DecodeError::MalformedIdentifier), d.read_identifier());
+    let mut d = ber::Decoder::new(b"\x1f\x81");
+    assert_eq!(Err(ber::DecodeError::NotEnoughBytes), d.read_identifier());
+}
+
+#[test]
+fn lengths() {
+    let mut bytes = Vec::new();
+    ber::write_length(&mut bytes, Length::Definite(5));
+    ber::write_length(&mut bytes, Length::Definite(200));
+    ber::write_length(&mut bytes, Length::Definite(0x1234));
+    ber::write_length(&mut bytes, Length::Indefinite);
+    assert_eq!(vec![0x05, 0x81, 0xc8, 0x82, 0x12, 0x34, 0x80], bytes);
+
+    // Long form with a redundant leading octet
+    let data = [0x83, 0x00, 0x00, 0x01, 0xaa];
+    let mut d = ber::Decoder::new(&data);
+    assert_eq!(Ok(Length::Definite(1)), d.read_length());
+
+    let mut d = ber::Decoder::new(b"\x82\x01\x00\x00");
+    assert_eq!(Err(ber::DecodeError::NotEnoughBytes), d.read_length());
+    let mut d = ber::Decoder::new(b"\xff");
+    assert_eq!(Err(ber::DecodeError::MalformedLength), d.read_length());
+}
+
+#[test]
+fn bool() {
+    assert_eq!(vec![0x01, 0x01, 0xff], encode(&true));
+    assert_eq!(vec![0x01, 0x01, 0x00], encode(&false));
+    assert_eq!(Ok(true), decode::<bool>(b"\x01\x01\x01"));
+    assert_eq!(Ok(false), decode::<bool>(b"\x01\x01\x00"));
+    assert_eq!(Err(ber::DecodeError::InvalidLength), decode::<bool>(b"\x01\x02\x00\x00"));
+    assert_eq!(Err(ber::DecodeError::UnexpectedTag), decode::<bool>(b"\x02\x01\x00"));
+}
+
+#[test]
+fn integers() {
+    assert_eq!(vec![0x02, 0x01, 0x00], encode(&0i32));
+    assert_eq!(vec![0x02, 0x01, 0x7f], encode(&127i32));
+    assert_eq!(vec![0x02, 0x02, 0x00, 0x80], encode(&128i32));
+    assert_eq!(vec![0x02, 0x01, 0x80], encode(&-128i32));
+    assert_eq!(vec![0x02, 0x02, 0xff, 0x7f], encode(&-129i32));
+    assert_eq!(vec![0x02, 0x05, 0x00, 0xff, 0xff, 0xff, 0xff], encode(&u32::MAX));
+
+    assert_eq!(Ok(128), decode::<i32>(b"\x02\x02\x00\x80"));
+    assert_eq!(Ok(-129), decode::<i16>(b"\x02\x02\xff\x7f"));
+    assert_eq!(Ok(u32::MAX), decode::<u32>(b"\x02\x05\x00\xff\xff\xff\xff"));
+    // Redundant leading octets are allowed in BER
+    assert_eq!(Ok(1), decode::<u8>(b"\x02\x03\x00\x00\x01"));
+    assert_eq!(Ok(-1), decode::<i8>(b"\x02\x02\xff\xff"));
+
+    assert_eq!(Err(ber::DecodeError::MalformedInt), decode::<u8>(b"\x02\x02\x01\x00"));
+    assert_eq!(Err(ber::DecodeError::MalformedInt), decode::<u8>(b"\x02\x01\xff"));
+    assert_eq!(Err(ber::DecodeError::MalformedInt), decode::<i32>(b"\x02\x00"));
+    assert_eq!(Err(ber::DecodeError::UnexpectedForm), decode::<i32>(b"\x22\x03\x02\x01\x00"));
+}
+
+#[test]
+fn null() {
+    assert_eq!(vec![0x05, 0x00], encode(&()));
+    assert_eq!(Ok(()), decode::<()>(b"\x05\x00"));
+    assert_eq!(Err(ber::DecodeError::InvalidLength), decode::<()>(b"\x05\x01\x00"));
+}
+
+#[test]
+fn bit_string() {
+    let b = BitString::from_bits(&[true, false, true, true, false, true, true, true, false, true]);
+    assert_eq!(vec![0x03, 0x03, 0x06, 0xb7, 0x40], encode(&b));
+    assert_eq!(Ok(b.clone()), decode::<BitString>(b"\x03\x03\x06\xb7\x40"));
+    // Unused bits need not be 0 in BER
+    assert_eq!(Ok(b.clone()), decode::<BitString>(b"\x03\x03\x06\xb7\x7f"));
+
+    assert_eq!(vec![0x03, 0x01, 0x00], encode(&BitString::new()));
+    assert_eq!(Ok(BitString::new()), decode::<BitString>(b"\x03\x01\x00"));
+
+    // Constructed, with indefinite length
+    let data = b"\x23\x80\x03\x02\x00\xb7\x03\x02\x06\x40\x00\x00";
+    assert_eq!(Ok(b), decode::<BitString>(data));
+
+    assert_eq!(Err(ber::DecodeError::InvalidUnusedBits), decode::<BitString>(b"\x03\x01\x03"));
+    assert_eq!(Err(ber::DecodeError::InvalidUnusedBits), decode::<BitString>(b"\x03\x02\x08\x00"));
+    assert_eq!(Err(ber::DecodeError::InvalidUnusedBits),
+               decode::<BitString>(b"\x23\x08\x03\x02\x06\xb4\x03\x02\x06\x40"));
+}
+
+#[test]
+fn octet_string() {
+    let enc = Encoding::primitive(Tag::OCTET_STRING, vec![0x01, 0x02, 0x03]);
+    assert_eq!(vec![0x04, 0x03, 0x01, 0x02, 0x03], enc.bytes());
+
+    let mut d = ber::Decoder::new(b"\x04\x03\x01\x02\x03");
+    assert_eq!(Ok(vec![0x01, 0x02, 0x03]), d.decode_string(Tag::OCTET_STRING));
+
+    // Constructed, with a nested constructed segment
+    let mut d = ber::Decoder::new(b"\x24\x0b\x04\x01\x01\x24\x80\x04\x02\x02\x03\x00\x00");
+    assert_eq!(Ok(vec![0x01, 0x02, 0x03]), d.decode_string(Tag::OCTET_STRING));
+
+    let mut d = ber::Decoder::new(b"\x24\x03\x02\x01\x01");
+    assert_eq!(Err(ber::DecodeError::UnexpectedTag), d.decode_string(Tag::OCTET_STRING));
+}
+
+#[test]
+fn strings() {
+    assert_eq!(b"\x0c\x03h\xc3\xa9".to_vec(), encode(&"hé".to_string()));
+    assert_eq!(Ok("hé".to_string()), decode::<String>(b"\x0c\x03h\xc3\xa9"));
+    assert_eq!(Err(ber::DecodeError::InvalidUtf8), decode::<String>(b"\x0c\x01\xff"));
+
+    let s = IA5String::new("abc").unwrap();
+    assert_eq!(b"\x16\x03abc".to_vec(), encode(&s));
+    assert_eq!(Ok(s), decode::<IA5String>(b"\x36\x07\x04\x01a\x04\x02bc"));
+    assert_eq!(Err(ber::DecodeError::InvalidCharacter), decode::<IA5String>(b"\x16\x01\x80"));
+
+    let s = BMPString::new("hé").unwrap();
+    assert_eq!(b"\x1e\x04\x00h\x00\xe9".to_vec(), encode(&s));
+    assert_eq!(Ok(s), decode::<BMPString>(b"\x1e\x04\x00h\x00\xe9"));
+    assert_eq!(Err(ber::DecodeError::InvalidLength), decode::<BMPString>(b"\x1e\x03\x00h\x00"));
+}
+
+#[test]
+fn real_and_oid() {
+    assert_eq!(vec![0x09, 0x03, 0x80, 0x01, 0x05], encode(&10f64));
+    assert_eq!(Ok(10.), decode::<f64>(b"\x09\x03\x80\x01\x05"));
+    assert_eq!(Ok(0.), decode::<f64>(b"\x09\x00"));
+
+    let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+    let data = b"\x06\x06\x2a\x86\x48\x86\xf7\x0d";
+    assert_eq!(data.to_vec(), encode(&oid));
+    assert_eq!(Ok(oid), decode::<ObjectIdentifier>(data));
+    assert_eq!(Err(ber::DecodeError::InvalidOid), decode::<ObjectIdentifier>(b"\x06\x01\x80"));
+}
+
+#[test]
+fn times() {
+    let t = UTCTime::new("240229134530Z").unwrap();
+    assert_eq!(b"\x17\x0d240229134530Z".to_vec(), encode(&t));
+    assert_eq!(Ok(t), decode::<UTCTime>(b"\x17\x0d240229134530Z"));
+
+    let t = GeneralizedTime::new("20240229134530.5Z").unwrap();
+    assert_eq!(b"\x18\x1120240229134530.5Z".to_vec(), encode(&t));
+    assert_eq!(Ok(t), decode::<GeneralizedTime>(b"\x18\x1120240229134530.5Z"));
+    assert_eq!(Err(ber::DecodeError::InvalidTime), decode::<UTCTime>(b"\x17\x02ab"));
+}
+
+#[test]
+fn sequence_of() {
+    let v = vec![1u8, 2, 44];
+    assert_eq!(vec![0x30, 0x09, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02, 0x02, 0x01, 0x2c], encode(&v));
+    assert_eq!(Ok(v), decode::<Vec<u8>>(b"\x30\x09\x02\x01\x01\x02\x01\x02\x02\x01\x2c"));
+    assert_eq!(Ok(Vec::<bool>::new()), decode::<Vec<bool>>(b"\x30\x00"));
+    assert_eq!(Ok(vec![true, false]), decode::<Vec<bool>>(b"\x30\x80\x01\x01\xff\x01\x01\x00\x00\x00"));
+
+    // SET OF, implicitly tagged
+    let v = vec![true];
+    let enc = v.to_ber(Tag::SET).unwrap();
+    assert_eq!(vec![0x31, 0x03, 0x01, 0x01, 0xff], enc.bytes());
+    let mut d = ber::Decoder::new(b"\x31\x03\x01\x01\xff");
+    assert_eq!(Ok(v), Vec::<bool>::from_ber(&mut d, Tag::SET));
+}
+
+#[test]
+fn tagging() {
+    let foo = Foo {
+        a: 5,
+        b: Some(true),
+        c: "hi".to_string(),
+    };
+    let data = b"\x30\x0d\x02\x01\x05\x80\x01\xff\x7f\x28\x04\x0c\x02hi";
+    assert_eq!(data.to_vec(), encode(&foo));
+    assert_eq!(Ok(foo), decode::<Foo>(data));
+
+    let foo = Foo {
+        a: -1,
+        b: None,
+        c: String::new(),
+    };
+    assert_eq!(Ok(foo), decode::<Foo>(b"\x30\x80\x02\x01\xff\x7f\x28\x80\x0c\x00\x00\x00\x00\x00"));
+
+    assert_eq!(Err(ber::DecodeError::UnexpectedTag),
+               decode::<Foo>(b"\x30\x06\x02\x01\x05\x81\x01\xff"));
+    assert_eq!(Err(ber::DecodeError::TrailingData),
+               decode::<Foo>(b"\x30\x0a\x02\x01\x05\x7f\x28\x02\x0c\x00\x05\x00"));
+}
+
+#[test]
+fn indefinite_length() {
+    let mut enc = Encoding::constructed(Tag::SEQUENCE, vec![true.to_ber(Tag::BOOLEAN).unwrap()]);
+    enc.set_indefinite(true);
+    assert_eq!(vec![0x30, 0x80, 0x01, 0x01, 0xff, 0x00, 0x00], enc.bytes());
+
+    let data = b"\x30\x80\x30\x80\x05\x00\x00\x00\x00\x00\x05\x00";
+    let mut d = ber::Decoder::new(data);
+    let outer = d.read_element().unwrap();
+    assert!(outer.indefinite);
+    assert_eq!(b"\x30\x80\x05\x00\x00\x00", outer.contents);
+    assert_eq!(Ok(()), <()>::from_ber(&mut d, Tag::NULL));
+    assert!(d.is_empty());
+
+    // A primitive element can't have an indefinite length
+    let mut d = ber::Decoder::new(b"\x04\x80\x00\x00");
+    assert_eq!(Err(ber::DecodeError::MalformedLength), d.read_element());
+    // Missing end-of-contents
+    let mut d = ber::Decoder::new(b"\x30\x80\x05\x00");
+    assert_eq!(Err(ber::DecodeError::NotEnoughBytes), d.read_element());
+
+    let mut data = vec![0x30, 0x80];
+    for _ in 0..100 {
+        data.extend_from_slice(&[0x30, 0x80]);
+    }
+    let mut d = ber::Decoder::new(&data);
+    assert_eq!(Err(ber::DecodeError::NestingTooDeep), d.read_element());
+}
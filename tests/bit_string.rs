@@ -5,16 +5,16 @@ use asn1::aper::{self, APerElement, Constraint, Constraints};
 #[test]
 fn get_set() {
     let mut b = BitString::with_len(64);
-    assert_eq!(false, b.is_set(0));
+    assert!(!b.is_set(0));
     b.set(0, true);
-    assert_eq!(true, b.is_set(0));
+    assert!(b.is_set(0));
 }
 
 #[test]
 fn get_set_non_boundary() {
     let mut b = BitString::with_len(64);
     b.set(9, true);
-    assert_eq!(true, b.is_set(9));
+    assert!(b.is_set(9));
 }
 
 #[test]
@@ -23,14 +23,14 @@ fn decode_padded() {
     let mut d = aper::Decoder::new(data);
     let b = BitString::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(20))),
+        size: Some(Constraint::new(Some(20), Some(20))),
     }).unwrap();
     println!("{:?}", b);
     for i in 0..20 {
-        if i == 17 || i == 18 || i == 19 {
-            assert_eq!(true, b.is_set(i));
+        if i == 8 || i == 9 || i == 10 {
+            assert!(b.is_set(i));
         } else {
-            assert_eq!(false, b.is_set(i));
+            assert!(!b.is_set(i));
         }
     }
 }
@@ -42,14 +42,14 @@ fn decode_padded_small() {
     d.read(4).unwrap();
     let b = BitString::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(4))),
+        size: Some(Constraint::new(Some(4), Some(4))),
     }).unwrap();
     println!("{:?}", b);
     for i in 0..4 {
-        if i == 1 || i == 2 || i == 3 {
-            assert_eq!(true, b.is_set(i));
+        if i == 0 || i == 1 || i == 2 {
+            assert!(b.is_set(i));
         } else {
-            assert_eq!(false, b.is_set(i));
+            assert!(!b.is_set(i));
         }
     }
 }
@@ -60,24 +60,175 @@ fn decode_unpadded() {
     let mut d = aper::Decoder::new(data);
     let b = BitString::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(24))),
+        size: Some(Constraint::new(Some(24), Some(24))),
     }).unwrap();
     println!("{:?}", b);
     for i in 0..24 {
-        if i == 5 || i == 6 || i == 7 {
-            assert_eq!(true, b.is_set(i));
+        if i == 16 || i == 17 || i == 18 {
+            assert!(b.is_set(i));
         } else {
-            assert_eq!(false, b.is_set(i));
+            assert!(!b.is_set(i));
         }
     }
 }
 
 #[test]
 fn encode_padded_small() {
-    let bs = BitString::with_bytes_and_len(&vec![0x0e as u8], 4);
+    let bs = BitString::with_bytes_and_len(&[0xe0_u8], 4);
     let target: Vec<u8> = vec![0xe0];
     assert_eq!(target, *bs.to_aper(Constraints{
         value: None,
-        size: Some(Constraint::new(None, Some(4))),
+        size: Some(Constraint::new(Some(4), Some(4))),
     }).unwrap().bytes());
 }
+
+#[test]
+fn asn1_bit_order() {
+    let b = BitString::with_bytes_and_len(&[0x80, 0x01], 16);
+    assert!(b.is_set(0));
+    assert!(!b.is_set(7));
+    assert!(b.is_set(15));
+    assert!(!b.is_set(16));
+}
+
+#[test]
+fn iter_push_truncate() {
+    let mut b = BitString::new();
+    b.push(true);
+    b.push(false);
+    b.push(true);
+    assert_eq!(vec![true, false, true], b.iter().collect::<Vec<bool>>());
+    assert_eq!(vec![0xa0], b.to_bytes());
+    b.truncate(1);
+    assert_eq!(1, b.get_num_bits());
+    assert_eq!(vec![0x80], b.to_bytes());
+}
+
+#[test]
+fn from_bits_and_slice() {
+    let b = BitString::from_bits(&[false, true, true, false, true]);
+    assert_eq!(BitString::with_bytes_and_len(&[0x68], 5), b);
+    assert_eq!(BitString::from_bits(&[true, true, false]), b.slice(1..4));
+}
+
+#[test]
+fn unused_bits_are_cleared() {
+    let a = BitString::with_bytes_and_len(&[0xff], 4);
+    let b = BitString::with_bytes_and_len(&[0xf0], 4);
+    assert_eq!(a, b);
+    assert_eq!(vec![0xf0], a.to_bytes());
+}
+
+#[test]
+fn uint_conversions() {
+    let b = BitString::from_uint(0x2a, 22);
+    assert_eq!(22, b.get_num_bits());
+    assert_eq!(Some(0x2a), b.to_uint());
+    assert_eq!(vec![0x00, 0x00, 0xa8], b.to_bytes());
+
+    let ip = BitString::from(0xc0a80001_u32);
+    assert_eq!(32, ip.get_num_bits());
+    assert_eq!(vec![0xc0, 0xa8, 0x00, 0x01], ip.to_bytes());
+    assert_eq!(8, BitString::from(0x01_u8).get_num_bits());
+    assert_eq!(64, BitString::from(0x01_u64).get_num_bits());
+}
+
+#[test]
+fn bitwise_ops() {
+    let a = BitString::from_bits(&[true, true, false, false]);
+    let b = BitString::from_bits(&[true, false, true, false, true]);
+    assert_eq!(BitString::from_bits(&[true, false, false, false, false]), &a & &b);
+    assert_eq!(BitString::from_bits(&[true, true, true, false, true]), &a | &b);
+    assert_eq!(BitString::from_bits(&[false, true, true, false, true]), a.clone() ^ b);
+    assert_eq!(BitString::from_bits(&[false, false, true, true]), !a);
+}
+
+#[test]
+fn display() {
+    let b = BitString::from_bits(&[false, true, false, true]);
+    assert_eq!("'0101'B", format!("{}", b));
+    assert_eq!("'5'H", format!("{:X}", b));
+    assert_eq!("'c0a8'H", format!("{:x}", BitString::from(0xc0a8_u16)));
+    assert_eq!("''B", format!("{}", BitString::new()));
+}
+
+#[test]
+fn encode_decode_unaligned_long() {
+    let bs = BitString::from_uint(0x2a5, 12);
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(12), Some(12))),
+    };
+    let mut enc = true.to_aper(aper::UNCONSTRAINED).unwrap();
+    enc.append(&bs.to_aper(constraints).unwrap()).unwrap();
+    assert_eq!(vec![0x95, 0x28], *enc.bytes());
+
+    let bytes = enc.bytes().clone();
+    let mut d = aper::Decoder::new(&bytes);
+    assert!(bool::from_aper(&mut d, aper::UNCONSTRAINED).unwrap());
+    assert_eq!(bs, BitString::from_aper(&mut d, constraints).unwrap());
+}
+
+#[test]
+fn encode_decode_variable_size() {
+    // BIT STRING (SIZE(22..32)), e.g. gNB-ID
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(22), Some(32))),
+    };
+    let bs = BitString::from_uint(0x2a, 22);
+    let enc = bs.to_aper(constraints).unwrap();
+    // length 22 - 22 in 4 bits, padding, then 22 bits
+    assert_eq!(vec![0x00, 0x00, 0x00, 0xa8], *enc.bytes());
+
+    let bytes = enc.bytes().clone();
+    let mut d = aper::Decoder::new(&bytes);
+    assert_eq!(bs, BitString::from_aper(&mut d, constraints).unwrap());
+
+    let too_short = BitString::with_len(21);
+    assert_eq!(aper::EncodeError::InvalidSize, too_short.to_aper(constraints).unwrap_err());
+}
+
+#[test]
+fn encode_fixed_size_long_is_aligned() {
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(32), Some(32))),
+    };
+    let mut enc = true.to_aper(aper::UNCONSTRAINED).unwrap();
+    enc.append(&BitString::from(0xc0a80001_u32).to_aper(constraints).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0xc0, 0xa8, 0x00, 0x01], *enc.bytes());
+}
+
+#[test]
+fn size_without_lower_bound() {
+    // SIZE(MIN..4) is SIZE(0..4), not a fixed size of 4
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, Some(4))),
+    };
+    let bs = BitString::from_bits(&[true, true]);
+    let enc = bs.to_aper(constraints).unwrap();
+    assert_eq!(vec![0x40, 0xc0], *enc.bytes());
+    assert_eq!(bs, BitString::from_aper(&mut aper::Decoder::new(enc.bytes()), constraints).unwrap());
+    assert_eq!(None, constraints.size.unwrap().fixed_size());
+    assert_eq!(Some(4), Constraint::new(Some(4), Some(4)).fixed_size());
+}
+
+#[test]
+fn encode_variable_size_length_is_aligned() {
+    // SEQUENCE { BOOLEAN, BIT STRING (SIZE(0..300)) }: a length with more than 255 possible values is octet-aligned
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(0), Some(300))),
+    };
+    let bs = BitString::from_bits(&[true, false, true, false]);
+    let mut enc = true.to_aper(aper::UNCONSTRAINED).unwrap();
+    enc.append(&bs.to_aper(constraints).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0x00, 0x04, 0xa0], *enc.bytes());
+
+    let bytes = enc.bytes().clone();
+    let mut d = aper::Decoder::new(&bytes);
+    assert!(bool::from_aper(&mut d, aper::UNCONSTRAINED).unwrap());
+    assert_eq!(bs, BitString::from_aper(&mut d, constraints).unwrap());
+}
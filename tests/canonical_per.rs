@@ -0,0 +1,189 @@
+#[macro_use]
+extern crate asn1;
+use asn1::{BitString, GeneralizedTime, NamedBitString};
+use asn1::aper::{self, APerElement, Constraint, Constraints, DecodeError, UNCONSTRAINED};
+
+named_bits! {
+    pub enum Flags {
+        A = 0,
+        B = 1,
+        C = 2,
+    }
+}
+
+const FLAGS: Constraints = Constraints {
+    value: None,
+    size: Some(Constraint::new(Some(1), Some(8))),
+};
+
+const SET_OF: Constraints = Constraints {
+    value: None,
+    size: Some(Constraint::new(None, Some(8))),
+};
+
+// INTEGER, with the bounds of its value constraint
+#[derive(Debug, PartialEq)]
+struct Level(i64);
+
+impl APerElement for Level {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+    fn from_aper(decoder: &mut aper::Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let value = match constraints.value {
+            Some(value) => value,
+            None => return Err(DecodeError::MissingValueConstraint),
+        };
+        let ret = decoder.decode_int(value.min(), value.max());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(Level(ret.unwrap()))
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<aper::Encoding, aper::EncodeError> {
+        match constraints.value {
+            Some(value) => aper::encode_int(self.0, value.min(), value.max()),
+            None => Err(aper::EncodeError::MissingValueConstraint),
+        }
+    }
+}
+
+fn levels(min: i64, max: i64) -> Constraints {
+    Constraints {
+        value: Some(Constraint::new(Some(min), Some(max))),
+        size: None,
+    }
+}
+
+fn canonical(data: &[u8]) -> aper::Decoder<'_> {
+    let mut d = aper::Decoder::new(data);
+    d.set_canonical(true);
+    d
+}
+
+#[test]
+fn minimal_lengths_and_ints() {
+    assert_eq!(Ok(5), aper::Decoder::new(b"\x80\x05").decode_length());
+    assert_eq!(Err(DecodeError::NonMinimalLength), canonical(b"\x80\x05").decode_length());
+    assert_eq!(Ok(128), canonical(b"\x80\x80").decode_length());
+
+    assert_eq!(Ok(5), aper::Decoder::new(b"\x02\x00\x05").decode_int(None, None));
+    assert_eq!(Err(DecodeError::NonMinimalInt), canonical(b"\x02\x00\x05").decode_int(None, None));
+    assert_eq!(Err(DecodeError::NonMinimalInt), canonical(b"\x02\xff\x80").decode_int(None, None));
+    assert_eq!(Ok(128), canonical(b"\x02\x00\x80").decode_int(None, None));
+    assert_eq!(Err(DecodeError::NonMinimalInt), canonical(b"\x02\x00\x80").decode_int(Some(0), None));
+    assert_eq!(Ok(128), canonical(b"\x01\x80").decode_int(Some(0), None));
+}
+
+#[test]
+fn fragmentation() {
+    let data = vec![0x5a; 32768];
+    let enc = aper::encode_open_type(&aper::Encoding::with_bytes(data.clone())).unwrap();
+    assert_eq!(0xc2, enc.bytes()[0]);
+    assert_eq!(Ok(data.clone()), canonical(enc.bytes()).decode_open_type_bytes());
+
+    // The same octets in two fragments of 16K
+    let mut bytes = vec![0xc1];
+    bytes.extend_from_slice(&data[..16384]);
+    bytes.push(0xc1);
+    bytes.extend_from_slice(&data[16384..]);
+    bytes.push(0x00);
+    assert_eq!(Ok(data), aper::Decoder::new(&bytes).decode_open_type_bytes());
+    assert_eq!(Err(DecodeError::NonCanonicalFragmentation), canonical(&bytes).decode_open_type_bytes());
+}
+
+#[test]
+fn named_bits() {
+    let mut flags = NamedBitString::<Flags>::new();
+    flags.set(Flags::B, true);
+    let enc = flags.to_aper(FLAGS).unwrap();
+    assert_eq!(Ok(flags.clone()), NamedBitString::<Flags>::from_aper(&mut canonical(enc.bytes()), FLAGS));
+
+    let enc = BitString::from_bits(&[false, true, false, false]).to_aper(FLAGS).unwrap();
+    assert_eq!(Ok(flags), NamedBitString::<Flags>::from_aper(&mut aper::Decoder::new(enc.bytes()), FLAGS));
+    assert_eq!(Err(DecodeError::TrailingZeroBits),
+               NamedBitString::<Flags>::from_aper(&mut canonical(enc.bytes()), FLAGS));
+}
+
+#[test]
+fn reals_and_times() {
+    // 2 as 2 * 2^0 rather than 1 * 2^1
+    let data = b"\x03\x80\x00\x02";
+    assert_eq!(Ok(2.), f64::from_aper(&mut aper::Decoder::new(data), UNCONSTRAINED));
+    assert_eq!(Err(DecodeError::NonCanonicalReal), f64::from_aper(&mut canonical(data), UNCONSTRAINED));
+    let enc = 2f64.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(Ok(2.), f64::from_aper(&mut canonical(enc.bytes()), UNCONSTRAINED));
+
+    let t = GeneralizedTime::new("20240229120000.50Z").unwrap();
+    let enc = t.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(Ok(t), GeneralizedTime::from_aper(&mut aper::Decoder::new(enc.bytes()), UNCONSTRAINED));
+    assert_eq!(Err(DecodeError::NonCanonicalTime),
+               GeneralizedTime::from_aper(&mut canonical(enc.bytes()), UNCONSTRAINED));
+}
+
+#[test]
+fn defaults() {
+    assert!(aper::encode_default(&1u8, &1, UNCONSTRAINED).unwrap().is_none());
+    let enc = aper::encode_default(&2u8, &1, UNCONSTRAINED).unwrap().unwrap();
+    assert_eq!(&vec![0x02], enc.bytes());
+
+    assert_eq!(Ok(1), aper::Decoder::new(b"").decode_default(false, 1u8, UNCONSTRAINED));
+    assert_eq!(Ok(1), aper::Decoder::new(b"\x01").decode_default(true, 1u8, UNCONSTRAINED));
+    assert_eq!(Err(DecodeError::DefaultValueEncoded), canonical(b"\x01").decode_default(true, 1u8, UNCONSTRAINED));
+    assert_eq!(Ok(2), canonical(b"\x02").decode_default(true, 1u8, UNCONSTRAINED));
+}
+
+#[test]
+fn set_of_order() {
+    // SET (SIZE(0..8)) OF INTEGER (0..65535): the count takes 4 bits, and each element two aligned octets
+    let enc = aper::encode_set_of(&[300u16, 2, 1], SET_OF, UNCONSTRAINED).unwrap();
+    assert_eq!(&vec![0x30, 0x00, 0x01, 0x00, 0x02, 0x01, 0x2c], enc.bytes());
+    assert_eq!(Ok(vec![1u16, 2, 300]), canonical(enc.bytes()).decode_set_of::<u16>(SET_OF, UNCONSTRAINED));
+
+    let unsorted = b"\x30\x01\x2c\x00\x02\x00\x01";
+    assert_eq!(Ok(vec![300u16, 2, 1]), aper::Decoder::new(unsorted).decode_set_of::<u16>(SET_OF, UNCONSTRAINED));
+    assert_eq!(Err(DecodeError::UnsortedSetOf), canonical(unsorted).decode_set_of::<u16>(SET_OF, UNCONSTRAINED));
+}
+
+#[test]
+fn set_of_fixed_size() {
+    // SET (SIZE(3)) OF INTEGER (0..255) has no count
+    let triple = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(3), Some(3))),
+    };
+    let enc = aper::encode_set_of(&[3u8, 1, 2], triple, UNCONSTRAINED).unwrap();
+    assert_eq!(&vec![0x01, 0x02, 0x03], enc.bytes());
+    assert_eq!(Ok(vec![1u8, 2, 3]), canonical(b"\x01\x02\x03").decode_set_of::<u8>(triple, UNCONSTRAINED));
+    assert_eq!(Err(aper::EncodeError::InvalidSize),
+               aper::encode_set_of(&[1u8, 2], triple, UNCONSTRAINED).map(|_| ()));
+}
+
+#[test]
+fn set_of_constrained_elements() {
+    // SET (SIZE(0..8)) OF INTEGER (0..7): the elements are sorted by their 3-bit encodings padded to an octet
+    let enc = aper::encode_set_of(&[Level(5), Level(2), Level(1)], SET_OF, levels(0, 7)).unwrap();
+    assert_eq!(&vec![0x32, 0xa8], enc.bytes());
+    assert_eq!(Ok(vec![Level(1), Level(2), Level(5)]), canonical(enc.bytes()).decode_set_of(SET_OF, levels(0, 7)));
+
+    // SET (SIZE(0..8)) OF INTEGER (0..1000): each element takes two octets
+    let enc = aper::encode_set_of(&[Level(500), Level(2), Level(1)], SET_OF, levels(0, 1000)).unwrap();
+    assert_eq!(&vec![0x30, 0x00, 0x01, 0x00, 0x02, 0x01, 0xf4], enc.bytes());
+    let unsorted = b"\x30\x01\xf4\x00\x02\x00\x01";
+    assert_eq!(Err(DecodeError::UnsortedSetOf), canonical(unsorted).decode_set_of::<Level>(SET_OF, levels(0, 1000)));
+
+    let pair = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, Some(2))),
+    };
+    assert_eq!(Err(aper::EncodeError::InvalidSize),
+               aper::encode_set_of(&[Level(5), Level(2), Level(1)], pair, levels(0, 7)).map(|_| ()));
+
+    // SET (SIZE(4..MAX)) has a length determinant
+    let at_least_four = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(4), None)),
+    };
+    let data = b"\x03\x00\x01\x00\x02\x01\xf4";
+    assert_eq!(Err(DecodeError::InvalidSize),
+               canonical(data).decode_set_of::<Level>(at_least_four, levels(0, 1000)));
+}
@@ -0,0 +1,129 @@
+extern crate asn1;
+use asn1::BitString;
+use asn1::{ber, cer};
+use asn1::ber::{BerElement, Encoding, Rules, Tag};
+
+fn octet_string(data: &[u8]) -> Result<Vec<u8>, ber::DecodeError> {
+    let mut d = cer::decoder(data);
+    d.decode_string(Tag::OCTET_STRING)
+}
+
+fn segment(tag: u8, contents: &[u8]) -> Vec<u8> {
+    Encoding::primitive(Tag::universal(tag as u32), contents.to_vec()).bytes()
+}
+
+#[test]
+fn indefinite_lengths() {
+    assert_eq!(Ok(b"\x30\x80\x01\x01\xff\x00\x00".to_vec()), cer::encode(&vec![true]));
+    let v = vec![vec![1u8], vec![]];
+    let data = b"\x30\x80\x30\x80\x02\x01\x01\x00\x00\x30\x80\x00\x00\x00\x00";
+    assert_eq!(Ok(data.to_vec()), cer::encode(&v));
+    assert_eq!(Ok(v), cer::decode::<Vec<Vec<u8>>>(data));
+
+    assert_eq!(Err(ber::DecodeError::ConstructedDefiniteLength),
+               cer::decode::<Vec<bool>>(b"\x30\x03\x01\x01\xff"));
+    assert_eq!(Err(ber::DecodeError::NonMinimalLength),
+               cer::decode::<Vec<bool>>(b"\x30\x80\x01\x81\x01\xff\x00\x00"));
+    assert_eq!(Err(ber::DecodeError::InvalidBool), cer::decode::<Vec<bool>>(b"\x30\x80\x01\x01\x01\x00\x00"));
+}
+
+#[test]
+fn segmented_strings() {
+    let bytes: Vec<u8> = (0..2500).map(|i| i as u8).collect();
+    let mut data = vec![0x24, 0x80];
+    data.extend(segment(4, &bytes[..1000]));
+    data.extend(segment(4, &bytes[1000..2000]));
+    data.extend(segment(4, &bytes[2000..]));
+    data.extend_from_slice(&[0x00, 0x00]);
+    assert_eq!(data, Encoding::string(Tag::OCTET_STRING, bytes.clone()).to_bytes(Rules::Cer));
+    assert_eq!(Ok(bytes.clone()), octet_string(&data));
+    // Other rules ignore the segments
+    let mut d = ber::Decoder::new(&data);
+    assert_eq!(Ok(bytes.clone()), d.decode_string(Tag::OCTET_STRING));
+
+    // Up to 1000 octets, a string is primitive
+    let enc = Encoding::string(Tag::OCTET_STRING, bytes[..1000].to_vec()).to_bytes(Rules::Cer);
+    assert_eq!(segment(4, &bytes[..1000]), enc);
+    assert_eq!(Ok(bytes[..1000].to_vec()), octet_string(&enc));
+
+    // A string type other than OCTET STRING still has OCTET STRING segments
+    let s = "é".repeat(600);
+    let enc = cer::encode(&s).unwrap();
+    assert_eq!(&[0x2c, 0x80, 0x04, 0x82, 0x03, 0xe8], &enc[..6]);
+    assert_eq!(Ok(s), cer::decode::<String>(&enc));
+}
+
+#[test]
+fn segmentation_errors() {
+    let bytes = vec![0x55; 1001];
+    assert_eq!(Err(ber::DecodeError::UnsegmentedString), octet_string(&segment(4, &bytes)));
+
+    let mut data = vec![0x24, 0x80];
+    data.extend(segment(4, &bytes[..10]));
+    data.extend_from_slice(&[0x00, 0x00]);
+    assert_eq!(Err(ber::DecodeError::ConstructedString), octet_string(&data));
+
+    let mut data = vec![0x24, 0x80];
+    data.extend(segment(4, &bytes[..999]));
+    data.extend(segment(4, &bytes[999..]));
+    data.extend_from_slice(&[0x00, 0x00]);
+    assert_eq!(Err(ber::DecodeError::InvalidSegment), octet_string(&data));
+
+    let mut data = vec![0x24, 0x80];
+    data.extend(segment(4, &bytes[..1000]));
+    data.extend_from_slice(&[0x24, 0x80]);
+    data.extend(segment(4, &bytes[1000..]));
+    data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(Err(ber::DecodeError::InvalidSegment), octet_string(&data));
+}
+
+#[test]
+fn segmented_bit_string() {
+    let b = BitString::with_bytes_and_len(&vec![0xaa; 1500], 1500 * 8 - 3);
+    let enc = cer::encode(&b).unwrap();
+
+    let mut first = vec![0x00];
+    first.extend_from_slice(&[0xaa; 999]);
+    let mut last = vec![0x03];
+    last.extend_from_slice(&[0xaa; 500]);
+    last.push(0xa8);
+    let mut data = vec![0x23, 0x80];
+    data.extend(segment(3, &first));
+    data.extend(segment(3, &last));
+    data.extend_from_slice(&[0x00, 0x00]);
+    assert_eq!(data, enc);
+    assert_eq!(Ok(b), cer::decode::<BitString>(&enc));
+
+    let b = BitString::with_bytes_and_len(&[0xaa; 999], 999 * 8);
+    assert_eq!(4 + 1000, cer::encode(&b).unwrap().len());
+}
+
+#[test]
+fn set_order() {
+    let set = Encoding::set(Tag::SET,
+                            vec![true.to_ber(Tag::context(1)).unwrap(), 5i32.to_ber(Tag::context(0)).unwrap()]);
+    let sorted = b"\x31\x80\x80\x01\x05\x81\x01\xff\x00\x00";
+    assert_eq!(sorted.to_vec(), set.to_bytes(Rules::Cer));
+    assert_eq!(b"\x31\x06\x81\x01\xff\x80\x01\x05".to_vec(), set.bytes());
+
+    let mut d = cer::decoder(sorted);
+    let mut components = d.decode_set(Tag::SET).unwrap();
+    assert_eq!(Ok(5), i32::from_ber(&mut components, Tag::context(0)));
+    assert_eq!(Ok(true), bool::from_ber(&mut components, Tag::context(1)));
+
+    let mut d = cer::decoder(b"\x31\x80\x81\x01\xff\x80\x01\x05\x00\x00");
+    assert_eq!(Some(ber::DecodeError::UnsortedSet), d.decode_set(Tag::SET).err());
+}
+
+#[test]
+fn set_of_order() {
+    let set = Encoding::set_of(Tag::SET,
+                               vec![3i32.to_ber(Tag::INTEGER).unwrap(), 1i32.to_ber(Tag::INTEGER).unwrap()]);
+    let sorted = b"\x31\x80\x02\x01\x01\x02\x01\x03\x00\x00";
+    assert_eq!(sorted.to_vec(), set.to_bytes(Rules::Cer));
+
+    let mut d = cer::decoder(sorted);
+    assert!(d.decode_set_of(Tag::SET).is_ok());
+    let mut d = cer::decoder(b"\x31\x80\x02\x01\x03\x02\x01\x01\x00\x00");
+    assert_eq!(Some(ber::DecodeError::UnsortedSetOf), d.decode_set_of(Tag::SET).err());
+}
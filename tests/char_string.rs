@@ -0,0 +1,124 @@
+extern crate asn1;
+use asn1::{IA5String, NumericString, PermittedAlphabet, PrintableString, VisibleString};
+use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+
+fn size(min: i64, max: i64) -> Constraints {
+    Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(min), Some(max))),
+    }
+}
+
+#[test]
+fn validate() {
+    assert!(PrintableString::new("Hello, World").is_some());
+    assert!(PrintableString::new("a@b").is_none());
+    assert!(NumericString::new("123 456").is_some());
+    assert!(NumericString::new("12a").is_none());
+    assert!(VisibleString::new("tab\t").is_none());
+    assert!(IA5String::new("tab\t").is_some());
+}
+
+#[test]
+fn alphabet_widths() {
+    assert_eq!(4, NumericString::alphabet().char_bits());
+    assert_eq!(8, PrintableString::alphabet().char_bits());
+    assert_eq!(8, VisibleString::alphabet().char_bits());
+    assert_eq!(8, IA5String::alphabet().char_bits());
+    assert_eq!(2, PermittedAlphabet::new("ACGT").char_bits());
+    assert_eq!(74, PrintableString::alphabet().len());
+}
+
+#[test]
+fn encode_unconstrained() {
+    let s = PrintableString::new("Hi").unwrap();
+    let target: Vec<u8> = vec![0x02, 0x48, 0x69];
+    assert_eq!(target, *s.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn encode_size_constrained() {
+    // PrintableString (SIZE(1..150))
+    let s = PrintableString::new("Hi").unwrap();
+    let target: Vec<u8> = vec![0x01, 0x48, 0x69];
+    assert_eq!(target, *s.to_aper(size(1, 150)).unwrap().bytes());
+    assert_eq!(aper::EncodeError::InvalidSize,
+               PrintableString::new("").unwrap().to_aper(size(1, 150)).unwrap_err());
+}
+
+#[test]
+fn encode_numeric_fixed_size() {
+    // NumericString (SIZE(3)) fits in two octets, so there is no length and no alignment
+    let s = NumericString::new("123").unwrap();
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&s.to_aper(size(3, 3)).unwrap()).unwrap();
+    let target: Vec<u8> = vec![0x91, 0xa0];
+    assert_eq!(target, *enc.bytes());
+}
+
+#[test]
+fn encode_numeric_variable_size() {
+    let s = NumericString::new("1234567890").unwrap();
+    let target: Vec<u8> = vec![0xa0, 0x23, 0x45, 0x67, 0x89, 0xa1];
+    assert_eq!(target, *s.to_aper(size(0, 15)).unwrap().bytes());
+}
+
+#[test]
+fn encode_octet_aligned_length() {
+    // SEQUENCE { BOOLEAN, IA5String (SIZE(0..300)) }: a length with more than 255 possible values is octet-aligned
+    let s = IA5String::new("ab").unwrap();
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&s.to_aper(size(0, 300)).unwrap()).unwrap();
+    let target: Vec<u8> = vec![0x80, 0x00, 0x02, 0x61, 0x62];
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(&target);
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(s, IA5String::from_aper(&mut d, size(0, 300)).unwrap());
+}
+
+#[test]
+fn permitted_alphabet() {
+    // IMSI ::= IA5String (FROM("0".."9")) (SIZE(6..15))
+    let digits = PermittedAlphabet::from_ranges(&[('0', '9')]);
+    let s = IA5String::new("001010123456789").unwrap();
+    let enc = s.to_aper_with_alphabet(size(6, 15), &digits).unwrap();
+    let target: Vec<u8> = vec![0x90, 0x00, 0x10, 0x10, 0x12, 0x34, 0x56, 0x78, 0x90];
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(s, IA5String::from_aper_with_alphabet(&mut d, size(6, 15), &digits).unwrap());
+
+    let bad = IA5String::new("00101a").unwrap();
+    assert_eq!(aper::EncodeError::InvalidCharacter,
+               bad.to_aper_with_alphabet(size(6, 15), &digits).unwrap_err());
+}
+
+#[test]
+fn permitted_alphabet_small() {
+    let acgt = PermittedAlphabet::new("TGCA");
+    let s = VisibleString::new("GATTACA").unwrap();
+    let enc = s.to_aper_with_alphabet(size(7, 7), &acgt).unwrap();
+    // 10 00 11 11 00 01 00
+    let target: Vec<u8> = vec![0x8f, 0x10];
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(s, VisibleString::from_aper_with_alphabet(&mut d, size(7, 7), &acgt).unwrap());
+}
+
+#[test]
+fn decode_round_trip() {
+    let s = VisibleString::new("PLMN name").unwrap();
+    let enc = s.to_aper(size(1, 150)).unwrap();
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(s, VisibleString::from_aper(&mut d, size(1, 150)).unwrap());
+}
+
+#[test]
+fn decode_invalid_character() {
+    let data = b"\x01\x40"; // '@' is not a PrintableString character
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(aper::DecodeError::InvalidCharacter,
+               PrintableString::from_aper(&mut d, UNCONSTRAINED).unwrap_err());
+}
@@ -1,3 +1,6 @@
+#![allow(clippy::assertions_on_constants, clippy::bool_assert_comparison, clippy::enum_variant_names,
+         clippy::unnecessary_cast, clippy::unnecessary_unwrap, clippy::useless_vec)]
+
 extern crate asn1;
 use asn1::{BitString, ExtensionMarker};
 use asn1::aper::{self, APerElement, Constraint, Constraints, Encoding, encode_int, UNCONSTRAINED};
@@ -27,7 +30,7 @@ impl APerElement for Foo {
             0 => {
                 let bs = BitString::from_aper(decoder , Constraints {
                     value: None,
-                    size: Some(Constraint::new(None, Some(4))),
+                    size: Some(Constraint::new(Some(4), Some(4))),
                 });
                 if bs.is_err() {
                     Err(bs.err().unwrap())
@@ -66,7 +69,7 @@ impl APerElement for Foo {
                 enc.append(&encode_int(0, Some(0), Some(2)).unwrap()).unwrap();
                 enc.append(&a.to_aper(Constraints {
                     value: None,
-                    size: Some(Constraint::new(None, Some(4))),
+                    size: Some(Constraint::new(Some(4), Some(4))),
                 }).unwrap()).unwrap();
             },
             Foo::Bar{ref a} => {
@@ -85,7 +88,7 @@ impl APerElement for Foo {
 
 #[test]
 fn encode_foo() {
-    let x: Foo = Foo::Foo{ a: BitString::with_bytes_and_len(&vec![0x0e], 4), };
+    let x: Foo = Foo::Foo{ a: BitString::with_bytes_and_len(&vec![0xe0], 4), };
     let target: Vec<u8> = vec![0x1c];
     assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
@@ -93,14 +96,14 @@ fn encode_foo() {
 #[test]
 fn encode_bar() {
     let x: Foo = Foo::Bar{ a: vec![0x46, 0x4f, 0x4f], };
-    let target: Vec<u8> = vec![32, 104, 201, 233, 224];
+    let target: Vec<u8> = vec![32, 3, 70, 79, 79];
     assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
 
 #[test]
 fn encode_baz() {
     let x: Foo = Foo::Baz{ a: 42, b: 300 };
-    let target: Vec<u8> = vec![69, 64, 37, 128];
+    let target: Vec<u8> = vec![64, 42, 1, 44];
     assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
 
@@ -113,7 +116,7 @@ fn decode_foo() {
     match f {
         Foo::Foo{a: x} => {
             for i in 0..4 {
-                if i == 1 || i == 2 || i == 3 {
+                if i == 0 || i == 1 || i == 2 {
                     assert_eq!(true, x.is_set(i));
                 } else {
                     assert_eq!(false, x.is_set(i));
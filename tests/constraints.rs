@@ -0,0 +1,161 @@
+extern crate asn1;
+use asn1::PermittedAlphabet;
+use asn1::aper::Constraint;
+use asn1::notation::{self, AssignmentKind, Module, PerConstraints, ResolveError, Type};
+
+const CONSTANTS: &str = r#"
+NGAP-Constants DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    maxnoofCells        INTEGER ::= 16384
+    maxnoofPDUSessions  INTEGER ::= 256
+    maxnoofERABs        INTEGER ::= maxnoofPDUSessions
+END
+"#;
+
+const IES: &str = r#"
+NGAP-IEs DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+IMPORTS maxnoofCells, maxnoofPDUSessions, maxnoofERABs FROM NGAP-Constants;
+
+    PDUSessionID ::= INTEGER (0..255)
+    Priority ::= INTEGER (1..8, ..., 9..15)
+    Small ::= INTEGER (1..4 | 10 | 20<..<30)
+    Both ::= INTEGER ((0..100) ^ (50..MAX))
+    Except ::= INTEGER (0..100 EXCEPT 50)
+    AllExcept ::= INTEGER (ALL EXCEPT 0)
+    Empty ::= INTEGER (1..4 ^ 6..8)
+    Serial ::= PDUSessionID (1..300)
+    Contained ::= INTEGER (PDUSessionID | 1000)
+    ValidIDs INTEGER ::= { 1 | 2 | 3, ... }
+    ValueSet ::= INTEGER (ValidIDs)
+    Qualified ::= INTEGER (0..NGAP-Constants.maxnoofERABs)
+    Unknown ::= INTEGER (0..maxnoofNothing)
+    Loop ::= INTEGER (0..loopValue)
+    loopValue INTEGER ::= loopValue
+
+    PDUSessionList ::= SEQUENCE (SIZE (1..maxnoofPDUSessions)) OF PDUSessionID
+    ERABList ::= SEQUENCE SIZE (1..maxnoofERABs, ...) OF PDUSessionID
+    Flags ::= BIT STRING (SIZE (8))
+    Payload ::= OCTET STRING (SIZE (0..MAX))
+    Container ::= OCTET STRING (CONTAINING PDUSessionID)
+    Tagged ::= [0] OCTET STRING (SIZE (1..4))
+
+    Digits ::= NumericString (SIZE (1..4))
+    Hex ::= IA5String (FROM ("0".."9" | "A".."F"))
+    HexString ::= Hex (SIZE (2..16))
+    Extended ::= IA5String (FROM ("0".."9") ^ SIZE (1..8), ...)
+    Single ::= PrintableString ("abc" | "def")
+    MixedUnion ::= VisibleString (SIZE (1..4) | FROM ("a".."z"))
+    Patterned ::= IA5String (PATTERN "[0-9]+")
+    Utf8 ::= UTF8String (SIZE (1..150))
+END
+"#;
+
+fn modules() -> Vec<Module> {
+    let mut modules = notation::parse(CONSTANTS).unwrap();
+    modules.extend(notation::parse(IES).unwrap());
+    modules
+}
+
+fn effective(modules: &[Module], name: &str) -> Result<PerConstraints, ResolveError> {
+    let ty: Type = match modules[1].assignment(name).unwrap().kind {
+        AssignmentKind::Type(ref ty) => ty.clone(),
+        _ => panic!("{} isn't a type", name),
+    };
+    notation::per_constraints(&ty, modules)
+}
+
+fn value(modules: &[Module], name: &str) -> (Option<Constraint>, bool) {
+    let per = effective(modules, name).unwrap();
+    (per.value, per.value_extensible)
+}
+
+fn size(modules: &[Module], name: &str) -> (Option<Constraint>, bool) {
+    let per = effective(modules, name).unwrap();
+    (per.size, per.size_extensible)
+}
+
+fn range(min: i64, max: i64) -> Option<Constraint> {
+    Some(Constraint::new(Some(min), Some(max)))
+}
+
+#[test]
+fn integer_constraints() {
+    let modules = modules();
+    assert_eq!(value(&modules, "PDUSessionID"), (range(0, 255), false));
+    assert_eq!(value(&modules, "Priority"), (range(1, 8), true));
+    assert_eq!(value(&modules, "Small"), (range(1, 29), false));
+    assert_eq!(value(&modules, "Both"), (range(50, 100), false));
+    assert_eq!(value(&modules, "Except"), (range(0, 100), false));
+    assert_eq!(value(&modules, "AllExcept"), (None, false));
+    assert_eq!(value(&modules, "Serial"), (range(1, 255), false));
+    assert_eq!(value(&modules, "Contained"), (range(0, 1000), false));
+    assert_eq!(value(&modules, "ValueSet"), (range(1, 3), true));
+    assert_eq!(effective(&modules, "Empty"), Err(ResolveError::EmptyConstraint));
+
+    let per = effective(&modules, "PDUSessionID").unwrap();
+    assert_eq!(per.size, None);
+    assert_eq!(per.alphabet, None);
+    assert_eq!(per.constraints().value, range(0, 255));
+}
+
+#[test]
+fn references() {
+    let modules = modules();
+    assert_eq!(value(&modules, "Qualified"), (range(0, 256), false));
+    assert_eq!(size(&modules, "PDUSessionList"), (range(1, 256), false));
+    assert_eq!(size(&modules, "ERABList"), (range(1, 256), true));
+    assert_eq!(effective(&modules, "Unknown"), Err(ResolveError::UnknownReference("maxnoofNothing".to_string())));
+    assert_eq!(effective(&modules, "Loop"), Err(ResolveError::TooDeep));
+
+    // A module only resolves its own assignments
+    let ty = match modules[1].assignment("PDUSessionList").unwrap().kind {
+        AssignmentKind::Type(ref ty) => ty.clone(),
+        _ => unreachable!(),
+    };
+    assert_eq!(notation::per_constraints(&ty, &modules[1]),
+               Err(ResolveError::UnknownReference("maxnoofPDUSessions".to_string())));
+}
+
+#[test]
+fn size_constraints() {
+    let modules = modules();
+    assert_eq!(size(&modules, "Flags"), (range(8, 8), false));
+    assert_eq!(size(&modules, "Payload"), (None, false));
+    assert_eq!(size(&modules, "Container"), (None, false));
+    assert_eq!(size(&modules, "Tagged"), (range(1, 4), false));
+    assert_eq!(size(&modules, "Digits"), (range(1, 4), false));
+    assert_eq!(size(&modules, "Utf8"), (None, false));
+}
+
+#[test]
+fn permitted_alphabets() {
+    let modules = modules();
+    let hex = PermittedAlphabet::from_ranges(&[('0', '9'), ('A', 'F')]);
+
+    let per = effective(&modules, "Hex").unwrap();
+    assert_eq!(per.alphabet, Some(hex.clone()));
+    assert_eq!(per.size, None);
+
+    let per = effective(&modules, "HexString").unwrap();
+    assert_eq!(per.alphabet, Some(hex));
+    assert_eq!(per.size, range(2, 16));
+
+    // An extensible PermittedAlphabet constraint isn't PER-visible, but an extensible size constraint is
+    let per = effective(&modules, "Extended").unwrap();
+    assert_eq!(per.alphabet, Some(asn1::IA5String::alphabet()));
+    assert_eq!((per.size, per.size_extensible), (range(1, 8), true));
+
+    assert_eq!(effective(&modules, "Digits").unwrap().alphabet, Some(asn1::NumericString::alphabet()));
+    assert_eq!(effective(&modules, "Utf8").unwrap().alphabet, None);
+}
+
+#[test]
+fn invisible_constraints() {
+    let modules = modules();
+    for name in &["Single", "MixedUnion", "Patterned"] {
+        let per = effective(&modules, name).unwrap();
+        assert_eq!(per.size, None, "{}", name);
+        assert_eq!(per.value, None, "{}", name);
+    }
+    assert_eq!(effective(&modules, "Single").unwrap().alphabet, Some(asn1::PrintableString::alphabet()));
+    assert_eq!(effective(&modules, "MixedUnion").unwrap().alphabet, Some(asn1::VisibleString::alphabet()));
+}
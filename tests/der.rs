@@ -0,0 +1,226 @@
+#[macro_use]
+extern crate asn1;
+use asn1::{BitString, GeneralizedTime, IA5String, NamedBitString, ObjectIdentifier, UTCTime};
+use asn1::{ber, der};
+use asn1::ber::{BerElement, Encoding, Rules, Tag};
+
+named_bits! {
+    pub enum KeyUsage {
+        DigitalSignature = 0,
+        NonRepudiation = 1,
+        KeyEncipherment = 2,
+    }
+}
+
+// AlgorithmIdentifier ::= SEQUENCE { algorithm OBJECT IDENTIFIER, parameters NULL }
+#[derive(Debug, PartialEq)]
+struct AlgorithmIdentifier {
+    algorithm: ObjectIdentifier,
+}
+
+impl BerElement for AlgorithmIdentifier {
+    const TAG: Tag = Tag::SEQUENCE;
+
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let d = decoder.decode_constructed(tag);
+        if d.is_err() {
+            return Err(d.err().unwrap());
+        }
+        let mut d = d.unwrap();
+        let algorithm = ObjectIdentifier::from_ber(&mut d, ObjectIdentifier::TAG);
+        if algorithm.is_err() {
+            return Err(algorithm.err().unwrap());
+        }
+        let ret = <()>::from_ber(&mut d, Tag::NULL);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = d.finish();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(AlgorithmIdentifier { algorithm: algorithm.unwrap() })
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<Encoding, ber::EncodeError> {
+        Ok(Encoding::constructed(tag,
+                                 vec![self.algorithm.to_ber(ObjectIdentifier::TAG).unwrap(),
+                                      ().to_ber(Tag::NULL).unwrap()]))
+    }
+}
+
+// Names ::= SEQUENCE {
+//     version [0] IMPLICIT INTEGER DEFAULT 1,
+//     names SET OF IA5String
+// }
+#[derive(Debug, PartialEq)]
+struct Names {
+    version: i32,
+    names: Vec<IA5String>,
+}
+
+impl BerElement for Names {
+    const TAG: Tag = Tag::SEQUENCE;
+
+    fn from_ber(decoder: &mut ber::Decoder, tag: Tag) -> Result<Self, ber::DecodeError> {
+        let d = decoder.decode_constructed(tag);
+        if d.is_err() {
+            return Err(d.err().unwrap());
+        }
+        let mut d = d.unwrap();
+        let version = d.decode_default(Tag::context(0), 1);
+        if version.is_err() {
+            return Err(version.err().unwrap());
+        }
+
+        let set = d.decode_set_of(Tag::SET);
+        if set.is_err() {
+            return Err(set.err().unwrap());
+        }
+        let mut set = set.unwrap();
+        let mut names = Vec::new();
+        while !set.is_empty() {
+            let ret = IA5String::from_ber(&mut set, IA5String::TAG);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            names.push(ret.unwrap());
+        }
+
+        let ret = d.finish();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(Names {
+            version: version.unwrap(),
+            names,
+        })
+    }
+
+    fn to_ber(&self, tag: Tag) -> Result<Encoding, ber::EncodeError> {
+        let mut elements = Vec::new();
+        if let Some(e) = ber::encode_default(&self.version, &1, Tag::context(0)).unwrap() {
+            elements.push(e);
+        }
+        let names = self.names.iter().map(|n| n.to_ber(IA5String::TAG).unwrap()).collect();
+        elements.push(Encoding::set_of(Tag::SET, names));
+        Ok(Encoding::constructed(tag, elements))
+    }
+}
+
+fn names(version: i32, names: &[&str]) -> Names {
+    Names {
+        version,
+        names: names.iter().map(|n| IA5String::new(n).unwrap()).collect(),
+    }
+}
+
+#[test]
+fn algorithm_identifier() {
+    let alg = AlgorithmIdentifier { algorithm: "1.2.840.113549.1.1.11".parse().unwrap() };
+    let data = b"\x30\x0d\x06\x09\x2a\x86\x48\x86\xf7\x0d\x01\x01\x0b\x05\x00";
+    assert_eq!(Ok(data.to_vec()), der::encode(&alg));
+    assert_eq!(Ok(alg), der::decode::<AlgorithmIdentifier>(data));
+}
+
+#[test]
+fn set_of_order() {
+    let n = names(1, &["bb", "a", "ab"]);
+    let data = b"\x30\x0d\x31\x0b\x16\x01a\x16\x02ab\x16\x02bb";
+    assert_eq!(Ok(data.to_vec()), der::encode(&n));
+    assert_eq!(b"\x30\x0d\x31\x0b\x16\x02bb\x16\x01a\x16\x02ab".to_vec(), n.to_ber(Tag::SEQUENCE).unwrap().bytes());
+
+    assert_eq!(Ok(names(1, &["a", "ab", "bb"])), der::decode::<Names>(data));
+    let unsorted = b"\x30\x0d\x31\x0b\x16\x02bb\x16\x01a\x16\x02ab";
+    assert_eq!(Err(ber::DecodeError::UnsortedSetOf), der::decode::<Names>(unsorted));
+    let mut d = ber::Decoder::new(unsorted);
+    assert_eq!(Ok(n), Names::from_ber(&mut d, Tag::SEQUENCE));
+}
+
+#[test]
+fn default_omission() {
+    assert_eq!(Ok(b"\x30\x05\x31\x03\x16\x01a".to_vec()), der::encode(&names(1, &["a"])));
+    assert_eq!(Ok(b"\x30\x08\x80\x01\x02\x31\x03\x16\x01a".to_vec()), der::encode(&names(2, &["a"])));
+
+    assert_eq!(Ok(names(2, &["a"])), der::decode::<Names>(b"\x30\x08\x80\x01\x02\x31\x03\x16\x01a"));
+    let explicit_default = b"\x30\x08\x80\x01\x01\x31\x03\x16\x01a";
+    assert_eq!(Err(ber::DecodeError::DefaultValueEncoded), der::decode::<Names>(explicit_default));
+    let mut d = ber::Decoder::new(explicit_default);
+    assert_eq!(Ok(names(1, &["a"])), Names::from_ber(&mut d, Tag::SEQUENCE));
+}
+
+#[test]
+fn definite_lengths() {
+    let mut enc = Encoding::constructed(Tag::SEQUENCE, vec![true.to_ber(Tag::BOOLEAN).unwrap()]);
+    enc.set_indefinite(true);
+    assert_eq!(vec![0x30, 0x03, 0x01, 0x01, 0xff], enc.to_bytes(Rules::Der));
+
+    assert_eq!(Err(ber::DecodeError::IndefiniteLength), der::decode::<Vec<bool>>(b"\x30\x80\x00\x00"));
+    assert_eq!(Err(ber::DecodeError::NonMinimalLength), der::decode::<Vec<bool>>(b"\x30\x81\x00"));
+    let mut data = vec![0x04, 0x82, 0x00, 0x80];
+    data.extend_from_slice(&[0; 0x80]);
+    let mut d = der::decoder(&data);
+    assert_eq!(Err(ber::DecodeError::NonMinimalLength), d.decode_string(Tag::OCTET_STRING));
+
+    let mut data = vec![0x04, 0x81, 0x80];
+    data.extend_from_slice(&[0; 0x80]);
+    let mut d = der::decoder(&data);
+    assert_eq!(Ok(vec![0; 0x80]), d.decode_string(Tag::OCTET_STRING));
+}
+
+#[test]
+fn primitive_values() {
+    assert_eq!(Ok(true), der::decode::<bool>(b"\x01\x01\xff"));
+    assert_eq!(Err(ber::DecodeError::InvalidBool), der::decode::<bool>(b"\x01\x01\x01"));
+
+    assert_eq!(Ok(128), der::decode::<i32>(b"\x02\x02\x00\x80"));
+    assert_eq!(Err(ber::DecodeError::NonMinimalInt), der::decode::<i32>(b"\x02\x02\x00\x7f"));
+    assert_eq!(Err(ber::DecodeError::NonMinimalInt), der::decode::<i32>(b"\x02\x02\xff\x80"));
+
+    assert_eq!(Ok(10.), der::decode::<f64>(b"\x09\x03\x80\x01\x05"));
+    assert_eq!(Err(ber::DecodeError::NonCanonicalReal), der::decode::<f64>(b"\x09\x03\x80\x00\x0a"));
+
+    assert_eq!(Err(ber::DecodeError::TrailingData), der::decode::<bool>(b"\x01\x01\xff\x00"));
+    assert_eq!(Err(ber::DecodeError::MalformedIdentifier), der::decode::<bool>(b"\x1f\x01\x01\xff"));
+}
+
+#[test]
+fn strings() {
+    let mut d = der::decoder(b"\x24\x03\x04\x01\x01");
+    assert_eq!(Err(ber::DecodeError::ConstructedString), d.decode_string(Tag::OCTET_STRING));
+    assert_eq!(Err(ber::DecodeError::ConstructedString),
+               der::decode::<IA5String>(b"\x36\x05\x04\x01a\x04\x00"));
+
+    assert_eq!(Ok(BitString::from_bits(&[true, false])), der::decode::<BitString>(b"\x03\x02\x06\x80"));
+    assert_eq!(Err(ber::DecodeError::NonZeroUnusedBits), der::decode::<BitString>(b"\x03\x02\x06\x81"));
+    assert_eq!(Err(ber::DecodeError::ConstructedString),
+               der::decode::<BitString>(b"\x23\x04\x03\x02\x06\x80"));
+}
+
+#[test]
+fn named_bits() {
+    let mut usage = NamedBitString::<KeyUsage>::from_bits(BitString::with_len(8));
+    usage.set(KeyUsage::DigitalSignature, true);
+    usage.set(KeyUsage::KeyEncipherment, true);
+    assert_eq!(Ok(b"\x03\x02\x05\xa0".to_vec()), der::encode(&usage));
+    assert_eq!(Ok(usage), der::decode::<NamedBitString<KeyUsage>>(b"\x03\x02\x05\xa0"));
+    assert_eq!(Err(ber::DecodeError::TrailingZeroBits),
+               der::decode::<NamedBitString<KeyUsage>>(b"\x03\x02\x04\xa0"));
+    assert_eq!(Ok(NamedBitString::new()), der::decode::<NamedBitString<KeyUsage>>(b"\x03\x01\x00"));
+}
+
+#[test]
+fn times() {
+    assert!(der::decode::<UTCTime>(b"\x17\x0d240229134530Z").is_ok());
+    assert_eq!(Err(ber::DecodeError::NonCanonicalTime), der::decode::<UTCTime>(b"\x17\x0b2402291345Z"));
+    assert_eq!(Err(ber::DecodeError::NonCanonicalTime),
+               der::decode::<UTCTime>(b"\x17\x11240229134530+0100"));
+
+    assert!(der::decode::<GeneralizedTime>(b"\x18\x1120240229134530.5Z").is_ok());
+    assert!(GeneralizedTime::new("20240229134530Z").unwrap().is_canonical());
+    assert!(!GeneralizedTime::new("20240229134530.50Z").unwrap().is_canonical());
+    assert!(!GeneralizedTime::new("20240229134530,5Z").unwrap().is_canonical());
+    assert!(!GeneralizedTime::new("20240229134530.5").unwrap().is_canonical());
+    assert_eq!(Err(ber::DecodeError::NonCanonicalTime),
+               der::decode::<GeneralizedTime>(b"\x18\x0c202402291345Z"));
+}
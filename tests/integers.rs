@@ -1,3 +1,5 @@
+#![allow(clippy::legacy_numeric_constants, clippy::unnecessary_cast)]
+
 extern crate asn1;
 use asn1::aper::{self, APerElement, UNCONSTRAINED};
 use std::i32;
@@ -66,9 +68,9 @@ fn std_i16() {
 
 #[test]
 fn std_i32() {
-    let data_min = b"\x04\x00\x00\x00\x00"; // i32::MIN
-    let data_med = b"\x04\x80\x00\x00\x2b"; // 43
-    let data_max = b"\x04\xff\xff\xff\xff"; // i32::MAX
+    let data_min = b"\x00\x00"; // i32::MIN
+    let data_med = b"\xc0\x80\x00\x00\x2b"; // 43
+    let data_max = b"\xc0\xff\xff\xff\xff"; // i32::MAX
     let mut d = aper::Decoder::new(data_min);
     assert_eq!(std::i32::MIN, i32::from_aper(&mut d, UNCONSTRAINED).unwrap());
     d = aper::Decoder::new(data_med);
@@ -105,9 +107,9 @@ fn std_u16() {
 
 #[test]
 fn std_u32() {
-    let data_min = b"\x04\x00\x00\x00\x00"; // u32::MIN
-    let data_med = b"\x04\x00\x00\x00\x2b"; // 43
-    let data_max = b"\x04\xff\xff\xff\xff"; // u32::MAX
+    let data_min = b"\x00\x00"; // u32::MIN
+    let data_med = b"\x00\x2b"; // 43
+    let data_max = b"\xc0\xff\xff\xff\xff"; // u32::MAX
     let mut d = aper::Decoder::new(data_min);
     assert_eq!(std::u32::MIN, u32::from_aper(&mut d, UNCONSTRAINED).unwrap());
     d = aper::Decoder::new(data_med);
@@ -115,3 +117,18 @@ fn std_u32() {
     d = aper::Decoder::new(data_max);
     assert_eq!(std::u32::MAX, u32::from_aper(&mut d, UNCONSTRAINED).unwrap());
 }
+
+#[test]
+fn encode_unconstrained_minimal_octets() {
+    assert_eq!(vec![0x01, 0x00], *aper::encode_int(0, None, None).unwrap().bytes());
+    assert_eq!(vec![0x01, 0x01], *aper::encode_int(1, None, None).unwrap().bytes());
+    assert_eq!(vec![0x02, 0x00, 0x80], *aper::encode_int(128, None, None).unwrap().bytes());
+    assert_eq!(vec![0x02, 0xff, 0x7f], *aper::encode_int(-129, None, None).unwrap().bytes());
+}
+
+#[test]
+fn encode_semiconstrained_minimal_octets() {
+    assert_eq!(vec![0x02, 0x01, 0x00], *aper::encode_int(256, Some(0), None).unwrap().bytes());
+    assert_eq!(vec![0x01, 0xff], *aper::encode_int(254, Some(-1), None).unwrap().bytes());
+    assert_eq!(Some(aper::EncodeError::InvalidValue), aper::encode_int(-2, Some(-1), None).err());
+}
@@ -1,3 +1,6 @@
+#![allow(clippy::bool_assert_comparison, clippy::disallowed_names, clippy::needless_range_loop,
+         clippy::unnecessary_cast, clippy::useless_vec)]
+
 extern crate asn1;
 use asn1::BitString;
 use asn1::aper::{self, APerElement, Constraint, Constraints, Encoding, UNCONSTRAINED};
@@ -14,7 +17,7 @@ impl APerElement for Foo {
     fn from_aper(decoder: &mut aper::Decoder, _: Constraints) -> Result<Self, aper::DecodeError> {
         let foo = BitString::from_aper(decoder , Constraints {
             value: None,
-            size: Some(Constraint::new(None, Some(4))),
+            size: Some(Constraint::new(Some(4), Some(4))),
         });
 
         let bar = Vec::<u8>::from_aper(decoder, Constraints {
@@ -24,7 +27,7 @@ impl APerElement for Foo {
 
         let baz = Vec::<BitString>::from_aper(decoder, Constraints {
             // here the "value" constraint is a constraint on the size of each element
-            value: Some(Constraint::new(None, Some(4))), 
+            value: Some(Constraint::new(Some(4), Some(4))), 
             // "size" behaves normally 
             size: Some(Constraint::new(None, Some(2))),
         });
@@ -49,7 +52,7 @@ impl APerElement for Foo {
     fn to_aper(&self, _: Constraints) -> Result<Encoding, aper::EncodeError> {
         let mut enc = self.foo.to_aper(Constraints {
             value: None,
-            size: Some(Constraint::new(None, Some(4))),
+            size: Some(Constraint::new(Some(4), Some(4))),
         }).unwrap();
 
         enc.append(&self.bar.to_aper(Constraints {
@@ -59,7 +62,7 @@ impl APerElement for Foo {
 
         enc.append(&self.baz.to_aper(Constraints {
             // here the "value" constraint is a constraint on the size of each element
-            value: Some(Constraint::new(None, Some(4))), 
+            value: Some(Constraint::new(Some(4), Some(4))), 
             // "size" behaves normally 
             size: Some(Constraint::new(None, Some(2))),
         }).unwrap()).unwrap();
@@ -71,14 +74,14 @@ impl APerElement for Foo {
 #[test]
 fn encode_foo() {
     let x = Foo {
-        foo: BitString::with_bytes_and_len(&vec![0x0e], 4),
+        foo: BitString::with_bytes_and_len(&vec![0xe0], 4),
         bar: vec![0x46, 0x4f, 0x4f],
         baz: vec![
-            BitString::with_bytes_and_len(&vec![0x0e], 4),
-            BitString::with_bytes_and_len(&vec![0x0e], 4),
+            BitString::with_bytes_and_len(&vec![0xe0], 4),
+            BitString::with_bytes_and_len(&vec![0xe0], 4),
         ],
     };
-    let target: Vec<u8> = vec![0xe0, 0x34, 0x64, 0xf4, 0xf0, 0x2e, 0xe0];
+    let target: Vec<u8> = vec![0xe0, 0x03, 0x46, 0x4f, 0x4f, 0x02, 0xee];
     assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
 
@@ -92,7 +95,7 @@ fn decode_foo() {
     let target_bar = vec![0x46 as u8, 0x4f as u8, 0x4f as u8];
 
     for i in 0..4 {
-        if i == 1 || i == 2 || i == 3 {
+        if i == 0 || i == 1 || i == 2 {
             assert_eq!(true, f.foo.is_set(i));
         } else {
             assert_eq!(false, f.foo.is_set(i));
@@ -107,7 +110,7 @@ fn decode_foo() {
     assert_eq!(f.baz.len(), 2);
     for i in 0..f.baz.len() {
         for j in 0..4 {
-            if j == 1 || j == 2 || j == 3 {
+            if j == 0 || j == 1 || j == 2 {
                 assert_eq!(true, f.baz[i].is_set(j));
             } else {
                 assert_eq!(false, f.baz[i].is_set(j));
@@ -0,0 +1,300 @@
+#[macro_use]
+extern crate asn1;
+use asn1::{BitString, GeneralizedTime, IA5String, NamedBitString, ObjectIdentifier};
+use asn1::aper::{self, APerElement};
+use asn1::jer::{self, Constraint, Constraints, DecodeError, JerElement, Value, UNCONSTRAINED};
+
+named_bits! {
+    pub enum Flags {
+        A = 0,
+        B = 1,
+        C = 2,
+    }
+}
+
+const FLAGS: Constraints = Constraints {
+    value: None,
+    size: Some(Constraint::new(Some(4), Some(4))),
+};
+
+// Shape ::= CHOICE {
+//     circle INTEGER (0..255),
+//     square NULL
+// }
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Circle(u8),
+    Square,
+}
+
+// Msg ::= SEQUENCE {
+//     id INTEGER (0..255),
+//     color ENUMERATED { red, green },
+//     flags BIT STRING (SIZE(4)),
+//     shape Shape OPTIONAL
+// }
+#[derive(Debug, PartialEq)]
+struct Msg {
+    id: u8,
+    green: bool,
+    flags: BitString,
+    shape: Option<Shape>,
+}
+
+impl JerElement for Msg {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    fn from_jer(value: &Value, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = value.check_members(&["id", "color", "flags", "shape"]);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let ret = value.member("id");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let id = u8::from_jer(ret.unwrap(), UNCONSTRAINED);
+        if id.is_err() {
+            return Err(id.err().unwrap());
+        }
+        let ret = value.member("color");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let green = match ret.unwrap().as_str() {
+            Ok("red") => false,
+            Ok("green") => true,
+            _ => return Err(DecodeError::UnexpectedType),
+        };
+        let ret = value.member("flags");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let flags = BitString::from_jer(ret.unwrap(), FLAGS);
+        if flags.is_err() {
+            return Err(flags.err().unwrap());
+        }
+        let shape = match value.get("shape").map(|v| v.single_member()) {
+            None => None,
+            Some(Ok(("circle", v))) => {
+                let ret = u8::from_jer(v, UNCONSTRAINED);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Some(Shape::Circle(ret.unwrap()))
+            }
+            Some(Ok(("square", v))) => {
+                let ret = <()>::from_jer(v, UNCONSTRAINED);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Some(Shape::Square)
+            }
+            Some(Ok(_)) => return Err(DecodeError::UnknownMember),
+            Some(Err(e)) => return Err(e),
+        };
+        Ok(Msg {
+            id: id.unwrap(),
+            green,
+            flags: flags.unwrap(),
+            shape,
+        })
+    }
+
+    fn to_jer(&self, _: Constraints) -> Result<Value, jer::EncodeError> {
+        let color = if self.green { "green" } else { "red" };
+        let mut members = vec![("id".to_string(), self.id.to_jer(UNCONSTRAINED).unwrap()),
+                               ("color".to_string(), Value::String(color.to_string())),
+                               ("flags".to_string(), self.flags.to_jer(FLAGS).unwrap())];
+        if let Some(ref shape) = self.shape {
+            let alt = match *shape {
+                Shape::Circle(r) => ("circle".to_string(), r.to_jer(UNCONSTRAINED).unwrap()),
+                Shape::Square => ("square".to_string(), ().to_jer(UNCONSTRAINED).unwrap()),
+            };
+            members.push(("shape".to_string(), Value::Object(vec![alt])));
+        }
+        Ok(Value::Object(members))
+    }
+}
+
+impl APerElement for Msg {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    fn from_aper(decoder: &mut aper::Decoder, _: Constraints) -> Result<Self, aper::DecodeError> {
+        let has_shape = decoder.read(1);
+        if has_shape.is_err() {
+            return Err(has_shape.err().unwrap());
+        }
+        let id = u8::from_aper(decoder, UNCONSTRAINED);
+        if id.is_err() {
+            return Err(id.err().unwrap());
+        }
+        let green = decoder.decode_int(Some(0), Some(1));
+        if green.is_err() {
+            return Err(green.err().unwrap());
+        }
+        let flags = BitString::from_aper(decoder, FLAGS);
+        if flags.is_err() {
+            return Err(flags.err().unwrap());
+        }
+        let mut shape = None;
+        if has_shape.unwrap() > 0 {
+            let ret = decoder.decode_int(Some(0), Some(1));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            shape = Some(if ret.unwrap() == 0 {
+                let ret = u8::from_aper(decoder, UNCONSTRAINED);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Shape::Circle(ret.unwrap())
+            } else {
+                Shape::Square
+            });
+        }
+        Ok(Msg {
+            id: id.unwrap(),
+            green: green.unwrap() == 1,
+            flags: flags.unwrap(),
+            shape,
+        })
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<aper::Encoding, aper::EncodeError> {
+        let mut enc = aper::Encoding::with_uint(self.shape.is_some() as u64, 1);
+        enc.append(&self.id.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+        enc.append(&aper::encode_int(self.green as i64, Some(0), Some(1)).unwrap()).unwrap();
+        enc.append(&self.flags.to_aper(FLAGS).unwrap()).unwrap();
+        match self.shape {
+            Some(Shape::Circle(r)) => {
+                enc.append(&aper::encode_int(0, Some(0), Some(1)).unwrap()).unwrap();
+                enc.append(&r.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+            }
+            Some(Shape::Square) => enc.append(&aper::encode_int(1, Some(0), Some(1)).unwrap()).unwrap(),
+            None => (),
+        }
+        Ok(enc)
+    }
+}
+
+#[test]
+fn scalars() {
+    assert_eq!(Ok("true".to_string()), jer::encode(&true));
+    assert_eq!(Ok("-12".to_string()), jer::encode(&-12i32));
+    assert_eq!(Ok("null".to_string()), jer::encode(&()));
+    assert_eq!(Ok(false), jer::decode::<bool>(" false "));
+    assert_eq!(Err(DecodeError::UnexpectedType), jer::decode::<bool>("0"));
+    assert_eq!(Ok(()), jer::decode::<()>("null"));
+
+    assert_eq!(Ok(200), jer::decode::<u8>("200"));
+    assert_eq!(Err(DecodeError::MalformedInt), jer::decode::<u8>("256"));
+    assert_eq!(Err(DecodeError::MalformedInt), jer::decode::<i32>("1.0"));
+    assert_eq!(Err(DecodeError::UnexpectedType), jer::decode::<i32>("\"1\""));
+
+    assert_eq!(Ok("1.5".to_string()), jer::encode(&1.5f64));
+    assert_eq!(Ok("1e300".to_string()), jer::encode(&1e300f64));
+    assert_eq!(Ok("\"-0\"".to_string()), jer::encode(&-0f64));
+    assert_eq!(Ok("\"-INF\"".to_string()), jer::encode(&f64::NEG_INFINITY));
+    assert_eq!(Ok(-2.5e-3), jer::decode::<f64>("-2.5E-3"));
+    assert_eq!(Ok(f64::INFINITY), jer::decode::<f64>("\"INF\""));
+    assert!(jer::decode::<f64>("\"NaN\"").unwrap().is_nan());
+    assert!(jer::decode::<f64>("\"-0\"").unwrap().is_sign_negative());
+    assert_eq!(Err(DecodeError::InvalidReal), jer::decode::<f64>("\"inf\""));
+
+    let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+    assert_eq!(Ok("\"1.2.840.113549\"".to_string()), jer::encode(&oid));
+    assert_eq!(Ok(oid), jer::decode("\"1.2.840.113549\""));
+    assert_eq!(Err(DecodeError::InvalidOid), jer::decode::<ObjectIdentifier>("\"3.1\""));
+
+    let t: GeneralizedTime = "20240229120000Z".parse().unwrap();
+    assert_eq!(Ok("\"20240229120000Z\"".to_string()), jer::encode(&t));
+    assert_eq!(Ok(t), jer::decode("\"20240229120000Z\""));
+    assert_eq!(Err(DecodeError::InvalidTime), jer::decode::<GeneralizedTime>("\"yesterday\""));
+}
+
+#[test]
+fn strings() {
+    let s = "a \"quoted\"\n\u{1}é".to_string();
+    let json = jer::encode(&s).unwrap();
+    assert_eq!("\"a \\\"quoted\\\"\\n\\u0001é\"", json);
+    assert_eq!(Ok(s), jer::decode(&json));
+    assert_eq!(Ok("é😀/".to_string()), jer::decode("\"\\u00e9\\ud83d\\ude00\\/\""));
+    assert_eq!(Err(DecodeError::InvalidCharacter), jer::decode::<IA5String>("\"é\""));
+
+    let size = Some(Constraint::new(Some(1), Some(2)));
+    assert_eq!(Ok(Value::String("DEAD".to_string())), jer::encode_octet_string(&[0xde, 0xad], size));
+    assert_eq!(Err(jer::EncodeError::InvalidSize), jer::encode_octet_string(&[1, 2, 3], size));
+    assert_eq!(Ok(vec![0xde, 0xad]), jer::decode_octet_string(&jer::parse("\"dEaD\"").unwrap(), size));
+    assert_eq!(Err(DecodeError::InvalidHex), jer::decode_octet_string(&jer::parse("\"DEA\"").unwrap(), size));
+    assert_eq!(Err(DecodeError::InvalidSize), jer::decode_octet_string(&jer::parse("\"\"").unwrap(), size));
+}
+
+#[test]
+fn bit_strings() {
+    let bits = BitString::from_bits(&[true, false, true]);
+    assert_eq!(Ok("{\"value\":\"A0\",\"length\":3}".to_string()), jer::encode(&bits));
+    assert_eq!(Ok(bits.clone()), jer::decode("{\"length\": 3, \"value\": \"a0\"}"));
+    assert_eq!(Err(DecodeError::InvalidBits), jer::decode::<BitString>("{\"value\":\"A000\",\"length\":3}"));
+    assert_eq!(Err(DecodeError::MissingMember), jer::decode::<BitString>("{\"value\":\"A0\"}"));
+
+    let bits = BitString::from_bits(&[true, false, true, false]);
+    assert_eq!(Value::String("A0".to_string()), bits.to_jer(FLAGS).unwrap());
+    assert_eq!(Ok(bits), BitString::from_jer(&Value::String("A0".to_string()), FLAGS));
+    assert_eq!(Err(jer::EncodeError::InvalidSize), BitString::from_bits(&[true]).to_jer(FLAGS));
+
+    let mut flags = NamedBitString::<Flags>::new();
+    flags.set(Flags::B, true);
+    assert_eq!(Ok("{\"value\":\"40\",\"length\":2}".to_string()), jer::encode(&flags));
+    assert_eq!(Ok(flags), jer::decode("{\"value\":\"40\",\"length\":8}"));
+}
+
+#[test]
+fn sequence_of() {
+    assert_eq!(Ok("[1,2]".to_string()), jer::encode(&vec![1u8, 2]));
+    assert_eq!(Ok(vec![vec![true], vec![]]), jer::decode::<Vec<Vec<bool>>>("[[true], []]"));
+    let size = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, Some(1))),
+    };
+    assert_eq!(Err(DecodeError::InvalidSize), Vec::<u8>::from_jer(&jer::parse("[1, 2]").unwrap(), size));
+    assert_eq!(Err(jer::EncodeError::InvalidSize), vec![1u8, 2].to_jer(size));
+}
+
+#[test]
+fn sequence_choice_and_enumerated() {
+    let msg = Msg {
+        id: 5,
+        green: true,
+        flags: BitString::from_bits(&[true, false, true, false]),
+        shape: Some(Shape::Circle(3)),
+    };
+    let json = "{\"id\":5,\"color\":\"green\",\"flags\":\"A0\",\"shape\":{\"circle\":3}}";
+    assert_eq!(Ok(json.to_string()), jer::encode(&msg));
+    assert_eq!(Ok(&msg), jer::decode::<Msg>(json).as_ref());
+
+    // A JSON fixture turned back into APER bytes
+    let fixture = "{\n  \"flags\": \"A0\",\n  \"color\": \"green\",\n  \"id\": 5,\n  \"shape\": { \"circle\": 3 }\n}";
+    let enc = jer::decode::<Msg>(fixture).unwrap().to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(&vec![0x80, 0x05, 0xd0, 0x03], enc.bytes());
+    assert_eq!(Ok(msg), Msg::from_aper(&mut aper::Decoder::new(enc.bytes()), UNCONSTRAINED));
+
+    let json = "{\"id\":0,\"color\":\"red\",\"flags\":\"00\"}";
+    assert_eq!(Ok(json.to_string()), jer::encode(&jer::decode::<Msg>(json).unwrap()));
+    assert_eq!(Err(DecodeError::UnknownMember),
+               jer::decode::<Msg>("{\"id\":0,\"color\":\"red\",\"flags\":\"00\",\"size\":1}"));
+    let two_alternatives = "{\"id\":0,\"color\":\"red\",\"flags\":\"00\",\"shape\":{\"circle\":1,\"square\":null}}";
+    assert_eq!(Err(DecodeError::UnknownMember), jer::decode::<Msg>(two_alternatives));
+    assert_eq!(Err(DecodeError::MissingMember), jer::decode::<Msg>("{\"id\":0,\"flags\":\"00\"}"));
+    assert_eq!(Err(DecodeError::UnexpectedType), jer::decode::<Msg>("[]"));
+}
+
+#[test]
+fn malformed_json() {
+    for &json in &["", "[1,]", "{\"a\" 1}", "01", "1.", "-", "\"abc", "\"\\x\"", "\"\t\"", "tru", "{1:2}"] {
+        assert_eq!(Err(DecodeError::MalformedJson), jer::parse(json), "{}", json);
+    }
+    assert_eq!(Err(DecodeError::TrailingData), jer::parse("1 2"));
+    let deep = format!("{}{}", "[".repeat(100), "]".repeat(100));
+    assert_eq!(Err(DecodeError::NestingTooDeep), jer::parse(&deep));
+}
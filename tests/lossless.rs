@@ -0,0 +1,257 @@
+extern crate asn1;
+use asn1::{ExtensionAdditions, ExtensionMarker, UnknownAlternative, UnknownExtensions};
+use asn1::aper::{self, APerElement, Constraints, Encoding, UNCONSTRAINED};
+
+// Foo ::= SEQUENCE { a INTEGER (0..7), ..., b BOOLEAN }
+#[derive(Debug, PartialEq)]
+struct Foo {
+    a: u8,
+    b: Option<bool>,
+    unknown: UnknownExtensions,
+}
+
+impl APerElement for Foo {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    fn from_aper(decoder: &mut aper::Decoder, _: Constraints) -> Result<Self, aper::DecodeError> {
+        let is_ext = ExtensionMarker::from_aper(decoder, UNCONSTRAINED);
+        if is_ext.is_err() {
+            return Err(is_ext.err().unwrap());
+        }
+        let a = decoder.decode_int(Some(0), Some(7));
+        if a.is_err() {
+            return Err(a.err().unwrap());
+        }
+
+        let mut b = None;
+        let mut unknown = UnknownExtensions::default();
+        if is_ext.unwrap() {
+            let ext = ExtensionAdditions::from_aper(decoder, UNCONSTRAINED);
+            if ext.is_err() {
+                return Err(ext.err().unwrap());
+            }
+            let ext = ext.unwrap();
+            b = match ext.decode::<bool>(0, UNCONSTRAINED) {
+                Some(Ok(v)) => Some(v),
+                Some(Err(e)) => return Err(e),
+                None => None,
+            };
+            unknown = ext.into_unknown(1);
+        }
+        Ok(Foo { a: a.unwrap() as u8, b, unknown })
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, aper::EncodeError> {
+        let ext = ExtensionAdditions::new(vec![self.b.map(|b| b.to_aper(UNCONSTRAINED).unwrap())], &self.unknown);
+        let mut enc = (!ext.is_empty() as ExtensionMarker).to_aper(UNCONSTRAINED).unwrap();
+        enc.append(&aper::encode_int(self.a as i64, Some(0), Some(7)).unwrap()).unwrap();
+        if !ext.is_empty() {
+            enc.append(&ext.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+        }
+        Ok(enc)
+    }
+}
+
+// A later version of Foo with two more extension additions.
+// FooV2 ::= SEQUENCE { a INTEGER (0..7), ..., b BOOLEAN, c INTEGER (0..255), d UTF8String }
+struct FooV2 {
+    a: u8,
+    b: Option<bool>,
+    c: Option<u8>,
+    d: Option<String>,
+}
+
+impl FooV2 {
+    fn encode(&self) -> Encoding {
+        let known = vec![
+            self.b.map(|b| b.to_aper(UNCONSTRAINED).unwrap()),
+            self.c.map(|c| c.to_aper(UNCONSTRAINED).unwrap()),
+            self.d.as_ref().map(|d| d.to_aper(UNCONSTRAINED).unwrap()),
+        ];
+        let ext = ExtensionAdditions::new(known, &UnknownExtensions::default());
+        let mut enc = (!ext.is_empty() as ExtensionMarker).to_aper(UNCONSTRAINED).unwrap();
+        enc.append(&aper::encode_int(self.a as i64, Some(0), Some(7)).unwrap()).unwrap();
+        if !ext.is_empty() {
+            enc.append(&ext.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+        }
+        enc
+    }
+}
+
+// Msg ::= CHOICE { foo Foo, ..., bar BOOLEAN }
+#[derive(Debug, PartialEq)]
+enum Msg {
+    Foo(Foo),
+    Bar(bool),
+    Unknown(UnknownAlternative),
+}
+
+impl APerElement for Msg {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    fn from_aper(decoder: &mut aper::Decoder, _: Constraints) -> Result<Self, aper::DecodeError> {
+        let is_ext = ExtensionMarker::from_aper(decoder, UNCONSTRAINED);
+        if is_ext.is_err() {
+            return Err(is_ext.err().unwrap());
+        }
+        if !is_ext.unwrap() {
+            let inner = Foo::from_aper(decoder, UNCONSTRAINED);
+            if inner.is_err() {
+                return Err(inner.err().unwrap());
+            }
+            return Ok(Msg::Foo(inner.unwrap()));
+        }
+
+        let index = decoder.decode_normally_small();
+        if index.is_err() {
+            return Err(index.err().unwrap());
+        }
+        match index.unwrap() {
+            0 => {
+                let bar = decoder.decode_open_type::<bool>(UNCONSTRAINED);
+                if bar.is_err() {
+                    return Err(bar.err().unwrap());
+                }
+                Ok(Msg::Bar(bar.unwrap()))
+            }
+            index => {
+                let value = decoder.decode_open_type_bytes();
+                if value.is_err() {
+                    return Err(value.err().unwrap());
+                }
+                Ok(Msg::Unknown(UnknownAlternative { index, value: value.unwrap() }))
+            }
+        }
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<Encoding, aper::EncodeError> {
+        match *self {
+            Msg::Foo(ref inner) => {
+                let mut enc = false.to_aper(UNCONSTRAINED).unwrap();
+                enc.append(&inner.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+                Ok(enc)
+            }
+            Msg::Bar(bar) => {
+                let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+                enc.append(&aper::encode_normally_small(0).unwrap()).unwrap();
+                enc.append(&aper::encode_open_type(&bar.to_aper(UNCONSTRAINED).unwrap()).unwrap()).unwrap();
+                Ok(enc)
+            }
+            Msg::Unknown(ref alt) => {
+                let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+                enc.append(&alt.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+                Ok(enc)
+            }
+        }
+    }
+}
+
+fn decode<T: APerElement>(data: &[u8], lossless: bool) -> T {
+    let mut d = aper::Decoder::new(data);
+    d.set_lossless(lossless);
+    T::from_aper(&mut d, UNCONSTRAINED).unwrap()
+}
+
+#[test]
+fn known_additions() {
+    let foo = Foo { a: 3, b: Some(true), unknown: UnknownExtensions::default() };
+    let enc = foo.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0xb0, 0x10, 0x01, 0x80], *enc.bytes());
+    assert_eq!(foo, decode::<Foo>(enc.bytes(), false));
+
+    let foo = Foo { a: 3, b: None, unknown: UnknownExtensions::default() };
+    assert_eq!(vec![0x30], *foo.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn unknown_additions_round_trip() {
+    let v2 = FooV2 { a: 5, b: None, c: Some(200), d: Some("héllo".to_string()) };
+    let data = v2.encode().bytes().clone();
+
+    let foo = decode::<Foo>(&data, true);
+    assert_eq!(5, foo.a);
+    assert_eq!(None, foo.b);
+    assert!(!foo.unknown.is_empty());
+    assert_eq!(data, *foo.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn unknown_additions_with_known() {
+    let v2 = FooV2 { a: 1, b: Some(false), c: None, d: Some(String::new()) };
+    let data = v2.encode().bytes().clone();
+
+    let foo = decode::<Foo>(&data, true);
+    assert_eq!(Some(false), foo.b);
+    assert_eq!(data, *foo.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn trailing_absent_additions() {
+    // The sender knows of three additions but only sends the first, so its bitmap is longer than ours
+    let v2 = FooV2 { a: 1, b: Some(true), c: None, d: None };
+    let data = v2.encode().bytes().clone();
+
+    let foo = decode::<Foo>(&data, true);
+    assert!(foo.unknown.is_empty());
+    assert_eq!(data, *foo.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn unknown_additions_dropped() {
+    let v2 = FooV2 { a: 5, b: Some(true), c: Some(200), d: None };
+    let data = v2.encode().bytes().clone();
+
+    let foo = decode::<Foo>(&data, false);
+    assert!(foo.unknown.is_empty());
+    let canonical = Foo { a: 5, b: Some(true), unknown: UnknownExtensions::default() };
+    assert_eq!(*canonical.to_aper(UNCONSTRAINED).unwrap().bytes(), *foo.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn unknown_alternative_round_trip() {
+    // baz UTF8String, the second extension alternative of a later version of Msg
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&aper::encode_normally_small(1).unwrap()).unwrap();
+    enc.append(&aper::encode_open_type(&"baz".to_string().to_aper(UNCONSTRAINED).unwrap()).unwrap()).unwrap();
+    let data = enc.bytes().clone();
+
+    let msg = decode::<Msg>(&data, true);
+    assert_eq!(Msg::Unknown(UnknownAlternative { index: 1, value: b"\x03baz".to_vec() }), msg);
+    assert_eq!(data, *msg.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn nested_round_trip() {
+    // The sub-decoder of the open type inherits lossless mode
+    let v2 = FooV2 { a: 2, b: None, c: Some(7), d: None };
+    let mut enc = false.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&v2.encode()).unwrap();
+    let data = enc.bytes().clone();
+
+    let msg = decode::<Msg>(&data, true);
+    assert_eq!(data, *msg.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    let mut wrapped = aper::encode_open_type(&msg.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    wrapped.append(&true.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    let mut d = aper::Decoder::new(wrapped.bytes());
+    d.set_lossless(true);
+    let inner = d.decode_open_type::<Msg>(UNCONSTRAINED).unwrap();
+    assert_eq!(data, *inner.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn normally_small() {
+    assert_eq!(vec![0x0a], *aper::encode_normally_small(5).unwrap().bytes());
+    assert_eq!(vec![0x80, 0x01, 0x40], *aper::encode_normally_small(64).unwrap().bytes());
+    assert_eq!(vec![0x80, 0x01, 0xc8], *aper::encode_normally_small(200).unwrap().bytes());
+    assert_eq!(vec![0x80, 0x02, 0x01, 0x00], *aper::encode_normally_small(256).unwrap().bytes());
+
+    let mut d = aper::Decoder::new(b"\x0a");
+    assert_eq!(Ok(5), d.decode_normally_small());
+    let mut d = aper::Decoder::new(b"\x80\x01\x40");
+    assert_eq!(Ok(64), d.decode_normally_small());
+    let mut d = aper::Decoder::new(b"\x80\x01\xc8");
+    assert_eq!(Ok(200), d.decode_normally_small());
+    let mut d = aper::Decoder::new(b"\x80\x02\x01\x00");
+    assert_eq!(Ok(256), d.decode_normally_small());
+}
@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate asn1;
+use asn1::{BitString, NamedBitString};
+use asn1::aper::{self, APerElement, Constraint, Constraints};
+
+// Flags ::= BIT STRING { flagA(0), flagB(1), flagC(2) } (SIZE(1..8))
+named_bits! {
+    pub enum Flags {
+        FlagA = 0,
+        FlagB = 1,
+        FlagC = 2,
+    }
+}
+
+const FLAGS: Constraints = Constraints {
+    value: None,
+    size: Some(Constraint::new(Some(1), Some(8))),
+};
+
+#[test]
+fn accessors() {
+    let mut flags = NamedBitString::<Flags>::new();
+    assert!(!flags.is_set(Flags::FlagC));
+    flags.set(Flags::FlagC, true);
+    assert!(flags.is_set(Flags::FlagC));
+    assert_eq!(3, flags.bits().get_num_bits());
+    flags.set(Flags::FlagA, true);
+    assert_eq!(vec![Flags::FlagA, Flags::FlagC], flags.names());
+}
+
+#[test]
+fn trailing_zeros_are_insignificant() {
+    let a = NamedBitString::<Flags>::from_bits(BitString::from_bits(&[false, true]));
+    let b = NamedBitString::<Flags>::from_bits(BitString::from_bits(&[false, true, false, false, false, false]));
+    assert_eq!(a, b);
+}
+
+#[test]
+fn encode_trimmed() {
+    let flags = NamedBitString::<Flags>::from_bits(BitString::from_bits(&[false, true, false, false, false, false]));
+    // length 2 in 3 bits, then octet-aligned bits 01
+    let target: Vec<u8> = vec![0x20, 0x40];
+    assert_eq!(target, *flags.to_aper(FLAGS).unwrap().bytes());
+}
+
+#[test]
+fn encode_empty_keeps_lower_bound() {
+    let flags = NamedBitString::<Flags>::new();
+    let target: Vec<u8> = vec![0x00, 0x00];
+    assert_eq!(target, *flags.to_aper(FLAGS).unwrap().bytes());
+}
+
+#[test]
+fn decode() {
+    let data = b"\x40\x20"; // length 3, then bits 001
+    let mut d = aper::Decoder::new(data);
+    let flags = NamedBitString::<Flags>::from_aper(&mut d, FLAGS).unwrap();
+    assert_eq!(vec![Flags::FlagC], flags.names());
+    assert!(!flags.is_set(Flags::FlagA));
+}
+
+#[test]
+fn round_trip_after_unaligned_field() {
+    let mut flags = NamedBitString::<Flags>::new();
+    flags.set(Flags::FlagA, true);
+    flags.set(Flags::FlagB, true);
+
+    let mut enc = true.to_aper(aper::UNCONSTRAINED).unwrap();
+    enc.append(&flags.to_aper(FLAGS).unwrap()).unwrap();
+    // 1, length 2 in 3 bits, padding, bits 11
+    let target: Vec<u8> = vec![0x90, 0xc0];
+    assert_eq!(target, *enc.bytes());
+
+    let bytes = enc.bytes().clone();
+    let mut d = aper::Decoder::new(&bytes);
+    assert!(bool::from_aper(&mut d, aper::UNCONSTRAINED).unwrap());
+    assert_eq!(flags, NamedBitString::<Flags>::from_aper(&mut d, FLAGS).unwrap());
+}
@@ -0,0 +1,375 @@
+extern crate asn1;
+use asn1::notation::{self, Argument, AssignmentKind, Bound, Class, ClassField, Constraint, ElementSet, ElementSetSpecs,
+                     Endpoint, ErrorKind, ExtensionAddition, FieldKind, Position, Presence, Reference, SyntaxToken,
+                     TagDefault, Type, TypeKind, Value};
+
+const NGAP: &str = r#"
+NGAP-PDU-Contents {
+itu-t (0) identified-organization (4) etsi (0) mobileDomain (0)
+ngran-access (22) modules (3) ngap (1) version1 (1) ngap-PDU-Contents (1) }
+
+DEFINITIONS AUTOMATIC TAGS ::=
+
+BEGIN
+
+IMPORTS
+    Cause,
+    maxnoofPDUSessions
+FROM NGAP-IEs
+
+    ProtocolIE-Container{},
+    NGAP-PROTOCOL-IES
+FROM NGAP-Containers;
+
+-- A comment -- PDUSessionResourceSetupRequest ::= SEQUENCE {
+    protocolIEs     ProtocolIE-Container { {PDUSessionResourceSetupRequestIEs} },
+    ...
+}
+
+PDUSessionResourceSetupRequestIEs NGAP-PROTOCOL-IES ::= {
+    { ID id-Cause CRITICALITY ignore TYPE Cause PRESENCE optional },
+    ...
+}
+
+PDUSessionList ::= SEQUENCE (SIZE(1..maxnoofPDUSessions)) OF PDUSessionItem
+
+PDUSessionItem ::= SEQUENCE {
+    id              INTEGER (0..255),
+    flags           BIT STRING (SIZE (8, ...)) OPTIONAL,
+    priority        INTEGER { low(0), high(1) } DEFAULT low,
+    ...,
+    [[  extra       OCTET STRING,
+        more        BOOLEAN ]],
+    ...,
+    last            ENUMERATED { a, b, ..., c }
+}
+
+END
+"#;
+
+fn assignment_type<'a>(module: &'a notation::Module, name: &str) -> &'a Type {
+    match module.assignment(name).unwrap().kind {
+        AssignmentKind::Type(ref ty) => ty,
+        ref kind => panic!("{:?}", kind),
+    }
+}
+
+fn range(lower: i64, upper: Bound) -> ElementSet {
+    ElementSet::Range {
+        lower: Endpoint {
+            value: Bound::Value(Value::Integer(lower)),
+            open: false,
+        },
+        upper: Endpoint {
+            value: upper,
+            open: false,
+        },
+    }
+}
+
+fn subtype(root: ElementSet, extensible: bool) -> Constraint {
+    Constraint::Subtype(ElementSetSpecs {
+                            root: Some(root),
+                            extensible,
+                            additions: None,
+                        })
+}
+
+#[test]
+fn module_header() {
+    let modules = notation::parse(NGAP).unwrap();
+    assert_eq!(1, modules.len());
+    let module = &modules[0];
+    assert_eq!("NGAP-PDU-Contents", module.name);
+    assert_eq!(Position { line: 2, column: 1 }, module.position);
+    assert_eq!(TagDefault::Automatic, module.tag_default);
+    assert_eq!(None, module.exports);
+    match module.oid {
+        Some(Value::Braced(ref parts)) => {
+            assert_eq!(1, parts.len());
+            assert_eq!(Value::NamedNumber("itu-t".to_string(), Box::new(Value::Integer(0))), parts[0][0]);
+            assert_eq!(9, parts[0].len());
+        }
+        ref oid => panic!("{:?}", oid),
+    }
+
+    assert_eq!(2, module.imports.len());
+    assert_eq!(vec!["Cause", "maxnoofPDUSessions"], module.imports[0].symbols);
+    assert_eq!("NGAP-IEs", module.imports[0].module);
+    assert_eq!(vec!["ProtocolIE-Container", "NGAP-PROTOCOL-IES"], module.imports[1].symbols);
+    assert_eq!(None, module.imports[1].oid);
+
+    let names: Vec<&str> = module.assignments.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(vec!["PDUSessionResourceSetupRequest",
+                    "PDUSessionResourceSetupRequestIEs",
+                    "PDUSessionList",
+                    "PDUSessionItem"],
+               names);
+    assert_eq!(Position { line: 19, column: 17 }, module.assignments[0].position);
+}
+
+#[test]
+fn types_and_components() {
+    let modules = notation::parse(NGAP).unwrap();
+    let module = &modules[0];
+
+    // A SIZE constraint before OF constrains the SEQUENCE OF
+    let list = assignment_type(module, "PDUSessionList");
+    let size = range(1, Bound::Value(Value::Reference(Reference::new("maxnoofPDUSessions"))));
+    assert_eq!(vec![subtype(ElementSet::Size(Box::new(subtype(size, false))), false)], list.constraints);
+    match list.kind {
+        TypeKind::SequenceOf { name: None, ref element } => {
+            assert_eq!(TypeKind::Reference {
+                           reference: Reference::new("PDUSessionItem"),
+                           arguments: vec![],
+                       },
+                       element.kind)
+        }
+        ref kind => panic!("{:?}", kind),
+    }
+
+    let components = match assignment_type(module, "PDUSessionItem").kind {
+        TypeKind::Sequence(ref components) => components.clone(),
+        ref kind => panic!("{:?}", kind),
+    };
+    let names: Vec<&str> = components.root.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(vec!["id", "flags", "priority", "last"], names);
+    assert_eq!(vec![subtype(range(0, Bound::Value(Value::Integer(255))), false)], components.root[0].ty.constraints);
+    assert_eq!(Presence::Optional, components.root[1].presence);
+    let size = subtype(ElementSet::Value(Value::Integer(8)), true);
+    assert_eq!(vec![subtype(ElementSet::Size(Box::new(size)), false)], components.root[1].ty.constraints);
+    assert_eq!(Presence::Default(Value::Reference(Reference::new("low"))), components.root[2].presence);
+    match components.root[3].ty.kind {
+        TypeKind::Enumerated { ref root, extensions: Some(ref extensions) } => {
+            assert_eq!(2, root.len());
+            assert_eq!("c", extensions[0].name);
+        }
+        ref kind => panic!("{:?}", kind),
+    }
+
+    let extensions = components.extensions.unwrap();
+    assert_eq!(1, extensions.len());
+    match extensions[0] {
+        ExtensionAddition::Group { version: None, ref components } => {
+            assert_eq!(2, components.len());
+            assert_eq!(TypeKind::Boolean, components[1].ty.kind);
+        }
+        ref addition => panic!("{:?}", addition),
+    }
+}
+
+#[test]
+fn parameterized_types_and_object_sets() {
+    let modules = notation::parse(NGAP).unwrap();
+    let module = &modules[0];
+
+    let components = match assignment_type(module, "PDUSessionResourceSetupRequest").kind {
+        TypeKind::Sequence(ref components) => components.clone(),
+        ref kind => panic!("{:?}", kind),
+    };
+    assert_eq!(Some(vec![]), components.extensions);
+    match components.root[0].ty.kind {
+        TypeKind::Reference { ref reference, ref arguments } => {
+            assert_eq!("ProtocolIE-Container", reference.name);
+            let ies = Type::new(TypeKind::Reference {
+                                    reference: Reference::new("PDUSessionResourceSetupRequestIEs"),
+                                    arguments: vec![],
+                                });
+            let set = ElementSetSpecs {
+                root: Some(ElementSet::ContainedSubtype(ies)),
+                extensible: false,
+                additions: None,
+            };
+            assert_eq!(&vec![Argument::Set(set)], arguments);
+        }
+        ref kind => panic!("{:?}", kind),
+    }
+
+    // The objects are in defined syntax, which needs the class to be understood
+    match module.assignment("PDUSessionResourceSetupRequestIEs").unwrap().kind {
+        AssignmentKind::ValueSet { ref ty, ref set } => {
+            assert_eq!(TypeKind::Reference {
+                           reference: Reference::new("NGAP-PROTOCOL-IES"),
+                           arguments: vec![],
+                       },
+                       ty.kind);
+            assert!(set.extensible);
+            match set.root {
+                Some(ElementSet::Value(Value::Braced(ref parts))) => {
+                    assert_eq!(1, parts.len());
+                    assert_eq!(8, parts[0].len());
+                    assert_eq!(Value::Reference(Reference::new("id-Cause")), parts[0][1]);
+                }
+                ref root => panic!("{:?}", root),
+            }
+        }
+        ref kind => panic!("{:?}", kind),
+    }
+}
+
+#[test]
+fn classes_and_relations() {
+    let modules = notation::parse(r#"
+NGAP-Containers DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+EXPORTS NGAP-PROTOCOL-IES, ProtocolIE-Container{};
+NGAP-PROTOCOL-IES ::= CLASS {
+    &id             ProtocolIE-ID                   UNIQUE,
+    &criticality    Criticality,
+    &Value,
+    &presence       Presence DEFAULT optional
+}
+WITH SYNTAX {
+    ID              &id
+    CRITICALITY     &criticality
+    TYPE            &Value
+    [PRESENCE       &presence]
+}
+
+ProtocolIE-Container {NGAP-PROTOCOL-IES : IEsSetParam} ::=
+    SEQUENCE (SIZE (0..maxProtocolIEs)) OF
+    ProtocolIE-Field {{IEsSetParam}}
+
+ProtocolIE-Field {NGAP-PROTOCOL-IES : IEsSetParam} ::= SEQUENCE {
+    id              NGAP-PROTOCOL-IES.&id               ({IEsSetParam}),
+    value           NGAP-PROTOCOL-IES.&Value            ({IEsSetParam}{@id})
+}
+END"#)
+        .unwrap();
+    let module = &modules[0];
+    assert_eq!(Some(vec!["NGAP-PROTOCOL-IES".to_string(), "ProtocolIE-Container".to_string()]),
+               module.exports);
+
+    let class = match module.assignment("NGAP-PROTOCOL-IES").unwrap().kind {
+        AssignmentKind::Class(ref class) => class.clone(),
+        ref kind => panic!("{:?}", kind),
+    };
+    assert_eq!(ClassField {
+                   name: "&id".to_string(),
+                   kind: FieldKind::Value(Type::new(TypeKind::Reference {
+                                                        reference: Reference::new("ProtocolIE-ID"),
+                                                        arguments: vec![],
+                                                    })),
+                   unique: true,
+                   optional: false,
+                   default: None,
+               },
+               class.fields[0]);
+    assert_eq!(FieldKind::Type, class.fields[2].kind);
+    assert_eq!(Some(Value::Reference(Reference::new("optional"))), class.fields[3].default);
+    assert_eq!(Class {
+                   fields: class.fields.clone(),
+                   syntax: Some(vec![SyntaxToken::Word("ID".to_string()),
+                                     SyntaxToken::Field("&id".to_string()),
+                                     SyntaxToken::Word("CRITICALITY".to_string()),
+                                     SyntaxToken::Field("&criticality".to_string()),
+                                     SyntaxToken::Word("TYPE".to_string()),
+                                     SyntaxToken::Field("&Value".to_string()),
+                                     SyntaxToken::Optional(vec![SyntaxToken::Word("PRESENCE".to_string()),
+                                                                SyntaxToken::Field("&presence".to_string())])]),
+               },
+               class);
+
+    let container = module.assignment("ProtocolIE-Container").unwrap();
+    assert_eq!(1, container.parameters.len());
+    assert_eq!("IEsSetParam", container.parameters[0].name);
+    assert!(container.parameters[0].governor.is_some());
+
+    let components = match module.assignment("ProtocolIE-Field").unwrap().kind {
+        AssignmentKind::Type(Type { kind: TypeKind::Sequence(ref components), .. }) => components.clone(),
+        ref kind => panic!("{:?}", kind),
+    };
+    assert_eq!(TypeKind::ClassField {
+                   class: Reference::new("NGAP-PROTOCOL-IES"),
+                   fields: vec!["&Value".to_string()],
+               },
+               components.root[1].ty.kind);
+    match components.root[1].ty.constraints[0] {
+        Constraint::Relation { ref components, .. } => assert_eq!(&vec!["id".to_string()], components),
+        ref constraint => panic!("{:?}", constraint),
+    }
+    match components.root[0].ty.constraints[0] {
+        Constraint::Subtype(ElementSetSpecs { root: Some(ElementSet::Braced(_)), .. }) => {}
+        ref constraint => panic!("{:?}", constraint),
+    }
+}
+
+#[test]
+fn set_operations() {
+    let modules = notation::parse("M DEFINITIONS ::= BEGIN
+        A ::= INTEGER (1 | 3..5 ^ (MIN..4) EXCEPT 4, ..., 6)
+        B ::= IA5String (FROM (\"a\"..\"z\") INTERSECTION SIZE (1..8))
+        C ::= INTEGER (0<..<MAX)
+    END")
+        .unwrap();
+    let a = assignment_type(&modules[0], "A");
+    match a.constraints[0] {
+        Constraint::Subtype(ElementSetSpecs { root: Some(ElementSet::Union(ref sets)), extensible: true,
+                                               additions: Some(ElementSet::Value(Value::Integer(6))) }) => {
+            assert_eq!(ElementSet::Value(Value::Integer(1)), sets[0]);
+            match sets[1] {
+                ElementSet::Intersection(ref sets) => {
+                    assert_eq!(range(3, Bound::Value(Value::Integer(5))), sets[0]);
+                    assert!(matches!(sets[1], ElementSet::Except(..)));
+                }
+                ref set => panic!("{:?}", set),
+            }
+        }
+        ref constraint => panic!("{:?}", constraint),
+    }
+    let b = assignment_type(&modules[0], "B");
+    assert!(matches!(b.constraints[0],
+                     Constraint::Subtype(ElementSetSpecs { root: Some(ElementSet::Intersection(_)), .. })));
+    let c = assignment_type(&modules[0], "C");
+    assert_eq!(vec![subtype(ElementSet::Range {
+                                lower: Endpoint {
+                                    value: Bound::Value(Value::Integer(0)),
+                                    open: true,
+                                },
+                                upper: Endpoint {
+                                    value: Bound::Max,
+                                    open: true,
+                                },
+                            },
+                            false)],
+               c.constraints);
+}
+
+#[test]
+fn errors() {
+    let err = notation::parse("Foo DEFINITIONS ::= BEGIN\n  Bar ::= SEQUENCE { a }\nEND").unwrap_err();
+    assert_eq!(Position { line: 2, column: 24 }, err.position);
+    assert_eq!(ErrorKind::UnexpectedToken {
+                   expected: "a type",
+                   found: "}".to_string(),
+               },
+               err.kind);
+    assert_eq!("2:24: expected a type, found `}`", err.to_string());
+
+    let err = notation::parse("Foo DEFINITIONS ::= BEGIN\n  Bar ::= INTEGER").unwrap_err();
+    assert_eq!(ErrorKind::UnexpectedEnd { expected: "an assignment" }, err.kind);
+    assert_eq!(Position { line: 2, column: 18 }, err.position);
+
+    let err = notation::parse("Foo DEFINITIONS ::= BEGIN\n  /* a /* nested */ comment\nEND").unwrap_err();
+    assert_eq!(ErrorKind::UnterminatedComment, err.kind);
+    assert_eq!(Position { line: 2, column: 3 }, err.position);
+
+    let err = notation::parse("Foo DEFINITIONS ::= BEGIN Bar ::= BIT STRING (SIZE ('012'B)) END").unwrap_err();
+    assert_eq!(ErrorKind::InvalidString, err.kind);
+    assert_eq!(Position { line: 1, column: 53 }, err.position);
+
+    let err = notation::parse("Foo DEFINITIONS ::= BEGIN Bar ::= SEQUENCE { COMPONENTS OF Baz } END").unwrap_err();
+    assert_eq!(ErrorKind::Unsupported("COMPONENTS OF"), err.kind);
+
+    // A CHOICE needs an alternative in its root, as ENUMERATED needs an item
+    for text in &["Foo DEFINITIONS ::= BEGIN Bar ::= ENUMERATED { } END",
+                  "Foo DEFINITIONS ::= BEGIN Bar ::= CHOICE { } END"] {
+        let err = notation::parse(text).unwrap_err();
+        assert_eq!(Position { line: 1, column: text.find('}').unwrap() + 1 }, err.position);
+    }
+    let err = notation::parse("Foo DEFINITIONS ::= BEGIN Bar ::= CHOICE { ..., a NULL } END").unwrap_err();
+    assert_eq!(ErrorKind::UnexpectedToken {
+                   expected: "an identifier",
+                   found: "...".to_string(),
+               },
+               err.kind);
+}
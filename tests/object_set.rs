@@ -0,0 +1,124 @@
+#[macro_use]
+extern crate asn1;
+use asn1::{Criticality, ObjectSet, OpenValue, ProtocolContainer, ProtocolField};
+use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+
+object_set! {
+    pub enum ProtocolIes: 0..65535 {
+        MmeUeS1apId(u32) = 0 => Reject,
+        EnbUeS1apId(u16) = 8 => Ignore,
+        Flag(bool) = 40 => Notify,
+    }
+}
+
+object_set! {
+    pub enum InitiatingMessages: 0..255 {
+        Reset(ProtocolContainer<ProtocolIes>, IE_CONTAINER) = 14 => Reject,
+        Paging(u8) = 10 => Ignore,
+    }
+}
+
+const IE_CONTAINER: Constraints = Constraints {
+    value: None,
+    size: Some(Constraint::new(Some(0), Some(65535))),
+};
+
+#[test]
+fn table() {
+    assert_eq!(Some(Criticality::Ignore), ProtocolIes::criticality(8));
+    assert_eq!(None, ProtocolIes::criticality(9));
+    assert_eq!(3, ProtocolIes::OBJECTS.len());
+    assert_eq!(40, ProtocolIes::Flag(true).id());
+}
+
+#[test]
+fn known_field() {
+    let field = ProtocolField::new(ProtocolIes::EnbUeS1apId(42));
+    assert_eq!(Criticality::Ignore, field.criticality);
+    let enc = field.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x00, 0x08, 0x40, 0x02, 0x00, 0x2a], *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    let decoded = ProtocolField::<ProtocolIes>::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(Some(&ProtocolIes::EnbUeS1apId(42)), decoded.known());
+    assert_eq!(field, decoded);
+}
+
+#[test]
+fn field_after_choice_index() {
+    // The id is octet-aligned even when the field follows the 2-bit index of a PDU CHOICE
+    let field = ProtocolField::new(ProtocolIes::EnbUeS1apId(42));
+    let mut enc = aper::encode_int(1, Some(0), Some(2)).unwrap();
+    enc.append(&field.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x40, 0x00, 0x08, 0x40, 0x02, 0x00, 0x2a], *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(1, d.decode_int(Some(0), Some(2)).unwrap());
+    assert_eq!(field, ProtocolField::<ProtocolIes>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn unknown_field_pass_through() {
+    let data = b"\x00\x63\x80\x03\xab\xcd\xef";
+    let mut d = aper::Decoder::new(data);
+    let field = ProtocolField::<ProtocolIes>::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(99, field.id);
+    assert_eq!(Criticality::Notify, field.criticality);
+    assert_eq!(OpenValue::Unknown(vec![0xab, 0xcd, 0xef]), field.value);
+    assert_eq!(None, field.known());
+    assert_eq!(data.to_vec(), *field.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn malformed_known_value() {
+    // The u32 of id 0 is missing octets
+    let data = b"\x00\x00\x00\x01\xc0";
+    let mut d = aper::Decoder::new(data);
+    assert!(ProtocolField::<ProtocolIes>::from_aper(&mut d, UNCONSTRAINED).is_err());
+}
+
+#[test]
+fn container() {
+    let unknown = ProtocolField {
+        id: 99,
+        criticality: Criticality::Reject,
+        value: OpenValue::Unknown(vec![0xab]),
+    };
+    let ies = ProtocolContainer::new(vec![ProtocolField::new(ProtocolIes::EnbUeS1apId(42)), unknown]);
+
+    // Preceded by an extension bit, so the count is aligned
+    let mut enc = false.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&ies.to_aper(IE_CONTAINER).unwrap()).unwrap();
+    let target: Vec<u8> = vec![0x00, 0x00, 0x02, 0x00, 0x08, 0x40, 0x02, 0x00, 0x2a, 0x00, 0x63, 0x00, 0x01, 0xab];
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(&target);
+    assert!(!bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    let decoded = ProtocolContainer::<ProtocolIes>::from_aper(&mut d, IE_CONTAINER).unwrap();
+    assert_eq!(ies, decoded);
+    assert_eq!(Some(&ProtocolIes::EnbUeS1apId(42)), decoded.get(8).unwrap().known());
+    assert!(decoded.get(0).is_none());
+}
+
+#[test]
+fn container_size() {
+    let ies: ProtocolContainer<ProtocolIes> = ProtocolContainer::new(vec![]);
+    let extensions = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(65535))),
+    };
+    assert_eq!(Some(aper::EncodeError::InvalidSize), ies.to_aper(extensions).err());
+    assert_eq!(Some(aper::EncodeError::MissingSizeConstraint), ies.to_aper(UNCONSTRAINED).err());
+}
+
+#[test]
+fn procedure() {
+    let ies = ProtocolContainer::new(vec![ProtocolField::new(ProtocolIes::Flag(true))]);
+    let msg = ProtocolField::new(InitiatingMessages::Reset(ies));
+    let enc = msg.to_aper(UNCONSTRAINED).unwrap();
+    let target: Vec<u8> = vec![0x0e, 0x00, 0x07, 0x00, 0x01, 0x00, 0x28, 0x80, 0x01, 0x80];
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(msg, ProtocolField::<InitiatingMessages>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
@@ -0,0 +1,332 @@
+#[macro_use]
+extern crate asn1;
+use asn1::{BitString, ExtensionAdditions, IA5String, BMPString, NamedBitString, ObjectIdentifier, UnknownExtensions,
+           UTCTime};
+use asn1::ber::Tag;
+use asn1::oer::{self, Constraint, Constraints, Encoding, OerElement, UNCONSTRAINED};
+
+named_bits! {
+    pub enum Flags {
+        A = 0,
+        B = 1,
+        C = 2,
+    }
+}
+
+// Msg ::= SEQUENCE {
+//     id INTEGER (0..65535),
+//     name IA5String OPTIONAL,
+//     version INTEGER (0..255) DEFAULT 1,
+//     ...,
+//     flag BOOLEAN
+// }
+#[derive(Debug, PartialEq)]
+struct Msg {
+    id: u16,
+    name: Option<IA5String>,
+    version: u8,
+    flag: Option<bool>,
+    unknown: UnknownExtensions,
+}
+
+impl OerElement for Msg {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    fn from_oer(decoder: &mut oer::Decoder, _: Constraints) -> Result<Self, oer::DecodeError> {
+        let preamble = decoder.decode_preamble(3);
+        if preamble.is_err() {
+            return Err(preamble.err().unwrap());
+        }
+        let preamble = preamble.unwrap();
+
+        let id = u16::from_oer(decoder, UNCONSTRAINED);
+        if id.is_err() {
+            return Err(id.err().unwrap());
+        }
+        let mut name = None;
+        if preamble.is_set(1) {
+            let ret = IA5String::from_oer(decoder, UNCONSTRAINED);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            name = Some(ret.unwrap());
+        }
+        let version = decoder.decode_default(preamble.is_set(2), 1u8, UNCONSTRAINED);
+        if version.is_err() {
+            return Err(version.err().unwrap());
+        }
+
+        let mut flag = None;
+        let mut unknown = UnknownExtensions::default();
+        if preamble.is_set(0) {
+            let ext = ExtensionAdditions::from_oer(decoder, UNCONSTRAINED);
+            if ext.is_err() {
+                return Err(ext.err().unwrap());
+            }
+            let ext = ext.unwrap();
+            flag = match ext.decode_oer::<bool>(0, UNCONSTRAINED) {
+                Some(Ok(v)) => Some(v),
+                Some(Err(e)) => return Err(e),
+                None => None,
+            };
+            unknown = ext.into_unknown(1);
+        }
+        Ok(Msg {
+            id: id.unwrap(),
+            name,
+            version: version.unwrap(),
+            flag,
+            unknown,
+        })
+    }
+
+    fn to_oer(&self, _: Constraints) -> Result<Encoding, oer::EncodeError> {
+        let ext = ExtensionAdditions::new_oer(vec![self.flag.map(|f| f.to_oer(UNCONSTRAINED).unwrap())],
+                                              &self.unknown);
+        let version = oer::encode_default(&self.version, &1, UNCONSTRAINED).unwrap();
+        let mut enc = oer::encode_preamble(&BitString::from_bits(&[!ext.is_empty(),
+                                                                   self.name.is_some(),
+                                                                   version.is_some()]));
+        enc.append(&self.id.to_oer(UNCONSTRAINED).unwrap());
+        if let Some(ref name) = self.name {
+            enc.append(&name.to_oer(UNCONSTRAINED).unwrap());
+        }
+        if let Some(version) = version {
+            enc.append(&version);
+        }
+        if !ext.is_empty() {
+            enc.append(&ext.to_oer(UNCONSTRAINED).unwrap());
+        }
+        Ok(enc)
+    }
+}
+
+fn msg(id: u16, name: Option<&str>, version: u8, flag: Option<bool>) -> Msg {
+    Msg {
+        id,
+        name: name.map(|n| IA5String::new(n).unwrap()),
+        version,
+        flag,
+        unknown: UnknownExtensions::default(),
+    }
+}
+
+fn size(min: i64, max: i64) -> Constraints {
+    Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(min), Some(max))),
+    }
+}
+
+#[test]
+fn integers() {
+    assert_eq!(Ok(vec![0x05]), oer::encode(&5u8));
+    assert_eq!(Ok(vec![0xff]), oer::encode(&-1i8));
+    assert_eq!(Ok(vec![0x01, 0xf4]), oer::encode(&500u16));
+    assert_eq!(Ok(vec![0xff, 0xff, 0xff, 0xfe]), oer::encode(&-2i32));
+    assert_eq!(Ok(-2), oer::decode::<i32>(b"\xff\xff\xff\xfe"));
+
+    assert_eq!(&[0x01, 0xf5], oer::encode_int(501, Some(0), Some(1000)).unwrap().bytes());
+    assert_eq!(&[0x00, 0x05], oer::encode_int(5, Some(-1000), Some(1000)).unwrap().bytes());
+    assert_eq!(&[0, 0, 0, 0, 0, 0, 0, 0x05], oer::encode_int(5, Some(0), Some(1 << 40)).unwrap().bytes());
+    assert_eq!(&[0x02, 0x01, 0xf5], oer::encode_int(501, Some(0), None).unwrap().bytes());
+    assert_eq!(&[0x01, 0x80], oer::encode_int(128, Some(0), None).unwrap().bytes());
+    assert_eq!(&[0x02, 0x00, 0x80], oer::encode_int(128, None, None).unwrap().bytes());
+    assert_eq!(&[0x01, 0xff], oer::encode_int(-1, None, Some(10)).unwrap().bytes());
+    assert_eq!(Err(oer::EncodeError::InvalidValue), oer::encode_int(1001, Some(0), Some(1000)));
+
+    let mut d = oer::Decoder::new(b"\x02\x00\x80\x01\xff");
+    assert_eq!(Ok(128), d.decode_int(None, None));
+    assert_eq!(Ok(-1), d.decode_int(None, None));
+    let mut d = oer::Decoder::new(b"\xc8");
+    assert_eq!(Err(oer::DecodeError::MalformedInt), d.decode_int(Some(0), Some(100)));
+    let mut d = oer::Decoder::new(b"\x00");
+    assert_eq!(Err(oer::DecodeError::MalformedInt), d.decode_int(None, None));
+}
+
+#[test]
+fn lengths() {
+    assert_eq!(&[0x7f], oer::encode_length(127).bytes());
+    assert_eq!(&[0x81, 0xc8], oer::encode_length(200).bytes());
+    assert_eq!(&[0x82, 0x01, 0x2c], oer::encode_length(300).bytes());
+
+    let mut data = vec![0x81, 0xc8];
+    data.extend_from_slice(&[0x55; 200]);
+    let mut d = oer::Decoder::new(&data);
+    assert_eq!(Ok(vec![0x55; 200]), d.decode_string(None));
+    // The quantity of a SEQUENCE OF is not a length determinant
+    data[0] = 0x01;
+    assert_eq!(Ok(vec![0x55; 200]), oer::decode::<Vec<u8>>(&data));
+    let mut d = oer::Decoder::new(b"\x82\x01");
+    assert_eq!(Err(oer::DecodeError::NotEnoughBytes), d.decode_length());
+    let mut d = oer::Decoder::new(b"\x80");
+    assert_eq!(Err(oer::DecodeError::MalformedLength), d.decode_length());
+}
+
+#[test]
+fn canonical_restrictions() {
+    assert_eq!(Ok(true), oer::decode::<bool>(b"\x01"));
+    assert_eq!(Err(oer::DecodeError::InvalidBool), oer::decode_canonical::<bool>(b"\x01"));
+
+    let mut d = oer::Decoder::new(b"\x82\x00\x05\x01\x02\x03\x04\x05");
+    d.set_canonical(true);
+    assert_eq!(Err(oer::DecodeError::NonMinimalLength), d.decode_string(None));
+    let mut d = oer::Decoder::new(b"\x81\x05\x01\x02\x03\x04\x05");
+    assert_eq!(Ok(vec![1, 2, 3, 4, 5]), d.decode_string(None));
+    d = oer::Decoder::new(b"\x81\x05\x01\x02\x03\x04\x05");
+    d.set_canonical(true);
+    assert_eq!(Err(oer::DecodeError::NonMinimalLength), d.decode_string(None));
+
+    let mut d = oer::Decoder::new(b"\x02\x00\x05");
+    d.set_canonical(true);
+    assert_eq!(Err(oer::DecodeError::NonMinimalInt), d.decode_int(Some(0), None));
+    let mut d = oer::Decoder::new(b"\x02\x00\x05");
+    assert_eq!(Ok(5), d.decode_int(Some(0), None));
+
+    assert_eq!(Ok(vec![1u8]), oer::decode::<Vec<u8>>(b"\x02\x00\x01\x01"));
+    assert_eq!(Err(oer::DecodeError::NonMinimalLength), oer::decode_canonical::<Vec<u8>>(b"\x02\x00\x01\x01"));
+
+    assert_eq!(Ok(10.), oer::decode::<f64>(b"\x03\x80\x00\x0a"));
+    assert_eq!(Err(oer::DecodeError::NonCanonicalReal), oer::decode_canonical::<f64>(b"\x03\x80\x00\x0a"));
+    assert_eq!(Ok(vec![0x03, 0x80, 0x01, 0x05]), oer::encode(&10.));
+
+    assert!(oer::decode_canonical::<UTCTime>(b"\x0d240229134530Z").is_ok());
+    assert_eq!(Err(oer::DecodeError::NonCanonicalTime), oer::decode_canonical::<UTCTime>(b"\x0b2402291345Z"));
+    assert_eq!(Err(oer::DecodeError::InvalidTime), oer::decode::<UTCTime>(b"\x0b2402291345X"));
+}
+
+#[test]
+fn strings() {
+    let s = IA5String::new("abc").unwrap();
+    assert_eq!(&[0x03, 0x61, 0x62, 0x63], s.to_oer(UNCONSTRAINED).unwrap().bytes());
+    assert_eq!(&[0x61, 0x62, 0x63], s.to_oer(size(3, 3)).unwrap().bytes());
+    assert_eq!(Err(oer::EncodeError::InvalidSize), s.to_oer(size(4, 4)));
+    let mut d = oer::Decoder::new(b"abc");
+    assert_eq!(Ok(s), IA5String::from_oer(&mut d, size(3, 3)));
+    assert_eq!(Err(oer::DecodeError::InvalidCharacter), oer::decode::<IA5String>(b"\x01\x80"));
+
+    let s = BMPString::new("é").unwrap();
+    assert_eq!(Ok(vec![0x02, 0x00, 0xe9]), oer::encode(&s));
+    assert_eq!(Err(oer::DecodeError::InvalidLength), oer::decode::<BMPString>(b"\x01\xe9"));
+    assert_eq!(Ok(vec![0x03, 0x68, 0xc3, 0xa9]), oer::encode(&"hé".to_string()));
+
+    let fixed = Some(Constraint::new(Some(2), Some(2)));
+    assert_eq!(&[0x01, 0x02], oer::encode_string(&[1, 2], fixed).unwrap().bytes());
+    let variable = Some(Constraint::new(Some(1), Some(2)));
+    assert_eq!(&[0x02, 0x01, 0x02], oer::encode_string(&[1, 2], variable).unwrap().bytes());
+    let mut d = oer::Decoder::new(b"\x03\x01\x02\x03");
+    assert_eq!(Err(oer::DecodeError::InvalidSize), d.decode_string(Some(Constraint::new(Some(1), Some(2)))));
+
+    let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+    let data = b"\x06\x2a\x86\x48\x86\xf7\x0d";
+    assert_eq!(Ok(data.to_vec()), oer::encode(&oid));
+    assert_eq!(Ok(oid), oer::decode::<ObjectIdentifier>(data));
+}
+
+#[test]
+fn bit_strings() {
+    let b = BitString::from_bits(&[true, false, true]);
+    assert_eq!(Ok(vec![0x02, 0x05, 0xa0]), oer::encode(&b));
+    assert_eq!(Ok(b.clone()), oer::decode::<BitString>(b"\x02\x05\xa0"));
+    assert_eq!(&[0xa0], b.to_oer(size(3, 3)).unwrap().bytes());
+    let mut d = oer::Decoder::new(b"\xa0");
+    assert_eq!(Ok(b), BitString::from_oer(&mut d, size(3, 3)));
+    assert_eq!(Ok(vec![0x01, 0x00]), oer::encode(&BitString::new()));
+
+    assert_eq!(Ok(BitString::from_bits(&[true, false, true])), oer::decode::<BitString>(b"\x02\x05\xa1"));
+    assert_eq!(Err(oer::DecodeError::NonZeroUnusedBits), oer::decode_canonical::<BitString>(b"\x02\x05\xa1"));
+    assert_eq!(Err(oer::DecodeError::InvalidUnusedBits), oer::decode::<BitString>(b"\x02\x08\xa0"));
+    assert_eq!(Err(oer::DecodeError::InvalidUnusedBits), oer::decode::<BitString>(b"\x01\x03"));
+
+    let mut flags = NamedBitString::<Flags>::from_bits(BitString::with_len(8));
+    flags.set(Flags::B, true);
+    assert_eq!(Ok(vec![0x02, 0x06, 0x40]), oer::encode(&flags));
+    assert_eq!(&[0x02, 0x04, 0x40], flags.to_oer(size(4, 8)).unwrap().bytes());
+    assert_eq!(Ok(flags.clone()), oer::decode_canonical::<NamedBitString<Flags>>(b"\x02\x06\x40"));
+    assert_eq!(Err(oer::DecodeError::TrailingZeroBits),
+               oer::decode_canonical::<NamedBitString<Flags>>(b"\x02\x04\x40"));
+    assert_eq!(Ok(flags), oer::decode::<NamedBitString<Flags>>(b"\x02\x04\x40"));
+}
+
+#[test]
+fn sequence_of() {
+    assert_eq!(Ok(vec![0x01, 0x02, 0x01, 0x02]), oer::encode(&vec![1u8, 2]));
+    assert_eq!(Ok(vec![0x01, 0x00]), oer::encode(&Vec::<bool>::new()));
+    let strings = vec![IA5String::new("a").unwrap(), IA5String::new("b").unwrap()];
+    let constraints = Constraints {
+        value: Some(Constraint::new(Some(1), Some(1))),
+        size: Some(Constraint::new(Some(1), Some(4))),
+    };
+    assert_eq!(&[0x01, 0x02, 0x61, 0x62], strings.to_oer(constraints).unwrap().bytes());
+    let mut d = oer::Decoder::new(b"\x01\x02ab");
+    assert_eq!(Ok(strings), Vec::<IA5String>::from_oer(&mut d, constraints));
+    let mut d = oer::Decoder::new(b"\x01\x05abcde");
+    assert_eq!(Err(oer::DecodeError::InvalidSize), Vec::<IA5String>::from_oer(&mut d, constraints));
+}
+
+#[test]
+fn choice_and_enumerated() {
+    assert_eq!(&[0x81], oer::encode_tag(Tag::context(1)).bytes());
+    assert_eq!(&[0xbf, 0x64], oer::encode_tag(Tag::context(100)).bytes());
+    assert_eq!(&[0x7f, 0x3f], oer::encode_tag(Tag::application(63)).bytes());
+    let mut d = oer::Decoder::new(b"\x81\xbf\x64\x7f\x3f\xbf\x3e");
+    assert_eq!(Ok(Tag::context(1)), d.decode_tag());
+    assert_eq!(Ok(Tag::context(100)), d.decode_tag());
+    assert_eq!(Ok(Tag::application(63)), d.decode_tag());
+    assert_eq!(Err(oer::DecodeError::MalformedTag), d.decode_tag());
+
+    assert_eq!(&[0x05], oer::encode_enumerated(5).bytes());
+    assert_eq!(&[0x82, 0x00, 0xc8], oer::encode_enumerated(200).bytes());
+    assert_eq!(&[0x81, 0xff], oer::encode_enumerated(-1).bytes());
+    let mut d = oer::Decoder::new(b"\x05\x82\x00\xc8\x81\xff\x81\x05");
+    assert_eq!(Ok(5), d.decode_enumerated());
+    assert_eq!(Ok(200), d.decode_enumerated());
+    assert_eq!(Ok(-1), d.decode_enumerated());
+    assert_eq!(Ok(5), d.decode_enumerated());
+    let mut d = oer::Decoder::new(b"\x81\x05");
+    d.set_canonical(true);
+    assert_eq!(Err(oer::DecodeError::NonMinimalInt), d.decode_enumerated());
+}
+
+#[test]
+fn open_types() {
+    assert_eq!(&[0x02, 0x00, 0x2b], oer::encode_open_type(&43i16.to_oer(UNCONSTRAINED).unwrap()).bytes());
+    let mut d = oer::Decoder::new(b"\x02\x00\x2b\x03\x00\x2b\x00");
+    assert_eq!(Ok(43), d.decode_open_type::<i16>(UNCONSTRAINED));
+    assert_eq!(Err(oer::DecodeError::TrailingData), d.decode_open_type::<i16>(UNCONSTRAINED));
+}
+
+#[test]
+fn sequence() {
+    let m = msg(258, None, 1, None);
+    assert_eq!(Ok(vec![0x00, 0x01, 0x02]), oer::encode(&m));
+    assert_eq!(Ok(m), oer::decode_canonical::<Msg>(b"\x00\x01\x02"));
+
+    let m = msg(258, Some("ab"), 2, Some(true));
+    let data = b"\xe0\x01\x02\x02ab\x02\x02\x07\x80\x01\xff";
+    assert_eq!(Ok(data.to_vec()), oer::encode(&m));
+    assert_eq!(Ok(m), oer::decode_canonical::<Msg>(data));
+
+    let explicit_default = b"\x20\x01\x02\x01";
+    assert_eq!(Ok(msg(258, None, 1, None)), oer::decode::<Msg>(explicit_default));
+    assert_eq!(Err(oer::DecodeError::DefaultValueEncoded), oer::decode_canonical::<Msg>(explicit_default));
+
+    assert_eq!(Ok(msg(258, None, 1, None)), oer::decode::<Msg>(b"\x01\x01\x02"));
+    assert_eq!(Err(oer::DecodeError::NonZeroPadding), oer::decode_canonical::<Msg>(b"\x01\x01\x02"));
+    assert_eq!(Err(oer::DecodeError::TrailingData), oer::decode::<Msg>(b"\x00\x01\x02\x00"));
+}
+
+#[test]
+fn unknown_extensions() {
+    // flag = TRUE and an unknown addition
+    let data = b"\x80\x01\x02\x02\x06\xc0\x01\xff\x01\x05";
+    let m = oer::decode::<Msg>(data).unwrap();
+    assert_eq!(msg(258, None, 1, Some(true)), m);
+    assert_eq!(Ok(b"\x80\x01\x02\x02\x07\x80\x01\xff".to_vec()), oer::encode(&m));
+
+    let mut d = oer::Decoder::new(data);
+    d.set_lossless(true);
+    let m = Msg::from_oer(&mut d, UNCONSTRAINED).unwrap();
+    assert!(!m.unknown.is_empty());
+    assert_eq!(Ok(data.to_vec()), oer::encode(&m));
+}
@@ -0,0 +1,69 @@
+extern crate asn1;
+use asn1::{ObjectIdentifier, OidError, RelativeOid};
+use asn1::aper::{self, APerElement, UNCONSTRAINED};
+
+#[test]
+fn parse_and_print() {
+    let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+    assert_eq!(&[1, 2, 840, 113549], oid.arcs());
+    assert_eq!("1.2.840.113549", oid.to_string());
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(Err(OidError::InvalidArc), "1..2".parse::<ObjectIdentifier>());
+    assert_eq!(Err(OidError::InvalidArc), "1.-2".parse::<ObjectIdentifier>());
+    assert_eq!(Err(OidError::InvalidArc), "".parse::<ObjectIdentifier>());
+    assert_eq!(Err(OidError::TooFewArcs), "1".parse::<ObjectIdentifier>());
+    assert_eq!(Err(OidError::InvalidRootArcs), "3.1".parse::<ObjectIdentifier>());
+    assert_eq!(Err(OidError::InvalidRootArcs), "1.40".parse::<ObjectIdentifier>());
+    assert!("2.999.3".parse::<ObjectIdentifier>().is_ok());
+}
+
+#[test]
+fn contents_octets() {
+    let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+    assert_eq!(vec![0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d], oid.to_contents_octets());
+
+    let oid: ObjectIdentifier = "2.999.3".parse().unwrap();
+    assert_eq!(vec![0x88, 0x37, 0x03], oid.to_contents_octets());
+    assert_eq!(Ok(oid), ObjectIdentifier::from_contents_octets(&[0x88, 0x37, 0x03]));
+}
+
+#[test]
+fn malformed_contents() {
+    assert_eq!(Err(OidError::MalformedContents), ObjectIdentifier::from_contents_octets(&[0x2a, 0x86]));
+    assert_eq!(Err(OidError::MalformedContents), ObjectIdentifier::from_contents_octets(&[0x2a, 0x80, 0x01]));
+    assert_eq!(Err(OidError::TooFewArcs), ObjectIdentifier::from_contents_octets(&[]));
+}
+
+#[test]
+fn encode_decode() {
+    let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+    let target: Vec<u8> = vec![0x06, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d];
+    let enc = oid.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(oid, ObjectIdentifier::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let data = b"\x02\x2a\x86";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(aper::DecodeError::InvalidOid, ObjectIdentifier::from_aper(&mut d, UNCONSTRAINED).unwrap_err());
+}
+
+#[test]
+fn relative_oid() {
+    let rel: RelativeOid = "8571.3.2".parse().unwrap();
+    assert_eq!(vec![0xc2, 0x7b, 0x03, 0x02], rel.to_contents_octets());
+
+    let enc = rel.to_aper(UNCONSTRAINED).unwrap();
+    let target: Vec<u8> = vec![0x04, 0xc2, 0x7b, 0x03, 0x02];
+    assert_eq!(target, *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(rel, RelativeOid::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let base: ObjectIdentifier = "2.16.840".parse().unwrap();
+    assert_eq!("2.16.840.8571.3.2", rel.resolve(&base).to_string());
+    assert_eq!(Err(OidError::TooFewArcs), RelativeOid::new(&[]));
+}
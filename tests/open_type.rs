@@ -0,0 +1,67 @@
+extern crate asn1;
+use asn1::aper::{self, APerElement, Encoding, UNCONSTRAINED, encode_open_type};
+
+#[test]
+fn encode_padded() {
+    let enc = encode_open_type(&true.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x01, 0x80], *enc.bytes());
+
+    let enc = encode_open_type(&43_i16.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x02, 0x80, 0x2b], *enc.bytes());
+}
+
+#[test]
+fn encode_empty() {
+    // NULL has an empty encoding, which becomes a single 0 octet
+    let enc = encode_open_type(&().to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x01, 0x00], *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(Ok(()), d.decode_open_type::<()>(UNCONSTRAINED));
+}
+
+#[test]
+fn nested() {
+    // A field before and after the open type
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&encode_open_type(&43_i16.to_aper(UNCONSTRAINED).unwrap()).unwrap()).unwrap();
+    enc.append(&true.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0x02, 0x80, 0x2b, 0x80], *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(Ok(43), d.decode_open_type::<i16>(UNCONSTRAINED));
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn raw_bytes() {
+    let data = b"\x02\x80\x2b\x80";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Ok(vec![0x80, 0x2b]), d.decode_open_type_bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn bounded_sub_decoder() {
+    // The value is one octet long, so decoding an i16 from it runs out of bits
+    let data = b"\x01\x80\x2b";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::NotEnoughBits), d.decode_open_type::<i16>(UNCONSTRAINED));
+
+    // Unused trailing octets are skipped
+    let data = b"\x03\x80\x2b\xff\x80";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Ok(43), d.decode_open_type::<i16>(UNCONSTRAINED));
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn fragmented() {
+    let inner = Encoding::with_bytes(vec![0x5a; 20000]);
+    let enc = encode_open_type(&inner).unwrap();
+    assert_eq!(0xc1, enc.bytes()[0]);
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(Ok(vec![0x5a; 20000]), d.decode_open_type_bytes());
+}
@@ -0,0 +1,86 @@
+extern crate asn1;
+use asn1::real::{self, RealError};
+use asn1::aper::{self, APerElement, UNCONSTRAINED};
+
+fn encode(x: f64) -> Vec<u8> {
+    x.to_aper(UNCONSTRAINED).unwrap().bytes().clone()
+}
+
+fn decode(data: &[u8]) -> Result<f64, aper::DecodeError> {
+    let mut d = aper::Decoder::new(data);
+    f64::from_aper(&mut d, UNCONSTRAINED)
+}
+
+#[test]
+fn canonical_contents() {
+    assert_eq!(Vec::<u8>::new(), real::to_contents_octets(0.));
+    assert_eq!(vec![0x80, 0x00, 0x01], real::to_contents_octets(1.));
+    assert_eq!(vec![0x80, 0xff, 0x01], real::to_contents_octets(0.5));
+    assert_eq!(vec![0xc0, 0x00, 0x03], real::to_contents_octets(-3.));
+    assert_eq!(vec![0x80, 0x01, 0x05], real::to_contents_octets(10.));
+    assert_eq!(vec![0x81, 0xfb, 0xce, 0x01], real::to_contents_octets(5e-324));
+}
+
+#[test]
+fn special_values() {
+    assert_eq!(vec![0x00], encode(0.));
+    assert_eq!(vec![0x01, 0x43], encode(-0.));
+    assert_eq!(vec![0x01, 0x40], encode(f64::INFINITY));
+    assert_eq!(vec![0x01, 0x41], encode(f64::NEG_INFINITY));
+    assert_eq!(vec![0x01, 0x42], encode(f64::NAN));
+
+    assert_eq!(Ok(0.), decode(&[0x00]));
+    assert!(decode(&[0x01, 0x43]).unwrap().is_sign_negative());
+    assert_eq!(Ok(f64::INFINITY), decode(&[0x01, 0x40]));
+    assert_eq!(Ok(f64::NEG_INFINITY), decode(&[0x01, 0x41]));
+    assert!(decode(&[0x01, 0x42]).unwrap().is_nan());
+}
+
+#[test]
+fn encode_decode() {
+    assert_eq!(vec![0x03, 0x80, 0x00, 0x01], encode(1.));
+    for &x in &[1., -1., 0.1, 3.25, -1.234e-300, 6.02214076e23, f64::MAX, f64::MIN_POSITIVE, 5e-324] {
+        let bytes = encode(x);
+        assert_eq!(Ok(x), decode(&bytes));
+        assert_eq!(Ok(x), real::from_contents_octets(&bytes[1..]));
+    }
+}
+
+#[test]
+fn other_bases() {
+    // 1 * 16^1
+    assert_eq!(Ok(16.), real::from_contents_octets(&[0xa0, 0x01, 0x01]));
+    // 1 * 2^1 * 16^1
+    assert_eq!(Ok(32.), real::from_contents_octets(&[0xa4, 0x01, 0x01]));
+    // -3 * 8^-1
+    assert_eq!(Ok(-0.375), real::from_contents_octets(&[0xd0, 0xff, 0x03]));
+    // Even mantissa and a long-form exponent
+    assert_eq!(Ok(10.), real::from_contents_octets(&[0x83, 0x01, 0x00, 0x0a]));
+    // Out of range values saturate
+    assert_eq!(Ok(f64::INFINITY), real::from_contents_octets(&[0x81, 0x7f, 0xff, 0x01]));
+    assert_eq!(Ok(0.), real::from_contents_octets(&[0x81, 0x80, 0x00, 0x01]));
+}
+
+#[test]
+fn decimal() {
+    assert_eq!(Ok(-42.), real::from_contents_octets(b"\x01 -42"));
+    assert_eq!(Ok(3.25), real::from_contents_octets(b"\x023,25"));
+    assert_eq!(Ok(0.5), real::from_contents_octets(b"\x02.5"));
+    assert_eq!(Ok(150.), real::from_contents_octets(b"\x031.5E2"));
+    assert_eq!(Ok(-0.0015), real::from_contents_octets(b"\x03-1,5E-3"));
+
+    assert_eq!(Err(RealError::InvalidDecimal), real::from_contents_octets(b"\x011.5"));
+    assert_eq!(Err(RealError::InvalidDecimal), real::from_contents_octets(b"\x0215"));
+    assert_eq!(Err(RealError::InvalidDecimal), real::from_contents_octets(b"\x0315"));
+    assert_eq!(Err(RealError::InvalidDecimal), real::from_contents_octets(b"\x01--1"));
+    assert_eq!(Err(RealError::MalformedContents), real::from_contents_octets(b"\x041"));
+}
+
+#[test]
+fn malformed() {
+    assert_eq!(Err(RealError::MalformedContents), real::from_contents_octets(&[0x80]));
+    assert_eq!(Err(RealError::MalformedContents), real::from_contents_octets(&[0xb0, 0x00, 0x01]));
+    assert_eq!(Err(RealError::MalformedContents), real::from_contents_octets(&[0x40, 0x00]));
+    assert_eq!(Err(RealError::MalformedContents), real::from_contents_octets(&[0x44]));
+    assert_eq!(Err(aper::DecodeError::InvalidReal), decode(&[0x01, 0x80]));
+}
@@ -0,0 +1,199 @@
+extern crate asn1;
+use asn1::aper::Constraint;
+use asn1::notation::{self, ElementSet, ElementSetSpecs, Module, Reference, ResolveError, Resolver, Type,
+                     TypeKind, Value};
+
+const CONSTANTS: &str = r#"
+NGAP-Constants DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    maxProtocolIEs      INTEGER ::= 65535
+    maxnoofCells        INTEGER ::= 16384
+    maxnoofPDUSessions  INTEGER ::= 256
+    id-Cause            INTEGER ::= 15
+END
+"#;
+
+const CONTAINERS: &str = r#"
+NGAP-Containers DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+IMPORTS maxProtocolIEs FROM NGAP-Constants;
+
+NGAP-PROTOCOL-IES ::= CLASS {
+    &id         INTEGER (0..65535) UNIQUE,
+    &Value
+} WITH SYNTAX { ID &id TYPE &Value }
+
+ProtocolIE-Container {NGAP-PROTOCOL-IES : IEsSetParam} ::=
+    SEQUENCE (SIZE (0..maxProtocolIEs)) OF ProtocolIE-Field {{IEsSetParam}}
+
+ProtocolIE-Field {NGAP-PROTOCOL-IES : IEsSetParam} ::= SEQUENCE {
+    id      NGAP-PROTOCOL-IES.&id       ({IEsSetParam}),
+    value   NGAP-PROTOCOL-IES.&Value    ({IEsSetParam}{@id})
+}
+
+BoundedList {INTEGER : ub, Element} ::= SEQUENCE (SIZE (1..ub)) OF Element
+ShortList {Element} ::= BoundedList {4, Element}
+END
+"#;
+
+const IES: &str = r#"
+NGAP-IEs DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+IMPORTS maxnoofCells, maxnoofPDUSessions FROM NGAP-Constants
+        BoundedList{}, ShortList{} FROM NGAP-Containers;
+
+CellID ::= INTEGER (0..maxnoofCells)
+CellList ::= BoundedList {maxnoofCells, CellID}
+ShortCellList ::= ShortList {CellID}
+BadList ::= BoundedList {maxnoofCells}
+END
+"#;
+
+const CONTENTS: &str = r#"
+NGAP-PDU-Contents DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+IMPORTS id-Cause FROM NGAP-Constants
+        maxnoofPDUSessions, CellID FROM NGAP-IEs
+        ProtocolIE-Container{}, NGAP-PROTOCOL-IES FROM NGAP-Containers;
+
+SetupRequest ::= SEQUENCE {
+    protocolIEs     ProtocolIE-Container { {SetupRequestIEs} },
+    ...
+}
+
+SetupRequestIEs NGAP-PROTOCOL-IES ::= {
+    { ID id-Cause TYPE CellID },
+    ...
+}
+END
+"#;
+
+fn modules() -> Vec<Module> {
+    [CONSTANTS, CONTAINERS, IES, CONTENTS].iter().flat_map(|text| notation::parse(text).unwrap()).collect()
+}
+
+fn qualified(module: &str, name: &str) -> Reference {
+    Reference {
+        module: Some(module.to_string()),
+        name: name.to_string(),
+    }
+}
+
+fn resolve(modules: &[Module], module: &str, name: &str) -> Result<Type, ResolveError> {
+    modules.resolve_type(&qualified(module, name), &[])
+}
+
+#[test]
+fn value_references() {
+    let modules = modules();
+
+    // Through the imports of NGAP-IEs
+    assert_eq!(modules.resolve_value(&qualified("NGAP-IEs", "maxnoofCells")), Ok(Value::Integer(16384)));
+    assert_eq!(modules.resolve_value(&Reference::new("maxnoofPDUSessions")), Ok(Value::Integer(256)));
+    assert_eq!(modules.resolve_value(&qualified("NGAP-IEs", "maxnoofNothing")),
+               Err(ResolveError::UnknownReference("NGAP-IEs.maxnoofNothing".to_string())));
+
+    // References in what's resolved are qualified with where they're defined
+    let ty = resolve(&modules, "NGAP-IEs", "CellID").unwrap();
+    let per = notation::per_constraints(&ty, &modules[..]).unwrap();
+    assert_eq!(per.value, Some(Constraint::new(Some(0), Some(16384))));
+
+    // A module on its own only knows its own assignments
+    let ty = modules[2].resolve_type(&Reference::new("CellID"), &[]).unwrap();
+    assert_eq!(notation::per_constraints(&ty, &modules[2]),
+               Err(ResolveError::UnknownReference("NGAP-Constants.maxnoofCells".to_string())));
+}
+
+#[test]
+fn parameterized_types() {
+    let modules = modules();
+
+    let ty = resolve(&modules, "NGAP-IEs", "CellList").unwrap();
+    let per = notation::per_constraints(&ty, &modules[..]).unwrap();
+    assert_eq!(per.size, Some(Constraint::new(Some(1), Some(16384))));
+
+    // The actual parameters of an instance are substituted for the dummy ones
+    let list = match ty.kind {
+        TypeKind::Reference { ref reference, ref arguments } => {
+            assert_eq!(*reference, qualified("NGAP-Containers", "BoundedList"));
+            modules.resolve_type(reference, arguments).unwrap()
+        }
+        _ => panic!("{:?}", ty),
+    };
+    match list.kind {
+        TypeKind::SequenceOf { ref element, .. } => {
+            let cell_id = qualified("NGAP-IEs", "CellID");
+            assert!(matches!(element.kind, TypeKind::Reference { ref reference, .. } if *reference == cell_id))
+        }
+        _ => panic!("{:?}", list),
+    }
+
+    // A parameterized type whose actual parameters are dummy parameters
+    let ty = resolve(&modules, "NGAP-IEs", "ShortCellList").unwrap();
+    let per = notation::per_constraints(&ty, &modules[..]).unwrap();
+    assert_eq!(per.size, Some(Constraint::new(Some(1), Some(4))));
+
+    let ty = resolve(&modules, "NGAP-IEs", "BadList").unwrap();
+    assert_eq!(notation::per_constraints(&ty, &modules[..]),
+               Err(ResolveError::InvalidArguments("NGAP-Containers.BoundedList".to_string())));
+    assert_eq!(resolve(&modules, "NGAP-Containers", "BoundedList"),
+               Err(ResolveError::InvalidArguments("NGAP-Containers.BoundedList".to_string())));
+}
+
+#[test]
+fn information_object_sets() {
+    let modules = modules();
+
+    let request = resolve(&modules, "NGAP-PDU-Contents", "SetupRequest").unwrap();
+    let container = match request.kind {
+        TypeKind::Sequence(ref components) => components.root[0].ty.clone(),
+        _ => panic!("{:?}", request),
+    };
+    let per = notation::per_constraints(&container, &modules[..]).unwrap();
+    assert_eq!(per.size, Some(Constraint::new(Some(0), Some(65535))));
+
+    let (reference, arguments) = match container.kind {
+        TypeKind::Reference { reference, arguments } => (reference, arguments),
+        _ => panic!("{:?}", container),
+    };
+    let container = modules.resolve_type(&reference, &arguments).unwrap();
+    let field = match container.kind {
+        TypeKind::SequenceOf { ref element, .. } => {
+            match element.kind {
+                TypeKind::Reference { ref reference, ref arguments } => {
+                    modules.resolve_type(reference, arguments).unwrap()
+                }
+                _ => panic!("{:?}", element),
+            }
+        }
+        _ => panic!("{:?}", container),
+    };
+
+    // The table constraint of the field refers to the actual object set
+    let id = match field.kind {
+        TypeKind::Sequence(ref components) => components.root[0].ty.clone(),
+        _ => panic!("{:?}", field),
+    };
+    assert!(matches!(id.kind,
+                     TypeKind::ClassField { ref class, .. } if *class == qualified("NGAP-Containers",
+                                                                                   "NGAP-PROTOCOL-IES")));
+    let object_set = ElementSet::ContainedSubtype(Type::new(TypeKind::Reference {
+                                                                reference: qualified("NGAP-PDU-Contents",
+                                                                                     "SetupRequestIEs"),
+                                                                arguments: Vec::new(),
+                                                            }));
+    let braced = ElementSetSpecs {
+        root: Some(object_set),
+        extensible: false,
+        additions: None,
+    };
+    assert_eq!(id.constraints,
+               vec![notation::Constraint::Subtype(ElementSetSpecs {
+                                                      root: Some(ElementSet::Braced(Box::new(braced))),
+                                                      extensible: false,
+                                                      additions: None,
+                                                  })]);
+
+    // The objects of the set are qualified too
+    let set = resolve(&modules, "NGAP-PDU-Contents", "SetupRequestIEs").unwrap();
+    let text = format!("{:?}", set);
+    assert!(text.contains("module: Some(\"NGAP-Constants\"), name: \"id-Cause\""), "{}", text);
+    assert!(text.contains("module: Some(\"NGAP-IEs\"), name: \"CellID\""), "{}", text);
+    assert!(matches!(set.kind, TypeKind::Reference { .. }));
+}
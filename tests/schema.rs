@@ -0,0 +1,244 @@
+extern crate asn1;
+use asn1::{BitString, ObjectIdentifier, VisibleString};
+use asn1::aper::{self, APerElement, Constraint, DecodeError, EncodeError, UNCONSTRAINED};
+use asn1::schema::{self, Component, Type, Value};
+
+fn constrained_int(lb: i64, ub: i64) -> Type {
+    Type::Integer {
+        value: Some(Constraint::new(Some(lb), Some(ub))),
+        extensible: false,
+    }
+}
+
+fn round_trip(value: Value, ty: &Type, expected: &[u8]) {
+    let enc = schema::encode(&value, ty).unwrap();
+    assert_eq!(expected, &enc.bytes()[..]);
+    assert_eq!(Ok(value), schema::decode(&mut aper::Decoder::new(expected), ty));
+}
+
+#[test]
+fn sequence() {
+    // Msg ::= SEQUENCE { a INTEGER (0..7), b VisibleString (SIZE (4)), c INTEGER (0..1000), d BOOLEAN OPTIONAL }
+    let ty = Type::Sequence {
+        root: vec![Component::new(constrained_int(0, 7)),
+                   Component::new(Type::CharString {
+                       alphabet: VisibleString::alphabet(),
+                       size: Some(Constraint::new(Some(4), Some(4))),
+                       extensible: false,
+                   }),
+                   Component::new(constrained_int(0, 1000)),
+                   Component::optional(Type::Boolean)],
+        extensions: None,
+    };
+    let value = Value::Sequence {
+        root: vec![Some(Value::Integer(3)), Some(Value::String("ab12".to_string())), Some(Value::Integer(500)), None],
+        extensions: vec![],
+    };
+    round_trip(value, &ty, &[0x30, 0x61, 0x62, 0x31, 0x32, 0x01, 0xf4]);
+
+    // A mandatory component can't be absent
+    let value = Value::Sequence {
+        root: vec![Some(Value::Integer(3)), None, Some(Value::Integer(500)), None],
+        extensions: vec![],
+    };
+    assert_eq!(Err(EncodeError::InvalidValue), schema::encode(&value, &ty).map(|_| ()));
+}
+
+#[test]
+fn extensions() {
+    // Foo ::= SEQUENCE { a INTEGER (0..7), ..., b BOOLEAN }
+    let ty = Type::Sequence {
+        root: vec![Component::new(constrained_int(0, 7))],
+        extensions: Some(vec![Type::Boolean]),
+    };
+    let value = Value::Sequence {
+        root: vec![Some(Value::Integer(3))],
+        extensions: vec![Some(Value::Boolean(true))],
+    };
+    round_trip(value, &ty, b"\xb0\x10\x01\x80");
+
+    // An addition the type doesn't know about is kept as its octets
+    let ty = Type::Sequence {
+        root: vec![Component::new(constrained_int(0, 7))],
+        extensions: Some(vec![]),
+    };
+    let value = Value::Sequence {
+        root: vec![Some(Value::Integer(3))],
+        extensions: vec![Some(Value::Unknown(vec![0x80]))],
+    };
+    round_trip(value, &ty, b"\xb0\x10\x01\x80");
+}
+
+#[test]
+fn choice() {
+    // Foo ::= CHOICE { a BOOLEAN, b INTEGER (0..2), ..., c NULL }
+    let ty = Type::Choice {
+        root: vec![Type::Boolean, constrained_int(0, 2)],
+        extensions: Some(vec![Type::Null]),
+    };
+    let value = Value::Choice {
+        index: 1,
+        value: Box::new(Value::Integer(2)),
+    };
+    round_trip(value, &ty, &[0x60]);
+
+    // The extension alternative is an open type, holding a single 0 octet for NULL
+    let value = Value::Choice {
+        index: 2,
+        value: Box::new(Value::Null),
+    };
+    round_trip(value, &ty, &[0x80, 0x01, 0x00]);
+
+    let value = Value::Choice {
+        index: 3,
+        value: Box::new(Value::Null),
+    };
+    assert_eq!(Err(EncodeError::InvalidValue), schema::encode(&value, &ty).map(|_| ()));
+    assert_eq!(Ok(Value::Choice {
+                      index: 3,
+                      value: Box::new(Value::Unknown(vec![0x00])),
+                  }),
+               schema::decode(&mut aper::Decoder::new(b"\x81\x01\x00"), &ty));
+}
+
+#[test]
+fn same_as_typed_encoding() {
+    // SEQUENCE { BOOLEAN, INTEGER (0..1000) } encodes the same through the schema as with encode_int
+    let ty = Type::Sequence {
+        root: vec![Component::new(Type::Boolean), Component::new(constrained_int(0, 1000))],
+        extensions: None,
+    };
+    let value = Value::Sequence {
+        root: vec![Some(Value::Boolean(true)), Some(Value::Integer(500))],
+        extensions: vec![],
+    };
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&aper::encode_int(500, Some(0), Some(1000)).unwrap()).unwrap();
+    assert_eq!(&[0x80, 0x01, 0xf4], &enc.bytes()[..]);
+    round_trip(value, &ty, enc.bytes());
+
+    // Ranges larger than 64K have their number of octets in a bit-field
+    let ty = Type::Sequence {
+        root: vec![Component::new(Type::Boolean), Component::new(constrained_int(0, u32::MAX as i64))],
+        extensions: None,
+    };
+    let value = Value::Sequence {
+        root: vec![Some(Value::Boolean(true)), Some(Value::Integer(70000))],
+        extensions: vec![],
+    };
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&70000u32.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(&[0xc0, 0x01, 0x11, 0x70], &enc.bytes()[..]);
+    round_trip(value, &ty, enc.bytes());
+
+    // SEQUENCE OF BOOLEAN after a BOOLEAN, with an unconstrained length
+    let ty = Type::Sequence {
+        root: vec![Component::new(Type::Boolean),
+                   Component::new(Type::SequenceOf {
+                       element: Box::new(Type::Boolean),
+                       size: None,
+                       extensible: false,
+                   })],
+        extensions: None,
+    };
+    let value = Value::Sequence {
+        root: vec![Some(Value::Boolean(true)), Some(Value::SequenceOf(vec![Value::Boolean(true)]))],
+        extensions: vec![],
+    };
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&aper::encode_length(1).unwrap()).unwrap();
+    enc.append(&true.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(&[0x80, 0x01, 0x80], &enc.bytes()[..]);
+    round_trip(value, &ty, enc.bytes());
+}
+
+#[test]
+fn integers_and_enumerations() {
+    // INTEGER (0..7, ...) outside of its root is encoded as an unconstrained whole number
+    let ty = Type::Integer {
+        value: Some(Constraint::new(Some(0), Some(7))),
+        extensible: true,
+    };
+    round_trip(Value::Integer(5), &ty, &[0x50]);
+    round_trip(Value::Integer(10), &ty, &[0x80, 0x01, 0x0a]);
+    assert_eq!(Err(EncodeError::InvalidValue),
+               schema::encode(&Value::Integer(10), &constrained_int(0, 7)).map(|_| ()));
+
+    // INTEGER (0..MAX) and INTEGER
+    let ty = Type::Integer {
+        value: Some(Constraint::new(Some(0), None)),
+        extensible: false,
+    };
+    round_trip(Value::Integer(256), &ty, &[0x02, 0x01, 0x00]);
+    let ty = Type::Integer {
+        value: None,
+        extensible: false,
+    };
+    round_trip(Value::Integer(-128), &ty, &[0x01, 0x80]);
+    round_trip(Value::Integer(-129), &ty, &[0x02, 0xff, 0x7f]);
+    assert_eq!(Err(DecodeError::MalformedInt), schema::decode(&mut aper::Decoder::new(b"\x00"), &ty));
+
+    // ENUMERATED { a, b, c, ..., d, e }
+    let ty = Type::Enumerated {
+        root: 3,
+        extensible: true,
+    };
+    round_trip(Value::Enumerated(2), &ty, &[0x40]);
+    round_trip(Value::Enumerated(4), &ty, &[0x81]);
+}
+
+#[test]
+fn strings() {
+    // BIT STRING (SIZE (1..8)): the bits are aligned after the length
+    let ty = Type::BitString {
+        size: Some(Constraint::new(Some(1), Some(8))),
+        extensible: false,
+    };
+    round_trip(Value::BitString(BitString::from_bits(&[true, false, true])), &ty, &[0x40, 0xa0]);
+    assert_eq!(Err(EncodeError::InvalidSize),
+               schema::encode(&Value::BitString(BitString::new()), &ty).map(|_| ()));
+
+    // OCTET STRING (SIZE (2)) is not aligned
+    let ty = Type::Sequence {
+        root: vec![Component::new(Type::Boolean),
+                   Component::new(Type::OctetString {
+                       size: Some(Constraint::new(Some(2), Some(2))),
+                       extensible: false,
+                   })],
+        extensions: None,
+    };
+    let value = Value::Sequence {
+        root: vec![Some(Value::Boolean(true)), Some(Value::OctetString(vec![0x12, 0x34]))],
+        extensions: vec![],
+    };
+    round_trip(value, &ty, &[0x89, 0x1a, 0x00]);
+
+    // The types encoded with an unconstrained length agree with the crate's own encoders
+    let s = "h\u{e9}llo".to_string();
+    round_trip(Value::String(s.clone()), &Type::Utf8String, s.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let oid = ObjectIdentifier::new(&[1, 2, 840, 113549]).unwrap();
+    round_trip(Value::ObjectIdentifier(oid.clone()),
+               &Type::ObjectIdentifier,
+               oid.to_aper(UNCONSTRAINED).unwrap().bytes());
+    round_trip(Value::Real(0.5), &Type::Real, 0.5f64.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn sequence_of() {
+    // SEQUENCE OF BOOLEAN, including a count in fragments
+    let ty = Type::SequenceOf {
+        element: Box::new(Type::Boolean),
+        size: None,
+        extensible: false,
+    };
+    let value = Value::SequenceOf(vec![Value::Boolean(true), Value::Boolean(false), Value::Boolean(true)]);
+    round_trip(value, &ty, &[0x03, 0xa0]);
+
+    let value = Value::SequenceOf(vec![Value::Boolean(false); 20000]);
+    let enc = schema::encode(&value, &ty).unwrap();
+    assert_eq!(&[0xc1], &enc.bytes()[..1]);
+    assert_eq!(Ok(value), schema::decode(&mut aper::Decoder::new(enc.bytes()), &ty));
+
+    assert_eq!(Err(EncodeError::InvalidValue),
+               schema::encode(&Value::SequenceOf(vec![Value::Null]), &ty).map(|_| ()));
+}
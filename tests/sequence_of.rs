@@ -1,3 +1,6 @@
+#![allow(clippy::bool_assert_comparison, clippy::legacy_numeric_constants, clippy::needless_range_loop,
+         clippy::unnecessary_cast, clippy::useless_vec)]
+
 extern crate asn1;
 use asn1::BitString;
 use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
@@ -50,15 +53,15 @@ fn decode_sequence_of_u16() {
 fn encode_sequence_of_i32() {
     let v: Vec<i32> = vec![i32::MIN, i32::MIN + 1, i32::MIN + 2];
     let target: Vec<u8> = vec![0x3,
-                               0x04, 0x00, 0x00, 0x00, 0x00,
-                               0x04, 0x00, 0x00, 0x00, 0x01,
-                               0x04, 0x00, 0x00, 0x00, 0x02];
+                               0x00, 0x00,
+                               0x00, 0x01,
+                               0x00, 0x02];
     assert_eq!(target, *v.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
 
 #[test]
 fn decode_sequence_of_i32() {
-    let data = b"\x03\x04\x00\x00\x00\x00\x04\x00\x00\x00\x01\x04\x00\x00\x00\x02";
+    let data = b"\x03\x00\x00\x00\x01\x00\x02";
     let mut target = Vec::new();
     use std::i32;
     for i in 0..3 {
@@ -81,7 +84,7 @@ fn decode_sequence_of_short_bit_string() {
     let mut d = aper::Decoder::new(data);
     let v = Vec::<BitString>::from_aper(&mut d, Constraints {
         // here the "value" constraint is a constraint on the size of each element
-        value: Some(Constraint::new(None, Some(4))), 
+        value: Some(Constraint::new(Some(4), Some(4))), 
         // "size" behaves normally 
         size: Some(Constraint::new(None, Some(2))),
     }).unwrap();
@@ -89,7 +92,7 @@ fn decode_sequence_of_short_bit_string() {
 
     for i in 0..v.len() {
         for j in 0..4 {
-            if j == 1 || j == 2 || j == 3 {
+            if j == 0 || j == 1 || j == 2 {
                 assert_eq!(true, v[i].is_set(j));
             } else {
                 assert_eq!(false, v[i].is_set(j));
@@ -104,15 +107,15 @@ fn decode_sequence_of_long_bit_string() {
     let mut d = aper::Decoder::new(data);
     let v = Vec::<BitString>::from_aper(&mut d, Constraints {
         // here the "value" constraint is a constraint on the size of each element
-        value: Some(Constraint::new(None, Some(24))), 
+        value: Some(Constraint::new(Some(24), Some(24))), 
         // "size" behaves normally 
         size: Some(Constraint::new(None, Some(2))),
     }).unwrap();
     assert_eq!(v.len(), 2);
 
     for i in 0..v.len() {
-        for j in 0..20 {
-            if j == 5 || j == 6 || j == 7 {
+        for j in 0..24 {
+            if j == 16 || j == 17 || j == 18 {
                 assert_eq!(true, v[i].is_set(j));
             } else {
                 assert_eq!(false, v[i].is_set(j));
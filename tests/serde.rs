@@ -0,0 +1,75 @@
+#![cfg(feature = "serde")]
+#[macro_use]
+extern crate asn1;
+extern crate serde_json;
+use asn1::{BitString, Criticality, Date, GeneralizedTime, IA5String, NamedBitString, ObjectIdentifier,
+           UnknownAlternative};
+use asn1::aper::serde as aper_serde;
+
+named_bits! {
+    pub enum Flags {
+        A = 0,
+        B = 1,
+    }
+}
+
+#[test]
+fn bit_strings() {
+    let b = BitString::from_bits(&[true, false, true]);
+    assert_eq!("\"101\"", serde_json::to_string(&b).unwrap());
+    assert_eq!(b, serde_json::from_str::<BitString>("\"101\"").unwrap());
+    assert!(serde_json::from_str::<BitString>("\"102\"").is_err());
+
+    // The length and the octets in binary formats
+    let enc = aper_serde::encode(&b).unwrap();
+    assert_eq!(Ok(b.clone()), aper_serde::decode::<BitString>(enc.bytes()));
+
+    let mut flags = NamedBitString::<Flags>::new();
+    flags.set(Flags::B, true);
+    assert_eq!("\"01\"", serde_json::to_string(&flags).unwrap());
+    assert_eq!(flags, serde_json::from_str::<NamedBitString<Flags>>("\"01\"").unwrap());
+}
+
+#[test]
+fn strings_and_times() {
+    let s = IA5String::new("abc").unwrap();
+    assert_eq!("\"abc\"", serde_json::to_string(&s).unwrap());
+    assert_eq!(s, serde_json::from_str::<IA5String>("\"abc\"").unwrap());
+    assert!(serde_json::from_str::<IA5String>("\"\u{e9}\"").is_err());
+
+    let d = Date::new(2024, 2, 29).unwrap();
+    assert_eq!("\"2024-02-29\"", serde_json::to_string(&d).unwrap());
+    assert_eq!(d, serde_json::from_str::<Date>("\"2024-02-29\"").unwrap());
+    assert!(serde_json::from_str::<Date>("\"2023-02-29\"").is_err());
+
+    let t = GeneralizedTime::new("20240229123000.5Z").unwrap();
+    assert_eq!("\"20240229123000.5Z\"", serde_json::to_string(&t).unwrap());
+    let enc = aper_serde::encode(&t).unwrap();
+    assert_eq!(Ok(t), aper_serde::decode::<GeneralizedTime>(enc.bytes()));
+}
+
+#[test]
+fn oids() {
+    let oid = ObjectIdentifier::new(&[1, 2, 840, 113549]).unwrap();
+    assert_eq!("\"1.2.840.113549\"", serde_json::to_string(&oid).unwrap());
+    assert_eq!(oid, serde_json::from_str::<ObjectIdentifier>("\"1.2.840.113549\"").unwrap());
+
+    // The arcs in binary formats
+    let enc = aper_serde::encode(&oid).unwrap();
+    assert_eq!(Ok(oid), aper_serde::decode::<ObjectIdentifier>(enc.bytes()));
+}
+
+#[test]
+fn object_sets_and_extensions() {
+    assert_eq!("\"ignore\"", serde_json::to_string(&Criticality::Ignore).unwrap());
+    assert_eq!(Criticality::Notify, serde_json::from_str::<Criticality>("\"notify\"").unwrap());
+    assert!(serde_json::from_str::<Criticality>("\"fatal\"").is_err());
+
+    let alt = UnknownAlternative {
+        index: 2,
+        value: vec![0x01, 0xab],
+    };
+    let json = serde_json::to_string(&alt).unwrap();
+    assert_eq!("{\"index\":2,\"value\":\"01ab\"}", json);
+    assert_eq!(alt, serde_json::from_str::<UnknownAlternative>(&json).unwrap());
+}
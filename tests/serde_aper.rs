@@ -0,0 +1,183 @@
+#![cfg(feature = "serde")]
+extern crate asn1;
+#[macro_use]
+extern crate serde_derive;
+use asn1::aper::serde::{self, Constrain, Constrained, Deserializer, Error};
+use asn1::aper::{encode_int, APerElement, Constraint, Constraints, DecodeError, EncodeError, Encoding, UNCONSTRAINED};
+
+struct Small;
+impl Constrain for Small {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: Some(Constraint::new(Some(0), Some(7))),
+        size: None,
+    };
+}
+
+struct OneToFour;
+impl Constrain for OneToFour {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(4))),
+    };
+}
+
+struct ThreeVariants;
+impl Constrain for ThreeVariants {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: Some(Constraint::new(Some(0), Some(2))),
+        size: None,
+    };
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Msg {
+    flag: bool,
+    id: u16,
+    count: i64,
+    name: String,
+    extra: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Limited {
+    a: Constrained<u8, Small>,
+    b: Constrained<Vec<bool>, OneToFour>,
+    c: Option<Constrained<String, OneToFour>>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Outer {
+    first: Option<bool>,
+    inner: Inner,
+    pair: (Option<bool>, bool),
+    last: Option<Constrained<u8, Small>>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Inner {
+    value: Option<bool>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Choice {
+    Empty,
+    Number(u8),
+    Pair { x: bool, y: bool },
+}
+
+#[test]
+fn structs() {
+    let msg = Msg {
+        flag: true,
+        id: 300,
+        count: -2,
+        name: "abc".to_string(),
+        extra: Some(9),
+    };
+    let enc = serde::encode(&msg).unwrap();
+
+    // The same as the hand-written encoding, with the presence bit of extra in the preamble
+    let mut expected = Encoding::new();
+    expected.append_uint(1, 1);
+    expected.append(&true.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    expected.append(&300u16.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    expected.append(&encode_int(-2, None, None).unwrap()).unwrap();
+    expected
+        .append(&"abc".to_string().to_aper(UNCONSTRAINED).unwrap())
+        .unwrap();
+    expected.append(&9u8.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(expected.bytes(), enc.bytes());
+
+    assert_eq!(Ok(msg), serde::decode::<Msg>(enc.bytes()));
+}
+
+#[test]
+fn preamble() {
+    let outer = Outer {
+        first: None,
+        inner: Inner { value: Some(true) },
+        pair: (Some(false), true),
+        last: Some(Constrained::new(5)),
+    };
+    let enc = serde::encode(&outer).unwrap();
+    // The presence bits of first and last, inner with its own preamble, the pair with an inline presence bit, and last
+    assert_eq!(&vec![0x7b, 0x40], enc.bytes());
+    assert_eq!(Ok(outer), serde::decode::<Outer>(enc.bytes()));
+
+    // The struct is first met inside the sequence
+    let items = vec![Inner { value: None }, Inner { value: Some(true) }];
+    let enc = serde::encode(&items).unwrap();
+    assert_eq!(&vec![0x02, 0x60], enc.bytes());
+    assert_eq!(Ok(items), serde::decode::<Vec<Inner>>(enc.bytes()));
+
+    // Values read one after the other
+    let mut deserializer = Deserializer::new(b"\xa0");
+    assert_eq!(Ok(Inner { value: Some(false) }), deserializer.decode::<Inner>());
+    assert_eq!(Ok(Inner { value: Some(false) }), deserializer.decode::<Inner>());
+}
+
+#[test]
+fn constraints() {
+    let limited = Limited {
+        a: Constrained::new(5),
+        b: Constrained::new(vec![true, false]),
+        c: None,
+    };
+    let enc = serde::encode(&limited).unwrap();
+    // The presence bit of c, 3 bits for a, 2 bits for the length of b, then its items
+    assert_eq!(&vec![0x56], enc.bytes());
+    assert_eq!(Ok(limited), serde::decode::<Limited>(enc.bytes()));
+
+    let limited = Limited {
+        a: Constrained::new(8),
+        b: Constrained::new(vec![true]),
+        c: None,
+    };
+    assert_eq!(
+        Err(Error::Encode(EncodeError::InvalidValue)),
+        serde::encode(&limited).map(|_| ())
+    );
+    let limited = Limited {
+        a: Constrained::new(0),
+        b: Constrained::new(vec![]),
+        c: Some(Constrained::new("abcde".to_string())),
+    };
+    assert_eq!(
+        Err(Error::Encode(EncodeError::InvalidSize)),
+        serde::encode(&limited).map(|_| ())
+    );
+}
+
+#[test]
+fn enums() {
+    let values = vec![
+        Constrained::<Choice, ThreeVariants>::new(Choice::Empty),
+        Constrained::new(Choice::Number(200)),
+        Constrained::new(Choice::Pair { x: true, y: false }),
+    ];
+    for value in values {
+        let enc = serde::encode(&value).unwrap();
+        assert_eq!(Ok(value), serde::decode(enc.bytes()));
+    }
+
+    let enc = serde::encode(&Constrained::<Choice, ThreeVariants>::new(Choice::Number(200))).unwrap();
+    assert_eq!(&vec![0x40, 0xc8], enc.bytes());
+    // The index of the variant is read in the range of the variants without a constraint
+    assert_eq!(Ok(Choice::Number(200)), serde::decode(enc.bytes()));
+    assert_eq!(
+        Err(Error::Encode(EncodeError::MissingValueConstraint)),
+        serde::encode(&Choice::Empty).map(|_| ())
+    );
+    assert_eq!(
+        Err(Error::Decode(DecodeError::MalformedInt)),
+        serde::decode::<Choice>(b"\xc0")
+    );
+}
+
+#[test]
+fn fragments() {
+    let items: Vec<u8> = (0..20000).map(|i| i as u8).collect();
+    let enc = serde::encode(&items).unwrap();
+    assert_eq!(0xc1, enc.bytes()[0]);
+    assert_eq!(Ok(items), serde::decode::<Vec<u8>>(enc.bytes()));
+}
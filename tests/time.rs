@@ -0,0 +1,213 @@
+extern crate asn1;
+use asn1::{Date, DateTime, Duration, GeneralizedTime, TimeError, TimeOfDay, UTCTime};
+use asn1::aper::{self, APerElement, UNCONSTRAINED};
+
+fn round_trip<T: APerElement + PartialEq + std::fmt::Debug>(x: &T, target: &[u8]) {
+    let bytes = x.to_aper(UNCONSTRAINED).unwrap().bytes().clone();
+    assert_eq!(target, &bytes[..]);
+    let mut d = aper::Decoder::new(&bytes);
+    assert_eq!(*x, T::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn date_strings() {
+    let date: Date = "2024-02-29".parse().unwrap();
+    assert_eq!((2024, 2, 29), (date.year(), date.month(), date.day()));
+    assert_eq!("2024-02-29", date.to_string());
+    assert_eq!("-0044-03-15", "-0044-03-15".parse::<Date>().unwrap().to_string());
+    assert_eq!("+12345-01-01", Date::new(12345, 1, 1).unwrap().to_string());
+
+    assert_eq!(Err(TimeError::InvalidValue), "2023-02-29".parse::<Date>());
+    assert_eq!(Err(TimeError::InvalidValue), "2024-13-01".parse::<Date>());
+    assert_eq!(Err(TimeError::InvalidFormat), "2024-2-29".parse::<Date>());
+    assert_eq!(Err(TimeError::InvalidFormat), "20240229".parse::<Date>());
+}
+
+#[test]
+fn time_of_day_strings() {
+    let time: TimeOfDay = "13:45:30".parse().unwrap();
+    assert_eq!((13, 45, 30), (time.hour(), time.minute(), time.second()));
+    assert_eq!("13:45:30", time.to_string());
+    assert!("23:59:60".parse::<TimeOfDay>().is_ok());
+    assert!("24:00:00".parse::<TimeOfDay>().is_ok());
+    assert_eq!(Err(TimeError::InvalidValue), "24:00:01".parse::<TimeOfDay>());
+    assert_eq!(Err(TimeError::InvalidFormat), "13:45".parse::<TimeOfDay>());
+
+    let dt: DateTime = "2024-02-29T13:45:30".parse().unwrap();
+    assert_eq!(time, dt.time());
+    assert_eq!("2024-02-29T13:45:30", dt.to_string());
+}
+
+#[test]
+fn date_encoding() {
+    // immediate year
+    round_trip(&Date::new(2010, 6, 15).unwrap(), &[0x15, 0x5c]);
+    // near-future year, octet-aligned
+    round_trip(&Date::new(2024, 2, 29).unwrap(), &[0x40, 0x03, 0x1e, 0x00]);
+    // near-past year
+    round_trip(&Date::new(1749, 1, 1).unwrap(), &[0x80, 0x00, 0x00, 0x00]);
+    // remainder, as an unconstrained integer
+    round_trip(&Date::new(1500, 1, 1).unwrap(), &[0xc0, 0x02, 0x05, 0xdc, 0x00, 0x00]);
+    round_trip(&Date::new(-44, 3, 15).unwrap(), &[0xc0, 0x01, 0xd4, 0x27, 0x00]);
+}
+
+#[test]
+fn time_encoding() {
+    round_trip(&"13:45:30".parse::<TimeOfDay>().unwrap(), &[0x6d, 0xaf, 0x00]);
+    round_trip(&"2010-06-15T13:45:30".parse::<DateTime>().unwrap(), &[0x15, 0x5c, 0xdb, 0x5e]);
+}
+
+#[test]
+fn invalid_date_encoding() {
+    // 2023-02-29
+    let data = [0x40, 0x02, 0x1e, 0x00];
+    let mut d = aper::Decoder::new(&data);
+    assert_eq!(Err(aper::DecodeError::InvalidTime), Date::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn duration_strings() {
+    let d: Duration = "P1Y2M10DT2H30M".parse().unwrap();
+    assert_eq!((Some(1), Some(2), None, Some(10)), (d.years(), d.months(), d.weeks(), d.days()));
+    assert_eq!((Some(2), Some(30), None), (d.hours(), d.minutes(), d.seconds()));
+    assert_eq!("P1Y2M10DT2H30M", d.to_string());
+
+    let d: Duration = "PT1,50S".parse().unwrap();
+    assert_eq!(Some((2, 50)), d.fraction());
+    assert_eq!("PT1.50S", d.to_string());
+    assert_eq!("P2W", "P2W".parse::<Duration>().unwrap().to_string());
+
+    assert_eq!(Err(TimeError::InvalidFormat), "P".parse::<Duration>());
+    assert_eq!(Err(TimeError::InvalidFormat), "PT".parse::<Duration>());
+    assert_eq!(Err(TimeError::InvalidFormat), "P1D2Y".parse::<Duration>());
+    assert_eq!(Err(TimeError::InvalidFormat), "P1.5DT2H".parse::<Duration>());
+    assert_eq!(Err(TimeError::InvalidFormat), "P1H".parse::<Duration>());
+}
+
+#[test]
+fn duration_encoding() {
+    let d: Duration = "P1Y2M10DT2H30M".parse().unwrap();
+    round_trip(&d, &[0xdc, 0x01, 0x01, 0x01, 0x02, 0x01, 0x0a, 0x01, 0x02, 0x01, 0x1e]);
+
+    let d: Duration = "PT0.5S".parse().unwrap();
+    round_trip(&d, &[0x03, 0x01, 0x00, 0x01, 0x00, 0x01, 0x05]);
+
+    // The number of digits is INTEGER (1..MAX), but the value of the fraction is INTEGER (0..MAX)
+    let d: Duration = "PT1.0S".parse().unwrap();
+    assert_eq!(Some((1, 0)), d.fraction());
+    assert_eq!("PT1.0S", d.to_string());
+    round_trip(&d, &[0x03, 0x01, 0x01, 0x01, 0x00, 0x01, 0x00]);
+}
+
+#[test]
+fn generalized_time() {
+    let t: GeneralizedTime = "20240229134530.25+0100".parse().unwrap();
+    assert_eq!("2024-02-29T13:45:30", t.date_time().to_string());
+    assert_eq!(250_000_000, t.nanosecond());
+    assert_eq!(Some(60), t.utc_offset());
+
+    // Fractions of hours and minutes
+    let t: GeneralizedTime = "2024022913.5Z".parse().unwrap();
+    assert_eq!("2024-02-29T13:30:00", t.date_time().to_string());
+    let t: GeneralizedTime = "202402291345,75".parse().unwrap();
+    assert_eq!("2024-02-29T13:45:45", t.date_time().to_string());
+    assert_eq!(None, t.utc_offset());
+
+    let dt: DateTime = "2024-02-29T13:45:30".parse().unwrap();
+    let t = GeneralizedTime::from_parts(dt, 5_000_000, Some(-330)).unwrap();
+    assert_eq!("20240229134530.005-0530", t.as_str());
+    assert_eq!("20240229134530Z", GeneralizedTime::from_parts(dt, 0, Some(0)).unwrap().as_str());
+
+    assert_eq!(Err(TimeError::InvalidValue), "20240229244530Z".parse::<GeneralizedTime>());
+    assert_eq!(Err(TimeError::InvalidFormat), "2024022913453Z".parse::<GeneralizedTime>());
+    assert_eq!(Err(TimeError::InvalidFormat), "20240229134530.Z".parse::<GeneralizedTime>());
+    assert_eq!(Err(TimeError::InvalidFormat), "20240229134530ZZ".parse::<GeneralizedTime>());
+}
+
+#[test]
+fn utc_time() {
+    let t: UTCTime = "240229134530Z".parse().unwrap();
+    assert_eq!("2024-02-29T13:45:30", t.date_time().to_string());
+    assert_eq!(0, t.utc_offset());
+    let t: UTCTime = "7001010000-0800".parse().unwrap();
+    assert_eq!("1970-01-01T00:00:00", t.date_time().to_string());
+    assert_eq!(-480, t.utc_offset());
+
+    let dt: DateTime = "2049-12-31T23:59:59".parse().unwrap();
+    assert_eq!("491231235959Z", UTCTime::from_parts(dt, 0).unwrap().as_str());
+    let dt: DateTime = "2050-01-01T00:00:00".parse().unwrap();
+    assert_eq!(Err(TimeError::InvalidValue), UTCTime::from_parts(dt, 0));
+
+    assert_eq!(Err(TimeError::InvalidFormat), "240229134530".parse::<UTCTime>());
+    assert_eq!(Err(TimeError::InvalidFormat), "240229134530+01".parse::<UTCTime>());
+}
+
+#[test]
+fn useful_time_encoding() {
+    let t: GeneralizedTime = "20240229134530Z".parse().unwrap();
+    let mut target = vec![0x0f];
+    target.extend_from_slice(b"20240229134530Z");
+    round_trip(&t, &target);
+
+    let t: UTCTime = "240229134530Z".parse().unwrap();
+    let mut target = vec![0x0d];
+    target.extend_from_slice(b"240229134530Z");
+    round_trip(&t, &target);
+
+    let data = b"\x0420240";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::InvalidTime), GeneralizedTime::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_conversions {
+    extern crate chrono;
+    use self::chrono::{FixedOffset, NaiveDate, TimeZone};
+    use asn1::{Date, GeneralizedTime, UTCTime};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn conversions() {
+        let date = Date::from(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        assert_eq!("2024-02-29", date.to_string());
+        assert_eq!(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(), NaiveDate::try_from(date).unwrap());
+
+        let offset = FixedOffset::east_opt(3600).unwrap();
+        let dt = offset.with_ymd_and_hms(2024, 2, 29, 13, 45, 30).unwrap();
+        let t = GeneralizedTime::try_from(dt).unwrap();
+        assert_eq!("20240229134530+0100", t.as_str());
+        assert_eq!(dt, chrono::DateTime::<FixedOffset>::try_from(&t).unwrap());
+
+        let t = UTCTime::try_from(dt).unwrap();
+        assert_eq!("240229134530+0100", t.as_str());
+        assert_eq!(dt, chrono::DateTime::<FixedOffset>::try_from(&t).unwrap());
+
+        let local: GeneralizedTime = "20240229134530".parse().unwrap();
+        assert!(chrono::DateTime::<FixedOffset>::try_from(&local).is_err());
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_conversions {
+    extern crate time;
+    use self::time::{Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+    use asn1::{Date, DateTime, GeneralizedTime, TimeOfDay};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn conversions() {
+        let date = time::Date::from_calendar_date(2024, Month::February, 29).unwrap();
+        let dt = PrimitiveDateTime::new(date, Time::from_hms(13, 45, 30).unwrap());
+        assert_eq!("2024-02-29T13:45:30", DateTime::from(dt).to_string());
+        assert_eq!(dt, PrimitiveDateTime::try_from(DateTime::from(dt)).unwrap());
+        assert_eq!(date, time::Date::try_from(Date::from(date)).unwrap());
+
+        let leap: TimeOfDay = "23:59:60".parse().unwrap();
+        assert!(Time::try_from(leap).is_err());
+
+        let odt = dt.assume_offset(UtcOffset::from_hms(-5, 0, 0).unwrap());
+        let t = GeneralizedTime::try_from(odt).unwrap();
+        assert_eq!("20240229134530-0500", t.as_str());
+        assert_eq!(odt, OffsetDateTime::try_from(&t).unwrap());
+    }
+}
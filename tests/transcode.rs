@@ -0,0 +1,151 @@
+extern crate asn1;
+use asn1::{PermittedAlphabet, PrintableString, VisibleString};
+use asn1::aper::{self, APerElement, Constraint, Constraints, DecodeError, Encoding, UNCONSTRAINED, encode_fragmented,
+                 encode_int};
+use asn1::transcode::{self, Component, Type};
+
+fn constrained_int(lb: i64, ub: i64) -> Type {
+    Type::Integer {
+        value: Some(Constraint::new(Some(lb), Some(ub))),
+        extensible: false,
+    }
+}
+
+fn both_ways(ty: &Type, aligned: &[u8], unaligned: &[u8]) {
+    let enc = transcode::aper_to_uper(&mut aper::Decoder::new(aligned), ty).unwrap();
+    assert_eq!(unaligned, &enc.bytes()[..]);
+    let enc = transcode::uper_to_aper(&mut aper::Decoder::new(unaligned), ty).unwrap();
+    assert_eq!(aligned, &enc.bytes()[..]);
+}
+
+#[test]
+fn sequence() {
+    // Msg ::= SEQUENCE { a INTEGER (0..7), b VisibleString (SIZE (4)), c INTEGER (0..1000) }
+    let ty = Type::Sequence {
+        root: vec![Component::new(constrained_int(0, 7)),
+                   Component::new(Type::CharString {
+                       alphabet: VisibleString::alphabet(),
+                       size: Some(Constraint::new(Some(4), Some(4))),
+                       extensible: false,
+                   }),
+                   Component::new(constrained_int(0, 1000))],
+        extensions: None,
+    };
+
+    let mut enc = encode_int(3, Some(0), Some(7)).unwrap();
+    enc.append(&VisibleString::new("ab12")
+            .unwrap()
+            .to_aper(Constraints {
+                value: None,
+                size: Some(Constraint::new(Some(4), Some(4))),
+            })
+            .unwrap())
+        .unwrap();
+    enc.append(&encode_int(500, Some(0), Some(1000)).unwrap()).unwrap();
+    assert_eq!(&vec![0x60, 0x61, 0x62, 0x31, 0x32, 0x01, 0xf4], enc.bytes());
+
+    // 7-bit characters and a 10-bit integer, none of them aligned
+    both_ways(&ty, enc.bytes(), &[0x78, 0x71, 0x31, 0x64, 0xfa, 0x00]);
+}
+
+#[test]
+fn remapped_chars() {
+    // PrintableString (FROM ("a".."f")) (SIZE (1..8)): 4 bits per character in aligned PER, 3 in unaligned PER
+    let alphabet = PermittedAlphabet::from_ranges(&[('a', 'f')]);
+    let size = Some(Constraint::new(Some(1), Some(8)));
+    let ty = Type::CharString {
+        alphabet: alphabet.clone(),
+        size,
+        extensible: false,
+    };
+
+    let enc = PrintableString::new("face")
+        .unwrap()
+        .to_aper_with_alphabet(Constraints { value: None, size }, &alphabet)
+        .unwrap();
+    assert_eq!(&vec![0x60, 0x50, 0x24], enc.bytes());
+    both_ways(&ty, enc.bytes(), &[0x74, 0x28]);
+}
+
+#[test]
+fn extensions() {
+    // Foo ::= SEQUENCE { a INTEGER (0..7), ..., b BOOLEAN }
+    let ty = Type::Sequence {
+        root: vec![Component::new(constrained_int(0, 7))],
+        extensions: Some(vec![Type::Boolean]),
+    };
+    both_ways(&ty, b"\xb0\x10\x01\x80", b"\xb0\x10\x18\x00");
+
+    // An addition the schema doesn't know about
+    let ty = Type::Sequence {
+        root: vec![Component::new(constrained_int(0, 7))],
+        extensions: Some(vec![]),
+    };
+    assert_eq!(Err(DecodeError::UnknownExtension),
+               transcode::aper_to_uper(&mut aper::Decoder::new(b"\xb0\x10\x01\x80"), &ty).map(|_| ()));
+}
+
+#[test]
+fn choice() {
+    // Bar ::= CHOICE { a NULL, b INTEGER (0..255), ..., c OCTET STRING }
+    let ty = Type::Choice {
+        root: vec![Type::Null, constrained_int(0, 255)],
+        extensions: Some(vec![Type::OctetString {
+                                  size: None,
+                                  extensible: false,
+                              }]),
+    };
+    both_ways(&ty, b"\x40\xc8", b"\x72\x00");
+    both_ways(&ty, b"\x80\x04\x03\x01\x02\x03", b"\x80\x04\x03\x01\x02\x03");
+
+    let enc = transcode::aper_to_uper(&mut aper::Decoder::new(b"\x00"), &ty).unwrap();
+    assert_eq!(2, enc.num_bits());
+}
+
+#[test]
+fn integers() {
+    let semi = Type::Integer {
+        value: Some(Constraint::new(Some(0), None)),
+        extensible: false,
+    };
+    let ty = Type::Sequence {
+        root: vec![Component::new(Type::Boolean), Component::new(semi)],
+        extensions: None,
+    };
+    both_ways(&ty, b"\x80\x02\x01\x2c", b"\x81\x00\x96\x00");
+
+    // An extensible constraint with a value outside of it
+    let ty = Type::Integer {
+        value: Some(Constraint::new(Some(0), Some(7))),
+        extensible: true,
+    };
+    both_ways(&ty, b"\x80\x01\x10", b"\x80\x88\x00");
+
+    // Large ranges take a length in aligned PER
+    let ty = constrained_int(0, 1 << 24);
+    both_ways(&ty, b"\x40\x01\x00", b"\x00\x00\x80\x00");
+}
+
+#[test]
+fn fragments() {
+    // Msg ::= SEQUENCE { a BOOLEAN, b OCTET STRING }
+    let ty = Type::Sequence {
+        root: vec![Component::new(Type::Boolean),
+                   Component::new(Type::OctetString {
+                       size: None,
+                       extensible: false,
+                   })],
+        extensions: None,
+    };
+    let data: Vec<u8> = (0..20000).map(|i| i as u8).collect();
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&encode_fragmented(data.len(), |start, end| Ok(Encoding::with_bytes(data[start..end].to_vec())))
+            .unwrap())
+        .unwrap();
+
+    let uper = transcode::aper_to_uper(&mut aper::Decoder::new(enc.bytes()), &ty).unwrap();
+    assert_eq!(1 + 8 + 16384 * 8 + 16 + 3616 * 8, uper.num_bits());
+    assert_eq!(&[0xe0, 0x80, 0x00], &uper.bytes()[..3]);
+    let aper = transcode::uper_to_aper(&mut aper::Decoder::new(uper.bytes()), &ty).unwrap();
+    assert_eq!(enc.bytes(), aper.bytes());
+}
@@ -0,0 +1,121 @@
+extern crate asn1;
+use asn1::{BMPString, NumericString, UniversalString};
+use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+
+fn size(min: i64, max: i64) -> Constraints {
+    Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(min), Some(max))),
+    }
+}
+
+#[test]
+fn encode_utf8() {
+    let s = String::from("héllo");
+    let target: Vec<u8> = vec![0x06, 0x68, 0xc3, 0xa9, 0x6c, 0x6c, 0x6f];
+    assert_eq!(target, *s.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn decode_utf8() {
+    let data = b"\x06\x68\xc3\xa9\x6c\x6c\x6f";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!("héllo", String::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn decode_invalid_utf8() {
+    let data = b"\x02\xc3\x28";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(aper::DecodeError::InvalidUtf8, String::from_aper(&mut d, UNCONSTRAINED).unwrap_err());
+}
+
+#[test]
+fn utf8_size_counts_characters() {
+    // 5 characters in 6 octets
+    let s = String::from("héllo");
+    assert!(s.to_aper(size(1, 5)).is_ok());
+    assert_eq!(aper::EncodeError::InvalidSize, s.to_aper(size(1, 4)).unwrap_err());
+
+    let data = b"\x06\x68\xc3\xa9\x6c\x6c\x6f";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(aper::DecodeError::InvalidSize, String::from_aper(&mut d, size(1, 4)).unwrap_err());
+}
+
+#[test]
+fn utf8_fragmented() {
+    let s: String = (0..20000).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+    let enc = s.to_aper(UNCONSTRAINED).unwrap();
+    let bytes = enc.bytes();
+    assert_eq!(1 + 16384 + 2 + 3616, bytes.len());
+    assert_eq!(0xc1, bytes[0]);
+    assert_eq!(vec![0x8e, 0x20], bytes[16385..16387].to_vec());
+
+    let mut d = aper::Decoder::new(bytes);
+    assert_eq!(s, String::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn utf8_fragmented_exact_multiple() {
+    let s: String = (0..32768).map(|_| 'x').collect();
+    let enc = s.to_aper(UNCONSTRAINED).unwrap();
+    let bytes = enc.bytes();
+    // one fragment of 32K, then an empty final length
+    assert_eq!(1 + 32768 + 1, bytes.len());
+    assert_eq!(0xc2, bytes[0]);
+    assert_eq!(0x00, bytes[32769]);
+
+    let mut d = aper::Decoder::new(bytes);
+    assert_eq!(s, String::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn bmp_string() {
+    let s = BMPString::new("Ωx").unwrap();
+    let target: Vec<u8> = vec![0x02, 0x03, 0xa9, 0x00, 0x78];
+    let enc = s.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(s, BMPString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert!(BMPString::new("😀").is_none());
+}
+
+#[test]
+fn bmp_string_fixed_size_is_unaligned() {
+    // BMPString (SIZE(1)) takes 16 bits, so it isn't octet-aligned
+    let s = BMPString::new("Ω").unwrap();
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&s.to_aper(size(1, 1)).unwrap()).unwrap();
+    let target: Vec<u8> = vec![0x81, 0xd4, 0x80];
+    assert_eq!(target, *enc.bytes());
+}
+
+#[test]
+fn bmp_string_rejects_surrogates() {
+    let data = b"\x01\xd8\x00";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(aper::DecodeError::InvalidCharacter, BMPString::from_aper(&mut d, UNCONSTRAINED).unwrap_err());
+}
+
+#[test]
+fn universal_string() {
+    let s = UniversalString::new("a😀").unwrap();
+    let target: Vec<u8> = vec![0x02, 0x00, 0x00, 0x00, 0x61, 0x00, 0x01, 0xf6, 0x00];
+    let enc = s.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(s, UniversalString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn known_multiplier_fragmented() {
+    let digits: String = (0..40000).map(|i| (b'0' + (i % 10) as u8) as char).collect();
+    let s = NumericString::new(&digits).unwrap();
+    let enc = s.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(0xc2, enc.bytes()[0]);
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(s, NumericString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
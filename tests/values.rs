@@ -0,0 +1,245 @@
+extern crate asn1;
+use asn1::{BitString, ObjectIdentifier};
+use asn1::notation::{self, AssignmentKind, Module, Type, ValueError};
+use asn1::schema::{self, Value};
+
+const CONSTANTS: &str = r#"
+NGAP-Constants DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+    maxProtocolIEs      INTEGER ::= 65535
+    id-Cause            INTEGER ::= 15
+    id-CellList         INTEGER ::= 16
+    ngap-oid            OBJECT IDENTIFIER ::= { itu-t (0) identified-organization (4) etsi (0) 0 22 }
+END
+"#;
+
+const CONTAINERS: &str = r#"
+NGAP-Containers DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+IMPORTS maxProtocolIEs FROM NGAP-Constants;
+
+NGAP-PROTOCOL-IES ::= CLASS {
+    &id         INTEGER (0..65535) UNIQUE,
+    &criticality Criticality,
+    &Value
+} WITH SYNTAX { ID &id CRITICALITY &criticality TYPE &Value }
+
+Criticality ::= ENUMERATED { reject, ignore, notify }
+
+ProtocolIE-Container {NGAP-PROTOCOL-IES : IEsSetParam} ::=
+    SEQUENCE (SIZE (0..maxProtocolIEs)) OF ProtocolIE-Field {{IEsSetParam}}
+
+ProtocolIE-Field {NGAP-PROTOCOL-IES : IEsSetParam} ::= SEQUENCE {
+    id          NGAP-PROTOCOL-IES.&id           ({IEsSetParam}),
+    criticality NGAP-PROTOCOL-IES.&criticality  ({IEsSetParam}{@id}),
+    value       NGAP-PROTOCOL-IES.&Value        ({IEsSetParam}{@id})
+}
+END
+"#;
+
+const CONTENTS: &str = r#"
+NGAP-PDU-Contents DEFINITIONS AUTOMATIC TAGS ::= BEGIN
+IMPORTS id-Cause, id-CellList, ngap-oid FROM NGAP-Constants
+        ProtocolIE-Container{}, NGAP-PROTOCOL-IES, Criticality FROM NGAP-Containers;
+
+Message ::= SEQUENCE {
+    procedureCode   INTEGER (0..255),
+    criticality     Criticality,
+    value           SetupRequest
+}
+
+SetupRequest ::= SEQUENCE {
+    protocolIEs     ProtocolIE-Container { {SetupRequestIEs} },
+    ...
+}
+
+SetupRequestIEs NGAP-PROTOCOL-IES ::= {
+    { ID id-Cause CRITICALITY ignore TYPE Cause } |
+    { ID id-CellList CRITICALITY reject TYPE CellList },
+    ...
+}
+
+Cause ::= CHOICE {
+    radioNetwork    ENUMERATED { unspecified, handover-cancelled, ... },
+    misc            INTEGER { unknown(0), overload(1) } (0..7),
+    ...
+}
+CellList ::= SEQUENCE (SIZE (1..4)) OF BOOLEAN
+
+Settings ::= SEQUENCE {
+    name            PrintableString (SIZE (1..8)),
+    label           UTF8String OPTIONAL,
+    flags           BIT STRING { up(0), down(1), cached(3) } (SIZE (4)),
+    payload         OCTET STRING,
+    level           ENUMERATED { high(2), low, medium(1) },
+    ratio           REAL,
+    oid             OBJECT IDENTIFIER,
+    ...,
+    [[ extra        BOOLEAN,
+       more         NULL OPTIONAL ]]
+}
+
+Flags ::= BIT STRING (SIZE (8))
+Switches ::= SEQUENCE (SIZE (1..4)) OF BOOLEAN
+Unsupported ::= EXTERNAL
+END
+"#;
+
+fn modules() -> Vec<Module> {
+    [CONSTANTS, CONTAINERS, CONTENTS].iter().flat_map(|text| notation::parse(text).unwrap()).collect()
+}
+
+fn ty(modules: &[Module], name: &str) -> Type {
+    match modules[2].assignment(name).unwrap().kind {
+        AssignmentKind::Type(ref ty) => ty.clone(),
+        _ => panic!("{} isn't a type", name),
+    }
+}
+
+/// Parse `text` as a value of `name`, check that it round trips through its encoding, and print it.
+fn round_trip(modules: &[Module], name: &str, text: &str) -> String {
+    let ty = ty(modules, name);
+    let value = notation::parse_value(text, &ty, modules).unwrap();
+    let schema_ty = notation::schema_type(&ty, modules).unwrap();
+    let enc = schema::encode(&value, &schema_ty).unwrap();
+    let decoded = schema::decode(&mut asn1::aper::Decoder::new(enc.bytes()), &schema_ty).unwrap();
+    assert_eq!(decoded, value);
+    notation::print_value(&value, &ty, modules).unwrap()
+}
+
+#[test]
+fn sequences() {
+    let modules = modules();
+    let text = r#"{
+  name "cell",
+  flags { up, cached },
+  payload '0A1B'H,
+  level low,
+  ratio 0.5,
+  oid { 0 4 0 0 22 }
+}"#;
+    assert_eq!(round_trip(&modules, "Settings", text), text);
+
+    // Values are printed in one form, but can be written in any
+    let text = r#"{ oid { ngap-oid 1 }, name "ab", flags '1000'B, payload '0000101'B, level medium,
+                    ratio { mantissa 3, base 2, exponent -1 }, label "x""y", extra TRUE, more NULL }"#;
+    let printed = round_trip(&modules, "Settings", text);
+    assert_eq!(printed,
+               r#"{
+  name "ab",
+  label "x""y",
+  flags { up },
+  payload '0A'H,
+  level medium,
+  ratio 1.5,
+  oid { 0 4 0 0 22 1 },
+  extra TRUE,
+  more NULL
+}"#);
+
+    // The indexes of enumerations are in the order of their numbers
+    let ty = ty(&modules, "Settings");
+    let value = notation::parse_value(text, &ty, &modules[..]).unwrap();
+    match value {
+        Value::Sequence { ref root, ref extensions } => {
+            assert_eq!(root[4], Some(Value::Enumerated(1)));
+            assert_eq!(root[2], Some(Value::BitString(BitString::from_bits(&[true, false, false, false]))));
+            assert_eq!(extensions.len(), 1);
+        }
+        _ => panic!("{:?}", value),
+    }
+}
+
+#[test]
+fn open_types() {
+    let modules = modules();
+    let text = r#"{
+  procedureCode 15,
+  criticality reject,
+  value {
+    protocolIEs {
+      {
+        id 15,
+        criticality ignore,
+        value misc : overload
+      },
+      {
+        id 16,
+        criticality reject,
+        value {
+          TRUE,
+          FALSE
+        }
+      }
+    }
+  }
+}"#;
+    assert_eq!(round_trip(&modules, "Message", text), text);
+
+    // The value of an open type is kept as its encoding
+    let ty = ty(&modules, "SetupRequest");
+    let value = notation::parse_value("{ protocolIEs { { id id-CellList, criticality reject, value { TRUE } } } }",
+                                      &ty,
+                                      &modules[..])
+        .unwrap();
+    let field = Value::Sequence {
+        root: vec![Some(Value::Integer(16)), Some(Value::Enumerated(0)), Some(Value::Octets(vec![0x20]))],
+        extensions: vec![],
+    };
+    assert_eq!(value,
+               Value::Sequence {
+                   root: vec![Some(Value::SequenceOf(vec![field]))],
+                   extensions: vec![],
+               });
+
+    // An id with no object in the set only takes the encoding
+    let text = "{ protocolIEs { { id 99, criticality notify, value '00'H } } }";
+    let printed = notation::print_value(&notation::parse_value(text, &ty, &modules[..]).unwrap(), &ty, &modules[..]);
+    assert!(printed.unwrap().contains("value '00'H"));
+    assert_eq!(notation::parse_value("{ protocolIEs { { id 99, criticality notify, value TRUE } } }",
+                                     &ty,
+                                     &modules[..]),
+               Err(ValueError::InvalidValue("protocolIEs.0.value".to_string())));
+}
+
+#[test]
+fn elements() {
+    let modules = modules();
+    let boolean = Type::new(notation::TypeKind::Boolean);
+    assert_eq!(notation::parse_element::<bool, _>("TRUE", &boolean, &modules[..]), Ok(true));
+    assert_eq!(notation::print_element(&false, &boolean, &modules[..]).unwrap(), "FALSE");
+
+    let utf8 = Type::new(notation::TypeKind::String(notation::StringKind::UTF8String));
+    let label: String = notation::parse_element(r#""say ""hi""""#, &utf8, &modules[..]).unwrap();
+    assert_eq!(label, "say \"hi\"");
+    assert_eq!(notation::print_element(&label, &utf8, &modules[..]).unwrap(), r#""say ""hi""""#);
+
+    let flags: BitString = notation::parse_element("'A5'H", &ty(&modules, "Flags"), &modules[..]).unwrap();
+    assert_eq!(notation::print_element(&flags, &ty(&modules, "Flags"), &modules[..]).unwrap(), "'10100101'B");
+
+    let oid = ObjectIdentifier::new(&[0, 4, 0, 0, 22]).unwrap();
+    let oid_ty = Type::new(notation::TypeKind::ObjectIdentifier);
+    assert_eq!(notation::print_element(&oid, &oid_ty, &modules[..]).unwrap(), "{ 0 4 0 0 22 }");
+    assert_eq!(notation::parse_element::<ObjectIdentifier, _>("{ iso 3 6 1 }", &oid_ty, &modules[..]),
+               Ok(ObjectIdentifier::new(&[1, 3, 6, 1]).unwrap()));
+}
+
+#[test]
+fn errors() {
+    let modules = modules();
+    let settings = ty(&modules, "Settings");
+    let parse = |text: &str| notation::parse_value(text, &settings, &modules[..]);
+
+    assert!(matches!(parse("{ name \"a\""), Err(ValueError::Parse(_))));
+    assert!(matches!(parse("{ name \"a\" } extra"), Err(ValueError::Parse(_))));
+    let missing = r#"{ name "a", flags '0'B, payload ''H, level high, ratio 0 }"#;
+    assert_eq!(parse(missing), Err(ValueError::InvalidValue("oid".to_string())));
+    let wrong = r#"{ name "a", flags { up, sideways }, payload ''H, level high, ratio 0, oid { 1 2 } }"#;
+    assert_eq!(parse(wrong), Err(ValueError::InvalidValue("flags".to_string())));
+    let unknown = r#"{ name "a", flags '0'B, payload ''H, level high, ratio 0, oid { 1 2 }, colour red }"#;
+    assert_eq!(parse(unknown), Err(ValueError::InvalidValue("colour".to_string())));
+
+    let switches = ty(&modules, "Switches");
+    assert_eq!(notation::parse_value("{ TRUE, 1 }", &switches, &modules[..]),
+               Err(ValueError::InvalidValue("1".to_string())));
+    assert!(matches!(notation::schema_type(&ty(&modules, "Unsupported"), &modules[..]),
+                     Err(ValueError::Unsupported("EXTERNAL"))));
+}
@@ -0,0 +1,276 @@
+#[macro_use]
+extern crate asn1;
+use asn1::{BitString, Date, GeneralizedTime, IA5String, NamedBitString, ObjectIdentifier};
+use asn1::xer::{self, DecodeError, Encoding, XerElement};
+
+named_bits! {
+    pub enum Flags {
+        A = 0,
+        B = 1,
+        C = 2,
+    }
+}
+
+// Shape ::= CHOICE {
+//     circle INTEGER,
+//     empty NULL
+// }
+#[derive(Debug, PartialEq)]
+enum Shape {
+    Circle(i32),
+    Empty,
+}
+
+impl XerElement for Shape {
+    const NAME: &'static str = "Shape";
+
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, DecodeError> {
+        let ret = decoder.decode_any_element();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let (name, mut content) = ret.unwrap();
+        let shape = match name {
+            "circle" => {
+                let ret = i32::from_xer(&mut content);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Shape::Circle(ret.unwrap())
+            }
+            "empty" => {
+                let ret = <()>::from_xer(&mut content);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Shape::Empty
+            }
+            _ => return Err(DecodeError::UnexpectedElement),
+        };
+        let ret = decoder.finish();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(shape)
+    }
+
+    fn to_xer(&self) -> Result<Encoding, xer::EncodeError> {
+        let mut enc = Encoding::new();
+        match *self {
+            Shape::Circle(r) => enc.push("circle", r.to_xer().unwrap()),
+            Shape::Empty => enc.push("empty", ().to_xer().unwrap()),
+        }
+        Ok(enc)
+    }
+}
+
+// Msg ::= SEQUENCE {
+//     id INTEGER,
+//     name IA5String OPTIONAL,
+//     shapes SEQUENCE OF Shape
+// }
+#[derive(Debug, PartialEq)]
+struct Msg {
+    id: u16,
+    name: Option<IA5String>,
+    shapes: Vec<Shape>,
+}
+
+impl XerElement for Msg {
+    const NAME: &'static str = "Msg";
+
+    fn from_xer(decoder: &mut xer::Decoder) -> Result<Self, DecodeError> {
+        let ret = decoder.decode_element("id");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let id = u16::from_xer(&mut ret.unwrap());
+        if id.is_err() {
+            return Err(id.err().unwrap());
+        }
+        let mut name = None;
+        if decoder.peek_name() == Some("name") {
+            let ret = IA5String::from_xer(&mut decoder.decode_element("name").unwrap());
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            name = Some(ret.unwrap());
+        }
+        let ret = decoder.decode_element("shapes");
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let shapes = Vec::<Shape>::from_xer(&mut ret.unwrap());
+        if shapes.is_err() {
+            return Err(shapes.err().unwrap());
+        }
+        let ret = decoder.finish();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(Msg {
+            id: id.unwrap(),
+            name,
+            shapes: shapes.unwrap(),
+        })
+    }
+
+    fn to_xer(&self) -> Result<Encoding, xer::EncodeError> {
+        let mut enc = Encoding::new();
+        enc.push("id", self.id.to_xer().unwrap());
+        if let Some(ref name) = self.name {
+            enc.push("name", name.to_xer().unwrap());
+        }
+        enc.push("shapes", self.shapes.to_xer().unwrap());
+        Ok(enc)
+    }
+}
+
+#[test]
+fn scalars() {
+    assert_eq!("<BOOLEAN><true/></BOOLEAN>", xer::encode_canonical(&true).unwrap());
+    assert_eq!("<INTEGER>-12</INTEGER>", xer::encode_canonical(&-12i32).unwrap());
+    assert_eq!("<NULL/>", xer::encode_canonical(&()).unwrap());
+    assert_eq!(Ok(false), xer::decode::<bool>("<BOOLEAN> <false/> </BOOLEAN>"));
+    assert_eq!(Err(DecodeError::InvalidBool), xer::decode::<bool>("<BOOLEAN><yes/></BOOLEAN>"));
+    assert_eq!(Ok(()), xer::decode::<()>("<NULL></NULL>"));
+
+    assert_eq!(Ok(7), xer::decode::<u8>("<INTEGER> +007\n</INTEGER>"));
+    assert_eq!(Err(DecodeError::MalformedInt), xer::decode::<u8>("<INTEGER>256</INTEGER>"));
+    assert_eq!(Err(DecodeError::MalformedInt), xer::decode::<i32>("<INTEGER>1 2</INTEGER>"));
+    assert_eq!(Err(DecodeError::UnexpectedElement), xer::decode::<i32>("<REAL>1</REAL>"));
+
+    let reals = [(150., "1.5E2"), (-0.25, "-2.5E-1"), (0., "0"), (-0., "-0")];
+    for &(value, text) in &reals {
+        let xml = format!("<REAL>{}</REAL>", text);
+        assert_eq!(xml, xer::encode_canonical(&value).unwrap());
+        let decoded = xer::decode_canonical::<f64>(&xml).unwrap();
+        assert!(decoded == value && decoded.is_sign_negative() == value.is_sign_negative());
+    }
+    assert_eq!("<REAL><MINUS-INFINITY/></REAL>", xer::encode_canonical(&f64::NEG_INFINITY).unwrap());
+    assert!(xer::decode::<f64>("<REAL><NOT-A-NUMBER/></REAL>").unwrap().is_nan());
+    assert_eq!(Ok(150.), xer::decode::<f64>("<REAL>150.0</REAL>"));
+    assert_eq!(Err(DecodeError::NonCanonicalReal), xer::decode_canonical::<f64>("<REAL>150.0</REAL>"));
+    assert_eq!(Err(DecodeError::InvalidReal), xer::decode::<f64>("<REAL>inf</REAL>"));
+}
+
+#[test]
+fn strings() {
+    let s = "a < b & \"c\"\r\n".to_string();
+    let xml = xer::encode_canonical(&s).unwrap();
+    assert_eq!("<UTF8String>a &lt; b &amp; \"c\"<cr/><lf/></UTF8String>", xml);
+    assert_eq!(Ok(s), xer::decode(&xml));
+    assert_eq!(Ok("é<x>".to_string()),
+               xer::decode("<UTF8String>&#233;<![CDATA[<x>]]></UTF8String>"));
+    assert_eq!(Err(DecodeError::InvalidCharacter),
+               xer::decode::<IA5String>("<IA5String>é</IA5String>"));
+
+    let bits = BitString::from_bits(&[true, false, true, true]);
+    assert_eq!("<BIT_STRING>1011</BIT_STRING>", xer::encode_canonical(&bits).unwrap());
+    assert_eq!(Ok(bits.clone()), xer::decode("<BIT_STRING>10 11</BIT_STRING>"));
+    assert_eq!(Err(DecodeError::UnexpectedWhitespace),
+               xer::decode_canonical::<BitString>("<BIT_STRING>10 11</BIT_STRING>"));
+    assert_eq!(Err(DecodeError::InvalidBits), xer::decode::<BitString>("<BIT_STRING>12</BIT_STRING>"));
+
+    let mut flags = NamedBitString::<Flags>::new();
+    flags.set(Flags::B, true);
+    assert_eq!("<BIT_STRING>01</BIT_STRING>", xer::encode_canonical(&flags).unwrap());
+    assert_eq!(Ok(flags.clone()), xer::decode("<BIT_STRING>0100</BIT_STRING>"));
+    assert_eq!(Err(DecodeError::TrailingZeroBits),
+               xer::decode_canonical::<NamedBitString<Flags>>("<BIT_STRING>0100</BIT_STRING>"));
+
+    let content = Encoding::octet_string(&[0xde, 0xad, 0x01]);
+    assert_eq!("<OCTET_STRING>DEAD01</OCTET_STRING>", content.to_xml("OCTET_STRING", true));
+    let (_, content) = xer::parse("<OCTET_STRING>DE AD\n01</OCTET_STRING>").unwrap();
+    assert_eq!(Ok(vec![0xde, 0xad, 0x01]), xer::Decoder::new(&content).decode_octet_string());
+    let mut d = xer::Decoder::new(&content);
+    d.set_canonical(true);
+    assert_eq!(Err(DecodeError::UnexpectedWhitespace), d.decode_octet_string());
+    let (_, content) = xer::parse("<OCTET_STRING>dead01</OCTET_STRING>").unwrap();
+    let mut d = xer::Decoder::new(&content);
+    d.set_canonical(true);
+    assert_eq!(Err(DecodeError::NonCanonicalHex), d.decode_octet_string());
+}
+
+#[test]
+fn oids_and_times() {
+    let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+    let xml = "<OBJECT_IDENTIFIER>1.2.840.113549</OBJECT_IDENTIFIER>";
+    assert_eq!(xml, xer::encode_canonical(&oid).unwrap());
+    assert_eq!(Ok(oid.clone()), xer::decode("<OBJECT_IDENTIFIER>1.2.0840.113549</OBJECT_IDENTIFIER>"));
+    assert_eq!(Err(DecodeError::NonCanonicalOid),
+               xer::decode_canonical::<ObjectIdentifier>("<OBJECT_IDENTIFIER>1.2.0840.113549</OBJECT_IDENTIFIER>"));
+    assert_eq!(Err(DecodeError::InvalidOid),
+               xer::decode::<ObjectIdentifier>("<OBJECT_IDENTIFIER>3.1</OBJECT_IDENTIFIER>"));
+
+    let date: Date = "2024-02-29".parse().unwrap();
+    assert_eq!("<DATE>2024-02-29</DATE>", xer::encode_canonical(&date).unwrap());
+    assert_eq!(Ok(date), xer::decode("<DATE>2024-02-29</DATE>"));
+    assert_eq!(Err(DecodeError::InvalidTime), xer::decode::<Date>("<DATE>2023-02-29</DATE>"));
+
+    let xml = "<GeneralizedTime>20240229120000.5Z</GeneralizedTime>";
+    let t: GeneralizedTime = xer::decode_canonical(xml).unwrap();
+    assert_eq!(xml, xer::encode_canonical(&t).unwrap());
+    let xml = "<GeneralizedTime>20240229120000.50Z</GeneralizedTime>";
+    assert!(xer::decode::<GeneralizedTime>(xml).is_ok());
+    assert_eq!(Err(DecodeError::NonCanonicalTime), xer::decode_canonical::<GeneralizedTime>(xml));
+}
+
+#[test]
+fn sequence_of() {
+    let v = vec![true, false];
+    assert_eq!("<SEQUENCE_OF><true/><false/></SEQUENCE_OF>", xer::encode_canonical(&v).unwrap());
+    assert_eq!(Ok(v), xer::decode("<SEQUENCE_OF>\n  <true/>\n  <false/>\n</SEQUENCE_OF>"));
+
+    let v = vec![1u8, 2];
+    let xml = "<SEQUENCE_OF><INTEGER>1</INTEGER><INTEGER>2</INTEGER></SEQUENCE_OF>";
+    assert_eq!(xml, xer::encode_canonical(&v).unwrap());
+    assert_eq!(Ok(v), xer::decode(xml));
+    assert_eq!(Ok(Vec::<u8>::new()), xer::decode("<SEQUENCE_OF/>"));
+    assert_eq!(Err(DecodeError::UnexpectedElement),
+               xer::decode::<Vec<u8>>("<SEQUENCE_OF><REAL>1</REAL></SEQUENCE_OF>"));
+    assert_eq!(Err(DecodeError::UnexpectedText), xer::decode::<Vec<u8>>("<SEQUENCE_OF>1</SEQUENCE_OF>"));
+}
+
+#[test]
+fn sequence_and_choice() {
+    let msg = Msg {
+        id: 5,
+        name: Some(IA5String::new("x").unwrap()),
+        shapes: vec![Shape::Circle(3), Shape::Empty],
+    };
+    let canonical = "<Msg><id>5</id><name>x</name><shapes><Shape><circle>3</circle></Shape><Shape><empty/></Shape>\
+                     </shapes></Msg>";
+    assert_eq!(canonical, xer::encode_canonical(&msg).unwrap());
+    assert_eq!(Ok(&msg), xer::decode_canonical::<Msg>(canonical).as_ref());
+
+    let basic = xer::encode(&msg).unwrap();
+    assert_eq!("<Msg>\n  <id>5</id>\n  <name>x</name>\n  <shapes>\n    <Shape>\n      <circle>3</circle>\n    \
+                </Shape>\n    <Shape>\n      <empty/>\n    </Shape>\n  </shapes>\n</Msg>",
+               basic);
+    assert_eq!(Ok(&msg), xer::decode::<Msg>(&basic).as_ref());
+    assert_eq!(Err(DecodeError::UnexpectedWhitespace), xer::decode_canonical::<Msg>(&basic));
+
+    let xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!-- a message -->\n\
+               <Msg xmlns:x=\"urn:x\"><id>5</id><shapes/></Msg>\n";
+    let msg = Msg {
+        id: 5,
+        name: None,
+        shapes: Vec::new(),
+    };
+    assert_eq!(Ok(msg), xer::decode(xml));
+    assert_eq!(Err(DecodeError::MissingElement), xer::decode::<Msg>("<Msg><id>5</id></Msg>"));
+    assert_eq!(Err(DecodeError::TrailingData), xer::decode::<Msg>("<Msg><id>5</id><shapes/><x/></Msg>"));
+    assert_eq!(Err(DecodeError::UnexpectedElement),
+               xer::decode::<Shape>("<Shape><square>1</square></Shape>"));
+}
+
+#[test]
+fn malformed_xml() {
+    assert_eq!(Err(DecodeError::MalformedXml), xer::decode::<i32>("<INTEGER>1</INT>"));
+    assert_eq!(Err(DecodeError::MalformedXml), xer::decode::<i32>("<INTEGER>1"));
+    assert_eq!(Err(DecodeError::MalformedXml), xer::decode::<String>("<UTF8String>&bogus;</UTF8String>"));
+    assert_eq!(Err(DecodeError::TrailingData), xer::decode::<i32>("<INTEGER>1</INTEGER><INTEGER>2</INTEGER>"));
+    let deep = format!("{}{}", "<a>".repeat(100), "</a>".repeat(100));
+    assert_eq!(Err(DecodeError::NestingTooDeep), xer::parse(&deep).map(|_| ()));
+}